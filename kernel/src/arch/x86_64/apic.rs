@@ -6,7 +6,7 @@ use x86_64::{
     structures::paging::{FrameAllocator, Mapper, PhysFrame, Size4KiB},
 };
 
-use crate::{interrupts::InterruptIndex, serial_println};
+use crate::{info, interrupts::InterruptIndex};
 
 #[derive(Clone)]
 struct MyHandler {
@@ -181,7 +181,7 @@ unsafe fn init_timer(lapic_pointer: *mut u32) {
     let ticr = unsafe { lapic_pointer.offset(APICOffset::Ticr as isize / 4) };
     unsafe { ticr.write_volatile(2_500_000) }; // Very slow // TODO: Spead this up when more processes
 
-    serial_println!("Local APIC timer initialized");
+    info!("Local APIC timer initialized");
 }
 
 unsafe fn init_keyboard(lapic_pointer: *mut u32) {
@@ -227,6 +227,18 @@ unsafe fn init_io_apic(
         ioapic_pointer.offset(0).write_volatile(0x29); // Select mouse redirection entry high
         ioapic_pointer.offset(4).write_volatile(0); // Destination (CPU 0)
     }
+
+    // Configure COM1 interrupt (IRQ 4 -> interrupt vector 36)
+    unsafe {
+        // IRQ 4 uses redirection entry 4: registers 0x18 (low) and 0x19 (high)
+        ioapic_pointer.offset(0).write_volatile(0x18); // Select COM1 redirection entry low
+        ioapic_pointer
+            .offset(4)
+            .write_volatile(InterruptIndex::Serial as u8 as u32); // Vector + delivery mode (fixed=000)
+
+        ioapic_pointer.offset(0).write_volatile(0x19); // Select COM1 redirection entry high
+        ioapic_pointer.offset(4).write_volatile(0); // Destination (CPU 0)
+    }
 }
 
 fn map_apic(
@@ -1,12 +1,15 @@
 #[cfg(uefi)]
 use crate::arch::x86_64::apic; // TODO: Auto import correct arch
-use crate::{hlt_loop, println, serial_println};
+use crate::{
+    drivers::mouse::{self, PacketDecoder},
+    hlt_loop, println, serial_println,
+};
 use core::u64;
 
 use lazy_static::lazy_static;
 #[cfg(not(uefi))]
 use pic8259::ChainedPics;
-use ps2_mouse::{Mouse, MouseState};
+use ps2_mouse::Mouse;
 use spin::{self, lazy::Lazy};
 use spinning_top::Spinlock;
 use x86_64::{
@@ -15,10 +18,12 @@ use x86_64::{
 };
 
 pub static MOUSE: Lazy<Spinlock<Mouse>> = Lazy::new(|| Spinlock::new(Mouse::new()));
+static MOUSE_DECODER: Spinlock<PacketDecoder> = Spinlock::new(PacketDecoder::new());
 
 pub const PIC_1_OFFSET: u8 = 32;
 pub const PIC_2_OFFSET: u8 = PIC_1_OFFSET + 8;
 pub const KEYBOARD_INTERRUPT: u8 = PIC_1_OFFSET + 1;
+pub const SERIAL_INTERRUPT: u8 = PIC_1_OFFSET + 4;
 pub const MOUSE_INTERRUPT: u8 = PIC_1_OFFSET + 12;
 
 #[cfg(not(uefi))]
@@ -30,6 +35,7 @@ pub static PICS: spin::Mutex<ChainedPics> =
 pub enum InterruptIndex {
     Timer = PIC_1_OFFSET,
     Keyboard = KEYBOARD_INTERRUPT,
+    Serial = SERIAL_INTERRUPT,
     Mouse = MOUSE_INTERRUPT,
 }
 
@@ -54,6 +60,7 @@ lazy_static! {
         idt[InterruptIndex::Timer.as_u8()].set_handler_fn(timer_handler);
         idt[InterruptIndex::Keyboard.as_u8()].set_handler_fn(keyboard_interrupt_handler);
         idt[InterruptIndex::Mouse.as_u8()].set_handler_fn(mouse_interrupt_handler);
+        idt[InterruptIndex::Serial.as_u8()].set_handler_fn(serial_interrupt_handler);
 
         idt.general_protection_fault.set_handler_fn(general_protection_fault_handler);
         // idt.security_exception
@@ -69,12 +76,14 @@ pub fn init_idt() {
 
 pub fn init_mouse() {
     MOUSE.lock().init().unwrap();
-    MOUSE.lock().set_on_complete(on_complete);
-}
 
-// This will be fired when a packet is finished being processed.
-fn on_complete(mouse_state: MouseState) {
-    crate::desktop::input::add_mouse_state(mouse_state);
+    // The ps2_mouse crate only knows 3 byte packets, so we decode them ourselves
+    // and can switch on the scroll wheel when the mouse has one.
+    x86_64::instructions::interrupts::without_interrupts(|| match mouse::enable_scroll_wheel() {
+        Ok(true) => serial_println!("Mouse: scroll wheel enabled"),
+        Ok(false) => serial_println!("Mouse: no scroll wheel"),
+        Err(e) => serial_println!("Mouse: could not probe scroll wheel: {}", e),
+    });
 }
 
 // An example interrupt based on https://os.phil-opp.com/hardware-interrupts/. The ps2 mouse is configured to fire
@@ -83,9 +92,9 @@ extern "x86-interrupt" fn mouse_interrupt_handler(_stack_frame: InterruptStackFr
     let mut port = PortReadOnly::new(0x60);
     let packet = unsafe { port.read() };
 
-    // I know this is a bad practice but we are sort of forced to do this here
-    // I spent 3h trying to do it otherwise but none of the solutions worked.
-    MOUSE.lock().process_packet(packet);
+    if let Some(packet) = MOUSE_DECODER.lock().push(packet) {
+        crate::desktop::input::add_mouse_state(packet);
+    }
 
     #[cfg(not(uefi))]
     {
@@ -147,7 +156,8 @@ extern "x86-interrupt" fn double_fault_handler(
     hlt_loop();
 }
 
-extern "x86-interrupt" fn timer_handler(_stack_frame: InterruptStackFrame) {
+#[cfg_attr(not(processes_enabled), allow(unused_variables))]
+extern "x86-interrupt" fn timer_handler(stack_frame: InterruptStackFrame) {
     // Notify the Programmable Interrupt Controller (PIC) that the interrupt has been handled
     #[cfg(not(uefi))]
     unsafe {
@@ -159,7 +169,13 @@ extern "x86-interrupt" fn timer_handler(_stack_frame: InterruptStackFrame) {
     apic::end_interrupt();
 
     #[cfg(processes_enabled)]
-    crate::tasks::scheduler::schedule();
+    {
+        // Killed programs are stopped only in user code, where they hold no locks
+        if stack_frame.code_segment.rpl() == x86_64::PrivilegeLevel::Ring3 {
+            crate::tasks::process::stop_if_killed();
+        }
+        crate::tasks::scheduler::schedule();
+    }
 }
 
 extern "x86-interrupt" fn keyboard_interrupt_handler(_stack_frame: InterruptStackFrame) {
@@ -179,6 +195,22 @@ extern "x86-interrupt" fn keyboard_interrupt_handler(_stack_frame: InterruptStac
     apic::end_interrupt();
 }
 
+// COM1 received something, the serial console reads it from the queue
+extern "x86-interrupt" fn serial_interrupt_handler(_stack_frame: InterruptStackFrame) {
+    crate::serial::receive_interrupt();
+
+    #[cfg(not(uefi))]
+    {
+        unsafe {
+            PICS.lock()
+                .notify_end_of_interrupt(InterruptIndex::Serial.as_u8());
+        }
+    }
+
+    #[cfg(uefi)]
+    apic::end_interrupt();
+}
+
 #[cfg(test)]
 mod tests {
     #[test_case]
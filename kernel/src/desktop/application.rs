@@ -1,7 +1,15 @@
 use alloc::string::String;
 use pc_keyboard::KeyCode;
 
-use crate::surface::Surface;
+use crate::{
+    desktop::{
+        context_menu::ContextMenu,
+        dialog::{CloseRequest, ModalDialog},
+        mouse::MouseEvent,
+        shortcuts::Accelerator,
+    },
+    surface::Surface,
+};
 
 pub trait Application {
     fn init(&mut self, surface: &mut Surface);
@@ -10,4 +18,55 @@ pub trait Application {
     fn handle_char_input(&mut self, c: char, ctrl_pressed: bool, surface: &mut Surface);
     fn handle_key_input(&mut self, key: KeyCode, surface: &mut Surface);
     fn handle_mouse_click(&mut self, x: usize, y: usize, surface: &mut Surface);
+
+    /// Every pointer event over the window (moves, presses and releases of any
+    /// button, wheel, double-clicks). Plain left clicks still arrive through
+    /// `handle_mouse_click` as well, so simple apps only need that one.
+    fn handle_mouse_event(&mut self, _event: &MouseEvent, _surface: &mut Surface) {}
+
+    /// Menu to pop up for a right-click at (x, y), None for no menu
+    fn context_menu(&mut self, _x: usize, _y: usize, _surface: &mut Surface) -> Option<ContextMenu> {
+        None
+    }
+
+    /// An item of the menu from `context_menu` was picked
+    fn handle_context_menu_action(&mut self, _action: &'static str, _surface: &mut Surface) {}
+
+    /// Called after the window was resized; `surface` already has the new size and
+    /// still holds the previous shapes. Apps with a fixed layout can ignore it.
+    fn handle_resize(&mut self, _surface: &mut Surface) {}
+
+    /// Called after the theme changed. The window manager has already reset the
+    /// surface background; apps that take their colors from the theme rebuild
+    /// their shapes here.
+    fn handle_theme_change(&mut self, _surface: &mut Surface) {}
+
+    /// Called when the user tries to close the window. Apps with unsaved state
+    /// can keep the window open or ask through a modal dialog.
+    fn on_close_requested(&mut self, _surface: &mut Surface) -> CloseRequest {
+        CloseRequest::Close
+    }
+
+    /// Shortcuts this app wants while focused. They take precedence over
+    /// regular key input and are delivered through `handle_accelerator`.
+    fn accelerators(&self) -> &'static [Accelerator] {
+        &[]
+    }
+
+    fn handle_accelerator(&mut self, _action: &'static str, _surface: &mut Surface) {}
+
+    /// Answer to the dialog returned by `on_close_requested`, as a button index.
+    /// Returns true if the window should close now.
+    fn on_close_dialog_result(&mut self, _button: usize, _surface: &mut Surface) -> bool {
+        true
+    }
+
+    /// Dialog to open on top of the window, polled by the window manager every
+    /// frame so apps can ask for confirmation from any input handler
+    fn take_dialog(&mut self) -> Option<ModalDialog> {
+        None
+    }
+
+    /// Answer to a dialog from `take_dialog`, as a button index
+    fn on_dialog_result(&mut self, _button: usize, _surface: &mut Surface) {}
 }
@@ -1,14 +1,16 @@
 use alloc::{
     format,
     string::{String, ToString},
-    vec::Vec,
 };
 use noto_sans_mono_bitmap::{FontWeight, RasterHeight};
 
 use crate::{
-    desktop::application::Application,
-    framebuffer::Color,
-    surface::{Shape, Surface},
+    desktop::{
+        application::Application,
+        theme,
+        widgets::{Button, Label, Ui, WidgetEvent, layout::Grid},
+    },
+    surface::Surface,
 };
 
 pub enum Operation {
@@ -24,48 +26,40 @@ pub enum CalculatorState {
     Result(f64),
 }
 
+const BUTTONS: [&str; 16] = [
+    "7", "8", "9", ":", //
+    "4", "5", "6", "x", //
+    "1", "2", "3", "-", //
+    "0", ".", "=", "+",
+];
+
 pub struct Calculator {
     state: CalculatorState,
-    previous_display_text: String,
     display_text: String,
     current_input: String,
-    display_idx: usize,
-    button_regions: Vec<(usize, usize, usize, usize)>, // (x, y, width, height)
+    ui: Ui<Calculator>,
+    display_id: usize,
 }
 
 impl Calculator {
     pub fn new(_args: Option<String>) -> Self {
         Self {
             state: CalculatorState::InputFirst,
-            previous_display_text: String::new(),
             display_text: "0".to_string(),
             current_input: String::new(),
-            display_idx: 0,
-            button_regions: Vec::new(),
+            ui: Ui::new(),
+            display_id: 0,
         }
     }
 
-    fn handle_button_click(&mut self, idx: usize) {
-        let label = match idx {
-            0 => "7",
-            1 => "8",
-            2 => "9",
-            3 => ":",
-            4 => "4",
-            5 => "5",
-            6 => "6",
-            7 => "x",
-            8 => "1",
-            9 => "2",
-            10 => "3",
-            11 => "-",
-            12 => "0",
-            13 => ".",
-            14 => "=",
-            15 => "+",
-            _ => return, // Invalid index
+    fn on_button(&mut self, id: usize, _event: WidgetEvent, _surface: &mut Surface) {
+        let Some(label) = self.ui.button(id).map(|button| button.label().to_string()) else {
+            return;
         };
+        self.handle_button_click(&label);
+    }
 
+    fn handle_button_click(&mut self, label: &str) {
         match label {
             "0" | "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" => {
                 self.current_input.push_str(label);
@@ -131,90 +125,51 @@ impl Calculator {
 
 impl Application for Calculator {
     fn init(&mut self, surface: &mut Surface) {
-        let button_height: usize = 50;
-        let button_width: usize = 40;
-        let button_spacing: usize = 5;
-        let start_x = 15;
-        let start_y = 85;
-
-        surface.add_shape(Shape::Rectangle {
-            x: 15,
-            y: 15,
-            width: 175,
-            height: 60,
-            color: Color::WHITE,
-            filled: true,
-            hide: false,
-        });
-
-        self.display_idx = surface.add_shape(Shape::Text {
-            x: 20,
-            y: 30,
-            content: self.display_text.clone(),
-            color: Color::BLACK,
-            background_color: Color::WHITE,
-            font_size: RasterHeight::Size32,
-            font_weight: FontWeight::Light,
-            hide: false,
-        });
-
-        let buttons = [
-            ["7", "8", "9", ":"],
-            ["4", "5", "6", "x"],
-            ["1", "2", "3", "-"],
-            ["0", ".", "=", "+"],
-        ];
-
-        for (row, button_row) in buttons.iter().enumerate() {
-            for (col, &button) in button_row.iter().enumerate() {
-                let x = start_x + col * (button_width + button_spacing);
-                let y = start_y + row * (button_height + button_spacing);
-                self.button_regions
-                    .push((x, y, button_width, button_height));
-
-                surface.add_shape(Shape::Rectangle {
-                    x,
-                    y,
-                    width: button_width,
-                    height: button_height,
-                    color: Color::WHITE,
-                    filled: true,
-                    hide: false,
-                });
-                surface.add_shape(Shape::Text {
-                    x: x + 13,
-                    y: y + 15,
-                    content: button.to_string(),
-                    color: Color::BLACK,
-                    background_color: Color::WHITE,
-                    font_size: RasterHeight::Size24,
-                    font_weight: FontWeight::Light,
-                    hide: false,
-                });
-            }
+        let theme = theme::current();
+        self.display_id = self.ui.add(
+            Label::new(15, 15, &self.display_text)
+                .filled(175, 60, theme.input_background)
+                .with_font(RasterHeight::Size32, FontWeight::Light),
+            None,
+        );
+
+        let grid = Grid::new(15, 85, 4, 40, 50, 5);
+        for (i, label) in BUTTONS.iter().enumerate() {
+            self.ui.add(
+                Button::new(grid.nth(i), label)
+                    .with_color(theme.input_background)
+                    .with_font(RasterHeight::Size24, FontWeight::Light),
+                Some(Self::on_button),
+            );
         }
+
+        self.ui.attach(surface);
     }
 
-    fn handle_mouse_click(&mut self, x: usize, y: usize, _surface: &mut Surface) {
-        for (idx, &(button_x, button_y, width, height)) in self.button_regions.iter().enumerate() {
-            if x >= button_x && x < button_x + width && y >= button_y && y < button_y + height {
-                self.handle_button_click(idx);
-                return;
-            }
+    fn handle_theme_change(&mut self, surface: &mut Surface) {
+        surface.clear_all_shapes();
+        self.ui.clear();
+        self.init(surface);
+    }
+
+    fn handle_mouse_click(&mut self, x: usize, y: usize, surface: &mut Surface) {
+        if let Some((callback, id, event)) = self.ui.handle_click(x, y, surface) {
+            callback(self, id, event, surface);
         }
     }
 
     fn render(&mut self, surface: &mut Surface) {
-        if self.display_text == self.previous_display_text {
-            return; // No change in display text, nothing to update
+        if let Some(display) = self.ui.label(self.display_id) {
+            display.set_text(&self.display_text, surface);
         }
+    }
 
-        surface.update_text_content(self.display_idx, self.display_text.clone(), None);
-
-        self.previous_display_text = self.display_text.clone();
+    fn handle_char_input(&mut self, c: char, _ctrl_pressed: bool, surface: &mut Surface) {
+        if let Some((callback, id, event)) = self.ui.handle_char(c, surface) {
+            callback(self, id, event, surface);
+        }
     }
 
-    fn handle_char_input(&mut self, _c: char, _ctrl_pressed: bool, _surface: &mut Surface) {}
     fn handle_key_input(&mut self, _key: pc_keyboard::KeyCode, _surface: &mut Surface) {}
     fn get_title(&self) -> Option<String> {
         None
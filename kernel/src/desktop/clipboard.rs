@@ -0,0 +1,56 @@
+//! System clipboard shared by every app. It holds either text or a file
+//! copied in the file manager; pasting a file where text is wanted gives
+//! its path.
+
+use alloc::string::String;
+use spin::Mutex;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClipboardOperation {
+    Copy,
+    Cut,
+}
+
+#[derive(Clone, Debug)]
+pub struct ClipboardEntry {
+    pub file_path: String,
+    pub file_name: String,
+    pub is_directory: bool,
+    pub operation: ClipboardOperation,
+}
+
+#[derive(Clone, Debug)]
+pub enum ClipboardContent {
+    Text(String),
+    File(ClipboardEntry),
+}
+
+static CLIPBOARD: Mutex<Option<ClipboardContent>> = Mutex::new(None);
+
+pub fn set_text(text: String) {
+    *CLIPBOARD.lock() = Some(ClipboardContent::Text(text));
+}
+
+pub fn set_file(entry: ClipboardEntry) {
+    *CLIPBOARD.lock() = Some(ClipboardContent::File(entry));
+}
+
+/// Text to paste, a file gives its path
+pub fn text() -> Option<String> {
+    match CLIPBOARD.lock().as_ref()? {
+        ClipboardContent::Text(text) => Some(text.clone()),
+        ClipboardContent::File(entry) => Some(entry.file_path.clone()),
+    }
+}
+
+/// File to paste, None when the clipboard holds text
+pub fn file() -> Option<ClipboardEntry> {
+    match CLIPBOARD.lock().as_ref()? {
+        ClipboardContent::File(entry) => Some(entry.clone()),
+        ClipboardContent::Text(_) => None,
+    }
+}
+
+pub fn clear() {
+    *CLIPBOARD.lock() = None;
+}
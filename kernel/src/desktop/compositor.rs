@@ -0,0 +1,139 @@
+//! Puts the off-screen window buffers on the screen.
+//!
+//! Every window draws into its own buffer, and only the screen regions that
+//! changed are composited again: the desktop is redrawn there first, then the
+//! windows on top of it from the bottom up. The pixels below a window are
+//! always fresh when it is blended, which is what lets windows be translucent
+//! and cast shadows without smearing.
+
+use crate::{
+    framebuffer::{FrameBufferWriter, OffscreenBuffer},
+    surface::Rect,
+};
+
+/// How much the shadow darkens right next to the window, out of 255
+const SHADOW_STRENGTH: usize = 90;
+
+/// Blend `src` over `dst`. Both rows are in the screen's pixel format, and
+/// every channel gets the same treatment, so the channel order doesn't matter.
+fn blend_row(dst: &mut [u8], src: &[u8], alpha: u8) {
+    let alpha = alpha as u16;
+    for (dst, src) in dst.iter_mut().zip(src) {
+        *dst = ((*src as u16 * alpha + *dst as u16 * (255 - alpha)) / 255) as u8;
+    }
+}
+
+/// Draw the part of `buffer` inside `clip`. `origin` is where the top left
+/// corner of the buffer goes on screen. The first `titlebar_height` rows use
+/// `titlebar_alpha`, the rest `alpha`.
+pub fn composite(
+    framebuffer: &mut FrameBufferWriter,
+    buffer: &OffscreenBuffer,
+    origin: (usize, usize),
+    clip: Rect,
+    alpha: u8,
+    titlebar_height: usize,
+    titlebar_alpha: u8,
+) {
+    let bounds = Rect::new(origin.0, origin.1, buffer.width(), buffer.height());
+    let Some(area) = bounds.intersection(&clip) else {
+        return;
+    };
+    let local_x = area.x - origin.0;
+
+    for y in area.y..area.y + area.height {
+        let local_y = y - origin.1;
+        let alpha = if local_y < titlebar_height {
+            titlebar_alpha
+        } else {
+            alpha
+        };
+        if alpha == 0 {
+            continue;
+        }
+
+        let src = buffer
+            .writer
+            .read_raw_pixel_row(local_x, local_y, area.width);
+        if alpha == 255 {
+            framebuffer.write_raw_pixel_row(area.x, y, &src);
+        } else {
+            let mut dst = framebuffer.read_raw_pixel_row(area.x, y, area.width);
+            blend_row(&mut dst, &src, alpha);
+            framebuffer.write_raw_pixel_row(area.x, y, &dst);
+        }
+    }
+}
+
+/// Area darkened by the shadow of a window with the given bounds
+pub fn shadow_bounds(bounds: Rect, size: usize) -> Rect {
+    Rect::new(
+        bounds.x,
+        bounds.y,
+        bounds.width + size,
+        bounds.height + size,
+    )
+}
+
+/// Darken the screen around `bounds` as if the window floated above it. The
+/// shadow is pushed towards the bottom right by half of `size` and fades out
+/// over the other half. Pixels under the window itself are left alone.
+pub fn draw_shadow(framebuffer: &mut FrameBufferWriter, bounds: Rect, size: usize, clip: Rect) {
+    let offset = size / 2;
+    if offset == 0 {
+        return;
+    }
+
+    let Some(area) = shadow_bounds(bounds, size).intersection(&clip) else {
+        return;
+    };
+    if bounds.contains_rect(&area) {
+        return;
+    }
+
+    // The window moved by `offset`, fully dark
+    let core = Rect::new(
+        bounds.x + offset,
+        bounds.y + offset,
+        bounds.width,
+        bounds.height,
+    );
+    let distance = |value: usize, start: usize, len: usize| {
+        if value < start {
+            start - value
+        } else if value >= start + len {
+            value - (start + len) + 1
+        } else {
+            0
+        }
+    };
+    let bytes_per_pixel = framebuffer.bytes_per_pixel();
+
+    for y in area.y..area.y + area.height {
+        let dy = distance(y, core.y, core.height);
+        if dy > offset {
+            continue;
+        }
+
+        let mut row = framebuffer.read_raw_pixel_row(area.x, y, area.width);
+        for (i, pixel) in row.chunks_exact_mut(bytes_per_pixel).enumerate() {
+            let x = area.x + i;
+            if bounds.contains_point(x, y) {
+                continue;
+            }
+
+            // Rounded-ish corners: the larger distance plus half the smaller one
+            let dx = distance(x, core.x, core.width);
+            let fade = dx.max(dy) + dx.min(dy) / 2;
+            if fade > offset {
+                continue;
+            }
+
+            let strength = SHADOW_STRENGTH * (offset + 1 - fade) / (offset + 1);
+            for channel in pixel.iter_mut() {
+                *channel = (*channel as usize * (255 - strength) / 255) as u8;
+            }
+        }
+        framebuffer.write_raw_pixel_row(area.x, y, &row);
+    }
+}
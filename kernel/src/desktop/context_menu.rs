@@ -0,0 +1,192 @@
+use alloc::vec::Vec;
+
+use crate::{desktop::theme, framebuffer::FrameBufferWriter, surface::Rect};
+
+const ITEM_HEIGHT: usize = 22;
+const SEPARATOR_HEIGHT: usize = 8;
+const MIN_MENU_WIDTH: usize = 120;
+const TEXT_PADDING: usize = 12;
+const CHAR_WIDTH: usize = 7;
+
+/// Who opened a context menu, decides where the picked action goes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuOwner {
+    /// Desktop background, actions are run by the desktop loop
+    Desktop,
+    /// Window content, actions go to `Application::handle_context_menu_action`
+    Window(usize),
+    /// Taskbar button of a window, handled by the window manager
+    Taskbar(usize),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MenuItem {
+    pub label: &'static str,
+    /// Passed back to the owner when the item is picked
+    pub action: &'static str,
+    pub enabled: bool,
+}
+
+impl MenuItem {
+    pub const fn new(label: &'static str, action: &'static str) -> Self {
+        MenuItem {
+            label,
+            action,
+            enabled: true,
+        }
+    }
+
+    pub const fn separator() -> Self {
+        MenuItem {
+            label: "",
+            action: "",
+            enabled: false,
+        }
+    }
+
+    pub const fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    fn is_separator(&self) -> bool {
+        self.action.is_empty()
+    }
+
+    fn height(&self) -> usize {
+        if self.is_separator() {
+            SEPARATOR_HEIGHT
+        } else {
+            ITEM_HEIGHT
+        }
+    }
+}
+
+/// Popup list of actions. The window manager draws it above every window
+/// and closes it on the next click.
+pub struct ContextMenu {
+    items: Vec<MenuItem>,
+    x: usize,
+    y: usize,
+    hovered: Option<usize>,
+    pub needs_redraw: bool,
+}
+
+impl ContextMenu {
+    pub fn new(items: Vec<MenuItem>) -> Self {
+        ContextMenu {
+            items,
+            x: 0,
+            y: 0,
+            hovered: None,
+            needs_redraw: true,
+        }
+    }
+
+    /// Put the top-left corner at the pointer, moved back so it stays on screen
+    pub fn place(&mut self, x: usize, y: usize, screen_width: usize, screen_height: usize) {
+        let (width, height) = self.size();
+        self.x = x.min(screen_width.saturating_sub(width));
+        self.y = y.min(screen_height.saturating_sub(height));
+        self.needs_redraw = true;
+    }
+
+    fn size(&self) -> (usize, usize) {
+        let longest = self
+            .items
+            .iter()
+            .map(|item| item.label.len())
+            .max()
+            .unwrap_or(0);
+        let width = (longest * CHAR_WIDTH + 2 * TEXT_PADDING).max(MIN_MENU_WIDTH);
+        let height = self.items.iter().map(|item| item.height()).sum::<usize>() + 2;
+        (width, height)
+    }
+
+    pub fn bounds(&self) -> Rect {
+        let (width, height) = self.size();
+        Rect::new(self.x, self.y, width, height)
+    }
+
+    /// Enabled item under a screen position
+    pub fn item_at(&self, x: usize, y: usize) -> Option<usize> {
+        if !self.bounds().contains_point(x, y) {
+            return None;
+        }
+
+        let mut item_y = self.y + 1;
+        for (i, item) in self.items.iter().enumerate() {
+            if y >= item_y && y < item_y + item.height() {
+                return item.enabled.then_some(i);
+            }
+            item_y += item.height();
+        }
+        None
+    }
+
+    pub fn action(&self, index: usize) -> Option<&'static str> {
+        self.items.get(index).map(|item| item.action)
+    }
+
+    /// Track the pointer; returns true if the highlighted item changed
+    pub fn update_hover(&mut self, x: usize, y: usize) -> bool {
+        let hovered = self.item_at(x, y);
+        if hovered == self.hovered {
+            return false;
+        }
+
+        self.hovered = hovered;
+        self.needs_redraw = true;
+        true
+    }
+
+    pub fn render(&mut self, framebuffer: &mut FrameBufferWriter) {
+        let bounds = self.bounds();
+        let right = bounds.x + bounds.width - 1;
+        let bottom = bounds.y + bounds.height - 1;
+        let theme = theme::current();
+
+        framebuffer.draw_rect((bounds.x, bounds.y), (right, bottom), theme.menu_background);
+        framebuffer.draw_rect_outline((bounds.x, bounds.y), (right, bottom), theme.border);
+
+        let mut item_y = bounds.y + 1;
+        for (i, item) in self.items.iter().enumerate() {
+            if item.is_separator() {
+                let line_y = item_y + SEPARATOR_HEIGHT / 2;
+                framebuffer.draw_line((bounds.x + 4, line_y), (right - 4, line_y), theme.separator);
+            } else {
+                let hovered = self.hovered == Some(i);
+                let bg = if hovered {
+                    theme.selection
+                } else {
+                    theme.menu_background
+                };
+                let fg = if !item.enabled {
+                    theme.disabled_text
+                } else if hovered {
+                    theme.selection_text
+                } else {
+                    theme.text
+                };
+
+                framebuffer.draw_rect(
+                    (bounds.x + 1, item_y),
+                    (right - 1, item_y + ITEM_HEIGHT - 1),
+                    bg,
+                );
+                framebuffer.draw_raw_text(
+                    item.label,
+                    bounds.x + TEXT_PADDING,
+                    item_y + 3,
+                    fg,
+                    bg,
+                    theme.font_weight,
+                    theme.font_size,
+                );
+            }
+            item_y += item.height();
+        }
+
+        self.needs_redraw = false;
+    }
+}
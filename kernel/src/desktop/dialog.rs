@@ -0,0 +1,203 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use pc_keyboard::KeyCode;
+
+use crate::{desktop::theme, framebuffer::FrameBufferWriter, surface::Rect};
+
+const DIALOG_WIDTH: usize = 320;
+const DIALOG_HEIGHT: usize = 120;
+const DIALOG_TITLE_HEIGHT: usize = 20;
+const DIALOG_BUTTON_WIDTH: usize = 90;
+const DIALOG_BUTTON_HEIGHT: usize = 25;
+const DIALOG_BUTTON_SPACING: usize = 10;
+const CHAR_WIDTH: usize = 7;
+
+/// What a close request turns into, see [`crate::desktop::application::Application::on_close_requested`]
+pub enum CloseRequest {
+    /// Close the window right away
+    Close,
+    /// Keep the window open
+    Cancel,
+    /// Ask the user first; the answer goes to `on_close_dialog_result`
+    Ask(ModalDialog),
+}
+
+/// Message box drawn by the window manager on top of its parent window.
+/// While it is open the parent gets no input.
+pub struct ModalDialog {
+    pub title: String,
+    pub message: String,
+    pub buttons: Vec<&'static str>,
+    /// Button picked with Enter
+    pub default_button: usize,
+    /// Button picked with Escape
+    pub cancel_button: usize,
+    focused_button: usize,
+    pub needs_redraw: bool,
+}
+
+impl ModalDialog {
+    pub fn new(title: &str, message: &str, buttons: &[&'static str]) -> Self {
+        Self {
+            title: title.to_string(),
+            message: message.to_string(),
+            buttons: buttons.to_vec(),
+            default_button: 0,
+            cancel_button: buttons.len().saturating_sub(1),
+            focused_button: 0,
+            needs_redraw: true,
+        }
+    }
+
+    /// Standard "Save changes?" prompt. Buttons: 0 = Save, 1 = Don't Save, 2 = Cancel.
+    pub fn save_changes(name: &str) -> Self {
+        Self::new(
+            "Save changes?",
+            &alloc::format!("Save changes to {} before closing?", name),
+            &["Save", "Don't Save", "Cancel"],
+        )
+    }
+
+    /// Screen bounds of the dialog, centered on the parent content area
+    pub fn bounds(&self, parent: Rect) -> Rect {
+        let width = DIALOG_WIDTH.min(parent.width);
+        let height = DIALOG_HEIGHT.min(parent.height);
+
+        Rect::new(
+            parent.x + (parent.width - width) / 2,
+            parent.y + (parent.height - height) / 2,
+            width,
+            height,
+        )
+    }
+
+    fn button_bounds(&self, parent: Rect, index: usize) -> Rect {
+        let bounds = self.bounds(parent);
+        let count = self.buttons.len();
+        let total_width =
+            count * DIALOG_BUTTON_WIDTH + count.saturating_sub(1) * DIALOG_BUTTON_SPACING;
+        let start_x = bounds.x + bounds.width.saturating_sub(total_width) / 2;
+
+        Rect::new(
+            start_x + index * (DIALOG_BUTTON_WIDTH + DIALOG_BUTTON_SPACING),
+            bounds.y + bounds.height - DIALOG_BUTTON_HEIGHT - 10,
+            DIALOG_BUTTON_WIDTH,
+            DIALOG_BUTTON_HEIGHT,
+        )
+    }
+
+    /// Button under a screen position
+    pub fn button_at(&self, parent: Rect, x: usize, y: usize) -> Option<usize> {
+        (0..self.buttons.len()).find(|&i| self.button_bounds(parent, i).contains_point(x, y))
+    }
+
+    /// Handle typed characters: Enter picks the focused button, Escape cancels, Tab cycles
+    pub fn handle_char_input(&mut self, c: char) -> Option<usize> {
+        match c {
+            '\n' | '\r' => Some(self.focused_button),
+            '\x1b' => Some(self.cancel_button),
+            '\t' => {
+                self.focus_next(1);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    pub fn handle_key_input(&mut self, key: KeyCode) -> Option<usize> {
+        match key {
+            KeyCode::Return => Some(self.focused_button),
+            KeyCode::Escape => Some(self.cancel_button),
+            KeyCode::ArrowRight => {
+                self.focus_next(1);
+                None
+            }
+            KeyCode::ArrowLeft => {
+                self.focus_next(self.buttons.len().saturating_sub(1));
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn focus_next(&mut self, step: usize) {
+        if !self.buttons.is_empty() {
+            self.focused_button = (self.focused_button + step) % self.buttons.len();
+            self.needs_redraw = true;
+        }
+    }
+
+    pub fn render(&mut self, framebuffer: &mut FrameBufferWriter, parent: Rect) {
+        let bounds = self.bounds(parent);
+        let right = bounds.x + bounds.width - 1;
+        let bottom = bounds.y + bounds.height - 1;
+        let theme = theme::current();
+
+        // Body and titlebar
+        framebuffer.draw_rect(
+            (bounds.x, bounds.y),
+            (right, bottom),
+            theme.dialog_background,
+        );
+        framebuffer.draw_rect(
+            (bounds.x, bounds.y),
+            (right, bounds.y + DIALOG_TITLE_HEIGHT),
+            theme.titlebar,
+        );
+        framebuffer.draw_rect_outline((bounds.x, bounds.y), (right, bottom), theme.border);
+
+        framebuffer.draw_raw_text(
+            &self.title,
+            bounds.x + 6,
+            bounds.y + 2,
+            theme.title_text,
+            theme.titlebar,
+            theme.title_font_weight,
+            theme.font_size,
+        );
+
+        // Cut the message to what fits, dialogs are meant for short questions
+        let max_chars = bounds.width.saturating_sub(20) / CHAR_WIDTH;
+        let message: String = self.message.chars().take(max_chars).collect();
+        framebuffer.draw_raw_text(
+            &message,
+            bounds.x + 10,
+            bounds.y + DIALOG_TITLE_HEIGHT + 15,
+            theme.text,
+            theme.dialog_background,
+            theme.font_weight,
+            theme.font_size,
+        );
+
+        for (i, label) in self.buttons.iter().enumerate() {
+            let button = self.button_bounds(parent, i);
+            let (bg, fg) = if i == self.focused_button {
+                (theme.selection, theme.selection_text)
+            } else {
+                (theme.control, theme.control_text)
+            };
+            let button_right = button.x + button.width - 1;
+            let button_bottom = button.y + button.height - 1;
+
+            framebuffer.draw_rect((button.x, button.y), (button_right, button_bottom), bg);
+            framebuffer.draw_rect_outline(
+                (button.x, button.y),
+                (button_right, button_bottom),
+                theme.control_border,
+            );
+            framebuffer.draw_raw_text(
+                label,
+                button.x + button.width.saturating_sub(label.len() * CHAR_WIDTH) / 2,
+                button.y + 5,
+                fg,
+                bg,
+                theme.font_weight,
+                theme.font_size,
+            );
+        }
+
+        self.needs_redraw = false;
+    }
+}
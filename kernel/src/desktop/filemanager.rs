@@ -1,22 +1,35 @@
 use alloc::{
     format,
     string::{String, ToString},
+    vec,
     vec::Vec,
 };
 use pc_keyboard::KeyCode;
 
 use crate::{
-    desktop::{application::Application, input::add_file_open_request},
-    framebuffer::Color,
+    debug,
+    desktop::{
+        application::Application,
+        clipboard::{self, ClipboardEntry, ClipboardOperation},
+        context_menu::{ContextMenu, MenuItem},
+        dialog::ModalDialog,
+        input::add_file_open_request,
+        mouse::{MouseEvent, MouseEventKind},
+        shortcuts::{Accelerator, Modifiers},
+        theme,
+    },
+    error,
     fs::{
         fat32::FileEntry,
         manager::{
-            copy_directory, copy_file, create_directory, create_file, delete_directory,
-            delete_file, list_directory, move_item, rename_item,
+            FsEventKind, WatchId, copy_directory, copy_file, create_directory, create_file,
+            list_directory, move_item, path_exists, poll_events, rename_item, rewatch, unwatch,
+            watch,
         },
+        search::{FindMatch, FindQuery, Finder},
+        trash::{self, TrashEntry},
     },
-    serial_println,
-    surface::{Shape, Surface},
+    surface::{Rect, Shape, Surface},
 };
 use noto_sans_mono_bitmap::{FontWeight, RasterHeight};
 
@@ -25,6 +38,8 @@ const FILE_ENTRY_HEIGHT: usize = 20;
 const BUTTON_HEIGHT: usize = 25;
 const MARGIN: usize = 10;
 const TEXT_INPUT_HEIGHT: usize = 25;
+const SEARCH_LIST_Y: usize = 70;
+const WHEEL_ROWS: isize = 3;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum FileManagerMode {
@@ -35,20 +50,8 @@ pub enum FileManagerMode {
     DeleteFolder,
     ViewFile(FileEntry),
     Rename(FileEntry),
-}
-
-#[derive(Clone, Debug, PartialEq)]
-pub enum ClipboardOperation {
-    Copy,
-    Cut,
-}
-
-#[derive(Clone, Debug)]
-pub struct ClipboardEntry {
-    pub file_path: String,
-    pub file_name: String,
-    pub is_directory: bool,
-    pub operation: ClipboardOperation,
+    Search,
+    Trash,
 }
 
 #[derive(Clone, Debug)]
@@ -67,13 +70,29 @@ pub struct FileManager {
     open_file_options: Option<Vec<(usize, String)>>, // Y offset, name
     selected_open_file_app: Option<String>,
 
-    // Clipboard for copy/cut/paste operations
-    clipboard: Option<ClipboardEntry>,
-
     // Directory navigation - now path-based
     current_path: String,
     directory_stack: Vec<DirectoryPath>,
 
+    // Search mode: the running walk and what it found so far
+    search_finder: Option<Finder>,
+    search_results: Vec<FindMatch>,
+    selected_search_result: Option<usize>,
+    search_btn_idx: Option<usize>,
+
+    // Trash view
+    trash_entries: Vec<TrashEntry>,
+    selected_trash_entry: Option<usize>,
+    trash_scroll: usize,
+    trash_btn_idx: Option<usize>,
+
+    // Confirmation waiting to be opened by the window manager
+    pending_dialog: Option<ModalDialog>,
+
+    // Watch on current_path so changes made by other apps show up live
+    dir_watch: WatchId,
+    needs_refresh: bool,
+
     // UI element indices
     status_text_idx: Option<usize>,
     input_text_idx: Option<usize>,
@@ -102,8 +121,30 @@ pub struct FileManager {
     ui_initialized: bool,
 }
 
+/// Apps that can open `file_name`: the one to use by default, if any, and the others.
+/// Shared with the shell's `open` command.
+pub fn recommended_apps(file_name: &str) -> (Option<&'static str>, Vec<&'static str>) {
+    let recomended = if file_name.to_lowercase().ends_with(".txt") {
+        Some("notepad")
+    } else {
+        None
+    };
+
+    let other: Vec<&'static str> = if let Some(rec) = recomended {
+        match rec {
+            "notepad" => ["calculator"].to_vec(),
+            _ => ["notepad", "calculator"].to_vec(),
+        }
+    } else {
+        ["notepad", "calculator"].to_vec()
+    };
+
+    (recomended, other)
+}
+
 impl FileManager {
-    pub fn new(_args: Option<String>) -> Self {
+    /// `args` is an optional directory to start in instead of the root
+    pub fn new(args: Option<String>) -> Self {
         let mut fm = Self {
             mode: FileManagerMode::Browse,
             files: Vec::new(),
@@ -114,13 +155,25 @@ impl FileManager {
             open_file_options: None,
             selected_open_file_app: None,
 
-            // Initialize clipboard as empty
-            clipboard: None,
-
             // Initialize at root directory
             current_path: "/".to_string(),
             directory_stack: Vec::new(),
 
+            search_finder: None,
+            search_results: Vec::new(),
+            selected_search_result: None,
+            search_btn_idx: None,
+
+            trash_entries: Vec::new(),
+            selected_trash_entry: None,
+            trash_scroll: 0,
+            trash_btn_idx: None,
+
+            pending_dialog: None,
+
+            dir_watch: watch("/"),
+            needs_refresh: false,
+
             status_text_idx: None,
             input_text_idx: None,
             breadcrumb_text_idx: None,
@@ -146,43 +199,33 @@ impl FileManager {
             ui_initialized: false,
         };
 
-        fm.refresh_file_list();
+        match args {
+            Some(path) if matches!(path_exists(&path), Ok(Some(true))) => fm.navigate_to_path(&path),
+            _ => fm.refresh_file_list(),
+        }
         fm
     }
 
-    fn load_recomended_open_list(
-        &self,
-        file_name: &String,
-    ) -> (Option<&'static str>, Vec<&'static str>) {
-        let recomended = if file_name.to_lowercase().ends_with(".txt") {
-            Some("notepad")
-        } else {
-            None
-        };
-
-        let other: Vec<&'static str> = if let Some(rec) = recomended {
-            match rec {
-                "notepad" => ["calculator"].to_vec(),
-                _ => ["notepad", "calculator"].to_vec(),
-            }
-        } else {
-            ["notepad", "calculator"].to_vec()
-        };
-
-        (recomended, other)
-    }
-
     fn refresh_file_list(&mut self) {
+        // Follow the current directory; this also drops events we already account for
+        rewatch(self.dir_watch, &self.current_path);
+        self.needs_refresh = false;
+
         match list_directory(&self.current_path) {
             Ok(files) => {
-                // Include both files and directories, filter out "." entry
-                self.files = files.into_iter().filter(|f| f.name != ".").collect();
+                // Include both files and directories, filter out "." and the trash,
+                // which has its own view
+                let is_root = self.current_path == "/";
+                self.files = files
+                    .into_iter()
+                    .filter(|f| f.name != "." && !(is_root && f.name == trash::TRASH_DIR[1..]))
+                    .collect();
                 self.status_message = format!("Found {} items", self.files.len());
-                serial_println!("File Manager: Found {} items", self.files.len());
+                debug!("File Manager: Found {} items", self.files.len());
             }
             Err(e) => {
                 self.status_message = format!("Error: {}", e);
-                serial_println!("File Manager: Error listing directory: {}", e);
+                error!("File Manager: Error listing directory: {}", e);
             }
         }
     }
@@ -198,6 +241,8 @@ impl FileManager {
             FileManagerMode::DeleteFolder => self.setup_delete_folder_ui(surface),
             FileManagerMode::ViewFile(_) => self.setup_view_file_ui(surface),
             FileManagerMode::Rename(_) => self.setup_rename_ui(surface),
+            FileManagerMode::Search => self.setup_search_ui(surface),
+            FileManagerMode::Trash => self.setup_trash_ui(surface),
         }
 
         self.ui_initialized = true;
@@ -220,6 +265,7 @@ impl FileManager {
 
     /// Update file selection highlighting without rebuilding the entire UI
     pub fn update_file_selection(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
         if !self.ui_initialized || self.mode != FileManagerMode::Browse {
             return;
         }
@@ -228,7 +274,7 @@ impl FileManager {
         if let Some(prev_idx) = self.previous_selected_file {
             if prev_idx < self.file_list_shapes.len() {
                 let (bg_idx, _) = self.file_list_shapes[prev_idx];
-                surface.update_rectangle_color(bg_idx, Color::WHITE);
+                surface.update_rectangle_color(bg_idx, theme.input_background);
             }
         }
 
@@ -236,7 +282,7 @@ impl FileManager {
         if let Some(curr_idx) = self.selected_file_index {
             if curr_idx < self.file_list_shapes.len() {
                 let (bg_idx, _) = self.file_list_shapes[curr_idx];
-                surface.update_rectangle_color(bg_idx, Color::new(150, 200, 255));
+                surface.update_rectangle_color(bg_idx, theme.selection);
             }
         }
 
@@ -265,6 +311,8 @@ impl FileManager {
         self.cut_btn_idx = None;
         self.paste_btn_idx = None;
         self.rename_btn_idx = None;
+        self.search_btn_idx = None;
+        self.trash_btn_idx = None;
 
         self.file_list_shapes.clear();
         self.previous_selected_file = None;
@@ -272,6 +320,7 @@ impl FileManager {
     }
 
     fn setup_browse_ui(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
         let width = surface.width;
         let height = surface.height;
 
@@ -281,8 +330,8 @@ impl FileManager {
             x: MARGIN,
             y: 10,
             content: breadcrumb_text,
-            color: Color::BLACK,
-            background_color: Color::new(240, 240, 240),
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -295,7 +344,7 @@ impl FileManager {
                 y: 8,
                 width: 50,
                 height: 20,
-                color: Color::new(200, 200, 255),
+                color: theme.control,
                 filled: true,
                 hide: false,
             }));
@@ -305,7 +354,7 @@ impl FileManager {
                 y: 8,
                 width: 50,
                 height: 20,
-                color: Color::BLACK,
+                color: theme.control_border,
                 filled: false,
                 hide: false,
             });
@@ -314,8 +363,8 @@ impl FileManager {
                 x: width - 55,
                 y: 10,
                 content: "Up".to_string(),
-                color: Color::BLACK,
-                background_color: Color::new(200, 200, 255),
+                color: theme.control_text,
+                background_color: theme.control,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -328,7 +377,7 @@ impl FileManager {
         //     y: 40,
         //     width: width - 2 * MARGIN,
         //     height: FILE_LIST_HEIGHT,
-        //     color: Color::WHITE,
+        //     color: theme.input_background,
         //     filled: true,
         //     hide: false,
         // });
@@ -356,9 +405,9 @@ impl FileManager {
         {
             let y_pos = 45 + (i - self.scroll_offset) * FILE_ENTRY_HEIGHT;
             let bg_color = if Some(i) == self.selected_file_index {
-                Color::new(150, 200, 255)
+                theme.selection
             } else {
-                Color::WHITE
+                theme.input_background
             };
 
             // File entry background
@@ -389,7 +438,7 @@ impl FileManager {
                 x: MARGIN + 5,
                 y: y_pos + 3,
                 content: display_name,
-                color: Color::BLACK,
+                color: theme.text,
                 background_color: bg_color,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
@@ -410,7 +459,7 @@ impl FileManager {
                     x: width - 80,
                     y: y_pos + 3,
                     content: size_text,
-                    color: Color::BLACK,
+                    color: theme.text,
                     background_color: bg_color,
                     font_size: RasterHeight::Size16,
                     font_weight: FontWeight::Regular,
@@ -431,7 +480,7 @@ impl FileManager {
             y: button_y,
             width: 85,
             height: BUTTON_HEIGHT,
-            color: Color::new(220, 220, 220),
+            color: theme.control,
             filled: true,
             hide: false,
         }));
@@ -441,7 +490,7 @@ impl FileManager {
             y: button_y,
             width: 85,
             height: BUTTON_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -450,8 +499,8 @@ impl FileManager {
             x: MARGIN + 8,
             y: button_y + 5,
             content: "New File".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(220, 220, 220),
+            color: theme.control_text,
+            background_color: theme.control,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -463,7 +512,7 @@ impl FileManager {
             y: button_y,
             width: 95,
             height: BUTTON_HEIGHT,
-            color: Color::new(180, 220, 255),
+            color: theme.control,
             filled: true,
             hide: false,
         }));
@@ -473,7 +522,7 @@ impl FileManager {
             y: button_y,
             width: 95,
             height: BUTTON_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -482,8 +531,8 @@ impl FileManager {
             x: MARGIN + 102,
             y: button_y + 5,
             content: "New Folder".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(180, 220, 255),
+            color: theme.control_text,
+            background_color: theme.control,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -495,7 +544,7 @@ impl FileManager {
             y: button_y,
             width: 75,
             height: BUTTON_HEIGHT,
-            color: Color::new(255, 180, 180),
+            color: theme.control,
             filled: true,
             hide: false,
         }));
@@ -505,7 +554,7 @@ impl FileManager {
             y: button_y,
             width: 75,
             height: BUTTON_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -514,8 +563,8 @@ impl FileManager {
             x: MARGIN + 215,
             y: button_y + 5,
             content: "Delete".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(255, 180, 180),
+            color: theme.control_text,
+            background_color: theme.control,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -527,7 +576,7 @@ impl FileManager {
             y: button_y,
             width: 65,
             height: BUTTON_HEIGHT,
-            color: Color::new(180, 255, 180),
+            color: theme.control,
             filled: true,
             hide: false,
         }));
@@ -537,7 +586,7 @@ impl FileManager {
             y: button_y,
             width: 65,
             height: BUTTON_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -546,8 +595,8 @@ impl FileManager {
             x: MARGIN + 305,
             y: button_y + 5,
             content: "Open".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(180, 255, 180),
+            color: theme.control_text,
+            background_color: theme.control,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -562,7 +611,7 @@ impl FileManager {
             y: button_y2,
             width: 60,
             height: BUTTON_HEIGHT,
-            color: Color::new(200, 255, 200),
+            color: theme.control,
             filled: true,
             hide: false,
         }));
@@ -572,7 +621,7 @@ impl FileManager {
             y: button_y2,
             width: 60,
             height: BUTTON_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -581,8 +630,8 @@ impl FileManager {
             x: MARGIN + 15,
             y: button_y2 + 5,
             content: "Copy".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(200, 255, 200),
+            color: theme.control_text,
+            background_color: theme.control,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -594,7 +643,7 @@ impl FileManager {
             y: button_y2,
             width: 55,
             height: BUTTON_HEIGHT,
-            color: Color::new(255, 220, 150),
+            color: theme.control,
             filled: true,
             hide: false,
         }));
@@ -604,7 +653,7 @@ impl FileManager {
             y: button_y2,
             width: 55,
             height: BUTTON_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -613,18 +662,18 @@ impl FileManager {
             x: MARGIN + 88,
             y: button_y2 + 5,
             content: "Cut".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(255, 220, 150),
+            color: theme.control_text,
+            background_color: theme.control,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
         });
 
         // Paste button (only enabled if clipboard has content)
-        let paste_color = if self.clipboard.is_some() {
-            Color::new(255, 200, 255)
+        let paste_text = if clipboard::file().is_some() {
+            theme.control_text
         } else {
-            Color::new(200, 200, 200)
+            theme.disabled_text
         };
 
         self.paste_btn_idx = Some(surface.add_shape(Shape::Rectangle {
@@ -632,7 +681,7 @@ impl FileManager {
             y: button_y2,
             width: 70,
             height: BUTTON_HEIGHT,
-            color: paste_color,
+            color: theme.control,
             filled: true,
             hide: false,
         }));
@@ -642,7 +691,7 @@ impl FileManager {
             y: button_y2,
             width: 70,
             height: BUTTON_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -651,8 +700,8 @@ impl FileManager {
             x: MARGIN + 150,
             y: button_y2 + 5,
             content: "Paste".to_string(),
-            color: Color::BLACK,
-            background_color: paste_color,
+            color: paste_text,
+            background_color: theme.control,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -664,7 +713,7 @@ impl FileManager {
             y: button_y2,
             width: 80,
             height: BUTTON_HEIGHT,
-            color: Color::new(200, 200, 255),
+            color: theme.control,
             filled: true,
             hide: false,
         }));
@@ -674,7 +723,7 @@ impl FileManager {
             y: button_y2,
             width: 80,
             height: BUTTON_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -683,20 +732,38 @@ impl FileManager {
             x: MARGIN + 230,
             y: button_y2 + 5,
             content: "Rename".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(200, 200, 255),
+            color: theme.control_text,
+            background_color: theme.control,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
         });
 
+        // Search button
+        self.search_btn_idx = Some(self.add_button(
+            surface,
+            MARGIN + 305,
+            button_y2,
+            70,
+            "Search",
+        ));
+
+        // Trash button
+        self.trash_btn_idx = Some(self.add_button(
+            surface,
+            MARGIN + 385,
+            button_y2,
+            60,
+            "Trash",
+        ));
+
         // Status bar
         self.status_text_idx = Some(surface.add_shape(Shape::Text {
             x: MARGIN,
             y: height - 25,
             content: self.status_message.clone(),
-            color: Color::BLACK,
-            background_color: Color::new(240, 240, 240),
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -704,6 +771,7 @@ impl FileManager {
     }
 
     fn setup_new_folder_ui(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
         let width = surface.width;
         let height = surface.height;
 
@@ -712,8 +780,8 @@ impl FileManager {
             x: MARGIN,
             y: 50,
             content: "Create New Folder".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(240, 240, 240),
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Bold,
             hide: false,
@@ -724,8 +792,8 @@ impl FileManager {
             x: MARGIN,
             y: 80,
             content: "Folder name:".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(240, 240, 240),
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -737,7 +805,7 @@ impl FileManager {
             y: 100,
             width: width - 2 * MARGIN,
             height: TEXT_INPUT_HEIGHT,
-            color: Color::WHITE,
+            color: theme.input_background,
             filled: true,
             hide: false,
         });
@@ -747,7 +815,7 @@ impl FileManager {
             y: 100,
             width: width - 2 * MARGIN,
             height: TEXT_INPUT_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -757,8 +825,8 @@ impl FileManager {
             x: MARGIN + 5,
             y: 105,
             content: format!("{}_", self.input_text),
-            color: Color::BLACK,
-            background_color: Color::WHITE,
+            color: theme.text,
+            background_color: theme.input_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -773,7 +841,7 @@ impl FileManager {
             y: button_y,
             width: 80,
             height: BUTTON_HEIGHT,
-            color: Color::new(180, 255, 180),
+            color: theme.control,
             filled: true,
             hide: false,
         }));
@@ -783,7 +851,7 @@ impl FileManager {
             y: button_y,
             width: 80,
             height: BUTTON_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -792,8 +860,8 @@ impl FileManager {
             x: MARGIN + 20,
             y: button_y + 5,
             content: "Create".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(180, 255, 180),
+            color: theme.control_text,
+            background_color: theme.control,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -805,7 +873,7 @@ impl FileManager {
             y: button_y,
             width: 80,
             height: BUTTON_HEIGHT,
-            color: Color::new(220, 220, 220),
+            color: theme.control,
             filled: true,
             hide: false,
         }));
@@ -815,7 +883,7 @@ impl FileManager {
             y: button_y,
             width: 80,
             height: BUTTON_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -824,8 +892,8 @@ impl FileManager {
             x: MARGIN + 120,
             y: button_y + 5,
             content: "Cancel".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(220, 220, 220),
+            color: theme.control_text,
+            background_color: theme.control,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -836,8 +904,8 @@ impl FileManager {
             x: MARGIN,
             y: height - 25,
             content: self.status_message.clone(),
-            color: Color::BLACK,
-            background_color: Color::new(240, 240, 240),
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -845,6 +913,7 @@ impl FileManager {
     }
 
     fn setup_delete_folder_ui(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
         let height = surface.height;
 
         let selected_name = if let Some(index) = self.selected_file_index {
@@ -862,8 +931,8 @@ impl FileManager {
             x: MARGIN,
             y: 50,
             content: "Delete Folder".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(240, 240, 240),
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Bold,
             hide: false,
@@ -877,8 +946,8 @@ impl FileManager {
                 "Are you sure you want to delete folder '{}'?",
                 selected_name
             ),
-            color: Color::BLACK,
-            background_color: Color::new(240, 240, 240),
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -887,9 +956,9 @@ impl FileManager {
         surface.add_shape(Shape::Text {
             x: MARGIN,
             y: 100,
-            content: "Warning: This will only work if the folder is empty!".to_string(),
-            color: Color::new(200, 0, 0),
-            background_color: Color::new(240, 240, 240),
+            content: "The folder and its contents will be moved to the Trash".to_string(),
+            color: theme.error_text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -904,7 +973,7 @@ impl FileManager {
             y: button_y,
             width: 100,
             height: BUTTON_HEIGHT,
-            color: Color::new(255, 180, 180),
+            color: theme.control,
             filled: true,
             hide: false,
         }));
@@ -914,7 +983,7 @@ impl FileManager {
             y: button_y,
             width: 100,
             height: BUTTON_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -923,8 +992,8 @@ impl FileManager {
             x: MARGIN + 10,
             y: button_y + 5,
             content: "Delete Folder".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(255, 180, 180),
+            color: theme.control_text,
+            background_color: theme.control,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -936,7 +1005,7 @@ impl FileManager {
             y: button_y,
             width: 80,
             height: BUTTON_HEIGHT,
-            color: Color::new(220, 220, 220),
+            color: theme.control,
             filled: true,
             hide: false,
         }));
@@ -946,7 +1015,7 @@ impl FileManager {
             y: button_y,
             width: 80,
             height: BUTTON_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -955,8 +1024,8 @@ impl FileManager {
             x: MARGIN + 135,
             y: button_y + 5,
             content: "Cancel".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(220, 220, 220),
+            color: theme.control_text,
+            background_color: theme.control,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -967,8 +1036,8 @@ impl FileManager {
             x: MARGIN,
             y: height - 25,
             content: self.status_message.clone(),
-            color: Color::BLACK,
-            background_color: Color::new(240, 240, 240),
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -994,7 +1063,7 @@ impl FileManager {
     }
 
     pub fn navigate_into_directory(&mut self, dir_name: &str) -> Result<(), &'static str> {
-        serial_println!("Navigating into directory: {}", dir_name);
+        debug!("Navigating into directory: {}", dir_name);
 
         if dir_name == ".." {
             return self.navigate_up();
@@ -1037,6 +1106,7 @@ impl FileManager {
     }
 
     fn setup_new_file_ui(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
         let width = surface.width;
         let height = surface.height;
 
@@ -1045,8 +1115,8 @@ impl FileManager {
             x: MARGIN,
             y: 50,
             content: "Create New File".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(240, 240, 240),
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Bold,
             hide: false,
@@ -1057,8 +1127,8 @@ impl FileManager {
             x: MARGIN,
             y: 80,
             content: "Filename:".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(240, 240, 240),
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -1070,7 +1140,7 @@ impl FileManager {
             y: 100,
             width: width - 2 * MARGIN,
             height: TEXT_INPUT_HEIGHT,
-            color: Color::WHITE,
+            color: theme.input_background,
             filled: true,
             hide: false,
         });
@@ -1080,7 +1150,7 @@ impl FileManager {
             y: 100,
             width: width - 2 * MARGIN,
             height: TEXT_INPUT_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -1090,8 +1160,8 @@ impl FileManager {
             x: MARGIN + 5,
             y: 105,
             content: format!("{}_", self.input_text),
-            color: Color::BLACK,
-            background_color: Color::WHITE,
+            color: theme.text,
+            background_color: theme.input_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -1106,7 +1176,7 @@ impl FileManager {
             y: button_y,
             width: 80,
             height: BUTTON_HEIGHT,
-            color: Color::new(180, 255, 180),
+            color: theme.control,
             filled: true,
             hide: false,
         }));
@@ -1116,7 +1186,7 @@ impl FileManager {
             y: button_y,
             width: 80,
             height: BUTTON_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -1125,8 +1195,8 @@ impl FileManager {
             x: MARGIN + 20,
             y: button_y + 5,
             content: "Create".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(180, 255, 180),
+            color: theme.control_text,
+            background_color: theme.control,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -1138,7 +1208,7 @@ impl FileManager {
             y: button_y,
             width: 80,
             height: BUTTON_HEIGHT,
-            color: Color::new(220, 220, 220),
+            color: theme.control,
             filled: true,
             hide: false,
         }));
@@ -1148,7 +1218,7 @@ impl FileManager {
             y: button_y,
             width: 80,
             height: BUTTON_HEIGHT,
-            color: Color::BLACK,
+            color: theme.control_border,
             filled: false,
             hide: false,
         });
@@ -1157,8 +1227,8 @@ impl FileManager {
             x: MARGIN + 115,
             y: button_y + 5,
             content: "Back".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(220, 220, 220),
+            color: theme.control_text,
+            background_color: theme.control,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -1169,8 +1239,8 @@ impl FileManager {
             x: MARGIN,
             y: height - 25,
             content: "Enter filename and content, then click Create".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(240, 240, 240),
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -1178,6 +1248,7 @@ impl FileManager {
     }
 
     fn setup_delete_file_ui(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
         let height = surface.height;
 
         if let Some(idx) = self.selected_file_index {
@@ -1187,8 +1258,8 @@ impl FileManager {
                     x: MARGIN,
                     y: 50,
                     content: "Delete File".to_string(),
-                    color: Color::BLACK,
-                    background_color: Color::new(240, 240, 240),
+                    color: theme.text,
+                    background_color: theme.window_background,
                     font_size: RasterHeight::Size16,
                     font_weight: FontWeight::Bold,
                     hide: false,
@@ -1199,8 +1270,8 @@ impl FileManager {
                     x: MARGIN,
                     y: 100,
                     content: format!("Are you sure you want to delete '{}'?", file.name),
-                    color: Color::BLACK,
-                    background_color: Color::new(240, 240, 240),
+                    color: theme.text,
+                    background_color: theme.window_background,
                     font_size: RasterHeight::Size16,
                     font_weight: FontWeight::Regular,
                     hide: false,
@@ -1209,9 +1280,9 @@ impl FileManager {
                 surface.add_shape(Shape::Text {
                     x: MARGIN,
                     y: 130,
-                    content: "This action cannot be undone!".to_string(),
-                    color: Color::new(200, 0, 0),
-                    background_color: Color::new(240, 240, 240),
+                    content: "It can be restored from the Trash".to_string(),
+                    color: theme.error_text,
+                    background_color: theme.window_background,
                     font_size: RasterHeight::Size16,
                     font_weight: FontWeight::Bold,
                     hide: false,
@@ -1226,7 +1297,7 @@ impl FileManager {
                     y: button_y,
                     width: 100,
                     height: BUTTON_HEIGHT,
-                    color: Color::new(255, 100, 100),
+                    color: theme.control,
                     filled: true,
                     hide: false,
                 }));
@@ -1236,7 +1307,7 @@ impl FileManager {
                     y: button_y,
                     width: 100,
                     height: BUTTON_HEIGHT,
-                    color: Color::BLACK,
+                    color: theme.control_border,
                     filled: false,
                     hide: false,
                 });
@@ -1245,8 +1316,8 @@ impl FileManager {
                     x: MARGIN + 15,
                     y: button_y + 5,
                     content: "Yes, Delete".to_string(),
-                    color: Color::BLACK,
-                    background_color: Color::new(255, 100, 100),
+                    color: theme.control_text,
+                    background_color: theme.control,
                     font_size: RasterHeight::Size16,
                     font_weight: FontWeight::Regular,
                     hide: false,
@@ -1258,7 +1329,7 @@ impl FileManager {
                     y: button_y,
                     width: 80,
                     height: BUTTON_HEIGHT,
-                    color: Color::new(220, 220, 220),
+                    color: theme.control,
                     filled: true,
                     hide: false,
                 }));
@@ -1268,7 +1339,7 @@ impl FileManager {
                     y: button_y,
                     width: 80,
                     height: BUTTON_HEIGHT,
-                    color: Color::BLACK,
+                    color: theme.control_border,
                     filled: false,
                     hide: false,
                 });
@@ -1277,8 +1348,8 @@ impl FileManager {
                     x: MARGIN + 135,
                     y: button_y + 5,
                     content: "Cancel".to_string(),
-                    color: Color::BLACK,
-                    background_color: Color::new(220, 220, 220),
+                    color: theme.control_text,
+                    background_color: theme.control,
                     font_size: RasterHeight::Size16,
                     font_weight: FontWeight::Regular,
                     hide: false,
@@ -1288,6 +1359,7 @@ impl FileManager {
     }
 
     fn setup_view_file_ui(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
         let height = surface.height;
 
         if let FileManagerMode::ViewFile(file) = &self.mode {
@@ -1296,8 +1368,8 @@ impl FileManager {
                 x: MARGIN,
                 y: 50,
                 content: format!("Select an application to open: {}", file.name),
-                color: Color::BLACK,
-                background_color: Color::new(240, 240, 240),
+                color: theme.text,
+                background_color: theme.window_background,
                 font_size: RasterHeight::Size20,
                 font_weight: FontWeight::Bold,
                 hide: false,
@@ -1307,14 +1379,14 @@ impl FileManager {
                 x: MARGIN,
                 y: 70,
                 content: "Recomended:".to_string(),
-                color: Color::BLACK,
-                background_color: Color::new(240, 240, 240),
+                color: theme.text,
+                background_color: theme.window_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Bold,
                 hide: false,
             });
 
-            let (recommended, all) = self.load_recomended_open_list(&file.name);
+            let (recommended, all) = recommended_apps(&file.name);
 
             if recommended.is_some() && self.selected_open_file_app.is_none() {
                 self.selected_open_file_app = recommended.map(|s| s.to_string());
@@ -1328,7 +1400,7 @@ impl FileManager {
                     y: 90,
                     width: 200,
                     height: 20,
-                    color: Color::new(150, 200, 255),
+                    color: theme.selection,
                     filled: true,
                     hide: false,
                 });
@@ -1348,8 +1420,8 @@ impl FileManager {
                 content: recommended
                     .unwrap_or("No recommended apps found")
                     .to_string(),
-                color: Color::BLACK,
-                background_color: Color::new(240, 240, 240),
+                color: theme.text,
+                background_color: theme.window_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -1359,8 +1431,8 @@ impl FileManager {
                 x: MARGIN,
                 y: 110,
                 content: "Other:".to_string(),
-                color: Color::BLACK,
-                background_color: Color::new(240, 240, 240),
+                color: theme.text,
+                background_color: theme.window_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Bold,
                 hide: false,
@@ -1373,7 +1445,7 @@ impl FileManager {
                         y: 130 + i * 20,
                         width: 200,
                         height: 20,
-                        color: Color::new(150, 200, 255),
+                        color: theme.selection,
                         filled: true,
                         hide: false,
                     });
@@ -1383,8 +1455,8 @@ impl FileManager {
                     x: MARGIN,
                     y: 130 + i * 20,
                     content: app.to_string(),
-                    color: Color::BLACK,
-                    background_color: Color::new(240, 240, 240),
+                    color: theme.text,
+                    background_color: theme.window_background,
                     font_size: RasterHeight::Size16,
                     font_weight: FontWeight::Regular,
                     hide: false,
@@ -1403,7 +1475,7 @@ impl FileManager {
                 y: button_y,
                 width: 80,
                 height: BUTTON_HEIGHT,
-                color: Color::new(220, 220, 220),
+                color: theme.control,
                 filled: true,
                 hide: false,
             }));
@@ -1413,7 +1485,7 @@ impl FileManager {
                 y: button_y,
                 width: 80,
                 height: BUTTON_HEIGHT,
-                color: Color::BLACK,
+                color: theme.control_border,
                 filled: false,
                 hide: false,
             });
@@ -1422,8 +1494,8 @@ impl FileManager {
                 x: MARGIN + 25,
                 y: button_y + 5,
                 content: "Back".to_string(),
-                color: Color::BLACK,
-                background_color: Color::new(220, 220, 220),
+                color: theme.control_text,
+                background_color: theme.control,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -1434,7 +1506,7 @@ impl FileManager {
                 y: button_y,
                 width: 80,
                 height: BUTTON_HEIGHT,
-                color: Color::new(180, 255, 180),
+                color: theme.control,
                 filled: true,
                 hide: false,
             }));
@@ -1444,7 +1516,7 @@ impl FileManager {
                 y: button_y,
                 width: 80,
                 height: BUTTON_HEIGHT,
-                color: Color::BLACK,
+                color: theme.control_border,
                 filled: false,
                 hide: false,
             });
@@ -1453,8 +1525,8 @@ impl FileManager {
                 x: MARGIN + 115,
                 y: button_y + 5,
                 content: "Open".to_string(),
-                color: Color::BLACK,
-                background_color: Color::new(180, 255, 180),
+                color: theme.control_text,
+                background_color: theme.control,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -1463,6 +1535,7 @@ impl FileManager {
     }
 
     fn setup_rename_ui(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
         let width = surface.width;
         let height = surface.height;
 
@@ -1472,8 +1545,8 @@ impl FileManager {
                 x: MARGIN,
                 y: 50,
                 content: "Rename Item".to_string(),
-                color: Color::BLACK,
-                background_color: Color::new(240, 240, 240),
+                color: theme.text,
+                background_color: theme.window_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Bold,
                 hide: false,
@@ -1484,8 +1557,8 @@ impl FileManager {
                 x: MARGIN,
                 y: 80,
                 content: format!("Current name: {}", file.name),
-                color: Color::BLACK,
-                background_color: Color::new(240, 240, 240),
+                color: theme.text,
+                background_color: theme.window_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -1496,8 +1569,8 @@ impl FileManager {
                 x: MARGIN,
                 y: 110,
                 content: "New name:".to_string(),
-                color: Color::BLACK,
-                background_color: Color::new(240, 240, 240),
+                color: theme.text,
+                background_color: theme.window_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -1509,7 +1582,7 @@ impl FileManager {
                 y: 130,
                 width: width - 2 * MARGIN,
                 height: TEXT_INPUT_HEIGHT,
-                color: Color::WHITE,
+                color: theme.input_background,
                 filled: true,
                 hide: false,
             });
@@ -1519,7 +1592,7 @@ impl FileManager {
                 y: 130,
                 width: width - 2 * MARGIN,
                 height: TEXT_INPUT_HEIGHT,
-                color: Color::BLACK,
+                color: theme.control_border,
                 filled: false,
                 hide: false,
             });
@@ -1529,8 +1602,8 @@ impl FileManager {
                 x: MARGIN + 5,
                 y: 135,
                 content: format!("{}_", self.input_text),
-                color: Color::BLACK,
-                background_color: Color::WHITE,
+                color: theme.text,
+                background_color: theme.input_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -1545,7 +1618,7 @@ impl FileManager {
                 y: button_y,
                 width: 80,
                 height: BUTTON_HEIGHT,
-                color: Color::new(200, 200, 255),
+                color: theme.control,
                 filled: true,
                 hide: false,
             }));
@@ -1555,7 +1628,7 @@ impl FileManager {
                 y: button_y,
                 width: 80,
                 height: BUTTON_HEIGHT,
-                color: Color::BLACK,
+                color: theme.control_border,
                 filled: false,
                 hide: false,
             });
@@ -1564,8 +1637,8 @@ impl FileManager {
                 x: MARGIN + 20,
                 y: button_y + 5,
                 content: "Rename".to_string(),
-                color: Color::BLACK,
-                background_color: Color::new(200, 200, 255),
+                color: theme.control_text,
+                background_color: theme.control,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -1577,7 +1650,7 @@ impl FileManager {
                 y: button_y,
                 width: 80,
                 height: BUTTON_HEIGHT,
-                color: Color::new(220, 220, 220),
+                color: theme.control,
                 filled: true,
                 hide: false,
             }));
@@ -1587,7 +1660,7 @@ impl FileManager {
                 y: button_y,
                 width: 80,
                 height: BUTTON_HEIGHT,
-                color: Color::BLACK,
+                color: theme.control_border,
                 filled: false,
                 hide: false,
             });
@@ -1596,8 +1669,8 @@ impl FileManager {
                 x: MARGIN + 115,
                 y: button_y + 5,
                 content: "Cancel".to_string(),
-                color: Color::BLACK,
-                background_color: Color::new(220, 220, 220),
+                color: theme.control_text,
+                background_color: theme.control,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -1608,8 +1681,8 @@ impl FileManager {
                 x: MARGIN,
                 y: height - 25,
                 content: "Enter new name and click Rename".to_string(),
-                color: Color::BLACK,
-                background_color: Color::new(240, 240, 240),
+                color: theme.text,
+                background_color: theme.window_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -1617,21 +1690,29 @@ impl FileManager {
         }
     }
 
-    fn handle_browse_click(&mut self, x: usize, y: usize, surface: &mut Surface) -> bool {
-        // Check file list clicks
+    /// Index of the file list entry under a point
+    fn file_index_at(&self, x: usize, y: usize, surface: &Surface) -> Option<usize> {
         if x >= MARGIN && x < surface.width - MARGIN && y >= 45 && y < 45 + FILE_LIST_HEIGHT {
-            let clicked_index = self.scroll_offset + (y - 45) / FILE_ENTRY_HEIGHT;
-            if clicked_index < self.files.len() {
-                self.selected_file_index = Some(clicked_index);
-                // Use optimized update instead of full UI rebuild
-                self.update_file_selection(surface);
-                return true;
+            let index = self.scroll_offset + (y - 45) / FILE_ENTRY_HEIGHT;
+            if index < self.files.len() {
+                return Some(index);
             }
         }
+        None
+    }
+
+    fn handle_browse_click(&mut self, x: usize, y: usize, surface: &mut Surface) -> bool {
+        // Check file list clicks
+        if let Some(clicked_index) = self.file_index_at(x, y, surface) {
+            self.selected_file_index = Some(clicked_index);
+            // Use optimized update instead of full UI rebuild
+            self.update_file_selection(surface);
+            return true;
+        }
 
         // Check up button click
         if self.up_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, surface.width - 60, 8, 50, 20) {
+            if Rect::new(surface.width - 60, 8, 50, 20).contains_point(x, y) {
                 if let Ok(_) = self.navigate_up() {
                     self.setup_ui(surface);
                 } else {
@@ -1643,47 +1724,27 @@ impl FileManager {
 
         // Check button clicks
         if self.new_file_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN, surface.height - 60, 65, BUTTON_HEIGHT) {
-                self.mode = FileManagerMode::NewFile;
-                self.input_text.clear();
-                self.setup_ui(surface);
+            if Rect::new(MARGIN, surface.height - 60, 65, BUTTON_HEIGHT).contains_point(x, y) {
+                self.start_input_mode(FileManagerMode::NewFile, surface);
                 return true;
             }
         }
 
         if self.new_folder_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN + 75, surface.height - 60, 65, BUTTON_HEIGHT) {
-                self.mode = FileManagerMode::NewFolder;
-                self.input_text.clear();
-                self.setup_ui(surface);
+            if Rect::new(MARGIN + 75, surface.height - 60, 65, BUTTON_HEIGHT).contains_point(x, y) {
+                self.start_input_mode(FileManagerMode::NewFolder, surface);
                 return true;
             }
         }
 
         if self.delete_file_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN + 150, surface.height - 60, 65, BUTTON_HEIGHT) {
-                if let Some(idx) = self.selected_file_index {
-                    if let Some(file) = self.files.get(idx) {
-                        if file.is_directory {
-                            self.mode = FileManagerMode::DeleteFolder;
-                        } else {
-                            self.mode = FileManagerMode::DeleteFile;
-                        }
-                        self.setup_ui(surface);
-                    }
-                } else {
-                    // Use optimized status update instead of full UI rebuild
-                    self.update_status_message(
-                        surface,
-                        "Please select a file or folder to delete".to_string(),
-                    );
-                }
-                return true;
+            if Rect::new(MARGIN + 150, surface.height - 60, 65, BUTTON_HEIGHT).contains_point(x, y) {
+                return self.handle_delete_request(surface);
             }
         }
 
         if self.view_file_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN + 225, surface.height - 60, 65, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN + 225, surface.height - 60, 65, BUTTON_HEIGHT).contains_point(x, y) {
                 return self.handle_view_file(surface);
             }
         }
@@ -1693,35 +1754,85 @@ impl FileManager {
 
         // Copy button
         if self.copy_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN, button_y2, 50, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN, button_y2, 50, BUTTON_HEIGHT).contains_point(x, y) {
                 return self.handle_copy_click(surface);
             }
         }
 
         // Cut button
         if self.cut_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN + 60, button_y2, 50, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN + 60, button_y2, 50, BUTTON_HEIGHT).contains_point(x, y) {
                 return self.handle_cut_click(surface);
             }
         }
 
         // Paste button
-        if self.paste_btn_idx.is_some() && self.clipboard.is_some() {
-            if self.is_button_clicked(x, y, MARGIN + 120, button_y2, 50, BUTTON_HEIGHT) {
+        if self.paste_btn_idx.is_some() && clipboard::file().is_some() {
+            if Rect::new(MARGIN + 120, button_y2, 50, BUTTON_HEIGHT).contains_point(x, y) {
                 return self.handle_paste_click(surface);
             }
         }
 
         // Rename button
         if self.rename_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN + 180, button_y2, 60, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN + 180, button_y2, 60, BUTTON_HEIGHT).contains_point(x, y) {
                 return self.handle_rename_click_from_browse(surface);
             }
         }
 
+        // Search button
+        if self.search_btn_idx.is_some()
+            && Rect::new(MARGIN + 305, surface.height - 60, 70, BUTTON_HEIGHT).contains_point(x, y)
+        {
+            self.mode = FileManagerMode::Search;
+            self.input_text.clear();
+            self.search_finder = None;
+            self.search_results.clear();
+            self.selected_search_result = None;
+            self.status_message =
+                "Type a pattern (e.g. *.txt size>1k) and press Enter".to_string();
+            self.setup_ui(surface);
+            return true;
+        }
+
+        // Trash button
+        if self.trash_btn_idx.is_some()
+            && Rect::new(MARGIN + 385, surface.height - 60, 60, BUTTON_HEIGHT).contains_point(x, y)
+        {
+            self.open_trash(surface);
+            return true;
+        }
+
         false
     }
 
+    fn start_input_mode(&mut self, mode: FileManagerMode, surface: &mut Surface) {
+        self.mode = mode;
+        self.input_text.clear();
+        self.setup_ui(surface);
+    }
+
+    /// Ask for confirmation before moving the selected entry to the trash
+    fn handle_delete_request(&mut self, surface: &mut Surface) -> bool {
+        if let Some(idx) = self.selected_file_index {
+            if let Some(file) = self.files.get(idx) {
+                if file.is_directory {
+                    self.mode = FileManagerMode::DeleteFolder;
+                } else {
+                    self.mode = FileManagerMode::DeleteFile;
+                }
+                self.setup_ui(surface);
+            }
+        } else {
+            // Use optimized status update instead of full UI rebuild
+            self.update_status_message(
+                surface,
+                "Please select a file or folder to delete".to_string(),
+            );
+        }
+        true
+    }
+
     fn handle_view_file(&mut self, surface: &mut Surface) -> bool {
         if let Some(idx) = self.selected_file_index {
             if let Some(file) = self.files.get(idx).cloned() {
@@ -1753,14 +1864,14 @@ impl FileManager {
 
     fn handle_new_file_click(&mut self, x: usize, y: usize, surface: &mut Surface) -> bool {
         if self.create_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN, surface.height - 60, 80, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN, surface.height - 60, 80, BUTTON_HEIGHT).contains_point(x, y) {
                 self.create_file(surface);
                 return true;
             }
         }
 
         if self.back_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN + 90, surface.height - 60, 80, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN + 90, surface.height - 60, 80, BUTTON_HEIGHT).contains_point(x, y) {
                 self.mode = FileManagerMode::Browse;
                 self.setup_ui(surface);
                 return true;
@@ -1772,14 +1883,14 @@ impl FileManager {
 
     fn handle_new_folder_click(&mut self, x: usize, y: usize, surface: &mut Surface) -> bool {
         if self.create_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN, surface.height - 60, 80, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN, surface.height - 60, 80, BUTTON_HEIGHT).contains_point(x, y) {
                 self.create_folder(surface);
                 return true;
             }
         }
 
         if self.back_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN + 90, surface.height - 60, 80, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN + 90, surface.height - 60, 80, BUTTON_HEIGHT).contains_point(x, y) {
                 self.mode = FileManagerMode::Browse;
                 self.setup_ui(surface);
                 return true;
@@ -1791,14 +1902,14 @@ impl FileManager {
 
     fn handle_delete_folder_click(&mut self, x: usize, y: usize, surface: &mut Surface) -> bool {
         if self.confirm_delete_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN, surface.height - 60, 100, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN, surface.height - 60, 100, BUTTON_HEIGHT).contains_point(x, y) {
                 self.delete_selected_folder(surface);
                 return true;
             }
         }
 
         if self.back_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN + 110, surface.height - 60, 80, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN + 110, surface.height - 60, 80, BUTTON_HEIGHT).contains_point(x, y) {
                 self.mode = FileManagerMode::Browse;
                 self.setup_ui(surface);
                 return true;
@@ -1851,7 +1962,7 @@ impl FileManager {
                     format!("{}/{}", self.current_path, folder.name)
                 };
 
-                match delete_directory(&folder_path) {
+                match trash::move_to_trash(&folder_path) {
                     Ok(_) => {
                         self.refresh_file_list();
                         self.selected_file_index = None;
@@ -1859,7 +1970,7 @@ impl FileManager {
                         self.setup_ui(surface);
                         self.update_status_message(
                             surface,
-                            "Folder deleted successfully".to_string(),
+                            "Folder moved to Trash".to_string(),
                         );
                     }
                     Err(e) => {
@@ -1875,14 +1986,14 @@ impl FileManager {
 
     fn handle_delete_click(&mut self, x: usize, y: usize, surface: &mut Surface) -> bool {
         if self.confirm_delete_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN, surface.height - 60, 100, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN, surface.height - 60, 100, BUTTON_HEIGHT).contains_point(x, y) {
                 self.delete_selected_file(surface);
                 return true;
             }
         }
 
         if self.back_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN + 110, surface.height - 60, 80, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN + 110, surface.height - 60, 80, BUTTON_HEIGHT).contains_point(x, y) {
                 self.mode = FileManagerMode::Browse;
                 self.setup_ui(surface);
                 return true;
@@ -1894,7 +2005,7 @@ impl FileManager {
 
     fn handle_view_click(&mut self, x: usize, y: usize, surface: &mut Surface) -> bool {
         if self.back_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN, surface.height - 60, 80, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN, surface.height - 60, 80, BUTTON_HEIGHT).contains_point(x, y) {
                 self.mode = FileManagerMode::Browse;
                 self.setup_ui(surface);
 
@@ -1903,7 +2014,7 @@ impl FileManager {
         }
 
         if self.confirm_open_file_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN + 90, surface.height - 60, 80, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN + 90, surface.height - 60, 80, BUTTON_HEIGHT).contains_point(x, y) {
                 if let Some(app) = self.selected_open_file_app.clone() {
                     let file = self
                         .files
@@ -1938,7 +2049,7 @@ impl FileManager {
 
         if let Some(apps) = &self.open_file_options.clone() {
             for (app_y, app) in apps {
-                if self.is_button_clicked(x, y, MARGIN, *app_y, 200, 20) {
+                if Rect::new(MARGIN, *app_y, 200, 20).contains_point(x, y) {
                     self.selected_open_file_app = Some(app.to_string());
                     self.setup_ui(surface);
                     return true;
@@ -1949,16 +2060,630 @@ impl FileManager {
         false
     }
 
-    fn is_button_clicked(
-        &self,
+    /// Add a filled button with an outline and a label, returns the background shape
+    fn add_button(
+        &mut self,
+        surface: &mut Surface,
         x: usize,
         y: usize,
-        btn_x: usize,
-        btn_y: usize,
-        btn_width: usize,
-        btn_height: usize,
-    ) -> bool {
-        x >= btn_x && x < btn_x + btn_width && y >= btn_y && y < btn_y + btn_height
+        width: usize,
+        label: &str,
+    ) -> usize {
+        let theme = theme::current();
+        let idx = surface.add_shape(Shape::Rectangle {
+            x,
+            y,
+            width,
+            height: BUTTON_HEIGHT,
+            color: theme.control,
+            filled: true,
+            hide: false,
+        });
+
+        surface.add_shape(Shape::Rectangle {
+            x,
+            y,
+            width,
+            height: BUTTON_HEIGHT,
+            color: theme.control_border,
+            filled: false,
+            hide: false,
+        });
+
+        surface.add_shape(Shape::Text {
+            x: x + (width.saturating_sub(label.len() * 7)) / 2,
+            y: y + 5,
+            content: label.to_string(),
+            color: theme.control_text,
+            background_color: theme.control,
+            font_size: RasterHeight::Size16,
+            font_weight: FontWeight::Regular,
+            hide: false,
+        });
+
+        idx
+    }
+
+    fn max_visible_search_results(&self, surface: &Surface) -> usize {
+        surface.height.saturating_sub(SEARCH_LIST_Y + 70) / FILE_ENTRY_HEIGHT
+    }
+
+    fn setup_search_ui(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
+        let width = surface.width;
+        let height = surface.height;
+
+        surface.add_shape(Shape::Text {
+            x: MARGIN,
+            y: 10,
+            content: format!("Search in {}", self.current_path),
+            color: theme.text,
+            background_color: theme.window_background,
+            font_size: RasterHeight::Size16,
+            font_weight: FontWeight::Bold,
+            hide: false,
+        });
+
+        // Query input
+        surface.add_shape(Shape::Rectangle {
+            x: MARGIN,
+            y: 35,
+            width: width - 2 * MARGIN,
+            height: TEXT_INPUT_HEIGHT,
+            color: theme.input_background,
+            filled: true,
+            hide: false,
+        });
+
+        surface.add_shape(Shape::Rectangle {
+            x: MARGIN,
+            y: 35,
+            width: width - 2 * MARGIN,
+            height: TEXT_INPUT_HEIGHT,
+            color: theme.control_border,
+            filled: false,
+            hide: false,
+        });
+
+        self.input_text_idx = Some(surface.add_shape(Shape::Text {
+            x: MARGIN + 5,
+            y: 40,
+            content: format!("{}_", self.input_text),
+            color: theme.text,
+            background_color: theme.input_background,
+            font_size: RasterHeight::Size16,
+            font_weight: FontWeight::Regular,
+            hide: false,
+        }));
+
+        // Results found so far; more rows are appended while the search runs
+        let visible = self.max_visible_search_results(surface);
+        for i in 0..self.search_results.len().min(visible) {
+            self.add_search_result_row(surface, i);
+        }
+
+        let button_y = height - 60;
+        self.create_btn_idx = Some(self.add_button(
+            surface,
+            MARGIN,
+            button_y,
+            80,
+            "Search",
+        ));
+        self.view_file_btn_idx = Some(self.add_button(
+            surface,
+            MARGIN + 90,
+            button_y,
+            70,
+            "Go to",
+        ));
+        self.back_btn_idx = Some(self.add_button(
+            surface,
+            MARGIN + 170,
+            button_y,
+            70,
+            "Back",
+        ));
+
+        self.status_text_idx = Some(surface.add_shape(Shape::Text {
+            x: MARGIN,
+            y: height - 25,
+            content: self.status_message.clone(),
+            color: theme.text,
+            background_color: theme.window_background,
+            font_size: RasterHeight::Size16,
+            font_weight: FontWeight::Regular,
+            hide: false,
+        }));
+    }
+
+    fn add_search_result_row(&mut self, surface: &mut Surface, index: usize) {
+        let theme = theme::current();
+        let Some(result) = self.search_results.get(index) else {
+            return;
+        };
+
+        let y_pos = SEARCH_LIST_Y + index * FILE_ENTRY_HEIGHT;
+        let bg_color = if Some(index) == self.selected_search_result {
+            theme.selection
+        } else {
+            theme.input_background
+        };
+
+        let mut label = if result.entry.is_directory {
+            format!("{}/", result.path)
+        } else {
+            result.path.clone()
+        };
+        let max_chars = (surface.width - 2 * MARGIN - 10) / 7;
+        if label.len() > max_chars {
+            // Keep the end of the path, it is the interesting part
+            let mut start = label.len() - (max_chars - 3);
+            while !label.is_char_boundary(start) {
+                start += 1;
+            }
+            label = format!("...{}", &label[start..]);
+        }
+
+        let bg_idx = surface.add_shape(Shape::Rectangle {
+            x: MARGIN + 2,
+            y: y_pos,
+            width: surface.width - 2 * MARGIN - 4,
+            height: FILE_ENTRY_HEIGHT - 2,
+            color: bg_color,
+            filled: true,
+            hide: false,
+        });
+
+        let name_idx = surface.add_shape(Shape::Text {
+            x: MARGIN + 5,
+            y: y_pos + 3,
+            content: label,
+            color: theme.text,
+            background_color: bg_color,
+            font_size: RasterHeight::Size16,
+            font_weight: FontWeight::Regular,
+            hide: false,
+        });
+
+        self.file_list_shapes.push((bg_idx, name_idx));
+    }
+
+    fn start_search(&mut self, surface: &mut Surface) {
+        let query = match FindQuery::parse(&self.input_text) {
+            Ok(query) => query,
+            Err(e) => {
+                self.update_status_message(surface, format!("Invalid search: {}", e));
+                return;
+            }
+        };
+
+        self.search_finder = Some(Finder::new(&self.current_path, query));
+        self.search_results.clear();
+        self.selected_search_result = None;
+        self.status_message = "Searching...".to_string();
+        self.setup_ui(surface);
+    }
+
+    /// Scan one more directory of a running search and show the new results
+    fn step_search(&mut self, surface: &mut Surface) {
+        let Some(finder) = self.search_finder.as_mut() else {
+            return;
+        };
+
+        match finder.step() {
+            Some(matches) => {
+                if matches.is_empty() {
+                    return;
+                }
+
+                let first_new = self.search_results.len();
+                self.search_results.extend(matches);
+
+                let visible = self.max_visible_search_results(surface);
+                for i in first_new..self.search_results.len().min(visible) {
+                    self.add_search_result_row(surface, i);
+                }
+
+                let message = format!("Searching... {} found", self.search_results.len());
+                self.update_status_message(surface, message);
+            }
+            None => {
+                self.search_finder = None;
+                let message = format!("Search done, {} found", self.search_results.len());
+                self.update_status_message(surface, message);
+            }
+        }
+    }
+
+    fn update_search_selection(&mut self, surface: &mut Surface, index: usize) {
+        let theme = theme::current();
+        let previous = self
+            .selected_search_result
+            .and_then(|prev| self.file_list_shapes.get(prev));
+        if let Some(&(bg_idx, _)) = previous {
+            surface.update_rectangle_color(bg_idx, theme.input_background);
+        }
+
+        if let Some(&(bg_idx, _)) = self.file_list_shapes.get(index) {
+            surface.update_rectangle_color(bg_idx, theme.selection);
+        }
+
+        self.selected_search_result = Some(index);
+    }
+
+    /// Rebuild the navigation stack so "Up" walks back through the parents of `path`
+    fn navigate_to_path(&mut self, path: &str) {
+        self.directory_stack.clear();
+
+        let mut current = "/".to_string();
+        for component in path.split('/').filter(|c| !c.is_empty()) {
+            self.directory_stack.push(DirectoryPath {
+                path: current.clone(),
+                name: if current == "/" {
+                    "Root".to_string()
+                } else {
+                    current.split('/').next_back().unwrap_or("Unknown").to_string()
+                },
+            });
+
+            current = if current == "/" {
+                format!("/{}", component)
+            } else {
+                format!("{}/{}", current, component)
+            };
+        }
+
+        self.current_path = current;
+        self.selected_file_index = None;
+        self.scroll_offset = 0;
+        self.refresh_file_list();
+    }
+
+    /// Leave search mode and show the selected result in its folder
+    fn go_to_search_result(&mut self, surface: &mut Surface) {
+        let Some(result) = self
+            .selected_search_result
+            .and_then(|i| self.search_results.get(i))
+            .cloned()
+        else {
+            self.update_status_message(surface, "Please select a result".to_string());
+            return;
+        };
+
+        self.search_finder = None;
+        self.mode = FileManagerMode::Browse;
+
+        if result.entry.is_directory {
+            self.navigate_to_path(&result.path);
+        } else {
+            let parent = match result.path.rfind('/') {
+                Some(0) | None => "/".to_string(),
+                Some(idx) => result.path[..idx].to_string(),
+            };
+            self.navigate_to_path(&parent);
+            self.selected_file_index = self.files.iter().position(|f| f.name == result.entry.name);
+        }
+
+        self.setup_ui(surface);
+    }
+
+    fn handle_search_click(&mut self, x: usize, y: usize, surface: &mut Surface) -> bool {
+        let visible = self.max_visible_search_results(surface);
+        if x >= MARGIN
+            && x < surface.width - MARGIN
+            && y >= SEARCH_LIST_Y
+            && y < SEARCH_LIST_Y + visible * FILE_ENTRY_HEIGHT
+        {
+            let clicked = (y - SEARCH_LIST_Y) / FILE_ENTRY_HEIGHT;
+            if clicked < self.search_results.len() {
+                if self.selected_search_result == Some(clicked) {
+                    // Second click on the same result acts like "Go to"
+                    self.go_to_search_result(surface);
+                } else {
+                    self.update_search_selection(surface, clicked);
+                }
+                return true;
+            }
+        }
+
+        let button_y = surface.height - 60;
+        if Rect::new(MARGIN, button_y, 80, BUTTON_HEIGHT).contains_point(x, y) {
+            self.start_search(surface);
+            return true;
+        }
+
+        if Rect::new(MARGIN + 90, button_y, 70, BUTTON_HEIGHT).contains_point(x, y) {
+            self.go_to_search_result(surface);
+            return true;
+        }
+
+        if Rect::new(MARGIN + 170, button_y, 70, BUTTON_HEIGHT).contains_point(x, y) {
+            self.search_finder = None;
+            self.mode = FileManagerMode::Browse;
+            self.refresh_file_list();
+            self.setup_ui(surface);
+            return true;
+        }
+
+        false
+    }
+
+    fn open_trash(&mut self, surface: &mut Surface) {
+        self.mode = FileManagerMode::Trash;
+        self.trash_entries = trash::list_trash();
+        self.selected_trash_entry = None;
+        self.trash_scroll = 0;
+        self.status_message = format!("{} item(s) in Trash", self.trash_entries.len());
+        self.setup_ui(surface);
+    }
+
+    fn setup_trash_ui(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
+        let width = surface.width;
+        let height = surface.height;
+
+        let max_chars = (width - 2 * MARGIN - 10) / 7;
+        let visible = self.max_visible_search_results(surface);
+
+        let total: u64 = self.trash_entries.iter().map(|e| e.size).sum();
+        let mut title = format!("Trash ({} KB)", total.div_ceil(1024));
+        if self.trash_entries.len() > visible {
+            let last = (self.trash_scroll + visible).min(self.trash_entries.len());
+            title += &format!(
+                ", {}-{} of {}",
+                self.trash_scroll + 1,
+                last,
+                self.trash_entries.len()
+            );
+        }
+        surface.add_shape(Shape::Text {
+            x: MARGIN,
+            y: 10,
+            content: title,
+            color: theme.text,
+            background_color: theme.window_background,
+            font_size: RasterHeight::Size16,
+            font_weight: FontWeight::Bold,
+            hide: false,
+        });
+
+        for (i, entry) in self
+            .trash_entries
+            .iter()
+            .enumerate()
+            .skip(self.trash_scroll)
+            .take(visible)
+        {
+            let y_pos = SEARCH_LIST_Y + (i - self.trash_scroll) * FILE_ENTRY_HEIGHT;
+            let bg_color = if Some(i) == self.selected_trash_entry {
+                theme.selection
+            } else {
+                theme.input_background
+            };
+
+            let mut label = format!("{}  ({})", entry.original_name(), entry.original_path);
+            if label.len() > max_chars {
+                let mut end = max_chars - 3;
+                while !label.is_char_boundary(end) {
+                    end -= 1;
+                }
+                label = format!("{}...", &label[..end]);
+            }
+
+            let bg_idx = surface.add_shape(Shape::Rectangle {
+                x: MARGIN + 2,
+                y: y_pos,
+                width: width - 2 * MARGIN - 4,
+                height: FILE_ENTRY_HEIGHT - 2,
+                color: bg_color,
+                filled: true,
+                hide: false,
+            });
+
+            let name_idx = surface.add_shape(Shape::Text {
+                x: MARGIN + 5,
+                y: y_pos + 3,
+                content: label,
+                color: theme.text,
+                background_color: bg_color,
+                font_size: RasterHeight::Size16,
+                font_weight: FontWeight::Regular,
+                hide: false,
+            });
+
+            self.file_list_shapes.push((bg_idx, name_idx));
+        }
+
+        let button_y = height - 60;
+        self.create_btn_idx = Some(self.add_button(
+            surface,
+            MARGIN,
+            button_y,
+            80,
+            "Restore",
+        ));
+        self.confirm_delete_btn_idx = Some(self.add_button(
+            surface,
+            MARGIN + 90,
+            button_y,
+            70,
+            "Delete",
+        ));
+        self.add_button(
+            surface,
+            MARGIN + 170,
+            button_y,
+            70,
+            "Empty",
+        );
+        self.back_btn_idx = Some(self.add_button(
+            surface,
+            MARGIN + 250,
+            button_y,
+            70,
+            "Back",
+        ));
+
+        self.status_text_idx = Some(surface.add_shape(Shape::Text {
+            x: MARGIN,
+            y: height - 25,
+            content: self.status_message.clone(),
+            color: theme.text,
+            background_color: theme.window_background,
+            font_size: RasterHeight::Size16,
+            font_weight: FontWeight::Regular,
+            hide: false,
+        }));
+    }
+
+    fn update_trash_selection(&mut self, surface: &mut Surface, index: usize) {
+        let theme = theme::current();
+        // Bring the entry into view first, the rebuild draws the selection
+        let visible = self.max_visible_search_results(surface).max(1);
+        if index < self.trash_scroll || index >= self.trash_scroll + visible {
+            self.trash_scroll = if index < self.trash_scroll {
+                index
+            } else {
+                index + 1 - visible
+            };
+            self.selected_trash_entry = Some(index);
+            self.setup_ui(surface);
+            return;
+        }
+
+        let row_shapes = |entry: usize| {
+            entry
+                .checked_sub(self.trash_scroll)
+                .and_then(|row| self.file_list_shapes.get(row).copied())
+        };
+
+        if let Some((bg_idx, _)) = self.selected_trash_entry.and_then(row_shapes) {
+            surface.update_rectangle_color(bg_idx, theme.input_background);
+        }
+
+        if let Some((bg_idx, _)) = row_shapes(index) {
+            surface.update_rectangle_color(bg_idx, theme.selection);
+        }
+
+        self.selected_trash_entry = Some(index);
+    }
+
+    /// Scroll the trash list by `rows`, positive scrolls down
+    fn scroll_trash(&mut self, surface: &mut Surface, rows: isize) {
+        let visible = self.max_visible_search_results(surface);
+        let max_scroll = self.trash_entries.len().saturating_sub(visible);
+        let scroll = self.trash_scroll.saturating_add_signed(rows).min(max_scroll);
+
+        if scroll != self.trash_scroll {
+            self.trash_scroll = scroll;
+            self.setup_ui(surface);
+        }
+    }
+
+    fn handle_trash_key(&mut self, key: KeyCode, surface: &mut Surface) {
+        let page = self.max_visible_search_results(surface).max(1) as isize;
+        let last = self.trash_entries.len().saturating_sub(1);
+
+        match key {
+            KeyCode::ArrowUp | KeyCode::ArrowDown if !self.trash_entries.is_empty() => {
+                let index = match self.selected_trash_entry {
+                    Some(i) if key == KeyCode::ArrowUp => i.saturating_sub(1),
+                    Some(i) => (i + 1).min(last),
+                    None => 0,
+                };
+                self.update_trash_selection(surface, index);
+            }
+            KeyCode::PageUp => self.scroll_trash(surface, -page),
+            KeyCode::PageDown => self.scroll_trash(surface, page),
+            _ => {}
+        }
+    }
+
+    fn selected_trash_name(&self) -> Option<String> {
+        self.selected_trash_entry
+            .and_then(|i| self.trash_entries.get(i))
+            .map(|e| e.trash_name.clone())
+    }
+
+    /// Re-read the trash index after an action and rebuild the view
+    fn reload_trash(&mut self, surface: &mut Surface, message: String) {
+        self.trash_entries = trash::list_trash();
+        self.selected_trash_entry = None;
+        let visible = self.max_visible_search_results(surface);
+        self.trash_scroll = self
+            .trash_scroll
+            .min(self.trash_entries.len().saturating_sub(visible));
+        self.status_message = message;
+        self.setup_ui(surface);
+    }
+
+    fn handle_trash_click(&mut self, x: usize, y: usize, surface: &mut Surface) -> bool {
+        let visible = self.max_visible_search_results(surface);
+        if x >= MARGIN
+            && x < surface.width - MARGIN
+            && y >= SEARCH_LIST_Y
+            && y < SEARCH_LIST_Y + visible * FILE_ENTRY_HEIGHT
+        {
+            let clicked = self.trash_scroll + (y - SEARCH_LIST_Y) / FILE_ENTRY_HEIGHT;
+            if clicked < self.trash_entries.len() {
+                self.update_trash_selection(surface, clicked);
+                return true;
+            }
+        }
+
+        let button_y = surface.height - 60;
+
+        // Restore
+        if Rect::new(MARGIN, button_y, 80, BUTTON_HEIGHT).contains_point(x, y) {
+            let Some(name) = self.selected_trash_name() else {
+                self.update_status_message(surface, "Please select an item".to_string());
+                return true;
+            };
+
+            match trash::restore(&name) {
+                Ok(path) => self.reload_trash(surface, format!("Restored to {}", path)),
+                Err(e) => self.update_status_message(surface, format!("Error restoring: {}", e)),
+            }
+            return true;
+        }
+
+        // Delete permanently
+        if Rect::new(MARGIN + 90, button_y, 70, BUTTON_HEIGHT).contains_point(x, y) {
+            let Some(name) = self.selected_trash_name() else {
+                self.update_status_message(surface, "Please select an item".to_string());
+                return true;
+            };
+
+            match trash::delete_permanently(&name) {
+                Ok(_) => self.reload_trash(surface, "Item deleted permanently".to_string()),
+                Err(e) => self.update_status_message(surface, format!("Error deleting: {}", e)),
+            }
+            return true;
+        }
+
+        // Empty trash, after confirmation in on_dialog_result
+        if Rect::new(MARGIN + 170, button_y, 70, BUTTON_HEIGHT).contains_point(x, y) {
+            if self.trash_entries.is_empty() {
+                self.update_status_message(surface, "Trash is already empty".to_string());
+            } else {
+                self.pending_dialog = Some(ModalDialog::new(
+                    "Empty Trash",
+                    &format!("Delete {} item(s) for good?", self.trash_entries.len()),
+                    &["Empty", "Cancel"],
+                ));
+            }
+            return true;
+        }
+
+        if Rect::new(MARGIN + 250, button_y, 70, BUTTON_HEIGHT).contains_point(x, y) {
+            self.mode = FileManagerMode::Browse;
+            self.refresh_file_list();
+            self.setup_ui(surface);
+            return true;
+        }
+
+        false
     }
 
     fn create_file(&mut self, surface: &mut Surface) {
@@ -2007,9 +2732,9 @@ impl FileManager {
                     format!("{}/{}", self.current_path, filename)
                 };
 
-                match delete_file(&file_path) {
+                match trash::move_to_trash(&file_path) {
                     Ok(_) => {
-                        self.status_message = format!("File '{}' deleted successfully", filename);
+                        self.status_message = format!("File '{}' moved to Trash", filename);
                         self.refresh_file_list();
                         self.selected_file_index = None;
                         self.mode = FileManagerMode::Browse;
@@ -2035,7 +2760,7 @@ impl FileManager {
                     format!("{}/{}", self.current_path, file.name)
                 };
 
-                self.clipboard = Some(ClipboardEntry {
+                clipboard::set_file(ClipboardEntry {
                     file_path: file_path.clone(),
                     file_name: file.name.clone(),
                     is_directory: file.is_directory,
@@ -2064,7 +2789,7 @@ impl FileManager {
                     format!("{}/{}", self.current_path, file.name)
                 };
 
-                self.clipboard = Some(ClipboardEntry {
+                clipboard::set_file(ClipboardEntry {
                     file_path: file_path.clone(),
                     file_name: file.name.clone(),
                     is_directory: file.is_directory,
@@ -2085,7 +2810,7 @@ impl FileManager {
     }
 
     fn handle_paste_click(&mut self, surface: &mut Surface) -> bool {
-        if let Some(clipboard_entry) = &self.clipboard.clone() {
+        if let Some(clipboard_entry) = &clipboard::file() {
             let dest_path = if self.current_path == "/" {
                 format!("/{}", clipboard_entry.file_name)
             } else {
@@ -2120,7 +2845,7 @@ impl FileManager {
 
                     // Clear clipboard after successful cut operation
                     if matches!(clipboard_entry.operation, ClipboardOperation::Cut) {
-                        self.clipboard = None;
+                        clipboard::clear();
                     }
 
                     self.refresh_file_list();
@@ -2154,14 +2879,14 @@ impl FileManager {
 
     fn handle_rename_click(&mut self, x: usize, y: usize, surface: &mut Surface) -> bool {
         if self.create_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN, surface.height - 60, 80, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN, surface.height - 60, 80, BUTTON_HEIGHT).contains_point(x, y) {
                 self.perform_rename(surface);
                 return true;
             }
         }
 
         if self.back_btn_idx.is_some() {
-            if self.is_button_clicked(x, y, MARGIN + 90, surface.height - 60, 80, BUTTON_HEIGHT) {
+            if Rect::new(MARGIN + 90, surface.height - 60, 80, BUTTON_HEIGHT).contains_point(x, y) {
                 self.mode = FileManagerMode::Browse;
                 self.setup_ui(surface);
                 return true;
@@ -2268,6 +2993,18 @@ impl Application for FileManager {
                 // Use optimized text update instead of full UI rebuild
                 self.update_input_text(surface);
             }
+            FileManagerMode::Search => {
+                if c == '\x08' {
+                    self.input_text.pop();
+                } else if c == '\n' {
+                    self.start_search(surface);
+                    return;
+                } else if c.is_ascii() && !c.is_control() {
+                    self.input_text.push(c);
+                }
+
+                self.update_input_text(surface);
+            }
             _ => {}
         }
     }
@@ -2335,6 +3072,7 @@ impl Application for FileManager {
                 }
                 _ => {}
             },
+            FileManagerMode::Trash => self.handle_trash_key(key, surface),
             _ => {}
         }
     }
@@ -2348,18 +3086,192 @@ impl Application for FileManager {
             FileManagerMode::DeleteFolder => self.handle_delete_folder_click(x, y, surface),
             FileManagerMode::ViewFile(_) => self.handle_view_click(x, y, surface),
             FileManagerMode::Rename(_) => self.handle_rename_click(x, y, surface),
+            FileManagerMode::Search => self.handle_search_click(x, y, surface),
+            FileManagerMode::Trash => self.handle_trash_click(x, y, surface),
         };
     }
 
-    fn render(&mut self, _surface: &mut Surface) {
-        // The UI is already set up, just make sure it's current
-        // This could be extended to handle dynamic updates
+    fn context_menu(&mut self, x: usize, y: usize, surface: &mut Surface) -> Option<ContextMenu> {
+        if self.mode != FileManagerMode::Browse {
+            return None;
+        }
+
+        let can_paste = clipboard::file().is_some();
+
+        // Right-clicking an entry selects it first, like a left click would
+        if let Some(index) = self.file_index_at(x, y, surface) {
+            self.selected_file_index = Some(index);
+            self.update_file_selection(surface);
+
+            return Some(ContextMenu::new(vec![
+                MenuItem::new("Open", "open"),
+                MenuItem::separator(),
+                MenuItem::new("Cut", "cut"),
+                MenuItem::new("Copy", "copy"),
+                MenuItem::new("Paste", "paste").enabled(can_paste),
+                MenuItem::separator(),
+                MenuItem::new("Rename", "rename"),
+                MenuItem::new("Delete", "delete"),
+            ]));
+        }
+
+        Some(ContextMenu::new(vec![
+            MenuItem::new("New File", "new_file"),
+            MenuItem::new("New Folder", "new_folder"),
+            MenuItem::separator(),
+            MenuItem::new("Paste", "paste").enabled(can_paste),
+            MenuItem::new("Refresh", "refresh"),
+        ]))
+    }
+
+    fn handle_context_menu_action(&mut self, action: &'static str, surface: &mut Surface) {
+        // The directory may have changed while the menu was open
+        if self.mode != FileManagerMode::Browse {
+            return;
+        }
+
+        match action {
+            "open" => {
+                self.handle_view_file(surface);
+            }
+            "cut" => {
+                self.handle_cut_click(surface);
+            }
+            "copy" => {
+                self.handle_copy_click(surface);
+            }
+            "paste" => {
+                self.handle_paste_click(surface);
+            }
+            "rename" => {
+                self.handle_rename_click_from_browse(surface);
+            }
+            "delete" => {
+                self.handle_delete_request(surface);
+            }
+            "new_file" => self.start_input_mode(FileManagerMode::NewFile, surface),
+            "new_folder" => self.start_input_mode(FileManagerMode::NewFolder, surface),
+            "refresh" => {
+                self.refresh_file_list();
+                self.setup_ui(surface);
+            }
+            _ => {}
+        }
+    }
+
+    fn render(&mut self, surface: &mut Surface) {
+        if self.mode == FileManagerMode::Search {
+            self.step_search(surface);
+        }
+
+        for event in poll_events(self.dir_watch) {
+            // The shown directory (or one above it) was moved: follow it
+            if let FsEventKind::Renamed { to } = event.kind {
+                let current = self.current_path.trim_end_matches('/');
+                if event.path.trim_end_matches('/').eq_ignore_ascii_case(current) {
+                    self.current_path = to;
+                    rewatch(self.dir_watch, &self.current_path);
+                }
+            }
+            self.needs_refresh = true;
+        }
+
+        // Only rebuild while browsing, the other modes pick the new list up when they return
+        if self.needs_refresh && self.mode == FileManagerMode::Browse {
+            self.refresh_file_list();
+            if self
+                .selected_file_index
+                .is_some_and(|idx| idx >= self.files.len())
+            {
+                self.selected_file_index = None;
+            }
+            self.setup_ui(surface);
+        }
+    }
+
+    fn handle_mouse_event(&mut self, event: &MouseEvent, surface: &mut Surface) {
+        match event.kind {
+            MouseEventKind::Wheel(notches) if self.mode == FileManagerMode::Trash => {
+                self.scroll_trash(surface, notches as isize * WHEEL_ROWS);
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_resize(&mut self, surface: &mut Surface) {
+        self.setup_ui(surface);
+    }
+
+    fn handle_theme_change(&mut self, surface: &mut Surface) {
+        self.setup_ui(surface);
+    }
+
+    fn take_dialog(&mut self) -> Option<ModalDialog> {
+        self.pending_dialog.take()
+    }
+
+    fn on_dialog_result(&mut self, button: usize, surface: &mut Surface) {
+        // The only dialog is the "Empty Trash" confirmation, button 0 is "Empty"
+        if button != 0 || self.mode != FileManagerMode::Trash {
+            return;
+        }
+
+        match trash::empty_trash() {
+            Ok(_) => self.reload_trash(surface, "Trash emptied".to_string()),
+            Err(e) => self.update_status_message(surface, format!("Error emptying: {}", e)),
+        }
+    }
+
+    fn accelerators(&self) -> &'static [Accelerator] {
+        const ACCELERATORS: &[Accelerator] = &[
+            Accelerator::new(Modifiers::CTRL, KeyCode::C, "copy"),
+            Accelerator::new(Modifiers::CTRL, KeyCode::X, "cut"),
+            Accelerator::new(Modifiers::CTRL, KeyCode::V, "paste"),
+        ];
+        ACCELERATORS
+    }
+
+    fn handle_accelerator(&mut self, action: &'static str, surface: &mut Surface) {
+        match (&self.mode, action) {
+            (FileManagerMode::Browse, "copy") => {
+                self.handle_copy_click(surface);
+            }
+            (FileManagerMode::Browse, "cut") => {
+                self.handle_cut_click(surface);
+            }
+            (FileManagerMode::Browse, "paste") => {
+                self.handle_paste_click(surface);
+            }
+            // Text from the clipboard goes into the name or search box
+            (
+                FileManagerMode::NewFile
+                | FileManagerMode::NewFolder
+                | FileManagerMode::Rename(_)
+                | FileManagerMode::Search,
+                "paste",
+            ) => {
+                if let Some(text) = clipboard::text() {
+                    let line = text.lines().next().unwrap_or("");
+                    self.input_text
+                        .extend(line.chars().filter(|c| c.is_ascii() && !c.is_control()));
+                    self.update_input_text(surface);
+                }
+            }
+            _ => {}
+        }
     }
 
     fn get_title(&self) -> Option<String> {
         match &self.mode {
             FileManagerMode::Browse => Some(format!("File Manager - {}", self.current_path)),
+            FileManagerMode::Trash => Some("File Manager - Trash".to_string()),
             _ => None,
         }
     }
 }
+
+impl Drop for FileManager {
+    fn drop(&mut self) {
+        unwatch(self.dir_watch);
+    }
+}
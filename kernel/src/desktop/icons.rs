@@ -0,0 +1,486 @@
+//! Icons on the desktop background: the built-in apps plus whatever lives in
+//! `/Desktop`. Double-clicking opens an icon, dragging drops it on another
+//! grid cell and the arrangement is kept in `/Desktop/.layout`.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use noto_sans_mono_bitmap::{FontWeight, RasterHeight};
+
+use crate::{
+    desktop::{
+        input::add_file_open_request,
+        main::DESKTOP_DIR,
+        mouse::{MouseButton, MouseEventKind},
+        theme,
+        widgets::char_width,
+        window_manager::generate_icon_for_app_str,
+    },
+    framebuffer::Color,
+    fs::manager::{
+        WatchId, create_directory, list_directory, path_exists, poll_events, read_text_file,
+        set_hidden, watch, write_file,
+    },
+    surface::{Rect, Shape, Surface},
+    warn,
+};
+
+/// Shapes are reserved up front, so this is also the most icons shown
+const MAX_ICONS: usize = 32;
+const CELL_WIDTH: usize = 96;
+const CELL_HEIGHT: usize = 64;
+const MARGIN: usize = 8;
+const ICON_SIZE: usize = 16;
+const LABEL_GAP: usize = 6;
+const LABEL_MAX_CHARS: usize = 11;
+const LAYOUT_FILE_NAME: &str = ".layout";
+const SELECTED_COLOR: Color = Color::new(0, 84, 166);
+
+/// Built-in apps, by launch name, with their labels
+const APPS: [(&str, &str); 8] = [
+    ("calculator", "Calculator"),
+    ("notepad", "Notepad"),
+    ("filemanager", "File Manager"),
+    ("terminal", "Terminal"),
+    ("sysinfo", "System Info"),
+    ("logviewer", "Log Viewer"),
+    ("tictactoe", "Tic-Tac-Toe"),
+    ("settings", "Settings"),
+];
+
+enum IconTarget {
+    App(&'static str),
+    File { path: String, is_directory: bool },
+}
+
+impl IconTarget {
+    /// Name the position is saved under
+    fn key(&self) -> String {
+        match self {
+            IconTarget::App(app) => format!("app:{}", app),
+            IconTarget::File { path, .. } => format!("file:{}", path),
+        }
+    }
+
+    fn icon_name(&self) -> &'static str {
+        match self {
+            IconTarget::App(app) => app,
+            IconTarget::File {
+                is_directory: true, ..
+            } => "filemanager",
+            IconTarget::File { .. } => "notepad",
+        }
+    }
+}
+
+struct DesktopIcon {
+    target: IconTarget,
+    label: String,
+    cell: (usize, usize),
+}
+
+struct Drag {
+    icon: usize,
+    /// Pointer offset from the corner of the icon's cell
+    grab: (i32, i32),
+    moved: bool,
+}
+
+pub struct DesktopIcons {
+    area: Rect,
+    icons: Vec<DesktopIcon>,
+    /// Cells read from the layout file, for icons that show up later
+    saved: Vec<(String, (usize, usize))>,
+    /// (image, label) shape ids, one pair per possible icon
+    slots: Vec<(usize, usize)>,
+    selected: Option<usize>,
+    drag: Option<Drag>,
+    dir_watch: WatchId,
+}
+
+fn layout_path() -> String {
+    format!("{}/{}", DESKTOP_DIR, LAYOUT_FILE_NAME)
+}
+
+/// Short enough to fit under the icon
+fn label_text(name: &str) -> String {
+    if name.chars().count() > LABEL_MAX_CHARS {
+        let mut text: String = name.chars().take(LABEL_MAX_CHARS - 2).collect();
+        text.push_str("..");
+        text
+    } else {
+        name.to_string()
+    }
+}
+
+fn label_width(label: &str) -> usize {
+    label.chars().count() * char_width(RasterHeight::Size16, FontWeight::Regular)
+}
+
+impl DesktopIcons {
+    /// Reserve the icon shapes in `area` of the desktop. Add this before the
+    /// taskbar and start menu so they are drawn over the icons.
+    pub fn new(area: Rect, surface: &mut Surface) -> Self {
+        let mut slots = Vec::with_capacity(MAX_ICONS);
+        for _ in 0..MAX_ICONS {
+            let image_idx = surface.add_shape(Shape::RawImage {
+                x: area.x,
+                y: area.y,
+                width: ICON_SIZE,
+                height: ICON_SIZE,
+                data: Vec::new(),
+                hide: true,
+            });
+            let label_idx = surface.add_shape(Shape::Text {
+                x: area.x,
+                y: area.y,
+                content: String::new(),
+                color: theme::current().icon_label,
+                background_color: surface.background_color,
+                font_size: RasterHeight::Size16,
+                font_weight: FontWeight::Regular,
+                hide: true,
+            });
+            slots.push((image_idx, label_idx));
+        }
+
+        let mut icons = DesktopIcons {
+            area,
+            icons: Vec::new(),
+            saved: Vec::new(),
+            slots,
+            selected: None,
+            drag: None,
+            dir_watch: watch(DESKTOP_DIR),
+        };
+        icons.load_layout();
+        icons.refresh(surface);
+        icons
+    }
+
+    fn columns(&self) -> usize {
+        (self.area.width.saturating_sub(MARGIN) / CELL_WIDTH).max(1)
+    }
+
+    fn rows(&self) -> usize {
+        (self.area.height.saturating_sub(MARGIN) / CELL_HEIGHT).max(1)
+    }
+
+    fn cell_origin(&self, (column, row): (usize, usize)) -> (usize, usize) {
+        (
+            self.area.x + MARGIN + column * CELL_WIDTH,
+            self.area.y + MARGIN + row * CELL_HEIGHT,
+        )
+    }
+
+    /// Cell whose corner is closest to the given point
+    fn cell_near(&self, x: usize, y: usize) -> (usize, usize) {
+        let column = (x + CELL_WIDTH / 2).saturating_sub(self.area.x + MARGIN) / CELL_WIDTH;
+        let row = (y + CELL_HEIGHT / 2).saturating_sub(self.area.y + MARGIN) / CELL_HEIGHT;
+        (column.min(self.columns() - 1), row.min(self.rows() - 1))
+    }
+
+    fn is_free(&self, cell: (usize, usize)) -> bool {
+        cell.0 < self.columns()
+            && cell.1 < self.rows()
+            && !self.icons.iter().any(|icon| icon.cell == cell)
+    }
+
+    /// Icons fill the grid top to bottom, then left to right
+    fn first_free_cell(&self) -> (usize, usize) {
+        (0..self.columns())
+            .flat_map(|column| (0..self.rows()).map(move |row| (column, row)))
+            .find(|&cell| self.is_free(cell))
+            .unwrap_or((0, 0))
+    }
+
+    fn load_layout(&mut self) {
+        let Ok(content) = read_text_file(&layout_path()) else {
+            return;
+        };
+
+        for line in content.lines() {
+            let mut fields = line.split('\t');
+            if let (Some(key), Some(Ok(column)), Some(Ok(row))) = (
+                fields.next(),
+                fields.next().map(str::parse),
+                fields.next().map(str::parse),
+            ) {
+                self.saved.push((key.to_string(), (column, row)));
+            }
+        }
+    }
+
+    fn save_layout(&mut self) {
+        let mut content = String::new();
+        for icon in &self.icons {
+            content.push_str(&format!(
+                "{}\t{}\t{}\n",
+                icon.target.key(),
+                icon.cell.0,
+                icon.cell.1
+            ));
+        }
+
+        let path = layout_path();
+        let result = (|| {
+            if path_exists(DESKTOP_DIR)?.is_none() {
+                create_directory(DESKTOP_DIR)?;
+            }
+            write_file(&path, content.as_bytes())?;
+            set_hidden(&path, true)
+        })();
+        if let Err(e) = result {
+            warn!("Failed to save the icon layout: {}", e);
+        }
+
+        self.saved = self
+            .icons
+            .iter()
+            .map(|icon| (icon.target.key(), icon.cell))
+            .collect();
+    }
+
+    /// Rebuild the icon list from the apps and the contents of `/Desktop`,
+    /// keeping the cells of icons that were already there
+    pub fn refresh(&mut self, surface: &mut Surface) {
+        let mut targets: Vec<(IconTarget, String)> = APPS
+            .iter()
+            .map(|&(app, label)| (IconTarget::App(app), label.to_string()))
+            .collect();
+
+        // /Desktop only exists once something has been put there
+        if let Ok(entries) = list_directory(DESKTOP_DIR) {
+            for entry in entries {
+                if entry.name.starts_with('.') || entry.is_hidden() {
+                    continue;
+                }
+
+                targets.push((
+                    IconTarget::File {
+                        path: format!("{}/{}", DESKTOP_DIR, entry.name),
+                        is_directory: entry.is_directory,
+                    },
+                    entry.name,
+                ));
+            }
+        }
+        targets.truncate(self.slots.len());
+
+        let previous = core::mem::take(&mut self.icons);
+        for (target, name) in targets {
+            let key = target.key();
+            let cell = previous
+                .iter()
+                .find(|icon| icon.target.key() == key)
+                .map(|icon| icon.cell)
+                .or_else(|| {
+                    self.saved
+                        .iter()
+                        .find(|(saved_key, _)| *saved_key == key)
+                        .map(|(_, cell)| *cell)
+                })
+                .filter(|&cell| self.is_free(cell))
+                .unwrap_or_else(|| self.first_free_cell());
+
+            self.icons.push(DesktopIcon {
+                target,
+                label: label_text(&name),
+                cell,
+            });
+        }
+
+        self.selected = None;
+        self.drag = None;
+
+        for (i, &(image_idx, label_idx)) in self.slots.iter().enumerate() {
+            match self.icons.get(i) {
+                Some(icon) => {
+                    surface.update_image_data(
+                        image_idx,
+                        generate_icon_for_app_str::<ICON_SIZE, ICON_SIZE>(icon.target.icon_name()),
+                    );
+                    surface.update_text_content(label_idx, icon.label.clone(), None);
+                    surface.update_text_color(label_idx, theme::current().icon_label);
+                    surface.update_text_background(label_idx, surface.background_color);
+                    surface.show_shape(image_idx);
+                    surface.show_shape(label_idx);
+                }
+                None => {
+                    surface.hide_shape(image_idx);
+                    surface.hide_shape(label_idx);
+                    surface.update_image_data(image_idx, Vec::new());
+                }
+            }
+        }
+
+        for i in 0..self.icons.len() {
+            let (x, y) = self.cell_origin(self.icons[i].cell);
+            self.move_icon_shapes(i, x, y, surface);
+        }
+    }
+
+    /// Pick up files added, removed or renamed in `/Desktop`
+    pub fn poll(&mut self, surface: &mut Surface) {
+        let changed = poll_events(self.dir_watch).iter().any(|event| {
+            !event
+                .path
+                .rsplit('/')
+                .next()
+                .is_some_and(|name| name.eq_ignore_ascii_case(LAYOUT_FILE_NAME))
+        });
+
+        if changed {
+            self.refresh(surface);
+        }
+    }
+
+    /// Place an icon's image and label with the cell corner at (x, y)
+    fn move_icon_shapes(&self, icon: usize, x: usize, y: usize, surface: &mut Surface) {
+        let (image_idx, label_idx) = self.slots[icon];
+        let label_x = x + CELL_WIDTH.saturating_sub(label_width(&self.icons[icon].label)) / 2;
+
+        surface.move_shape(image_idx, x + (CELL_WIDTH - ICON_SIZE) / 2, y + LABEL_GAP);
+        surface.move_shape(label_idx, label_x, y + LABEL_GAP * 2 + ICON_SIZE);
+    }
+
+    /// The icon image or label under the point
+    fn icon_at(&self, x: usize, y: usize) -> Option<usize> {
+        self.icons.iter().position(|icon| {
+            let (cell_x, cell_y) = self.cell_origin(icon.cell);
+            let width = label_width(&icon.label);
+            let image = Rect::new(
+                cell_x + (CELL_WIDTH - ICON_SIZE) / 2,
+                cell_y + LABEL_GAP,
+                ICON_SIZE,
+                ICON_SIZE,
+            );
+            let label = Rect::new(
+                cell_x + CELL_WIDTH.saturating_sub(width) / 2,
+                cell_y + LABEL_GAP * 2 + ICON_SIZE,
+                width,
+                RasterHeight::Size16.val(),
+            );
+            image.contains_point(x, y) || label.contains_point(x, y)
+        })
+    }
+
+    fn select(&mut self, icon: Option<usize>, surface: &mut Surface) {
+        if self.selected == icon {
+            return;
+        }
+
+        if let Some(old) = self.selected {
+            surface.update_text_background(self.slots[old].1, surface.background_color);
+        }
+        if let Some(new) = icon {
+            surface.update_text_background(self.slots[new].1, SELECTED_COLOR);
+        }
+        self.selected = icon;
+    }
+
+    /// Files go through the same queue the file manager uses; apps are
+    /// returned for the caller to launch
+    fn open(&self, icon: usize) -> Option<&'static str> {
+        match &self.icons[icon].target {
+            IconTarget::App(app) => Some(*app),
+            IconTarget::File { path, is_directory } => {
+                let app = if *is_directory {
+                    "filemanager"
+                } else {
+                    "notepad"
+                };
+                add_file_open_request(path.clone(), app.to_string());
+                None
+            }
+        }
+    }
+
+    /// Move an icon to the cell nearest to where it was dropped, trading
+    /// places with the icon already there
+    fn drop_icon(&mut self, icon: usize, x: usize, y: usize, surface: &mut Surface) {
+        let cell = self.cell_near(x, y);
+        let old_cell = self.icons[icon].cell;
+
+        if let Some(other) = self
+            .icons
+            .iter()
+            .position(|other| other.cell == cell)
+            .filter(|&other| other != icon)
+        {
+            self.icons[other].cell = old_cell;
+            let (other_x, other_y) = self.cell_origin(old_cell);
+            self.move_icon_shapes(other, other_x, other_y, surface);
+        }
+
+        self.icons[icon].cell = cell;
+        let (x, y) = self.cell_origin(cell);
+        self.move_icon_shapes(icon, x, y, surface);
+
+        if cell != old_cell {
+            self.save_layout();
+        }
+    }
+
+    /// An icon is being dragged and wants every mouse event until it is dropped
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Mouse input on the bare desktop. Returns an app to launch when its
+    /// icon is double-clicked.
+    pub fn handle_mouse_event(
+        &mut self,
+        kind: MouseEventKind,
+        x: usize,
+        y: usize,
+        surface: &mut Surface,
+    ) -> Option<&'static str> {
+        match kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let icon = self.icon_at(x, y);
+                self.select(icon, surface);
+                self.drag = icon.map(|icon| {
+                    let (cell_x, cell_y) = self.cell_origin(self.icons[icon].cell);
+                    Drag {
+                        icon,
+                        grab: (x as i32 - cell_x as i32, y as i32 - cell_y as i32),
+                        moved: false,
+                    }
+                });
+                None
+            }
+            MouseEventKind::DoubleClick(MouseButton::Left) => {
+                self.drag = None;
+                let icon = self.icon_at(x, y)?;
+                self.open(icon)
+            }
+            MouseEventKind::Move => {
+                let drag = self.drag.as_mut()?;
+                drag.moved = true;
+                let (icon, grab) = (drag.icon, drag.grab);
+
+                let max_x = self.area.x + self.area.width.saturating_sub(CELL_WIDTH);
+                let max_y = self.area.y + self.area.height.saturating_sub(CELL_HEIGHT);
+                let cell_x = (x as i32 - grab.0).clamp(self.area.x as i32, max_x as i32);
+                let cell_y = (y as i32 - grab.1).clamp(self.area.y as i32, max_y as i32);
+                self.move_icon_shapes(icon, cell_x as usize, cell_y as usize, surface);
+                None
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                let drag = self.drag.take()?;
+                if drag.moved {
+                    let (image_idx, _) = self.slots[drag.icon];
+                    let bounds = surface.get_shape_bounds(image_idx)?;
+                    let cell_x = bounds.x - (CELL_WIDTH - ICON_SIZE) / 2;
+                    let cell_y = bounds.y - LABEL_GAP;
+                    self.drop_icon(drag.icon, cell_x, cell_y, surface);
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+}
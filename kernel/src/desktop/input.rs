@@ -1,14 +1,17 @@
+use crate::desktop::mouse::{DoubleClickTracker, MouseButton, MouseButtons, MouseEventKind};
+use crate::drivers::mouse::MousePacket;
 use crate::framebuffer::SCREEN_SIZE;
 use crate::print;
+use crate::time::get_ms_since_epoch;
 
 use alloc::string::String;
+use alloc::vec::Vec;
 use conquer_once::spin::OnceCell;
 use crossbeam_queue::ArrayQueue;
-use ps2_mouse::MouseState;
 
 // OPTIMIZAÇÃO: Tamanhos de fila ajustados com base no uso real
 pub static SCANCODE_QUEUE: OnceCell<ArrayQueue<u8>> = OnceCell::uninit();
-pub static STATE_QUEUE: OnceCell<ArrayQueue<MouseState>> = OnceCell::uninit();
+pub static STATE_QUEUE: OnceCell<ArrayQueue<MousePacket>> = OnceCell::uninit();
 pub static CLICK_QUEUE: OnceCell<ArrayQueue<(i16, i16)>> = OnceCell::uninit();
 pub static FILE_OPEN_QUEUE: OnceCell<ArrayQueue<(String, String)>> = OnceCell::uninit();
 
@@ -32,7 +35,7 @@ pub fn add_scancode(scancode: u8) {
     push_to_queue(SCANCODE_QUEUE.get(), scancode, "scancode", "Scancode");
 }
 
-pub fn add_mouse_state(state: MouseState) {
+pub fn add_mouse_state(state: MousePacket) {
     push_to_queue(STATE_QUEUE.get(), state, "mouse state", "Mouse state");
 }
 
@@ -68,6 +71,16 @@ pub fn init_queues() {
         .expect("File open queue should only be initialized once");
 }
 
+/// Evento do rato com a posição e os botões do momento em que aconteceu
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QueuedMouseEvent {
+    pub kind: MouseEventKind,
+    pub x: i16,
+    pub y: i16,
+    /// Botões premidos depois deste evento
+    pub buttons: MouseButtons,
+}
+
 // OPTIMIZAÇÃO: Use fields packing para reduzir tamanho
 #[repr(C)]
 pub struct CurrentMouseState {
@@ -79,8 +92,12 @@ pub struct CurrentMouseState {
     pub right_button_down: bool,
     pub prev_left_button_down: bool,
     pub prev_right_button_down: bool,
+    pub middle_button_down: bool,
     pub has_moved: bool,
     _screen_size: (u16, u16),
+    // Eventos desde a última chamada a take_events
+    events: Vec<QueuedMouseEvent>,
+    double_click: DoubleClickTracker,
 }
 
 impl CurrentMouseState {
@@ -95,12 +112,37 @@ impl CurrentMouseState {
             right_button_down: false,
             prev_left_button_down: false,
             prev_right_button_down: false,
+            middle_button_down: false,
             has_moved: true,
             _screen_size: screen_size,
+            events: Vec::new(),
+            double_click: DoubleClickTracker::new(),
         }
     }
 
-    pub fn update(&mut self, state: MouseState) {
+    pub fn buttons(&self) -> MouseButtons {
+        MouseButtons {
+            left: self.left_button_down,
+            right: self.right_button_down,
+            middle: self.middle_button_down,
+        }
+    }
+
+    /// Eventos acumulados desde a última chamada, por ordem
+    pub fn take_events(&mut self) -> Vec<QueuedMouseEvent> {
+        core::mem::take(&mut self.events)
+    }
+
+    fn push_event(&mut self, kind: MouseEventKind, buttons: MouseButtons) {
+        self.events.push(QueuedMouseEvent {
+            kind,
+            x: self.x,
+            y: self.y,
+            buttons,
+        });
+    }
+
+    pub fn update(&mut self, state: MousePacket) {
         // OPTIMIZAÇÃO: Atualização otimizada com menos operações
         self.prev_x = self.x;
         self.prev_y = self.y;
@@ -108,19 +150,73 @@ impl CurrentMouseState {
         self.prev_right_button_down = self.right_button_down;
 
         // OPTIMIZAÇÃO: Cálculo direto com bounds checking otimizado
-        self.x = (self.x + state.get_x())
+        self.x = (self.x + state.dx)
             .max(0)
             .min(self._screen_size.0 as i16 - 1);
-        self.y = (self.y - state.get_y())
+        self.y = (self.y - state.dy)
             .max(0)
             .min(self._screen_size.1 as i16 - 1);
 
-        self.left_button_down = state.left_button_down();
-        self.right_button_down = state.right_button_down();
+        let prev_middle_button_down = self.middle_button_down;
+        self.left_button_down = state.left;
+        self.right_button_down = state.right;
+        self.middle_button_down = state.middle;
 
         // OPTIMIZAÇÃO: Cálculo otimizado de movimento
         self.has_moved = (self.x != self.prev_x) || (self.y != self.prev_y);
 
+        // O movimento acontece com os botões do pacote anterior
+        let mut buttons = MouseButtons {
+            left: self.prev_left_button_down,
+            right: self.prev_right_button_down,
+            middle: prev_middle_button_down,
+        };
+
+        // Movimentos seguidos juntam-se num só evento, na posição mais recente
+        if self.has_moved {
+            let (x, y) = (self.x, self.y);
+            match self.events.last_mut() {
+                Some(last) if last.kind == MouseEventKind::Move && last.buttons == buttons => {
+                    last.x = x;
+                    last.y = y;
+                }
+                _ => self.push_event(MouseEventKind::Move, buttons),
+            }
+        }
+
+        for (button, was_down, is_down) in [
+            (MouseButton::Left, self.prev_left_button_down, self.left_button_down),
+            (MouseButton::Right, self.prev_right_button_down, self.right_button_down),
+            (MouseButton::Middle, prev_middle_button_down, self.middle_button_down),
+        ] {
+            if is_down && !was_down {
+                buttons.set(button, true);
+                self.push_event(MouseEventKind::Down(button), buttons);
+                if self
+                    .double_click
+                    .press(button, get_ms_since_epoch(), self.x, self.y)
+                {
+                    self.push_event(MouseEventKind::DoubleClick(button), buttons);
+                }
+            } else if was_down && !is_down {
+                buttons.set(button, false);
+                self.push_event(MouseEventKind::Up(button), buttons);
+            }
+        }
+
+        if state.wheel != 0 {
+            let (x, y) = (self.x, self.y);
+            match self.events.last_mut() {
+                Some(QueuedMouseEvent {
+                    kind: MouseEventKind::Wheel(delta),
+                    x: last_x,
+                    y: last_y,
+                    ..
+                }) if (*last_x, *last_y) == (x, y) => *delta = delta.saturating_add(state.wheel),
+                _ => self.push_event(MouseEventKind::Wheel(state.wheel), buttons),
+            }
+        }
+
         // Detecção de clique otimizada
         if self.prev_left_button_down && !self.left_button_down && !self.has_moved {
             push_to_queue(
@@ -2,12 +2,13 @@ use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 use pc_keyboard::{DecodedKey, Error, HandleControl, KeyEvent, Keyboard, ScancodeSet1, layouts};
 
-use crate::serial_println;
+use crate::{info, warn};
 
 // Variáveis atômicas para modificar teclas
 pub static CTRL: AtomicBool = AtomicBool::new(false);
 pub static SHIFT: AtomicBool = AtomicBool::new(false);
 pub static ALT: AtomicBool = AtomicBool::new(false);
+pub static SUPER: AtomicBool = AtomicBool::new(false);
 
 // Controle de layout do teclado (0 = azerty, 1 = qwerty, 2 = dvorak)
 pub static CURRENT_LAYOUT: AtomicUsize = AtomicUsize::new(0);
@@ -51,13 +52,13 @@ pub fn set_keyboard_layout(layout: &str) -> bool {
         "qwerty" => 1,
         "dvorak" => 2,
         _ => {
-            serial_println!("[KEYBOARD] Layout inválido: {}", layout);
+            warn!("Layout inválido: {}", layout);
             return false;
         }
     };
     
     CURRENT_LAYOUT.store(layout_idx, Ordering::Relaxed);
-    serial_println!("[KEYBOARD] Layout alterado para: {}", layout);
+    info!("Layout alterado para: {}", layout);
     true
 }
 
@@ -0,0 +1,246 @@
+//! Kernel log viewer. Shows the records `dmesg` would, newest at the bottom,
+//! and keeps following new ones unless scrolled back.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use noto_sans_mono_bitmap::{FontWeight, RasterHeight};
+use pc_keyboard::KeyCode;
+
+use crate::{
+    desktop::{
+        application::Application,
+        mouse::{MouseEvent, MouseEventKind},
+        theme,
+        vt::{default_background, default_foreground, palette_color},
+    },
+    framebuffer::Color,
+    log::{self, Level},
+    surface::{Shape, Surface},
+};
+
+const MARGIN: usize = 5;
+const CHAR_WIDTH: usize = 7;
+const LINE_HEIGHT: usize = 16;
+const HEADER_HEIGHT: usize = LINE_HEIGHT + 2 * MARGIN;
+const WHEEL_LINES: isize = 3;
+
+pub struct LogViewer {
+    /// Least serious level shown
+    max_level: Level,
+    /// How many lines the view is scrolled back from the newest record
+    scroll: usize,
+    /// `log::next_sequence` when the rows were last filled
+    seen: Option<u64>,
+    /// Filter or position changed, the rows need filling again
+    changed: bool,
+    header: usize,
+    rows: Vec<usize>,
+    columns: usize,
+}
+
+impl LogViewer {
+    pub fn new(_args: Option<String>) -> Self {
+        Self {
+            max_level: log::max_level(),
+            scroll: 0,
+            seen: None,
+            changed: true,
+            header: 0,
+            rows: Vec::new(),
+            columns: 0,
+        }
+    }
+
+    fn build(&mut self, surface: &mut Surface) {
+        surface.clear_all_shapes();
+
+        self.header = surface.add_shape(Shape::Text {
+            x: MARGIN,
+            y: MARGIN,
+            content: String::new(),
+            color: theme::current().control_text,
+            background_color: theme::current().control,
+            font_size: RasterHeight::Size16,
+            font_weight: FontWeight::Bold,
+            hide: false,
+        });
+
+        let rows = surface.height.saturating_sub(HEADER_HEIGHT + MARGIN) / LINE_HEIGHT;
+        self.columns = surface.width.saturating_sub(2 * MARGIN) / CHAR_WIDTH;
+        self.rows = (0..rows)
+            .map(|row| {
+                surface.add_shape(Shape::Text {
+                    x: MARGIN,
+                    y: HEADER_HEIGHT + row * LINE_HEIGHT,
+                    content: String::new(),
+                    color: default_foreground(),
+                    background_color: default_background(),
+                    font_size: RasterHeight::Size16,
+                    font_weight: FontWeight::Regular,
+                    hide: true,
+                })
+            })
+            .collect();
+
+        self.changed = true;
+    }
+
+    /// Records passing the level filter
+    fn shown_count(&self) -> usize {
+        let mut count = 0;
+        log::for_each(|record| {
+            if record.level <= self.max_level {
+                count += 1;
+            }
+        });
+        count
+    }
+
+    fn refresh(&mut self, surface: &mut Surface) {
+        let sequence = log::next_sequence();
+
+        // Scrolled back, the view stays on the same records while new ones arrive
+        if let Some(seen) = self.seen.filter(|_| self.scroll > 0) {
+            let mut arrived = 0;
+            log::for_each(|record| {
+                if record.sequence >= seen && record.level <= self.max_level {
+                    arrived += 1;
+                }
+            });
+            self.scroll += arrived;
+        }
+        self.seen = Some(sequence);
+        self.changed = false;
+
+        let total = self.shown_count();
+        let visible = self.rows.len();
+        self.scroll = self.scroll.min(total.saturating_sub(visible));
+        let end = total - self.scroll;
+        let start = end.saturating_sub(visible);
+
+        let mut lines = Vec::with_capacity(visible);
+        let mut index = 0;
+        log::for_each(|record| {
+            if record.level > self.max_level {
+                return;
+            }
+            if (start..end).contains(&index) {
+                let text: String = record.to_string().chars().take(self.columns).collect();
+                lines.push((record.level, text));
+            }
+            index += 1;
+        });
+
+        for (row, &shape) in self.rows.iter().enumerate() {
+            match lines.get(row) {
+                Some((level, text)) => {
+                    surface.update_text_color(shape, level_color(*level));
+                    surface.update_text_content(shape, text.clone(), None);
+                    surface.update_shape_visibility(shape, true);
+                }
+                None => {
+                    surface.update_shape_visibility(shape, false);
+                }
+            }
+        }
+
+        let position = if self.scroll > 0 {
+            format!("{} newer below", self.scroll)
+        } else {
+            String::from("following")
+        };
+        let header = format!(
+            " {} and above, {} | 1-5 level, C clear ",
+            self.max_level.name(),
+            position
+        );
+        surface.update_text_content(self.header, header, None);
+    }
+
+    fn scroll_by(&mut self, lines: isize) {
+        self.scroll = if lines < 0 {
+            self.scroll + lines.unsigned_abs()
+        } else {
+            self.scroll.saturating_sub(lines as usize)
+        };
+        self.changed = true;
+    }
+}
+
+fn level_color(level: Level) -> Color {
+    match level {
+        Level::Error => palette_color(9),
+        Level::Warn => palette_color(11),
+        Level::Info => default_foreground(),
+        Level::Debug => palette_color(14),
+        Level::Trace => palette_color(8),
+    }
+}
+
+impl Application for LogViewer {
+    fn init(&mut self, surface: &mut Surface) {
+        self.build(surface);
+    }
+
+    fn render(&mut self, surface: &mut Surface) {
+        if self.changed || self.seen != Some(log::next_sequence()) {
+            self.refresh(surface);
+        }
+    }
+
+    fn get_title(&self) -> Option<String> {
+        Some(String::from("Log Viewer"))
+    }
+
+    fn handle_char_input(&mut self, c: char, _ctrl_pressed: bool, _surface: &mut Surface) {
+        match c {
+            '1'..='5' => {
+                self.max_level = Level::ALL[c as usize - '1' as usize];
+                self.scroll = 0;
+                // `log.level` may drop these records before they are kept, so
+                // asking for them turns them on from now on
+                if self.max_level > log::max_level() {
+                    log::set_max_level(self.max_level);
+                }
+            }
+            'c' | 'C' => {
+                log::clear();
+                self.scroll = 0;
+            }
+            _ => return,
+        }
+        self.changed = true;
+    }
+
+    fn handle_key_input(&mut self, key: KeyCode, _surface: &mut Surface) {
+        let page = (self.rows.len() / 2).max(1) as isize;
+        match key {
+            KeyCode::ArrowUp => self.scroll_by(-1),
+            KeyCode::ArrowDown => self.scroll_by(1),
+            KeyCode::PageUp => self.scroll_by(-page),
+            KeyCode::PageDown => self.scroll_by(page),
+            KeyCode::Home => self.scroll_by(-(log::CAPACITY as isize)),
+            KeyCode::End => self.scroll_by(log::CAPACITY as isize),
+            _ => {}
+        }
+    }
+
+    fn handle_mouse_click(&mut self, _x: usize, _y: usize, _surface: &mut Surface) {}
+
+    fn handle_mouse_event(&mut self, event: &MouseEvent, _surface: &mut Surface) {
+        if let MouseEventKind::Wheel(notches) = event.kind {
+            self.scroll_by(notches as isize * WHEEL_LINES);
+        }
+    }
+
+    fn handle_resize(&mut self, surface: &mut Surface) {
+        self.build(surface);
+    }
+
+    fn handle_theme_change(&mut self, surface: &mut Surface) {
+        self.build(surface);
+    }
+}
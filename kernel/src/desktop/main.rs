@@ -2,34 +2,207 @@ use core::sync::atomic::Ordering;
 
 use crate::{
     desktop::{
+        context_menu::{ContextMenu, MenuItem, MenuOwner},
+        icons::DesktopIcons,
         input::{
             CLICK_QUEUE, CurrentMouseState, FILE_OPEN_QUEUE, SCANCODE_QUEUE, STATE_QUEUE,
             init_queues,
-        }, keyboard::{ALT, CTRL, CURRENT_LAYOUT, SHIFT, create_keyboard, cycle_keyboard_layout, get_current_layout}, window_manager::{
-            WindowManager, generate_icon_for_app_str, launch_calculator, 
-            launch_filemanager, launch_notepad, launch_sysinfo, 
-            launch_terminal, launch_tictactoe
-        }
+        },
+        keyboard::{
+            ALT, CTRL, CURRENT_LAYOUT, SHIFT, SUPER, create_keyboard, cycle_keyboard_layout,
+            get_current_layout,
+        },
+        mouse::{MouseButton, MouseEventKind},
+        shortcuts::{Modifiers, ShortcutAction, ShortcutRegistry, WindowSwitcher},
+        theme,
+        wallpaper::{WALLPAPER_PATH, load_wallpaper},
+        window_manager::{
+            WindowManager, generate_icon_for_app_str, launch_app, launch_calculator,
+            launch_filemanager, launch_notepad, launch_settings, launch_sysinfo, launch_terminal,
+            launch_tictactoe,
+        },
+    },
+    error,
+    framebuffer::{self, FrameBufferWriter, SCREEN_SIZE},
+    fs::manager::{create_directory, create_text_file, path_exists},
+    info,
+    settings::{
+        self, DISPLAY_BACKGROUND, DISPLAY_WALLPAPER, KEYBOARD_LAYOUT, SESSION_RESTORE,
+        SESSION_WINDOWS, parse_color,
     },
-    framebuffer::{self, Color, FrameBufferWriter, SCREEN_SIZE},
-    serial_println,
+    shell::{Shell, ShellHost, console::SerialConsole},
     surface::{Rect, Shape, Surface},
-    time::get_utc_time,
+    time::get_local_time,
+    warn,
 };
 use alloc::string::String;
-use alloc::{format, string::ToString, vec::Vec};
+use alloc::{format, string::ToString, vec, vec::Vec};
 use noto_sans_mono_bitmap::{FontWeight, RasterHeight};
 use pc_keyboard::{DecodedKey, KeyCode, KeyState};
 
 use x86_64::instructions::interrupts::without_interrupts;
 
-const TASKBAR_HEIGHT: usize = 50;
-const TASKBAR_COLOR: Color = Color::new(175, 175, 175);
+pub const TASKBAR_HEIGHT: usize = 50;
+pub const DESKTOP_DIR: &str = "/Desktop";
+/// Script corrido quando o ambiente de trabalho arranca
+pub const AUTOEXEC_PATH: &str = "/autoexec.sh";
+
+/// O /autoexec.sh não tem janela, a saída vai para a porta série
+struct AutoexecHost;
+
+impl ShellHost for AutoexecHost {
+    fn write(&mut self, text: &str) {
+        for line in text.lines() {
+            info!("{}: {}", AUTOEXEC_PATH, line);
+        }
+    }
+}
+
+/// Formas da barra de tarefas e do menu Iniciar que mudam de cor com o tema
+#[derive(Default)]
+struct ShellShapes {
+    /// Fundos com a cor da barra de tarefas
+    panels: Vec<usize>,
+    /// Linhas e contornos
+    lines: Vec<usize>,
+    /// Textos sobre a barra de tarefas
+    texts: Vec<usize>,
+}
+
+impl ShellShapes {
+    fn apply_theme(&self, desktop: &mut Surface) {
+        let theme = theme::current();
+        for &idx in &self.panels {
+            desktop.update_rectangle_color(idx, theme.taskbar);
+        }
+        for &idx in &self.lines {
+            desktop.update_rectangle_color(idx, theme.border);
+        }
+        for &idx in &self.texts {
+            desktop.update_text_color(idx, theme.taskbar_text);
+            desktop.update_text_background(idx, theme.taskbar);
+        }
+    }
+}
+
+/// Entrada do menu Iniciar: formas do fundo, texto e ícone,
+/// área (x, y, largura, altura) e nome
+type StartMenuEntry<'a> = (usize, usize, usize, usize, usize, usize, usize, &'a str);
+
+/// Show or hide every shape of the start menu
+fn set_start_menu_visible(
+    desktop: &mut Surface,
+    entries: &[StartMenuEntry],
+    visible: bool,
+) {
+    for (idx, label_idx, icon_idx, _, _, _, _, _) in entries {
+        if visible {
+            desktop.show_shape(*idx);
+            desktop.show_shape(*label_idx);
+            desktop.show_shape(*icon_idx);
+        } else {
+            desktop.hide_shape(*idx);
+            desktop.hide_shape(*label_idx);
+            desktop.hide_shape(*icon_idx);
+        }
+    }
+}
+
+/// Botão da barra de tarefas debaixo do ponteiro
+fn taskbar_window_at(
+    taskbar_window_shapes: &[(usize, usize, usize, usize)],
+    x: usize,
+    y: usize,
+    screen_height: usize,
+) -> Option<usize> {
+    let taskbar_start_x = 170;
+    let icon_y = screen_height - TASKBAR_HEIGHT + 10;
+
+    taskbar_window_shapes
+        .iter()
+        .enumerate()
+        .find(|(window_index, _)| {
+            let icon_x = taskbar_start_x + window_index * 125;
+            x >= icon_x && x < icon_x + 120 && y >= icon_y && y < icon_y + 30
+        })
+        .map(|(_, (_, _, _, window_id))| *window_id)
+}
+
+fn desktop_menu() -> ContextMenu {
+    ContextMenu::new(vec![
+        MenuItem::new("New Text File", "new_file"),
+        MenuItem::separator(),
+        MenuItem::new("Open Terminal", "terminal"),
+    ])
+}
+
+/// Cria um ficheiro de texto vazio em /Desktop com um nome ainda livre
+fn create_desktop_file() -> Result<String, &'static str> {
+    if path_exists(DESKTOP_DIR)?.is_none() {
+        create_directory(DESKTOP_DIR)?;
+    }
+
+    let mut path = format!("{}/New File.txt", DESKTOP_DIR);
+    let mut n = 2;
+    while path_exists(&path)?.is_some() {
+        path = format!("{}/New File {}.txt", DESKTOP_DIR, n);
+        n += 1;
+    }
+
+    create_text_file(&path, "")?;
+    Ok(path)
+}
+
+/// Cor de fundo e papel de parede a partir das definições.
+/// Sem cor definida, fica a do tema.
+fn apply_display_settings(desktop: &mut Surface) {
+    desktop.background_color = settings::get(DISPLAY_BACKGROUND)
+        .and_then(|color| parse_color(&color))
+        .unwrap_or(theme::current().desktop_background);
+
+    let path = settings::get(DISPLAY_WALLPAPER).unwrap_or_else(|| WALLPAPER_PATH.to_string());
+    desktop.wallpaper = if path.is_empty() {
+        None
+    } else {
+        match load_wallpaper(&path) {
+            Ok(wallpaper) => Some(wallpaper),
+            Err(e) => {
+                warn!("No wallpaper ({}): {}", path, e);
+                None
+            }
+        }
+    };
+
+    desktop.force_full_redraw();
+}
+
+/// Muda uma definição e grava /etc/goofy.conf, se o valor mudou
+fn save_setting(key: &str, value: &str, what: &str) {
+    if !settings::set(key, value) {
+        return;
+    }
+
+    if let Err(e) = settings::save() {
+        warn!("Could not save {}: {}", what, e);
+    }
+}
+
+/// Guarda as aplicações abertas para as reabrir no próximo arranque
+fn save_session(window_manager: &WindowManager) {
+    let apps = window_manager.open_apps().join(",");
+    save_setting(SESSION_WINDOWS, &apps, "the session");
+}
 
 pub fn run_desktop() -> ! {
-    serial_println!("Running desktop...");
+    info!("Running desktop...");
     init_queues();
 
+    // Definições guardadas em /etc/goofy.conf (teclado, fuso horário, ...)
+    if let Err(e) = settings::load() {
+        warn!("Could not read the settings: {}", e);
+    }
+    settings::apply();
+
     let mut mouse_state = CurrentMouseState::new();
     let mut window_manager = WindowManager::new();
 
@@ -45,13 +218,28 @@ pub fn run_desktop() -> ! {
         .expect("File open queue not initialized");
 
     let screen_size = *SCREEN_SIZE.get().unwrap();
+    let theme = theme::current();
     let mut desktop = Surface::new(
         screen_size.0 as usize,
         screen_size.1 as usize,
-        Color::new(50, 111, 168),
+        theme.desktop_background,
     );
     desktop.just_fill_bg = true;
 
+    // Cor de fundo e papel de parede opcional, repetido em mosaico
+    apply_display_settings(&mut desktop);
+    let mut settings_generation = settings::generation();
+    let mut applied_display = (
+        settings::get(DISPLAY_BACKGROUND),
+        settings::get(DISPLAY_WALLPAPER),
+    );
+
+    // Ícones antes da barra de tarefas, para ficarem por baixo dela e do menu Iniciar
+    let mut desktop_icons = DesktopIcons::new(
+        Rect::new(0, 0, screen_size.0 as usize, screen_size.1 as usize - TASKBAR_HEIGHT - 1),
+        &mut desktop,
+    );
+
     let start_button_region = (
         0,
         screen_size.1 as usize - TASKBAR_HEIGHT,
@@ -59,39 +247,41 @@ pub fn run_desktop() -> ! {
         TASKBAR_HEIGHT,
     );
 
+    let mut shell_shapes = ShellShapes::default();
+
     // Taskbar
     const TASKBAR_CHUNK_AMOUNT: usize = 8;
     for i in 0..TASKBAR_CHUNK_AMOUNT {
-        desktop.add_shape(Shape::Rectangle {
+        shell_shapes.panels.push(desktop.add_shape(Shape::Rectangle {
             x: i * (screen_size.0 as usize / TASKBAR_CHUNK_AMOUNT),
             y: screen_size.1 as usize - TASKBAR_HEIGHT,
             width: screen_size.0 as usize / TASKBAR_CHUNK_AMOUNT,
             height: TASKBAR_HEIGHT,
-            color: TASKBAR_COLOR,
+            color: theme.taskbar,
             filled: true,
             hide: false,
-        });
-        desktop.add_shape(Shape::Rectangle {
+        }));
+        shell_shapes.lines.push(desktop.add_shape(Shape::Rectangle {
             x: i * (screen_size.0 as usize / TASKBAR_CHUNK_AMOUNT),
             y: screen_size.1 as usize - TASKBAR_HEIGHT - 1,
             width: screen_size.0 as usize / TASKBAR_CHUNK_AMOUNT,
             height: 1,
-            color: Color::BLACK,
+            color: theme.border,
             filled: true,
             hide: false,
-        });
+        }));
     }
 
     // Start button
-    desktop.add_shape(Shape::Rectangle {
+    shell_shapes.lines.push(desktop.add_shape(Shape::Rectangle {
         x: start_button_region.2,
         y: start_button_region.1,
         width: 1,
         height: start_button_region.3,
-        color: Color::BLACK,
+        color: theme.border,
         filled: true,
         hide: false,
-    });
+    }));
 
     desktop.add_shape(Shape::RawImage {
         x: start_button_region.0 + 30,
@@ -102,19 +292,18 @@ pub fn run_desktop() -> ! {
         hide: false,
     });
 
-    desktop.add_shape(Shape::Text {
+    shell_shapes.texts.push(desktop.add_shape(Shape::Text {
         x: start_button_region.0 + 60,
         y: start_button_region.1 + 15,
         content: "Start".to_string(),
-        color: Color::BLACK,
-        background_color: TASKBAR_COLOR,
+        color: theme.taskbar_text,
+        background_color: theme.taskbar,
         font_size: RasterHeight::Size24,
         font_weight: FontWeight::Light,
         hide: false,
-    });
+    }));
 
-    let mut start_menu_entries: Vec<(usize, usize, usize, usize, usize, usize, usize, &str)> =
-        Vec::new();
+    let mut start_menu_entries: Vec<StartMenuEntry> = Vec::new();
     let mut start_menu_open = false;
     let mut taskbar_window_shapes: Vec<(usize, usize, usize, usize)> = Vec::new();
     let mut prev_windows_state: Vec<(usize, String, bool)> = Vec::new();
@@ -123,30 +312,66 @@ pub fn run_desktop() -> ! {
     start_menu_entries.push((
         desktop.add_shape(Shape::Rectangle {
             x: 0,
-            y: screen_size.1 as usize - 265 - TASKBAR_HEIGHT - 2,
+            y: screen_size.1 as usize - 310 - TASKBAR_HEIGHT - 2,
             width: 201,
-            height: 267,
-            color: Color::BLACK,
+            height: 312,
+            color: theme.border,
             filled: false,
             hide: true,
         }),
         desktop.add_shape(Shape::Rectangle {
             x: 0,
-            y: screen_size.1 as usize - 265 - TASKBAR_HEIGHT - 1,
+            y: screen_size.1 as usize - 310 - TASKBAR_HEIGHT - 1,
             width: 200,
-            height: 265,
-            color: TASKBAR_COLOR,
+            height: 310,
+            color: theme.taskbar,
             filled: true,
             hide: true,
         }),
         desktop.add_shape(Shape::Empty),
         0,
-        screen_size.1 as usize - 290 - TASKBAR_HEIGHT,
+        screen_size.1 as usize - 335 - TASKBAR_HEIGHT,
         200,
-        300,
+        345,
         "",
     ));
 
+    // Settings start button
+    start_menu_entries.push((
+        desktop.add_shape(Shape::Rectangle {
+            x: 10,
+            y: screen_size.1 as usize - 320,
+            width: 180,
+            height: 1,
+            color: theme.border,
+            filled: true,
+            hide: true,
+        }),
+        desktop.add_shape(Shape::Text {
+            x: 40,
+            y: screen_size.1 as usize - 350,
+            content: "Settings".to_string(),
+            color: theme.taskbar_text,
+            background_color: theme.taskbar,
+            font_size: RasterHeight::Size20,
+            font_weight: FontWeight::Regular,
+            hide: true,
+        }),
+        desktop.add_shape(Shape::RawImage {
+            x: 15,
+            y: screen_size.1 as usize - 350,
+            width: 16,
+            height: 16,
+            data: generate_icon_for_app_str::<16, 16>("settings"),
+            hide: true,
+        }),
+        0,
+        screen_size.1 as usize - 360,
+        200,
+        45,
+        "Settings",
+    ));
+
     // Calculator start button
     start_menu_entries.push((
         desktop.add_shape(Shape::Rectangle {
@@ -154,7 +379,7 @@ pub fn run_desktop() -> ! {
             y: screen_size.1 as usize - 275,
             width: 180,
             height: 1,
-            color: Color::BLACK,
+            color: theme.border,
             filled: true,
             hide: true,
         }),
@@ -162,8 +387,8 @@ pub fn run_desktop() -> ! {
             x: 40,
             y: screen_size.1 as usize - 305,
             content: "Calculator".to_string(),
-            color: Color::BLACK,
-            background_color: TASKBAR_COLOR,
+            color: theme.taskbar_text,
+            background_color: theme.taskbar,
             font_size: RasterHeight::Size20,
             font_weight: FontWeight::Regular,
             hide: true,
@@ -190,7 +415,7 @@ pub fn run_desktop() -> ! {
             y: screen_size.1 as usize - 230,
             width: 180,
             height: 1,
-            color: Color::BLACK,
+            color: theme.border,
             filled: true,
             hide: true,
         }),
@@ -198,8 +423,8 @@ pub fn run_desktop() -> ! {
             x: 40,
             y: screen_size.1 as usize - 260,
             content: "Notepad".to_string(),
-            color: Color::BLACK,
-            background_color: TASKBAR_COLOR,
+            color: theme.taskbar_text,
+            background_color: theme.taskbar,
             font_size: RasterHeight::Size20,
             font_weight: FontWeight::Regular,
             hide: true,
@@ -226,7 +451,7 @@ pub fn run_desktop() -> ! {
             y: screen_size.1 as usize - 185,
             width: 180,
             height: 1,
-            color: Color::BLACK,
+            color: theme.border,
             filled: true,
             hide: true,
         }),
@@ -234,8 +459,8 @@ pub fn run_desktop() -> ! {
             x: 40,
             y: screen_size.1 as usize - 215,
             content: "File Manager".to_string(),
-            color: Color::BLACK,
-            background_color: TASKBAR_COLOR,
+            color: theme.taskbar_text,
+            background_color: theme.taskbar,
             font_size: RasterHeight::Size20,
             font_weight: FontWeight::Regular,
             hide: true,
@@ -262,7 +487,7 @@ pub fn run_desktop() -> ! {
             y: screen_size.1 as usize - 140,
             width: 180,
             height: 1,
-            color: Color::BLACK,
+            color: theme.border,
             filled: true,
             hide: true,
         }),
@@ -270,8 +495,8 @@ pub fn run_desktop() -> ! {
             x: 40,
             y: screen_size.1 as usize - 170,
             content: "System Info".to_string(),
-            color: Color::BLACK,
-            background_color: TASKBAR_COLOR,
+            color: theme.taskbar_text,
+            background_color: theme.taskbar,
             font_size: RasterHeight::Size20,
             font_weight: FontWeight::Regular,
             hide: true,
@@ -298,7 +523,7 @@ pub fn run_desktop() -> ! {
             y: screen_size.1 as usize - 95,
             width: 180,
             height: 1,
-            color: Color::BLACK,
+            color: theme.border,
             filled: true,
             hide: true,
         }),
@@ -306,8 +531,8 @@ pub fn run_desktop() -> ! {
             x: 40,
             y: screen_size.1 as usize - 125,
             content: "Tic-Tac-Toe".to_string(),
-            color: Color::BLACK,
-            background_color: TASKBAR_COLOR,
+            color: theme.taskbar_text,
+            background_color: theme.taskbar,
             font_size: RasterHeight::Size20,
             font_weight: FontWeight::Regular,
             hide: true,
@@ -334,7 +559,7 @@ pub fn run_desktop() -> ! {
             y: screen_size.1 as usize - 50,
             width: 0,
             height: 0,
-            color: Color::BLACK,
+            color: theme.border,
             filled: true,
             hide: true,
         }),
@@ -342,8 +567,8 @@ pub fn run_desktop() -> ! {
             x: 40,
             y: screen_size.1 as usize - 80,
             content: "Terminal".to_string(),
-            color: Color::BLACK,
-            background_color: TASKBAR_COLOR,
+            color: theme.taskbar_text,
+            background_color: theme.taskbar,
             font_size: RasterHeight::Size20,
             font_weight: FontWeight::Regular,
             hide: true,
@@ -363,24 +588,34 @@ pub fn run_desktop() -> ! {
         "Terminal",
     ));
 
+    // O marcador do menu tem o contorno e o fundo, as outras entradas a linha e o nome
+    for (idx, label_idx, _, _, _, _, _, label) in &start_menu_entries {
+        shell_shapes.lines.push(*idx);
+        if label.is_empty() {
+            shell_shapes.panels.push(*label_idx);
+        } else {
+            shell_shapes.texts.push(*label_idx);
+        }
+    }
+
     // Time and date background
-    desktop.add_shape(Shape::Rectangle {
+    shell_shapes.lines.push(desktop.add_shape(Shape::Rectangle {
         x: screen_size.0 as usize - 95,
         y: screen_size.1 as usize - TASKBAR_HEIGHT + 16,
         width: 1,
         height: TASKBAR_HEIGHT - 32,
-        color: Color::BLACK,
+        color: theme.border,
         filled: true,
         hide: false,
-    });
+    }));
 
     // Time
     let time_shape_idx = desktop.add_shape(Shape::Text {
         x: screen_size.0 as usize - 80,
         y: screen_size.1 as usize - TASKBAR_HEIGHT + 12,
         content: "22:42".to_string(),
-        color: Color::BLACK,
-        background_color: TASKBAR_COLOR,
+        color: theme.taskbar_text,
+        background_color: theme.taskbar,
         font_size: RasterHeight::Size16,
         font_weight: FontWeight::Regular,
         hide: false,
@@ -391,32 +626,90 @@ pub fn run_desktop() -> ! {
         x: screen_size.0 as usize - 80,
         y: screen_size.1 as usize - TASKBAR_HEIGHT + 8 + 16,
         content: "8/15/2025".to_string(),
-        color: Color::BLACK,
-        background_color: TASKBAR_COLOR,
+        color: theme.taskbar_text,
+        background_color: theme.taskbar,
         font_size: RasterHeight::Size16,
         font_weight: FontWeight::Regular,
         hide: false,
     });
+    shell_shapes.texts.push(time_shape_idx);
+    shell_shapes.texts.push(date_shape_idx);
 
-    serial_println!("Screen size: {}x{}", screen_size.0, screen_size.1);
+    info!("Screen size: {}x{}", screen_size.0, screen_size.1);
     
     // Inicializa teclado com layout atual
     let mut keyboard = create_keyboard(CURRENT_LAYOUT.load(Ordering::Relaxed));
-    serial_println!("Current keyboard layout: {}", get_current_layout());
+    info!("Current keyboard layout: {}", get_current_layout());
     
     // Rastreia layout anterior para detectar mudanças
     let mut last_layout = CURRENT_LAYOUT.load(Ordering::Relaxed);
 
+    // Atalhos globais e o seletor de janelas do Alt+Tab
+    let shortcuts = ShortcutRegistry::new();
+    let mut switcher = WindowSwitcher::new();
+
+    // Reabre as aplicações da sessão anterior
+    if settings::get_bool(SESSION_RESTORE, false) {
+        let apps = settings::get(SESSION_WINDOWS).unwrap_or_default();
+        for app in apps.split(',').filter(|app| !app.is_empty()) {
+            launch_app(&mut window_manager, app);
+        }
+    }
+
+    // Configuração de arranque do utilizador, sem recompilar o kernel
+    if let Ok(Some(false)) = path_exists(AUTOEXEC_PATH) {
+        let status = Shell::new().source(AUTOEXEC_PATH, &[], &mut AutoexecHost);
+        info!("{} exited with status {}", AUTOEXEC_PATH, status);
+    }
+
+    // Shell na porta série, para usar o sistema sem ecrã (-serial stdio)
+    let mut serial_console = SerialConsole::start();
+
     let time_update_ticks = 60 * 5;
     let mut ticks = 0u64;
 
     loop {
+        serial_console.poll();
+
         // Verifica se o layout mudou e atualiza o teclado se necessário
         let current_layout = CURRENT_LAYOUT.load(Ordering::Relaxed);
         if current_layout != last_layout {
             keyboard = create_keyboard(current_layout);
             last_layout = current_layout;
-            serial_println!("[KEYBOARD] Layout atualizado para: {}", get_current_layout());
+            info!("Layout atualizado para: {}", get_current_layout());
+
+            // Guarda o layout para o próximo arranque
+            save_setting(KEYBOARD_LAYOUT, get_current_layout(), "the layout");
+        }
+
+        // Definições alteradas, por exemplo pela aplicação Settings
+        if settings::generation() != settings_generation {
+            settings_generation = settings::generation();
+
+            // Novo tema: recolore a barra de tarefas, reconstrói os botões das
+            // janelas e pede às janelas que se redesenhem
+            let theme_changed = settings::apply_theme();
+            if theme_changed {
+                info!("Tema: {}", theme::current().name);
+                shell_shapes.apply_theme(&mut desktop);
+                window_manager.apply_theme();
+                prev_windows_state.clear();
+            }
+
+            let display = (
+                settings::get(DISPLAY_BACKGROUND),
+                settings::get(DISPLAY_WALLPAPER),
+            );
+            if theme_changed || display != applied_display {
+                applied_display = display;
+                apply_display_settings(&mut desktop);
+                // Os nomes dos ícones usam a cor de fundo
+                desktop_icons.refresh(&mut desktop);
+            }
+
+            if settings::get_bool(SESSION_RESTORE, false) {
+                save_session(&window_manager);
+            }
         }
 
         for _ in 0..10000 {
@@ -434,9 +727,72 @@ pub fn run_desktop() -> ! {
                         KeyCode::LAlt => {
                             ALT.store(key_event.state == KeyState::Down, Ordering::Relaxed);
                         }
+                        KeyCode::LWin | KeyCode::RWin => {
+                            SUPER.store(key_event.state == KeyState::Down, Ordering::Relaxed);
+                        }
                         _ => {}
                     }
-                    
+
+                    // Soltar o Alt confirma a janela escolhida no Alt+Tab
+                    if key_event.code == KeyCode::LAlt && key_event.state == KeyState::Up && switcher.visible {
+                        let bounds = switcher.bounds(screen_size.0 as usize, screen_size.1 as usize);
+                        if let Some(window_id) = switcher.finish() {
+                            window_manager.focus_window(window_id);
+                        }
+                        desktop.force_dirty_region(bounds.x, bounds.y, bounds.width, bounds.height);
+                    }
+
+                    // Atalhos globais primeiro, depois os aceleradores da janela em foco
+                    let mut consumed = false;
+                    if key_event.state == KeyState::Down {
+                        let modifiers = Modifiers::current();
+
+                        if let Some(action) = shortcuts.lookup(key_event.code, modifiers) {
+                            consumed = true;
+                            match action {
+                                ShortcutAction::NextWindow | ShortcutAction::PreviousWindow => {
+                                    switcher.cycle(
+                                        window_manager.get_switcher_windows(),
+                                        action == ShortcutAction::NextWindow,
+                                    );
+                                }
+                                ShortcutAction::CloseWindow => {
+                                    let closed = window_manager
+                                        .get_focused_window_id()
+                                        .and_then(|id| window_manager.request_close(id));
+                                    if let Some(rect) = closed {
+                                        desktop.force_dirty_region(rect.x, rect.y, rect.width, rect.height);
+                                    }
+                                }
+                                ShortcutAction::ToggleStartMenu => {
+                                    start_menu_open = !start_menu_open;
+                                    set_start_menu_visible(&mut desktop, &start_menu_entries, start_menu_open);
+                                }
+                                ShortcutAction::CycleKeyboardLayout => {
+                                    if cycle_keyboard_layout() {
+                                        info!("Layout alterado para: {}", get_current_layout());
+                                    }
+                                }
+                            }
+                        } else if window_manager.has_context_menu() && key_event.code == KeyCode::Escape {
+                            consumed = true;
+                            if let Some(rect) = window_manager.close_context_menu() {
+                                desktop.force_dirty_region(rect.x, rect.y, rect.width, rect.height);
+                            }
+                        } else if switcher.visible && key_event.code == KeyCode::Escape {
+                            consumed = true;
+                            let bounds = switcher.bounds(screen_size.0 as usize, screen_size.1 as usize);
+                            switcher.cancel();
+                            desktop.force_dirty_region(bounds.x, bounds.y, bounds.width, bounds.height);
+                        } else {
+                            consumed = window_manager.handle_accelerator(key_event.code, modifiers);
+                        }
+                    }
+
+                    if consumed {
+                        continue;
+                    }
+
                     if let Some(key) = keyboard.process_keyevent(key_event) {
                         let ctrl = CTRL.load(Ordering::Relaxed);
                         let shift = SHIFT.load(Ordering::Relaxed);
@@ -444,21 +800,19 @@ pub fn run_desktop() -> ! {
                         
                         match key {
                             DecodedKey::Unicode(character) => {
-                                window_manager.handle_char_input(
+                                let redraw_region = window_manager.handle_char_input(
                                     character,
                                     ctrl,
                                     alt,
                                     shift,
                                 );
+                                if let Some((x, y, width, height)) = redraw_region {
+                                    desktop.force_dirty_region(x, y, width, height);
+                                }
                             }
                             DecodedKey::RawKey(key) => {
-                                window_manager.handle_key_input(key);
-                                
-                                // Atalho para alternar layout: Ctrl+Alt+K
-                                if ctrl && alt && key == KeyCode::K {
-                                    if cycle_keyboard_layout() {
-                                        serial_println!("[KEYBOARD] Layout alterado para: {}", get_current_layout());
-                                    }
+                                if let Some((x, y, width, height)) = window_manager.handle_key_input(key) {
+                                    desktop.force_dirty_region(x, y, width, height);
                                 }
                             }
                         }
@@ -472,7 +826,7 @@ pub fn run_desktop() -> ! {
         }
 
         if ticks % time_update_ticks == 0 {
-            let raw_time = get_utc_time();
+            let raw_time = get_local_time();
 
             // Update time
             let time_str = format!("{:02}:{:02}", raw_time.hours, raw_time.minutes);
@@ -483,6 +837,9 @@ pub fn run_desktop() -> ! {
             desktop.update_text_content(date_shape_idx, date_str, None);
         }
 
+        // Ficheiros novos, apagados ou renomeados em /Desktop
+        desktop_icons.poll(&mut desktop);
+
         // Update taskbar window icons only when necessary
         let current_windows = window_manager.get_taskbar_windows();
 
@@ -521,10 +878,11 @@ pub fn run_desktop() -> ! {
                     break;
                 }
 
+                let theme = theme::current();
                 let bg_color = if *is_focused {
-                    Color::new(220, 220, 220)
+                    theme.taskbar_button_focused
                 } else {
-                    Color::new(160, 160, 160)
+                    theme.taskbar_button
                 };
 
                 let bg_idx = desktop.add_shape(Shape::Rectangle {
@@ -559,7 +917,7 @@ pub fn run_desktop() -> ! {
                     x: x + 26,
                     y: y + 8,
                     content: display_title,
-                    color: Color::BLACK,
+                    color: theme.taskbar_text,
                     background_color: bg_color,
                     font_size: RasterHeight::Size16,
                     font_weight: FontWeight::Regular,
@@ -569,6 +927,10 @@ pub fn run_desktop() -> ! {
                 taskbar_window_shapes.push((bg_idx, text_idx, icon_idx, *window_id));
             }
 
+            if settings::get_bool(SESSION_RESTORE, false) {
+                save_session(&window_manager);
+            }
+
             // Update the previous state cache
             prev_windows_state = current_windows
                 .iter()
@@ -576,6 +938,62 @@ pub fn run_desktop() -> ! {
                 .collect();
         }
 
+        // Eventos do rato para as aplicações (movimento, botões, roda, duplo clique).
+        // Vêm antes dos cliques para que um menu de contexto possa engolir o clique.
+        let modifiers = Modifiers::current();
+        for event in mouse_state.take_events() {
+            let kind = event.kind;
+            let (handled, redraw_region) =
+                window_manager.handle_mouse_event(kind, event.x, event.y, event.buttons, modifiers);
+            if let Some((x, y, width, height)) = redraw_region {
+                desktop.force_dirty_region(x, y, width, height);
+            }
+
+            // Ícones do ambiente de trabalho, só no fundo e com o menu Iniciar fechado.
+            // Um ícone a ser arrastado recebe tudo até ser largado.
+            let (x, y) = (event.x as usize, event.y as usize);
+            let over_desktop = desktop_icons.is_dragging()
+                || (!handled
+                    && !start_menu_open
+                    && y < screen_size.1 as usize - TASKBAR_HEIGHT
+                    && window_manager.window_at(x, y).is_none());
+            let opened = if over_desktop {
+                desktop_icons.handle_mouse_event(kind, x, y, &mut desktop)
+            } else {
+                None
+            };
+            if let Some(app) = opened {
+                launch_app(&mut window_manager, app);
+            }
+
+            // Botão direito fora das janelas: menu da barra de tarefas ou do ambiente de trabalho
+            if !handled && kind == MouseEventKind::Up(MouseButton::Right) {
+                let dirty = if let Some(window_id) =
+                    taskbar_window_at(&taskbar_window_shapes, x, y, screen_size.1 as usize)
+                {
+                    window_manager.open_taskbar_menu(window_id, x, y)
+                } else if y < screen_size.1 as usize - TASKBAR_HEIGHT {
+                    window_manager.open_context_menu(MenuOwner::Desktop, desktop_menu(), x, y)
+                } else {
+                    None
+                };
+                if let Some(rect) = dirty {
+                    desktop.force_dirty_region(rect.x, rect.y, rect.width, rect.height);
+                }
+            }
+        }
+
+        if let Some(action) = window_manager.take_desktop_action() {
+            match action {
+                "new_file" => match create_desktop_file() {
+                    Ok(path) => window_manager.open_app_handler(path, "notepad".to_string()),
+                    Err(e) => warn!("Could not create the file: {}", e),
+                },
+                "terminal" => launch_terminal(&mut window_manager),
+                _ => {}
+            }
+        }
+
         while let Some((x, y)) = click_queue.pop() {
             let (mut handled, redraw_region) = window_manager.handle_mouse_click(x, y);
             if let Some((x, y, width, height)) = redraw_region {
@@ -590,22 +1008,17 @@ pub fn run_desktop() -> ! {
             let y = y as usize;
 
             // Check for clicks on taskbar window icons
-            if !handled {
-                for (_bg_idx, _text_idx, _icon_idx, window_id) in &taskbar_window_shapes {
-                    let taskbar_start_x = 170;
-                    let window_index = taskbar_window_shapes
-                        .iter()
-                        .position(|(_, _, _, id)| id == window_id)
-                        .unwrap_or(0);
-                    let icon_x = taskbar_start_x + window_index * 125;
-                    let icon_y = screen_size.1 as usize - TASKBAR_HEIGHT + 10;
-
-                    if x >= icon_x && x < icon_x + 120 && y >= icon_y && y < icon_y + 30 {
-                        window_manager.handle_taskbar_click(*window_id);
-                        handled = true;
-                        break;
-                    }
+            let taskbar_hit = if handled {
+                None
+            } else {
+                taskbar_window_at(&taskbar_window_shapes, x, y, screen_size.1 as usize)
+            };
+            if let Some(window_id) = taskbar_hit {
+                let (_, redraw_region) = window_manager.handle_taskbar_click(window_id);
+                if let Some((x, y, width, height)) = redraw_region {
+                    desktop.force_dirty_region(x, y, width, height);
                 }
+                handled = true;
             }
 
             if start_menu_open {
@@ -638,6 +1051,19 @@ pub fn run_desktop() -> ! {
                             handled = true;
                             break;
                         }
+                        if *label == "Settings" {
+                            launch_settings(&mut window_manager);
+
+                            start_menu_open = false;
+                            for (idx, label_idx, icon_idx, _, _, _, _, _) in &start_menu_entries {
+                                desktop.hide_shape(*idx);
+                                desktop.hide_shape(*label_idx);
+                                desktop.hide_shape(*icon_idx);
+                            }
+
+                            handled = true;
+                            break;
+                        }
                         if *label == "Terminal" {
                             launch_terminal(&mut window_manager);
 
@@ -705,19 +1131,7 @@ pub fn run_desktop() -> ! {
                 && y < start_button_region.1 + start_button_region.3
             {
                 start_menu_open = !start_menu_open;
-
-                // Update start menu entries visibility
-                for (idx, label_idx, icon_idx, _, _, _, _, _) in &start_menu_entries {
-                    if start_menu_open {
-                        desktop.show_shape(*idx);
-                        desktop.show_shape(*label_idx);
-                        desktop.show_shape(*icon_idx);
-                    } else {
-                        desktop.hide_shape(*idx);
-                        desktop.hide_shape(*label_idx);
-                        desktop.hide_shape(*icon_idx);
-                    }
-                }
+                set_start_menu_visible(&mut desktop, &start_menu_entries, start_menu_open);
             }
         }
 
@@ -762,10 +1176,24 @@ pub fn run_desktop() -> ! {
             if let Some(fb) = framebuffer::FRAMEBUFFER.get() {
                 let mut fb_lock = fb.lock();
 
+                // Áreas de janelas que mudaram: o desktop redesenha-as e as janelas
+                // são compostas outra vez por cima
+                for rect in window_manager.take_damage() {
+                    desktop.force_dirty_region(rect.x, rect.y, rect.width, rect.height);
+                }
+
                 let dirty_regions: Vec<Rect> = desktop.get_dirty_regions().to_vec();
 
                 let desktop_rendered = desktop.render(&mut fb_lock, 0, 0, false);
-                let windows_rendered = window_manager.render(&mut fb_lock, &dirty_regions);
+                let mut windows_rendered = window_manager.render(&mut fb_lock, &dirty_regions);
+
+                // The Alt+Tab overlay goes on top of everything
+                if switcher.visible
+                    && (switcher.needs_redraw || desktop_rendered || windows_rendered)
+                {
+                    switcher.render(&mut fb_lock, screen_size.0 as usize, screen_size.1 as usize);
+                    windows_rendered = true;
+                }
 
                 let should_redraw_cursor = if mouse_state.has_moved {
                     true
@@ -819,7 +1247,7 @@ pub fn run_desktop() -> ! {
 
                 fb_lock.present_backbuffer_dirty();
             } else {
-                serial_println!("Framebuffer not initialized");
+                error!("Framebuffer not initialized");
             }
         });
 
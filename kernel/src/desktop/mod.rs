@@ -1,11 +1,24 @@
 pub mod application;
 pub mod calculator;
+pub mod clipboard;
+pub mod compositor;
+pub mod context_menu;
+pub mod dialog;
 pub mod filemanager;
+pub mod icons;
 pub mod input;
+pub mod logviewer;
 pub mod main;
+pub mod mouse;
 pub mod notepad;
+pub mod settings;
+pub mod shortcuts;
 pub mod sysinfo;
+pub mod theme;
 pub mod tictactoe;
 pub mod window_manager;
 pub mod terminal;
+pub mod vt;
 pub mod keyboard;
+pub mod widgets;
+pub mod wallpaper;
@@ -0,0 +1,114 @@
+use crate::desktop::shortcuts::Modifiers;
+
+/// Two presses closer than this (in ms) count as a double-click
+pub const DOUBLE_CLICK_MS: i64 = 400;
+/// ... as long as the pointer stayed within this many pixels
+pub const DOUBLE_CLICK_DISTANCE: i16 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MouseButtons {
+    pub left: bool,
+    pub right: bool,
+    pub middle: bool,
+}
+
+impl MouseButtons {
+    pub fn is_down(&self, button: MouseButton) -> bool {
+        match button {
+            MouseButton::Left => self.left,
+            MouseButton::Right => self.right,
+            MouseButton::Middle => self.middle,
+        }
+    }
+
+    pub fn any(&self) -> bool {
+        self.left || self.right || self.middle
+    }
+
+    pub fn set(&mut self, button: MouseButton, down: bool) {
+        match button {
+            MouseButton::Left => self.left = down,
+            MouseButton::Right => self.right = down,
+            MouseButton::Middle => self.middle = down,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MouseEventKind {
+    Move,
+    Down(MouseButton),
+    Up(MouseButton),
+    /// Sent after the `Down` of the second press
+    DoubleClick(MouseButton),
+    /// Wheel notches, positive scrolls down
+    Wheel(i8),
+}
+
+/// Mouse event as an application sees it. `x` and `y` are relative to the
+/// window content and can be outside of it while a button is held, since
+/// the window that got the press keeps getting events until the release.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MouseEvent {
+    pub kind: MouseEventKind,
+    pub x: i32,
+    pub y: i32,
+    /// Buttons held after this event
+    pub buttons: MouseButtons,
+    pub modifiers: Modifiers,
+}
+
+impl MouseEvent {
+    /// Position if it falls inside a surface of the given size
+    pub fn position_in(&self, width: usize, height: usize) -> Option<(usize, usize)> {
+        if self.x >= 0 && self.y >= 0 && (self.x as usize) < width && (self.y as usize) < height {
+            Some((self.x as usize, self.y as usize))
+        } else {
+            None
+        }
+    }
+}
+
+/// Remembers the last press to tell double-clicks apart
+pub struct DoubleClickTracker {
+    last: Option<(MouseButton, i64, i16, i16)>,
+}
+
+impl Default for DoubleClickTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DoubleClickTracker {
+    pub const fn new() -> Self {
+        DoubleClickTracker { last: None }
+    }
+
+    /// Record a press, returns true if it completes a double-click
+    pub fn press(&mut self, button: MouseButton, time_ms: i64, x: i16, y: i16) -> bool {
+        let repeated = self
+            .last
+            .is_some_and(|(last_button, last_time, last_x, last_y)| {
+                last_button == button
+                    && time_ms - last_time <= DOUBLE_CLICK_MS
+                    && (x - last_x).abs() <= DOUBLE_CLICK_DISTANCE
+                    && (y - last_y).abs() <= DOUBLE_CLICK_DISTANCE
+            });
+        if repeated {
+            // A third press starts over instead of being another double-click
+            self.last = None;
+            return true;
+        }
+
+        self.last = Some((button, time_ms, x, y));
+        false
+    }
+}
@@ -7,14 +7,25 @@ use noto_sans_mono_bitmap::{FontWeight, RasterHeight};
 use pc_keyboard::KeyCode;
 
 use crate::{
-    desktop::application::Application,
-    framebuffer::Color,
+    debug,
+    desktop::{
+        application::Application,
+        clipboard,
+        dialog::{CloseRequest, ModalDialog},
+        shortcuts::{Accelerator, Modifiers},
+        theme,
+    },
+    error,
     fs::{
         fat32::FileEntry,
-        manager::{list_directory, read_text_file, write_file},
+        manager::{
+            FsEventKind, WatchId, list_directory, poll_events, read_text_file, rewatch, unwatch,
+            watch, write_file,
+        },
     },
-    serial_println,
+    info,
     surface::{Shape, Surface},
+    warn,
 };
 
 #[derive(Clone, PartialEq)]
@@ -39,6 +50,10 @@ pub struct Notepad {
     has_changes: bool,
     mode: NotepadMode,
 
+    // Live updates when the open file is changed by another app
+    file_watch: Option<WatchId>,
+    changed_on_disk: bool,
+
     // Save-As dialog fields
     save_as_current_path: String,
     save_as_filename: String,
@@ -63,8 +78,7 @@ impl Notepad {
             match read_text_file(path) {
                 Ok(content) => content,
                 Err(error) => {
-                    serial_println!("Failed to open file {}", path);
-                    serial_println!("Error: {}", error);
+                    error!("Failed to open file {}: {}", path, error);
                     String::new()
                 }
             }
@@ -84,6 +98,8 @@ impl Notepad {
             previous_content: String::new(),
             prev_cursor_x: 0,
             prev_cursor_y: 0,
+            file_watch: args.as_deref().map(watch),
+            changed_on_disk: false,
             open_file_path: args,
             has_changes: false,
             mode: NotepadMode::Normal,
@@ -107,6 +123,35 @@ impl Notepad {
         }
     }
 
+    /// There is no selection yet, so copying takes the line under the cursor
+    fn copy_line(&mut self) {
+        let start = self.text_content[..self.cursor_position]
+            .rfind('\n')
+            .map_or(0, |i| i + 1);
+        let end = self.text_content[self.cursor_position..]
+            .find('\n')
+            .map_or(self.text_content.len(), |i| self.cursor_position + i + 1);
+        clipboard::set_text(self.text_content[start..end].to_string());
+    }
+
+    fn paste(&mut self) {
+        let Some(text) = clipboard::text() else {
+            return;
+        };
+
+        // The cursor moves a byte at a time, so only ASCII goes in
+        let text: String = text
+            .chars()
+            .filter(|c| c.is_ascii() && (*c == '\n' || !c.is_control()))
+            .collect();
+        self.text_content.insert_str(self.cursor_position, &text);
+        self.cursor_position += text.len();
+        self.has_changes = true;
+
+        self.update_display_lines();
+        self.update_scroll_if_needed();
+    }
+
     fn handle_save(&mut self) {
         if self.open_file_path.is_none() {
             // No file path set, show Save-As dialog
@@ -120,14 +165,16 @@ impl Notepad {
         ) {
             Ok(_) => {
                 self.has_changes = false;
+                self.changed_on_disk = false;
+                self.ignore_own_changes();
                 self.previous_content += " "; // Trigger redraw
-                serial_println!(
+                info!(
                     "File {} saved successfully.",
                     self.open_file_path.as_ref().unwrap()
                 );
             }
             Err(e) => {
-                serial_println!(
+                error!(
                     "Failed to save file {}: {}",
                     self.open_file_path.as_ref().unwrap(),
                     e
@@ -136,6 +183,50 @@ impl Notepad {
         }
     }
 
+    /// Drop the events caused by our own save
+    fn ignore_own_changes(&mut self) {
+        if let Some(id) = self.file_watch {
+            poll_events(id);
+        }
+    }
+
+    /// React to other apps touching the open file
+    fn handle_file_events(&mut self) {
+        let Some(id) = self.file_watch else {
+            return;
+        };
+
+        for event in poll_events(id) {
+            match event.kind {
+                FsEventKind::Modified | FsEventKind::Created => {
+                    if self.has_changes {
+                        // Don't throw away unsaved edits, just let the user know
+                        self.changed_on_disk = true;
+                        continue;
+                    }
+
+                    if let Ok(content) = read_text_file(&event.path) {
+                        self.text_content = content;
+                        self.cursor_position = self.cursor_position.min(self.text_content.len());
+                        while !self.text_content.is_char_boundary(self.cursor_position) {
+                            self.cursor_position -= 1;
+                        }
+                        self.update_display_lines();
+                        self.update_scroll_if_needed();
+                    }
+                }
+                FsEventKind::Deleted => {
+                    // The buffer is now the only copy
+                    self.has_changes = true;
+                }
+                FsEventKind::Renamed { to } => {
+                    rewatch(id, &to);
+                    self.open_file_path = Some(to);
+                }
+            }
+        }
+    }
+
     fn enter_save_as_mode(&mut self) {
         self.mode = NotepadMode::SaveAs;
 
@@ -176,10 +267,9 @@ impl Notepad {
                 self.save_as_folder_shapes_changed = true;
             }
             Err(e) => {
-                serial_println!(
+                error!(
                     "Failed to list directory {}: {}",
-                    self.save_as_current_path,
-                    e
+                    self.save_as_current_path, e
                 );
                 self.save_as_folders.clear();
                 self.save_as_folder_shapes_changed = true;
@@ -214,7 +304,7 @@ impl Notepad {
 
     fn perform_save_as(&mut self) -> bool {
         if self.save_as_filename.trim().is_empty() {
-            serial_println!("Please enter a filename");
+            warn!("Please enter a filename");
             return false;
         }
 
@@ -226,15 +316,20 @@ impl Notepad {
 
         match write_file(&file_path, &self.text_content.as_bytes()) {
             Ok(_) => {
+                match self.file_watch {
+                    Some(id) => rewatch(id, &file_path),
+                    None => self.file_watch = Some(watch(&file_path)),
+                }
                 self.open_file_path = Some(file_path.clone());
                 self.has_changes = false;
+                self.changed_on_disk = false;
                 self.mode = NotepadMode::Normal;
                 self.previous_content += " "; // Trigger redraw
-                serial_println!("File saved as: {}", file_path);
+                info!("File saved as: {}", file_path);
                 true
             }
             Err(e) => {
-                serial_println!("Failed to save file {}: {}", file_path, e);
+                error!("Failed to save file {}: {}", file_path, e);
                 false
             }
         }
@@ -330,6 +425,7 @@ impl Notepad {
     }
 
     fn render_save_as_dialog(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
         let width = surface.width;
         let height = surface.height;
 
@@ -342,8 +438,8 @@ impl Notepad {
                 x: 30,
                 y: 35,
                 content: "Save As".to_string(),
-                color: Color::BLACK,
-                background_color: Color::WHITE,
+                color: theme.text,
+                background_color: theme.input_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Bold,
                 hide: false,
@@ -354,8 +450,8 @@ impl Notepad {
                 x: 30,
                 y: 60,
                 content: format!("Location: {}", self.save_as_current_path),
-                color: Color::BLACK,
-                background_color: Color::WHITE,
+                color: theme.text,
+                background_color: theme.input_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -367,8 +463,8 @@ impl Notepad {
                 x: 30,
                 y: filename_y,
                 content: "Filename:".to_string(),
-                color: Color::BLACK,
-                background_color: Color::WHITE,
+                color: theme.text,
+                background_color: theme.input_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -380,7 +476,7 @@ impl Notepad {
                 y: filename_y + 20,
                 width: width - 80,
                 height: 25,
-                color: Color::new(240, 240, 240),
+                color: theme.window_background,
                 filled: true,
                 hide: false,
             });
@@ -390,7 +486,7 @@ impl Notepad {
                 y: filename_y + 20,
                 width: width - 80,
                 height: 25,
-                color: Color::BLACK,
+                color: theme.control_border,
                 filled: false,
                 hide: false,
             });
@@ -400,8 +496,8 @@ impl Notepad {
                 x: 35,
                 y: filename_y + 25,
                 content: format!("{}_", self.save_as_filename),
-                color: Color::BLACK,
-                background_color: Color::new(240, 240, 240),
+                color: theme.text,
+                background_color: theme.window_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -416,7 +512,7 @@ impl Notepad {
                 y: button_y,
                 width: 70,
                 height: 25,
-                color: Color::new(180, 255, 180),
+                color: theme.control,
                 filled: true,
                 hide: false,
             });
@@ -425,8 +521,8 @@ impl Notepad {
                 x: 50,
                 y: button_y + 5,
                 content: "Save".to_string(),
-                color: Color::BLACK,
-                background_color: Color::new(180, 255, 180),
+                color: theme.control_text,
+                background_color: theme.control,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -438,7 +534,7 @@ impl Notepad {
                 y: button_y,
                 width: 70,
                 height: 25,
-                color: Color::new(255, 180, 180),
+                color: theme.control,
                 filled: true,
                 hide: false,
             });
@@ -447,8 +543,8 @@ impl Notepad {
                 x: 125,
                 y: button_y + 5,
                 content: "Cancel".to_string(),
-                color: Color::BLACK,
-                background_color: Color::new(255, 180, 180),
+                color: theme.control_text,
+                background_color: theme.control,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -461,8 +557,8 @@ impl Notepad {
                 content:
                     "Use arrow keys to navigate, Enter to open folder, type filename and click Save"
                         .to_string(),
-                color: Color::new(100, 100, 100),
-                background_color: Color::WHITE,
+                color: theme.disabled_text,
+                background_color: theme.input_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -508,6 +604,7 @@ impl Notepad {
     }
 
     fn restore_normal_view(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
         self.save_as_folder_shapes.clear();
         self.save_as_selected_folder = None;
 
@@ -517,8 +614,8 @@ impl Notepad {
             x: 5,
             y: 5,
             content: self.get_display_text(),
-            color: Color::BLACK,
-            background_color: Color::WHITE,
+            color: theme.text,
+            background_color: theme.input_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -528,8 +625,8 @@ impl Notepad {
             x: 5,
             y: 5,
             content: "|".to_string(),
-            color: Color::BLACK,
-            background_color: Color::WHITE,
+            color: theme.text,
+            background_color: theme.input_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -537,6 +634,7 @@ impl Notepad {
     }
 
     fn update_save_as_folder_list(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
         // Clear old folder shapes
         let mut rm_offset = 0;
         for (bg_idx, text_idx) in &self.save_as_folder_shapes {
@@ -568,7 +666,7 @@ impl Notepad {
                 y: y_pos,
                 width: width - 80,
                 height: 18,
-                color: Color::WHITE,
+                color: theme.input_background,
                 filled: true,
                 hide: false,
             });
@@ -578,8 +676,8 @@ impl Notepad {
                 x: 35,
                 y: y_pos + 2,
                 content: format!("/{}", folder.name),
-                color: Color::BLACK,
-                background_color: Color::WHITE,
+                color: theme.text,
+                background_color: theme.input_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -590,6 +688,7 @@ impl Notepad {
     }
 
     fn update_save_as_selection(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
         let max_visible_folders = 8;
 
         for (display_idx, (bg_idx, _text_idx)) in self
@@ -600,9 +699,9 @@ impl Notepad {
         {
             let actual_idx = display_idx + self.save_as_scroll_offset;
             let bg_color = if Some(actual_idx) == self.save_as_selected_folder {
-                Color::new(150, 200, 255)
+                theme.selection
             } else {
-                Color::WHITE
+                theme.input_background
             };
 
             // Update background color
@@ -617,13 +716,14 @@ impl Notepad {
 
 impl Application for Notepad {
     fn init(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
         // Text content display
         self.text_area_idx = surface.add_shape(Shape::Text {
             x: 5,
             y: 5,
             content: self.get_display_text(),
-            color: Color::BLACK,
-            background_color: Color::WHITE,
+            color: theme.text,
+            background_color: theme.input_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -635,7 +735,7 @@ impl Application for Notepad {
             y: 5,
             width: 1,
             height: 16,
-            color: Color::BLACK,
+            color: theme.text,
             filled: true,
             hide: false,
         });
@@ -673,16 +773,10 @@ impl Application for Notepad {
             return;
         }
 
+        // Ctrl+S and Ctrl+Shift+S come in as accelerators. There is no open
+        // dialog (files are opened from the file manager), so Ctrl+O and any
+        // other Ctrl combination do nothing.
         if ctrl_pressed {
-            match ch {
-                's' | 'S' => {
-                    self.handle_save();
-                }
-                'o' | 'O' => {
-                    unimplemented!("Ctrl+O - Open file dialog not implemented.");
-                }
-                _ => {}
-            }
             return;
         }
 
@@ -762,6 +856,8 @@ impl Application for Notepad {
     }
 
     fn render(&mut self, surface: &mut Surface) {
+        self.handle_file_events();
+
         if self.mode == NotepadMode::SaveAs {
             self.render_save_as_dialog(surface);
             return;
@@ -789,13 +885,13 @@ impl Application for Notepad {
         }
     }
 
-    fn handle_mouse_click(&mut self, x: usize, y: usize, _surface: &mut Surface) {
+    fn handle_mouse_click(&mut self, x: usize, y: usize, surface: &mut Surface) {
         if self.mode != NotepadMode::SaveAs {
             return;
         }
 
-        let width = 600; // Assume standard window width
-        let height = 400; // Assume standard window height
+        let width = surface.width;
+        let height = surface.height;
 
         // Handle folder list clicks
         let list_start_y = 85;
@@ -818,7 +914,7 @@ impl Application for Notepad {
                     self.handle_save_as_navigation(&folder_name);
                 } else {
                     // Single click: select folder
-                    serial_println!("Selected folder: {}", self.save_as_folders[i].name);
+                    debug!("Selected folder: {}", self.save_as_folders[i].name);
                     self.save_as_selected_folder = Some(i);
                 }
                 return;
@@ -837,6 +933,78 @@ impl Application for Notepad {
         }
     }
 
+    fn handle_resize(&mut self, surface: &mut Surface) {
+        self.max_chars_per_line = surface.width.saturating_sub(10) / 7;
+        self.max_visible_lines = surface.height.saturating_sub(10) / 18;
+
+        self.update_display_lines();
+        self.update_scroll_if_needed();
+
+        // Force the text and the Save-As dialog to be rebuilt for the new size
+        self.previous_content.clear();
+        if self.mode == NotepadMode::SaveAs {
+            self.save_as_ui_initialized = false;
+        }
+    }
+
+    fn handle_theme_change(&mut self, surface: &mut Surface) {
+        // Same as a resize, but the text shapes need the new colors too
+        self.previous_content.clear();
+        if self.mode == NotepadMode::SaveAs {
+            self.save_as_ui_initialized = false;
+        } else {
+            self.restore_normal_view(surface);
+        }
+    }
+
+    fn accelerators(&self) -> &'static [Accelerator] {
+        const ACCELERATORS: &[Accelerator] = &[
+            Accelerator::new(Modifiers::CTRL, KeyCode::S, "save"),
+            Accelerator::new(Modifiers::CTRL_SHIFT, KeyCode::S, "save_as"),
+            Accelerator::new(Modifiers::CTRL, KeyCode::C, "copy_line"),
+            Accelerator::new(Modifiers::CTRL, KeyCode::V, "paste"),
+        ];
+        ACCELERATORS
+    }
+
+    fn handle_accelerator(&mut self, action: &'static str, _surface: &mut Surface) {
+        if self.mode == NotepadMode::SaveAs {
+            return;
+        }
+
+        match action {
+            "save" => self.handle_save(),
+            "save_as" => self.trigger_save_as(),
+            "copy_line" => self.copy_line(),
+            "paste" => self.paste(),
+            _ => {}
+        }
+    }
+
+    fn on_close_requested(&mut self, _surface: &mut Surface) -> CloseRequest {
+        if !self.has_changes {
+            return CloseRequest::Close;
+        }
+
+        let name = match &self.open_file_path {
+            Some(path) => path.split('/').next_back().unwrap_or("Untitled"),
+            None => "Untitled",
+        };
+        CloseRequest::Ask(ModalDialog::save_changes(name))
+    }
+
+    fn on_close_dialog_result(&mut self, button: usize, _surface: &mut Surface) -> bool {
+        match button {
+            0 => {
+                // Untitled buffers go through Save-As first, the window stays open for it
+                self.handle_save();
+                !self.has_changes && self.mode == NotepadMode::Normal
+            }
+            1 => true,
+            _ => false,
+        }
+    }
+
     fn get_title(&self) -> Option<String> {
         let base_title = if let Some(ref path) = self.open_file_path {
             path.split('/').last().unwrap_or("Untitled").to_string()
@@ -844,7 +1012,9 @@ impl Application for Notepad {
             "Untitled".to_string()
         };
 
-        let title = if self.has_changes {
+        let title = if self.changed_on_disk {
+            format!("{}* (changed on disk)", base_title)
+        } else if self.has_changes {
             format!("{}*", base_title)
         } else {
             base_title
@@ -853,3 +1023,11 @@ impl Application for Notepad {
         Some(format!("{} - Notepad", title))
     }
 }
+
+impl Drop for Notepad {
+    fn drop(&mut self) {
+        if let Some(id) = self.file_watch {
+            unwatch(id);
+        }
+    }
+}
@@ -0,0 +1,397 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use noto_sans_mono_bitmap::{FontWeight, RasterHeight};
+use pc_keyboard::KeyCode;
+
+use crate::{
+    desktop::{
+        application::Application,
+        keyboard::{get_current_layout, set_keyboard_layout},
+        mouse::MouseEvent,
+        theme::{self, THEMES},
+        wallpaper::WALLPAPER_PATH,
+        widgets::{Button, Checkbox, Label, ListView, TextInput, Ui, WidgetEvent, layout::Column},
+    },
+    settings::{
+        self, DISPLAY_BACKGROUND, DISPLAY_WALLPAPER, KEYBOARD_LAYOUT, SESSION_RESTORE, THEME,
+        TIMEZONE, format_color, parse_color,
+    },
+    surface::{Rect, Surface},
+    time::{format_timezone, get_local_time, get_timezone_offset, set_timezone_offset},
+};
+
+const CONTENT_X: usize = 140;
+const CONTENT_WIDTH: usize = 300;
+
+#[derive(Clone, Copy, PartialEq)]
+enum SettingsPage {
+    Keyboard,
+    Display,
+    Theme,
+    TimeZone,
+}
+
+/// Navigation buttons, in the order they are added to the ui (their ids)
+const PAGES: [(SettingsPage, &str); 4] = [
+    (SettingsPage::Keyboard, "Keyboard"),
+    (SettingsPage::Display, "Display"),
+    (SettingsPage::Theme, "Theme"),
+    (SettingsPage::TimeZone, "Time zone"),
+];
+
+const LAYOUTS: [(&str, &str); 3] = [
+    ("azerty", "AZERTY (French)"),
+    ("qwerty", "QWERTY (US)"),
+    ("dvorak", "Dvorak"),
+];
+
+/// UTC offsets in minutes, including the odd half and quarter hours
+const TIMEZONES: [i32; 38] = [
+    -720, -660, -600, -570, -540, -480, -420, -360, -300, -240, -210, -180, -120, -60, 0, 60, 120,
+    180, 210, 240, 270, 300, 330, 345, 360, 390, 420, 480, 525, 540, 570, 600, 630, 660, 720, 765,
+    780, 840,
+];
+
+/// Keyboard, display, theme and time zone settings, saved to `/etc/goofy.conf`
+pub struct Settings {
+    page: SettingsPage,
+    ui: Ui<Settings>,
+    status_id: usize,
+    status_text: String,
+    background_id: usize,
+    wallpaper_id: usize,
+    clock_id: usize,
+    clock_text: String,
+    height: usize,
+}
+
+impl Settings {
+    pub fn new(_args: Option<String>) -> Self {
+        Self {
+            page: SettingsPage::Keyboard,
+            ui: Ui::new(),
+            status_id: 0,
+            status_text: String::new(),
+            background_id: 0,
+            wallpaper_id: 0,
+            clock_id: 0,
+            clock_text: String::new(),
+            height: 0,
+        }
+    }
+
+    /// Rebuild the window for the current page
+    fn build(&mut self, surface: &mut Surface) {
+        surface.clear_all_shapes();
+        self.ui.clear();
+        self.height = surface.height;
+
+        let theme = theme::current();
+        let mut nav = Column::new(10, 10, 115, 5);
+        for (page, label) in PAGES {
+            let mut button = Button::new(nav.next(30), label);
+            if page == self.page {
+                button = button
+                    .with_color(theme.selection)
+                    .with_text_color(theme.selection_text);
+            }
+            self.ui.add(button, Some(Self::on_page));
+        }
+
+        let mut column = Column::new(CONTENT_X, 10, CONTENT_WIDTH, 8);
+        match self.page {
+            SettingsPage::Keyboard => self.build_keyboard(&mut column, surface),
+            SettingsPage::Display => self.build_display(&mut column),
+            SettingsPage::Theme => self.build_theme(&mut column, surface),
+            SettingsPage::TimeZone => self.build_timezone(&mut column, surface),
+        }
+
+        self.status_id = self.ui.add(
+            Label::new(CONTENT_X, self.height.saturating_sub(26), &self.status_text),
+            None,
+        );
+        self.ui.attach(surface);
+    }
+
+    fn add_title(&mut self, column: &mut Column, text: &str) {
+        let rect = column.next(24);
+        self.ui.add(
+            Label::new(rect.x, rect.y, text).with_font(RasterHeight::Size20, FontWeight::Bold),
+            None,
+        );
+    }
+
+    /// A list of choices with the current one selected
+    fn add_choices(
+        &mut self,
+        column: &mut Column,
+        height: usize,
+        items: Vec<String>,
+        selected: Option<usize>,
+        callback: fn(&mut Settings, usize, WidgetEvent, &mut Surface),
+        surface: &mut Surface,
+    ) {
+        let mut list = ListView::new(column.next(height));
+        list.set_items(items, surface);
+        list.select(selected, surface);
+        self.ui.add(list, Some(callback));
+    }
+
+    fn build_keyboard(&mut self, column: &mut Column, surface: &mut Surface) {
+        self.add_title(column, "Keyboard layout");
+        let current = get_current_layout();
+        self.add_choices(
+            column,
+            66,
+            LAYOUTS.iter().map(|(_, label)| label.to_string()).collect(),
+            LAYOUTS.iter().position(|(name, _)| *name == current),
+            Self::on_layout,
+            surface,
+        );
+    }
+
+    fn build_display(&mut self, column: &mut Column) {
+        self.add_title(column, "Display");
+
+        // Empty means the theme decides
+        let background = settings::get(DISPLAY_BACKGROUND).unwrap_or_default();
+        let rect = column.next(16);
+        self.ui.add(
+            Label::new(rect.x, rect.y, "Background #rrggbb, or empty"),
+            None,
+        );
+        self.background_id = self.ui.add(
+            TextInput::new(column.next(26))
+                .with_text(&background)
+                .with_max_length(7),
+            Some(Self::on_display_submit),
+        );
+
+        let wallpaper =
+            settings::get(DISPLAY_WALLPAPER).unwrap_or_else(|| WALLPAPER_PATH.to_string());
+        let rect = column.next(16);
+        self.ui.add(
+            Label::new(rect.x, rect.y, "Wallpaper BMP, tiled (empty for none)"),
+            None,
+        );
+        self.wallpaper_id = self.ui.add(
+            TextInput::new(column.next(26)).with_text(&wallpaper),
+            Some(Self::on_display_submit),
+        );
+
+        let rect = column.next(16);
+        self.ui.add(
+            Checkbox::new(
+                rect.x,
+                rect.y,
+                "Reopen windows at startup",
+                settings::get_bool(SESSION_RESTORE, false),
+            ),
+            Some(Self::on_session_restore),
+        );
+
+        let rect = column.next(30);
+        self.ui.add(
+            Button::new(Rect::new(rect.x, rect.y, 80, rect.height), "Apply"),
+            Some(Self::on_display_apply),
+        );
+    }
+
+    fn build_theme(&mut self, column: &mut Column, surface: &mut Surface) {
+        self.add_title(column, "Theme");
+        let current = theme::current().name;
+        self.add_choices(
+            column,
+            66,
+            THEMES
+                .iter()
+                .map(|theme| theme.display_name.to_string())
+                .collect(),
+            THEMES.iter().position(|theme| theme.name == current),
+            Self::on_theme,
+            surface,
+        );
+    }
+
+    fn build_timezone(&mut self, column: &mut Column, surface: &mut Surface) {
+        self.add_title(column, "Time zone");
+        let rect = column.next(16);
+        self.clock_text = self.clock();
+        self.clock_id = self
+            .ui
+            .add(Label::new(rect.x, rect.y, &self.clock_text), None);
+
+        // Whatever is left above the status line
+        let list_height = self.height.saturating_sub(110);
+        let offset = get_timezone_offset();
+        self.add_choices(
+            column,
+            list_height,
+            TIMEZONES
+                .iter()
+                .map(|&zone| format_timezone(zone))
+                .collect(),
+            TIMEZONES.iter().position(|&zone| zone == offset),
+            Self::on_timezone,
+            surface,
+        );
+    }
+
+    fn clock(&self) -> String {
+        let now = get_local_time();
+        format!(
+            "Local time: {:02}:{:02}  {}/{}/{}",
+            now.hours, now.minutes, now.day, now.month, now.year
+        )
+    }
+
+    fn set_status(&mut self, text: &str, surface: &mut Surface) {
+        self.status_text = text.to_string();
+        if let Some(status) = self.ui.label(self.status_id) {
+            status.set_text(text, surface);
+        }
+    }
+
+    /// Write the settings file and say how it went
+    fn save(&mut self, what: &str, surface: &mut Surface) {
+        let status = match settings::save() {
+            Ok(()) => format!("{} saved", what),
+            Err(e) => format!("Could not save: {}", e),
+        };
+        self.set_status(&status, surface);
+    }
+
+    fn on_page(&mut self, id: usize, _event: WidgetEvent, surface: &mut Surface) {
+        if let Some(&(page, _)) = PAGES.get(id).filter(|&&(page, _)| page != self.page) {
+            self.page = page;
+            self.status_text.clear();
+            self.build(surface);
+        }
+    }
+
+    fn on_layout(&mut self, _id: usize, event: WidgetEvent, surface: &mut Surface) {
+        if let WidgetEvent::Selected(index) = event {
+            let (name, _) = LAYOUTS[index];
+            set_keyboard_layout(name);
+            settings::set(KEYBOARD_LAYOUT, name);
+            self.save("Keyboard layout", surface);
+        }
+    }
+
+    fn on_display_submit(&mut self, _id: usize, event: WidgetEvent, surface: &mut Surface) {
+        if event == WidgetEvent::Submitted {
+            self.apply_display(surface);
+        }
+    }
+
+    fn on_display_apply(&mut self, _id: usize, _event: WidgetEvent, surface: &mut Surface) {
+        self.apply_display(surface);
+    }
+
+    fn apply_display(&mut self, surface: &mut Surface) {
+        let background = self
+            .ui
+            .text_input(self.background_id)
+            .map(|input| input.text().to_string())
+            .unwrap_or_default();
+        let wallpaper = self
+            .ui
+            .text_input(self.wallpaper_id)
+            .map(|input| input.text().trim().to_string())
+            .unwrap_or_default();
+
+        let background = if background.trim().is_empty() {
+            String::new()
+        } else {
+            let Some(color) = parse_color(&background) else {
+                self.set_status("Colors look like #326fa8", surface);
+                return;
+            };
+            format_color(color)
+        };
+
+        settings::set(DISPLAY_BACKGROUND, &background);
+        settings::set(DISPLAY_WALLPAPER, &wallpaper);
+        self.save("Display settings", surface);
+    }
+
+    fn on_session_restore(&mut self, _id: usize, event: WidgetEvent, surface: &mut Surface) {
+        if let WidgetEvent::Toggled(checked) = event {
+            settings::set(SESSION_RESTORE, if checked { "true" } else { "false" });
+            self.save("Session setting", surface);
+        }
+    }
+
+    fn on_theme(&mut self, _id: usize, event: WidgetEvent, surface: &mut Surface) {
+        if let WidgetEvent::Selected(index) = event {
+            // The desktop switches when it sees the setting change, and
+            // rebuilds this window with the rest
+            settings::set(THEME, THEMES[index].name);
+            self.save("Theme", surface);
+        }
+    }
+
+    fn on_timezone(&mut self, _id: usize, event: WidgetEvent, surface: &mut Surface) {
+        if let WidgetEvent::Selected(index) = event {
+            let offset = TIMEZONES[index];
+            set_timezone_offset(offset);
+            settings::set(TIMEZONE, &format_timezone(offset));
+            self.save("Time zone", surface);
+        }
+    }
+}
+
+impl Application for Settings {
+    fn init(&mut self, surface: &mut Surface) {
+        self.build(surface);
+    }
+
+    fn handle_theme_change(&mut self, surface: &mut Surface) {
+        self.build(surface);
+    }
+
+    fn render(&mut self, surface: &mut Surface) {
+        if self.page != SettingsPage::TimeZone {
+            return;
+        }
+
+        let clock = self.clock();
+        if clock != self.clock_text {
+            if let Some(label) = self.ui.label(self.clock_id) {
+                label.set_text(&clock, surface);
+            }
+            self.clock_text = clock;
+        }
+    }
+
+    fn get_title(&self) -> Option<String> {
+        None
+    }
+
+    fn handle_char_input(&mut self, c: char, _ctrl_pressed: bool, surface: &mut Surface) {
+        if let Some((callback, id, event)) = self.ui.handle_char(c, surface) {
+            callback(self, id, event, surface);
+        }
+    }
+
+    fn handle_key_input(&mut self, key: KeyCode, surface: &mut Surface) {
+        if let Some((callback, id, event)) = self.ui.handle_key(key, surface) {
+            callback(self, id, event, surface);
+        }
+    }
+
+    fn handle_mouse_click(&mut self, x: usize, y: usize, surface: &mut Surface) {
+        if let Some((callback, id, event)) = self.ui.handle_click(x, y, surface) {
+            callback(self, id, event, surface);
+        }
+    }
+
+    fn handle_mouse_event(&mut self, event: &MouseEvent, surface: &mut Surface) {
+        if let Some((callback, id, event)) = self.ui.handle_mouse_event(event, surface) {
+            callback(self, id, event, surface);
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use alloc::{string::String, vec::Vec};
+use core::sync::atomic::Ordering;
+use pc_keyboard::KeyCode;
+
+use crate::{
+    desktop::{
+        keyboard::{ALT, CTRL, SHIFT, SUPER},
+        theme,
+    },
+    framebuffer::{Color, FrameBufferWriter},
+    surface::Rect,
+};
+
+/// Modifier keys held with a shortcut. Matching is exact, so Alt+Tab and
+/// Shift+Alt+Tab are different shortcuts.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub super_key: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers {
+        ctrl: false,
+        alt: false,
+        shift: false,
+        super_key: false,
+    };
+    pub const CTRL: Modifiers = Modifiers {
+        ctrl: true,
+        ..Modifiers::NONE
+    };
+    pub const ALT: Modifiers = Modifiers {
+        alt: true,
+        ..Modifiers::NONE
+    };
+    pub const CTRL_SHIFT: Modifiers = Modifiers {
+        ctrl: true,
+        shift: true,
+        ..Modifiers::NONE
+    };
+    pub const CTRL_ALT: Modifiers = Modifiers {
+        ctrl: true,
+        alt: true,
+        ..Modifiers::NONE
+    };
+    pub const ALT_SHIFT: Modifiers = Modifiers {
+        alt: true,
+        shift: true,
+        ..Modifiers::NONE
+    };
+
+    /// Modifiers currently held down
+    pub fn current() -> Self {
+        Modifiers {
+            ctrl: CTRL.load(Ordering::Relaxed),
+            alt: ALT.load(Ordering::Relaxed),
+            shift: SHIFT.load(Ordering::Relaxed),
+            super_key: SUPER.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A key (by physical position) plus the modifiers that must be held with it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shortcut {
+    pub modifiers: Modifiers,
+    pub key: KeyCode,
+}
+
+impl Shortcut {
+    pub const fn new(modifiers: Modifiers, key: KeyCode) -> Self {
+        Shortcut { modifiers, key }
+    }
+
+    pub fn matches(&self, key: KeyCode, modifiers: Modifiers) -> bool {
+        self.key == key && self.modifiers == modifiers
+    }
+}
+
+/// Shortcut an application handles itself while its window is focused.
+/// `action` is passed back to `Application::handle_accelerator`.
+#[derive(Clone, Copy, Debug)]
+pub struct Accelerator {
+    pub shortcut: Shortcut,
+    pub action: &'static str,
+}
+
+impl Accelerator {
+    pub const fn new(modifiers: Modifiers, key: KeyCode, action: &'static str) -> Self {
+        Accelerator {
+            shortcut: Shortcut::new(modifiers, key),
+            action,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShortcutAction {
+    NextWindow,
+    PreviousWindow,
+    CloseWindow,
+    ToggleStartMenu,
+    CycleKeyboardLayout,
+}
+
+/// Desktop-wide shortcuts, checked before the focused window sees a key
+pub struct ShortcutRegistry {
+    entries: Vec<(Shortcut, ShortcutAction)>,
+}
+
+impl Default for ShortcutRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShortcutRegistry {
+    pub fn new() -> Self {
+        let mut registry = ShortcutRegistry {
+            entries: Vec::new(),
+        };
+
+        registry.register(
+            Shortcut::new(Modifiers::ALT, KeyCode::Tab),
+            ShortcutAction::NextWindow,
+        );
+        registry.register(
+            Shortcut::new(Modifiers::ALT_SHIFT, KeyCode::Tab),
+            ShortcutAction::PreviousWindow,
+        );
+        registry.register(
+            Shortcut::new(Modifiers::ALT, KeyCode::F4),
+            ShortcutAction::CloseWindow,
+        );
+        registry.register(
+            Shortcut::new(Modifiers::CTRL_ALT, KeyCode::K),
+            ShortcutAction::CycleKeyboardLayout,
+        );
+
+        // Super toggles the start menu; the key reports itself as held while down
+        for key in [KeyCode::LWin, KeyCode::RWin] {
+            registry.register(
+                Shortcut::new(
+                    Modifiers {
+                        super_key: true,
+                        ..Modifiers::NONE
+                    },
+                    key,
+                ),
+                ShortcutAction::ToggleStartMenu,
+            );
+        }
+
+        registry
+    }
+
+    /// Add a shortcut, replacing whatever was bound to the same keys
+    pub fn register(&mut self, shortcut: Shortcut, action: ShortcutAction) {
+        self.entries.retain(|(existing, _)| *existing != shortcut);
+        self.entries.push((shortcut, action));
+    }
+
+    pub fn unregister(&mut self, shortcut: Shortcut) {
+        self.entries.retain(|(existing, _)| *existing != shortcut);
+    }
+
+    pub fn lookup(&self, key: KeyCode, modifiers: Modifiers) -> Option<ShortcutAction> {
+        self.entries
+            .iter()
+            .find(|(shortcut, _)| shortcut.matches(key, modifiers))
+            .map(|(_, action)| *action)
+    }
+}
+
+const SWITCHER_ITEM_WIDTH: usize = 120;
+const SWITCHER_ITEM_HEIGHT: usize = 56;
+const SWITCHER_PADDING: usize = 10;
+
+/// Alt+Tab overlay listing the open windows, most recently focused first
+pub struct WindowSwitcher {
+    entries: Vec<(usize, String, Vec<Color>)>, // window id, title, 16x16 icon
+    selected: usize,
+    pub visible: bool,
+    pub needs_redraw: bool,
+}
+
+impl Default for WindowSwitcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WindowSwitcher {
+    pub fn new() -> Self {
+        WindowSwitcher {
+            entries: Vec::new(),
+            selected: 0,
+            visible: false,
+            needs_redraw: false,
+        }
+    }
+
+    /// Show the overlay, or move the selection if it is already showing
+    pub fn cycle(&mut self, entries: Vec<(usize, String, Vec<Color>)>, forward: bool) {
+        if !self.visible {
+            if entries.is_empty() {
+                return;
+            }
+
+            self.entries = entries;
+            self.selected = 0;
+            self.visible = true;
+        }
+
+        let count = self.entries.len();
+        self.selected = if forward {
+            (self.selected + 1) % count
+        } else {
+            (self.selected + count - 1) % count
+        };
+        self.needs_redraw = true;
+    }
+
+    /// Hide the overlay and return the picked window
+    pub fn finish(&mut self) -> Option<usize> {
+        if !self.visible {
+            return None;
+        }
+
+        self.visible = false;
+        let picked = self.entries.get(self.selected).map(|(id, _, _)| *id);
+        self.entries.clear();
+        picked
+    }
+
+    pub fn cancel(&mut self) {
+        self.visible = false;
+        self.entries.clear();
+    }
+
+    /// Screen area covered by the overlay
+    pub fn bounds(&self, screen_width: usize, screen_height: usize) -> Rect {
+        let per_row = ((screen_width - 2 * SWITCHER_PADDING) / SWITCHER_ITEM_WIDTH).max(1);
+        let columns = self.entries.len().clamp(1, per_row);
+        let rows = self.entries.len().div_ceil(per_row).max(1);
+
+        let width = columns * SWITCHER_ITEM_WIDTH + 2 * SWITCHER_PADDING;
+        let height = rows * SWITCHER_ITEM_HEIGHT + 2 * SWITCHER_PADDING;
+
+        Rect::new(
+            screen_width.saturating_sub(width) / 2,
+            screen_height.saturating_sub(height) / 2,
+            width,
+            height,
+        )
+    }
+
+    pub fn render(
+        &mut self,
+        framebuffer: &mut FrameBufferWriter,
+        screen_width: usize,
+        screen_height: usize,
+    ) {
+        let bounds = self.bounds(screen_width, screen_height);
+        let per_row = ((screen_width - 2 * SWITCHER_PADDING) / SWITCHER_ITEM_WIDTH).max(1);
+        let theme = theme::current();
+
+        framebuffer.draw_rect(
+            (bounds.x, bounds.y),
+            (bounds.x + bounds.width - 1, bounds.y + bounds.height - 1),
+            theme.switcher_background,
+        );
+        framebuffer.draw_rect_outline(
+            (bounds.x, bounds.y),
+            (bounds.x + bounds.width - 1, bounds.y + bounds.height - 1),
+            theme.border,
+        );
+
+        for (i, (_, title, icon)) in self.entries.iter().enumerate() {
+            let x = bounds.x + SWITCHER_PADDING + (i % per_row) * SWITCHER_ITEM_WIDTH;
+            let y = bounds.y + SWITCHER_PADDING + (i / per_row) * SWITCHER_ITEM_HEIGHT;
+            let (bg, fg) = if i == self.selected {
+                (theme.selection, theme.selection_text)
+            } else {
+                (theme.switcher_background, theme.title_text)
+            };
+
+            framebuffer.draw_rect(
+                (x + 2, y + 2),
+                (x + SWITCHER_ITEM_WIDTH - 3, y + SWITCHER_ITEM_HEIGHT - 3),
+                bg,
+            );
+            framebuffer.draw_raw_image(x + (SWITCHER_ITEM_WIDTH - 16) / 2, y + 8, 16, icon);
+
+            let max_chars = (SWITCHER_ITEM_WIDTH - 8) / 7;
+            let label: String = if title.chars().count() > max_chars {
+                let mut label: String = title.chars().take(max_chars - 2).collect();
+                label.push_str("..");
+                label
+            } else {
+                title.clone()
+            };
+            framebuffer.draw_raw_text(
+                &label,
+                x + (SWITCHER_ITEM_WIDTH - label.chars().count() * 7) / 2,
+                y + 30,
+                fg,
+                bg,
+                theme.font_weight,
+                theme.font_size,
+            );
+        }
+
+        self.needs_redraw = false;
+    }
+}
@@ -6,8 +6,7 @@ use alloc::{
 use noto_sans_mono_bitmap::{FontWeight, RasterHeight};
 
 use crate::{
-    desktop::application::Application,
-    framebuffer::Color,
+    desktop::{application::Application, theme},
     surface::{Shape, Surface},
     sysinfo::{SystemInfo, estimate_heap_usage, estimate_stack_usage, format_memory_size},
 };
@@ -32,6 +31,7 @@ impl SysInfo {
 
 impl Application for SysInfo {
     fn init(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
         let mut y_offset = 20;
         let line_height = 18;
         let x_start = 15;
@@ -41,8 +41,8 @@ impl Application for SysInfo {
             x: x_start,
             y: y_offset,
             content: "SYSTEM INFORMATION".to_string(),
-            color: Color::WHITE,
-            background_color: Color::DARKGRAY,
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size24,
             font_weight: FontWeight::Bold,
             hide: false,
@@ -57,8 +57,8 @@ impl Application for SysInfo {
                 "OS: {} {}",
                 self.system_info.os_name, self.system_info.os_version
             ),
-            color: Color::WHITE,
-            background_color: Color::DARKGRAY,
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -69,8 +69,8 @@ impl Application for SysInfo {
             x: x_start,
             y: y_offset,
             content: format!("Architecture: {}", self.system_info.architecture),
-            color: Color::WHITE,
-            background_color: Color::DARKGRAY,
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -81,8 +81,8 @@ impl Application for SysInfo {
             x: x_start,
             y: y_offset,
             content: format!("Processor: {}", self.system_info.processor_model),
-            color: Color::WHITE,
-            background_color: Color::DARKGRAY,
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -93,8 +93,8 @@ impl Application for SysInfo {
             x: x_start,
             y: y_offset,
             content: format!("Vendor: {}", self.system_info.processor_vendor),
-            color: Color::WHITE,
-            background_color: Color::DARKGRAY,
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -111,8 +111,8 @@ impl Application for SysInfo {
                         .map(|f| format!("{} MHz", f))
                         .unwrap_or("Unknown".to_string())
                 ),
-            color: Color::WHITE,
-            background_color: Color::DARKGRAY,
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -129,8 +129,8 @@ impl Application for SysInfo {
                         .map(|f| format!("{} MHz", f))
                         .unwrap_or("Unknown".to_string())
                 ),
-            color: Color::WHITE,
-            background_color: Color::DARKGRAY,
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -142,8 +142,8 @@ impl Application for SysInfo {
             x: x_start,
             y: y_offset,
             content: "MEMORY USAGE".to_string(),
-            color: Color::WHITE,
-            background_color: Color::DARKGRAY,
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Bold,
             hide: false,
@@ -158,8 +158,8 @@ impl Application for SysInfo {
                 format_memory_size(self.system_info.heap_used),
                 format_memory_size(self.system_info.heap_size)
             ),
-            color: Color::WHITE,
-            background_color: Color::DARKGRAY,
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -175,8 +175,8 @@ impl Application for SysInfo {
                 format_memory_size(stack_usage as u64),
                 format_memory_size(self.system_info.stack_size as u64)
             ),
-            color: Color::WHITE,
-            background_color: Color::DARKGRAY,
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -188,8 +188,8 @@ impl Application for SysInfo {
             x: x_start,
             y: y_offset,
             content: "CPU FEATURES".to_string(),
-            color: Color::WHITE,
-            background_color: Color::DARKGRAY,
+            color: theme.text,
+            background_color: theme.window_background,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Bold,
             hide: false,
@@ -224,8 +224,8 @@ impl Application for SysInfo {
                 x: x_start,
                 y: y_offset,
                 content: chunk.to_string(),
-                color: Color::WHITE,
-                background_color: Color::DARKGRAY,
+                color: theme.text,
+                background_color: theme.window_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -243,8 +243,8 @@ impl Application for SysInfo {
                 x: x_start,
                 y: y_offset,
                 content: "FILESYSTEM INFO".to_string(),
-                color: Color::WHITE,
-                background_color: Color::DARKGRAY,
+                color: theme.text,
+                background_color: theme.window_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Bold,
                 hide: false,
@@ -255,8 +255,8 @@ impl Application for SysInfo {
                 x: x_start,
                 y: y_offset,
                 content: format!("Type: {}", fs_info.filesystem_type),
-                color: Color::WHITE,
-                background_color: Color::DARKGRAY,
+                color: theme.text,
+                background_color: theme.window_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -267,8 +267,8 @@ impl Application for SysInfo {
                 x: x_start,
                 y: y_offset,
                 content: format!("Volume: {}", fs_info.volume_label),
-                color: Color::WHITE,
-                background_color: Color::DARKGRAY,
+                color: theme.text,
+                background_color: theme.window_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -282,8 +282,8 @@ impl Application for SysInfo {
                     "Total Size: {}",
                     format_memory_size(fs_info.total_size as u64)
                 ),
-                color: Color::WHITE,
-                background_color: Color::DARKGRAY,
+                color: theme.text,
+                background_color: theme.window_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -299,8 +299,8 @@ impl Application for SysInfo {
                     "Cluster Size: {} bytes ({} B/sector * {} sectors)",
                     cluster_size, fs_info.bytes_per_sector, fs_info.sectors_per_cluster
                 ),
-                color: Color::WHITE,
-                background_color: Color::DARKGRAY,
+                color: theme.text,
+                background_color: theme.window_background,
                 font_size: RasterHeight::Size16,
                 font_weight: FontWeight::Regular,
                 hide: false,
@@ -317,7 +317,7 @@ impl Application for SysInfo {
             y: self.refresh_button_region.1,
             width: self.refresh_button_region.2,
             height: self.refresh_button_region.3,
-            color: Color::new(200, 200, 255),
+            color: theme.control,
             filled: true,
             hide: false,
         });
@@ -326,8 +326,8 @@ impl Application for SysInfo {
             x: self.refresh_button_region.0 + 20,
             y: self.refresh_button_region.1 + 5,
             content: "Refresh Memory Data".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(200, 200, 255),
+            color: theme.control_text,
+            background_color: theme.control,
             font_size: RasterHeight::Size16,
             font_weight: FontWeight::Regular,
             hide: false,
@@ -381,4 +381,10 @@ impl Application for SysInfo {
     fn get_title(&self) -> Option<String> {
         None
     }
+
+    fn handle_theme_change(&mut self, surface: &mut Surface) {
+        surface.clear_all_shapes();
+        self.text_lines.clear();
+        self.init(surface);
+    }
 }
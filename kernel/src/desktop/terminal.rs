@@ -1,15 +1,102 @@
 use alloc::{
+    boxed::Box,
     format,
     string::{String, ToString},
+    vec,
     vec::Vec,
 };
-use alloc::vec;
 use noto_sans_mono_bitmap::{FontWeight, RasterHeight};
 use pc_keyboard::KeyCode;
 use crate::{
-    desktop::{application::Application, keyboard::{get_current_layout, set_keyboard_layout}}, framebuffer::Color, fs::manager::list_directory, surface::{Shape, Surface}, time::{get_ms_since_epoch, get_utc_time}
-    
+    desktop::{
+        application::Application,
+        clipboard,
+        mouse::{MouseButton, MouseEvent, MouseEventKind},
+        shortcuts::{Accelerator, Modifiers},
+        vt::{Cell, Screen, default_background, default_foreground, palette_color, style_runs},
+    },
+    fs::manager::{list_directory, path_exists, read_text_file, write_file},
+    fs::search::{FindQuery, Finder, has_wildcards, split_glob_root},
+    init::HEAP_SIZE,
+    shell::{
+        CommandIo, Shell, ShellHost,
+        builtins::{GrepOptions, grep_file, split_find_args},
+    },
+    settings::{self, TERMINAL_SCROLLBACK},
+    surface::{Rect, Shape, Surface},
+    time::get_ms_since_epoch,
+    warn,
 };
+#[cfg(processes_enabled)]
+use crate::tasks::process::{self, ProcessHandle};
+
+/// find/grep running in the background, one directory per frame
+struct SearchJob {
+    finder: Finder,
+    grep: Option<GrepOptions>,
+    found: usize,
+}
+
+/// User program started from the prompt, its output shows up while it runs
+#[cfg(processes_enabled)]
+struct ProgramJob {
+    path: String,
+    handle: ProcessHandle,
+}
+
+/// Text picked with the mouse. Points are (line, column) with lines counted
+/// like `Screen::line` and columns at the edges between cells, so the end
+/// column is left out.
+#[derive(Clone, Copy, PartialEq)]
+struct Selection {
+    anchor: (usize, usize),
+    head: (usize, usize),
+}
+
+impl Selection {
+    /// Start and end in reading order
+    fn ordered(&self) -> ((usize, usize), (usize, usize)) {
+        if self.anchor <= self.head {
+            (self.anchor, self.head)
+        } else {
+            (self.head, self.anchor)
+        }
+    }
+
+    /// Columns of `line` inside the selection, `cols` wide when it runs past the line
+    fn columns(&self, line: usize, cols: usize) -> Option<(usize, usize)> {
+        let (start, end) = self.ordered();
+        if line < start.0 || line > end.0 {
+            return None;
+        }
+
+        let from = if line == start.0 { start.1 } else { 0 };
+        let to = if line == end.0 { end.1 } else { cols };
+        (from < to).then_some((from, to))
+    }
+}
+
+/// Tab completion in progress. Another Tab moves to the next candidate as
+/// long as the line still reads the way the last one left it.
+struct Completion {
+    /// Command line and cursor from before the first Tab
+    line: String,
+    cursor: usize,
+    /// Where the word being completed starts
+    word_start: usize,
+    candidates: Vec<String>,
+    next: usize,
+    applied: String,
+}
+
+/// Ctrl+R: search back through the history while typing
+struct HistorySearch {
+    query: String,
+    /// History entry that matches, None when nothing does
+    found: Option<usize>,
+    /// Line to put back if the search is cancelled
+    original: String,
+}
 
 #[derive(Clone, PartialEq)]
 pub enum TerminalMode {
@@ -17,102 +104,124 @@ pub enum TerminalMode {
     Command,
 }
 
-pub struct Terminal {
-    // Campos otimizados com capacidades pré-definidas
-    prompt: &'static str,
+/// Um shell com a sua tela, num painel do terminal
+struct Session {
+    // Parte da janela onde o painel fica
+    area: Rect,
+
+    // Linha de comando
+    prompt: String,
     command_history: Vec<String>,
     current_command: String,
     command_cursor: usize,
-    output_lines: Vec<String>,
-    
-    // Sistema de display do Notepad
-    display_lines: Vec<String>,
-    command_lines: Vec<String>,
-    scroll_offset: usize,
-    text_area_idx: usize,
-    cursor_idx: usize,
-    max_chars_per_line: usize,
-    max_visible_lines: usize,
-    
-    // Cache para estado de renderização
-    previous_content: String,
-    prev_cursor_x: usize,
-    prev_cursor_y: usize,
-    
-    // Estado otimizado
-    show_cursor: bool,
+    history_index: usize,
+    history_search: Option<HistorySearch>,
+    // Comandos que ainda não foram gravados no arquivo do histórico
+    unsaved_history: Vec<String>,
+    completion: Option<Completion>,
+    // Onde o comando começa na tela: (screen.scrolled(), linha, coluna).
+    // None enquanto um comando roda e não há prompt.
+    input_origin: Option<(usize, usize, usize)>,
+
+    // Tela VT100 e o que já está desenhado dela
+    screen: Screen,
+    view_offset: usize,
+    row_shapes: Vec<Vec<usize>>,
+    drawn_cursor: Option<(usize, usize)>,
+    selection: Option<Selection>,
+    // Botão esquerdo segurado desde que a seleção começou
+    selecting: bool,
+
+    // Estado
     cursor_blink_state: bool,
     cursor_blink_timer: u64,
-    history_index: usize,
-    completion_cache: Option<(String, Vec<String>)>,
-    completion_index: usize,
     mode: TerminalMode,
-    needs_redraw: bool,
-    last_render_time: u64,
-    content_changed: bool,
+    search_job: Option<SearchJob>,
+    #[cfg(processes_enabled)]
+    program_job: Option<ProgramJob>,
+    shell: Shell,
 }
 
-// Comandos estáticos para lookup O(1) - ADICIONADO NOVO COMANDO
-static COMMANDS: &[&str] = &[
-    "help", "clear", "echo", "ls", "cat", "date", "time", 
-    "uptime", "version", "setkeyboard" // Novo comando
+// Comandos que precisam da janela do terminal, o resto fica com o shell
+static HOST_COMMANDS: &[(&str, &str)] = &[
+    ("clear", "Clear terminal"),
 ];
 
-
 // Constantes para evitar magic numbers
 const COMMAND_HISTORY_CAPACITY: usize = 50;
-const OUTPUT_LINES_CAPACITY: usize = 24;
+// Comandos acumulados antes de gravar o histórico, o resto vai quando o painel fecha
+const HISTORY_SAVE_BATCH: usize = 10;
+// Histórico guardado entre sessões, `~` é o $HOME do shell
+const HISTORY_FILE: &str = "~/.goofy_history";
 const CURRENT_COMMAND_CAPACITY: usize = 100;
 const CURSOR_BLINK_INTERVAL: u64 = 500;
-const DISPLAY_LINES_CAPACITY: usize = 50;
-
-impl Terminal {
-    pub fn new(_args: Option<String>) -> Self {
-        let mut terminal = Self {
-            prompt: "goofy> ",
+// Linhas guardadas acima da tela, `terminal.scrollback` em /etc/goofy.conf muda o padrão.
+// Cada linha guardada ocupa 4 bytes por caractere no heap, daí o limite.
+const SCROLLBACK_LINES: usize = 100;
+const MAX_SCROLLBACK_LINES: usize = 500;
+// Heap que o scrollback de cada painel pode ocupar: com MAX_SESSIONS painéis
+// fica num quarto do heap, seja qual for o número de linhas pedido
+const SCROLLBACK_BYTES: usize = HEAP_SIZE as usize / 4 / MAX_SESSIONS;
+// Linhas roladas por passo da roda do mouse
+const WHEEL_LINES: isize = 3;
+// Tamanho do primeiro painel até a janela dizer o dela
+const DEFAULT_COLS: usize = 84;
+const DEFAULT_ROWS: usize = 24;
+// Métricas da fonte de 16px; as linhas se encostam para as cores de fundo se juntarem
+const CELL_WIDTH: usize = 7;
+const CELL_HEIGHT: usize = 16;
+const MARGIN: usize = 5;
+// Cada shell guarda tela e scrollback no heap, que é pequeno
+const MAX_SESSIONS: usize = 4;
+const TAB_BAR_HEIGHT: usize = CELL_HEIGHT + 4;
+const MAX_TAB_WIDTH: usize = 160;
+const TAB_PADDING: usize = 6;
+const DIVIDER_WIDTH: usize = 1;
+
+impl Session {
+    fn new(area: Rect) -> Self {
+        let scrollback = settings::get(TERMINAL_SCROLLBACK)
+            .and_then(|lines| lines.trim().parse().ok())
+            .unwrap_or(SCROLLBACK_LINES)
+            .min(MAX_SCROLLBACK_LINES);
+        let (cols, rows) = grid_size(area);
+
+        let mut session = Self {
+            area,
+
+            prompt: prompt_for("/"),
             command_history: Vec::with_capacity(COMMAND_HISTORY_CAPACITY),
             current_command: String::with_capacity(CURRENT_COMMAND_CAPACITY),
             command_cursor: 0,
-            output_lines: Vec::with_capacity(OUTPUT_LINES_CAPACITY),
-            
-            // Sistema de display do Notepad
-            display_lines: Vec::with_capacity(DISPLAY_LINES_CAPACITY),
-            command_lines: Vec::new(),
-            scroll_offset: 0,
-            text_area_idx: 0,
-            cursor_idx: 0,
-            max_chars_per_line: 84,
-            max_visible_lines: 24,
-            
-            // Cache para renderização
-            previous_content: String::new(),
-            prev_cursor_x: 0,
-            prev_cursor_y: 0,
-            
-            // Estado
-            show_cursor: true,
+            history_index: 0,
+            history_search: None,
+            unsaved_history: Vec::new(),
+            completion: None,
+            input_origin: None,
+
+            screen: Screen::new(cols, rows, scrollback, SCROLLBACK_BYTES),
+            view_offset: 0,
+            row_shapes: Vec::new(),
+            drawn_cursor: None,
+            selection: None,
+            selecting: false,
+
             cursor_blink_state: true,
             cursor_blink_timer: 0,
-            history_index: 0,
-            completion_cache: None,
-            completion_index: 0,
             mode: TerminalMode::Command,
-            needs_redraw: true,
-            last_render_time: 0,
-            content_changed: true,
+            search_job: None,
+            #[cfg(processes_enabled)]
+            program_job: None,
+            shell: Shell::new(),
         };
 
+        session.load_history();
+
         // Mensagens iniciais
-        terminal.output_lines.extend_from_slice(&[
-            "GoofyOS Terminal v0.1.0".into(),
-            "Type 'help' for available commands".into(),
-            "".into()
-        ]);
-        
-        // Inicializa o sistema de display
-        terminal.update_display_lines();
-        
-        terminal
+        session.screen.write("GoofyOS Terminal v0.1.0\nType 'help' for available commands\n\n");
+        session.show_prompt();
+
+        session
     }
 
     fn execute_command(&mut self, command: &str) {
@@ -121,307 +230,607 @@ impl Terminal {
                 self.command_history.remove(0);
             }
             self.command_history.push(command.to_string());
+            self.unsaved_history.push(command.to_string());
+            if self.unsaved_history.len() >= HISTORY_SAVE_BATCH {
+                self.save_history();
+            }
         }
         self.history_index = self.command_history.len();
 
-        let output = self.process_command(command);
-        
-        self.output_lines.extend(output);
-        
-        if self.output_lines.len() > OUTPUT_LINES_CAPACITY {
-            let remove_count = self.output_lines.len() - OUTPUT_LINES_CAPACITY;
-            self.output_lines.drain(0..remove_count);
-        }
+        // O shell é emprestado enquanto roda, para poder escrever no terminal
+        let mut shell = core::mem::take(&mut self.shell);
+        shell.run(command, self);
+        self.prompt = prompt_for(shell.cwd());
+        self.shell = shell;
+        self.completion = None;
 
-        // Atualiza display e scroll
-        self.update_display_lines();
-        self.update_scroll_if_needed();
-        
-        self.content_changed = true;
-        self.needs_redraw = true;
-        self.completion_cache = None;
+        // find/grep e programas mostram o prompt quando terminam
+        if !self.job_running() {
+            self.show_prompt();
+        }
     }
 
-    fn process_command(&mut self, cmd: &str) -> Vec<String> {
-        let mut parts = cmd.split_whitespace();
-        let Some(first_part) = parts.next() else {
-            return Vec::new();
-        };
-
-        match first_part {
-            "help" => vec![
-                "Available commands:".into(),
-                "  help        - Show this help".into(),
-                "  clear       - Clear terminal".into(),
-                "  echo        - Print arguments".into(),
-                "  ls          - List files".into(),
-                "  cat         - Show file contents".into(),
-                "  date        - Show current date/time".into(),
-                "  time        - Show current time".into(),
-                "  uptime      - Show system uptime".into(),
-                "  version     - Show OS version".into(),
-                "  setkeyboard - Change keyboard layout".into(),
-                "".into(),
-            ],
-            "clear" => {
-                self.output_lines.clear();
-                self.display_lines.clear();
-                self.command_lines.clear();
-                vec!["Terminal cleared.".into()]
-            }
-            "echo" => {
-                let rest = parts.collect::<Vec<&str>>().join(" ");
-                if !rest.is_empty() { vec![rest] } else { vec!["".into()] }
-            }
-            "ls" => self.list_directory_command(),
-            "cat" => {
-                if let Some(filename) = parts.next() {
-                    match crate::fs::manager::read_text_file(filename) {
-                        Ok(content) => content.lines().map(String::from).collect(),
-                        Err(e) => vec![format!("Error reading file: {}", e)],
-                    }
-                } else {
-                    vec!["Usage: cat <filename>".into()]
-                }
-            }
-            "date" => {
-                let datetime = get_utc_time();
-                vec![format!(
-                    "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
-                    datetime.year, datetime.month, datetime.day,
-                    datetime.hours, datetime.minutes, datetime.seconds
-                )]
-            }
-            "time" => {
-                let datetime = get_utc_time();
-                vec![format!(
-                    "{:02}:{:02}:{:02}.{:03}",
-                    datetime.hours, datetime.minutes, datetime.seconds, datetime.millis
-                )]
-            }
-            "uptime" => {
-                let uptime_seconds = get_ms_since_epoch() as u64 / 1000;
-                let hours = uptime_seconds / 3600;
-                let minutes = (uptime_seconds % 3600) / 60;
-                let seconds = uptime_seconds % 60;
-                vec![format!("Uptime: {:02}:{:02}:{:02}", hours, minutes, seconds)]
-            }
-            "version" => vec!["GoofyOS v0.1.0 - Built with Rust".into()],
-            "setkeyboard" => self.set_keyboard_command(parts.next()), // Novo comando
-            _ => vec![format!("Command not found: {}", cmd)],
-        }
-    }
-
-    // No comando setkeyboard do terminal:
-    fn set_keyboard_command(&mut self, layout: Option<&str>) -> Vec<String> {
-        match layout {
-            Some(layout_name) => {
-                if set_keyboard_layout(&layout_name) {
-                    vec![format!("Keyboard layout changed to: {}", layout_name)]
-                } else {
-                    vec![format!("Failed to change keyboard layout to: {}", layout_name)]
-                }
-            }
-            None => {
-                vec![
-                    format!("Current keyboard layout: {:?}", get_current_layout()),
-                    "Usage: setkeyboard <layout>".into(),
-                ]
-            }
+    fn write_lines(&mut self, lines: Vec<String>) {
+        for line in lines {
+            self.screen.write(&line);
+            self.screen.write("\n");
         }
     }
 
-    fn list_directory_command(&mut self) -> Vec<String> {
-        match list_directory("/") {
-            Ok(entries) => {
-                let mut lines = Vec::with_capacity(entries.len() + 1);
-                lines.push("Directory listing:".into());
-                for entry in entries {
-                    lines.push(format!("{}", entry.name));
-                }
-                lines
-            }
-            Err(e) => vec![format!("Error listing directory: {}", e)],
+    /// Pula para a próxima linha se o cursor não estiver no começo de uma
+    fn finish_line(&mut self) {
+        if self.screen.cursor().1 != 0 {
+            self.screen.write("\n");
         }
     }
 
-    fn break_line(&self, text: &str, max_len: usize) -> Vec<String> {
-        let mut result = Vec::new();
-        let mut remaining = text;
+    fn show_prompt(&mut self) {
+        self.finish_line();
+        self.screen.write(&self.prompt);
+        let (row, col) = self.screen.cursor();
+        self.input_origin = Some((self.screen.scrolled(), row, col));
+        self.redraw_input();
+    }
+
+    /// Onde o comando começa agora, seguindo a tela quando ela rola
+    fn input_position(&self) -> Option<(usize, usize)> {
+        let (scrolled, row, col) = self.input_origin?;
+        let moved = self.screen.scrolled() - scrolled;
+        Some(row.checked_sub(moved).map_or((0, 0), |row| (row, col)))
+    }
+
+    /// Reescreve o comando sendo digitado e põe o cursor no lugar certo dele
+    fn redraw_input(&mut self) {
+        let Some((row, col)) = self.input_position() else {
+            return;
+        };
+        let (line, cursor) = self.input_line();
+        self.screen.set_cursor(row, col);
+        self.screen.erase_below();
+        self.screen.write(&line);
 
-        while remaining.len() > max_len {
-            let split_point = self.find_safe_split_point(remaining, max_len);
-            let (chunk, rest) = remaining.split_at(split_point);
-            result.push(chunk.to_string());
-            remaining = rest.trim_start_matches(' ');
+        // Escrever pode ter rolado a tela
+        let Some((row, col)) = self.input_position() else {
+            return;
+        };
+        let offset = col + cursor;
+        let mut cursor_row = row + offset / self.screen.cols();
+        if cursor_row >= self.screen.rows() {
+            // Cursor logo depois da última coluna da tela: abre uma linha para ele
+            self.screen.write("\n");
+            cursor_row -= 1;
         }
+        self.screen.set_cursor(cursor_row, offset % self.screen.cols());
+        self.follow_output();
+    }
+
+    /// O que aparece depois do prompt e onde fica o cursor nisso
+    fn input_line(&self) -> (String, usize) {
+        let Some(search) = &self.history_search else {
+            return (self.current_command.clone(), self.command_cursor);
+        };
 
-        if !remaining.is_empty() {
-            result.push(remaining.to_string());
+        let line = match search.found {
+            Some(index) => format!(
+                "(reverse-i-search)`{}': {}",
+                search.query, self.command_history[index]
+            ),
+            None => format!("(failed reverse-i-search)`{}': ", search.query),
+        };
+        let cursor = line.len();
+        (line, cursor)
+    }
+
+    /// Mensagem do próprio terminal; o comando sendo digitado volta logo abaixo
+    fn notice(&mut self, text: &str) {
+        if self.input_origin.is_none() {
+            self.finish_line();
+            self.screen.write(text);
+            self.screen.write("\n");
+            return;
         }
 
-        result
+        let cursor = self.command_cursor;
+        self.command_cursor = self.current_command.len();
+        self.redraw_input();
+        self.screen.write("\n");
+        self.screen.write(text);
+        self.show_prompt();
+        self.command_cursor = cursor;
+        self.redraw_input();
     }
 
-    fn find_safe_split_point(&self, text: &str, max_len: usize) -> usize {
-        if text.len() <= max_len {
-            return text.len();
+    /// Volta a mostrar o fim da saída
+    fn follow_output(&mut self) {
+        if self.view_offset != 0 {
+            self.view_offset = 0;
+            self.screen.mark_all_dirty();
         }
+    }
 
-        // Tenta encontrar um espaço para quebrar naturalmente
-        if let Some(last_space) = text[..max_len].rfind(' ') {
-            if last_space > max_len / 2 {
-                return last_space + 1;
+    /// Start find in the background
+    fn find_command(&mut self, root: String, query_args: &[String], io: &mut CommandIo) -> i32 {
+        match path_exists(&root) {
+            Ok(Some(true)) => {}
+            _ => {
+                io.error(&format!("find: {}: No such directory", root));
+                return 1;
             }
         }
 
-        // Se não encontrar espaço, quebra no máximo
-        max_len
-    }
+        let query = match FindQuery::parse(&query_args.join(" ")) {
+            Ok(query) => query,
+            Err(e) => {
+                io.error(&format!("find: {}", e));
+                return 2;
+            }
+        };
 
-    fn update_display_lines(&mut self) {
-        self.display_lines.clear();
-        self.command_lines.clear();
+        self.search_job = Some(SearchJob {
+            finder: Finder::new(&root, query),
+            grep: None,
+            found: 0,
+        });
+        0
+    }
 
-        // Processa cada linha de output com wrap
-        for line in &self.output_lines {
-            if line.len() <= self.max_chars_per_line {
-                self.display_lines.push(line.clone());
-            } else {
-                self.display_lines.extend(self.break_line(line, self.max_chars_per_line));
+    fn grep_command(&mut self, options: GrepOptions, target: String, io: &mut CommandIo) -> i32 {
+        // Single file: no need for a background walk
+        if !has_wildcards(&target) {
+            match path_exists(&target) {
+                Ok(Some(false)) => {
+                    let hits = grep_file(&target, &options);
+                    for hit in &hits {
+                        io.print(hit);
+                    }
+                    return if hits.is_empty() { 1 } else { 0 };
+                }
+                Ok(Some(true)) => {}
+                _ => {
+                    io.error(&format!("grep: {}: No such file or directory", target));
+                    return 2;
+                }
             }
         }
 
-        // Processa linha de comando
-        let command_line = format!("{}{}", self.prompt, self.current_command);
-        self.command_lines = self.break_line(&command_line, self.max_chars_per_line);
-        self.display_lines.extend_from_slice(&self.command_lines);
+        let (root, pattern) = if has_wildcards(&target) {
+            split_glob_root(&target)
+        } else {
+            (target, String::new())
+        };
+
+        let mut query = FindQuery::new(&pattern);
+        query.files_only = true;
+
+        self.search_job = Some(SearchJob {
+            finder: Finder::new(&root, query),
+            grep: Some(options),
+            found: 0,
+        });
+        0
     }
 
-    fn update_scroll_if_needed(&mut self) {
-        if self.display_lines.is_empty() || self.command_lines.is_empty() {
+    /// Advance a running find/grep by one directory and print what it found
+    fn step_search_job(&mut self) {
+        let Some(job) = self.search_job.as_mut() else {
             return;
+        };
+
+        let mut lines = Vec::new();
+        let mut done = false;
+        match job.finder.step() {
+            Some(matches) => {
+                for found in matches {
+                    match &job.grep {
+                        Some(options) => {
+                            let hits = grep_file(&found.path, options);
+                            job.found += hits.len();
+                            lines.extend(hits);
+                        }
+                        None => {
+                            job.found += 1;
+                            if found.entry.is_directory {
+                                lines.push(format!("{}/", found.path));
+                            } else {
+                                lines.push(found.path);
+                            }
+                        }
+                    }
+                }
+            }
+            None => {
+                lines.push(format!("{} match(es) in {} directories", job.found, job.finder.scanned_dirs()));
+                self.search_job = None;
+                done = true;
+            }
         }
 
-        // Calcula a linha do cursor baseado na posição dentro da linha de comando
-        let cursor_line = self.get_cursor_line();
-        
-        // Ajusta scroll se cursor está fora da área visível
-        if cursor_line < self.scroll_offset {
-            self.scroll_offset = cursor_line;
-            self.content_changed = true;
-        } else if cursor_line >= self.scroll_offset + self.max_visible_lines {
-            self.scroll_offset = cursor_line - self.max_visible_lines + 1;
-            self.content_changed = true;
+        self.write_lines(lines);
+        if done {
+            self.show_prompt();
         }
     }
 
-    fn get_cursor_line(&self) -> usize {
-        if self.command_lines.is_empty() {
-            return 0;
-        }
+    /// Show what the running program wrote since the last frame
+    #[cfg(processes_enabled)]
+    fn step_program_job(&mut self) {
+        let Some(job) = self.program_job.as_ref() else {
+            return;
+        };
 
-        let cursor_in_command = self.prompt.len() + self.command_cursor;
+        // A saída vai crua para a tela, com as sequências de escape
+        let (stdout, stderr) = process::take_output(&job.handle);
+        let exit_code = process::exit_code(&job.handle);
+        self.screen.write(&stdout);
+        self.screen.write(&stderr);
 
-        // Encontra em qual linha da command_lines o cursor está
-        let mut char_count = 0;
-        for (i, line) in self.command_lines.iter().enumerate() {
-            if char_count + line.len() >= cursor_in_command {
-                // O cursor está nesta linha
-                let output_lines_count = self.display_lines.len() - self.command_lines.len();
-                return output_lines_count + i;
-            }
-            char_count += line.len();
+        let Some(code) = exit_code else {
+            return;
+        };
+        let path = self.program_job.take().map(|job| job.path).unwrap_or_default();
+        // O shell já devolveu 0 ao lançar o programa, o `$?` certo só se sabe agora
+        self.shell.status = code;
+        if code != 0 {
+            self.finish_line();
+            self.screen.write(&format!("{}: exited with code {}\n", path, code));
         }
+        self.show_prompt();
+    }
 
-        // Se não encontrou, retorna a última linha
-        self.display_lines.len() - 1
+    /// find/grep or a program still running, input waits for them
+    fn job_running(&self) -> bool {
+        #[cfg(processes_enabled)]
+        if self.program_job.is_some() {
+            return true;
+        }
+        self.search_job.is_some()
     }
 
-    fn get_display_text(&self) -> String {
-        let start = self.scroll_offset;
-        let end = (self.scroll_offset + self.max_visible_lines).min(self.display_lines.len());
-        
-        if start >= self.display_lines.len() {
-            return String::new();
+    /// Stop whatever runs. A program is stopped by the scheduler on a later
+    /// timer tick, its job stays until step_program_job sees it gone.
+    fn stop_jobs(&mut self) {
+        #[cfg(processes_enabled)]
+        if let Some(job) = self.program_job.as_ref() {
+            process::kill(&job.handle);
         }
+        self.search_job = None;
+    }
 
-        self.display_lines[start..end].join("\n")
+    /// Ctrl+C: stop the job and get the prompt back
+    fn cancel_jobs(&mut self) {
+        self.stop_jobs();
+        self.finish_line();
+        self.screen.write("^C\n");
+        // Com um programa, o prompt volta quando ele terminar de fato
+        if !self.job_running() {
+            self.show_prompt();
+        }
     }
 
-    fn get_cursor_visual_position(&self) -> (usize, usize) {
-        if self.command_lines.is_empty() {
-            return (5, 5);
+    /// Atualiza os textos de uma linha da tela, um por trecho de mesma cor
+    fn draw_row(&mut self, surface: &mut Surface, row: usize, offset: usize) {
+        let mut cells = self.screen.view_row(row, offset).to_vec();
+        let cursor = self.drawn_cursor.filter(|&(cursor_row, _)| cursor_row == row);
+        if let Some(cell) = cursor.and_then(|(_, col)| cells.get_mut(col)) {
+            *cell = cell.inverted();
         }
 
-        let cursor_in_command = self.prompt.len() + self.command_cursor;
+        // Seleção também aparece com as cores trocadas, até o fim da linha se continuar
+        let line = self.screen.scrolled() + row - offset;
+        let cols = self.screen.cols();
+        if let Some((from, to)) = self.selection.and_then(|s| s.columns(line, cols)) {
+            if cells.len() < to {
+                cells.resize(to, Cell::BLANK);
+            }
+            for cell in &mut cells[from..to] {
+                *cell = cell.inverted();
+            }
+        }
 
-        // Encontra a linha e coluna dentro das command_lines
-        let mut char_count = 0;
-        let mut line_index = 0;
-        let mut col_in_line = 0;
+        let runs = style_runs(&cells);
+        let run_count = runs.len();
+        if self.row_shapes.len() <= row {
+            self.row_shapes.resize(row + 1, Vec::new());
+        }
 
-        for (i, line) in self.command_lines.iter().enumerate() {
-            if char_count + line.len() >= cursor_in_command {
-                line_index = i;
-                col_in_line = cursor_in_command - char_count;
-                break;
+        let y = self.area.y + MARGIN + row * CELL_HEIGHT;
+        let mut x = self.area.x + MARGIN;
+        for (i, (style, text)) in runs.into_iter().enumerate() {
+            // Cada linha guarda os textos que já usou, os que sobram ficam escondidos
+            if i == self.row_shapes[row].len() {
+                let shape = surface.add_shape(Shape::Text {
+                    x,
+                    y,
+                    content: String::new(),
+                    color: default_foreground(),
+                    background_color: default_background(),
+                    font_size: RasterHeight::Size16,
+                    font_weight: FontWeight::Regular,
+                    hide: true,
+                });
+                self.row_shapes[row].push(shape);
             }
-            char_count += line.len();
+
+            let shape = self.row_shapes[row][i];
+            let (color, background) = style.colors();
+            let width = text.chars().count() * CELL_WIDTH;
+            surface.move_shape(shape, x, y);
+            surface.update_text_color(shape, color);
+            surface.update_text_background(shape, background);
+            surface.update_text_content(shape, text, None);
+            surface.update_shape_visibility(shape, true);
+            x += width;
         }
 
-        // Calcula a linha absoluta
-        let output_lines_count = self.display_lines.len() - self.command_lines.len();
-        let absolute_line = output_lines_count + line_index;
+        for &shape in &self.row_shapes[row][run_count..] {
+            surface.update_shape_visibility(shape, false);
+        }
+    }
+
+    /// Quanto a visão está voltada no scrollback; a tela alternativa não tem
+    fn visible_offset(&self) -> usize {
+        if self.screen.in_alternate_screen() {
+            0
+        } else {
+            self.view_offset
+        }
+    }
 
-        // Calcula a linha visível relativa ao scroll
-        let visible_line = absolute_line.saturating_sub(self.scroll_offset);
+    /// Ponto da seleção sob o mouse, na borda de célula mais próxima
+    fn selection_point(&self, x: i32, y: i32) -> (usize, usize) {
+        let x = x - (self.area.x + MARGIN) as i32;
+        let y = y - (self.area.y + MARGIN) as i32;
+        let x = (x + CELL_WIDTH as i32 / 2).max(0) as usize;
+        let y = y.max(0) as usize;
+        let col = (x / CELL_WIDTH).min(self.screen.cols());
+        let row = (y / CELL_HEIGHT).min(self.screen.rows() - 1);
+        (self.screen.scrolled() + row - self.visible_offset(), col)
+    }
 
-        // CORREÇÃO: Usa constantes precisas para posicionamento igual ao Notepad
-        let x = 3 + col_in_line * 7; // 7 pixels por caractere como no Notepad
-        let y = 5 + visible_line * 18; // 18 pixels por linha como no Notepad
+    fn set_selection(&mut self, selection: Option<Selection>) {
+        if selection != self.selection {
+            self.selection = selection;
+            self.screen.mark_all_dirty();
+        }
+    }
 
-        (x, y)
+    /// Texto selecionado, sem os espaços do fim de cada linha
+    fn selected_text(&self) -> Option<String> {
+        let selection = self.selection?;
+        let (start, end) = selection.ordered();
+
+        let mut lines = Vec::new();
+        for line in start.0..=end.0 {
+            let Some(cells) = self.screen.line(line) else {
+                continue;
+            };
+            let Some((from, to)) = selection.columns(line, self.screen.cols()) else {
+                lines.push(String::new());
+                continue;
+            };
+            let text: String = cells
+                .iter()
+                .take(to)
+                .skip(from)
+                .map(|cell| cell.char())
+                .collect();
+            lines.push(text.trim_end().to_string());
+        }
+        Some(lines.join("\n"))
     }
 
-    // --- FUNÇÕES EXISTENTES DO TERMINAL ---
+    /// Cola como se fosse digitado; cada fim de linha roda o comando
+    fn paste(&mut self, surface: &mut Surface) {
+        let Some(text) = clipboard::text() else {
+            return;
+        };
 
-    fn update_completion_candidates(&mut self) {
-        let current = &self.current_command;
-        
-        if let Some((cached_input, _)) = &self.completion_cache {
-            if cached_input == current {
-                return;
+        for ch in text.chars() {
+            // Um comando colado pode ter começado algo que espera a vez
+            if self.job_running() {
+                break;
+            }
+            // A linha de entrada anda de byte em byte, então só passa ASCII
+            match ch {
+                '\t' => self.handle_char_input(' ', false, surface),
+                '\n' => self.handle_char_input(ch, false, surface),
+                ch if ch.is_ascii() && !ch.is_control() => {
+                    self.handle_char_input(ch, false, surface)
+                }
+                _ => {}
             }
         }
+    }
+
+    // --- FUNÇÕES EXISTENTES DO TERMINAL ---
+
+    /// Nomes de comando para a primeira palavra, caminhos para o resto
+    fn completion_candidates(&self, line: &str, word_start: usize, word: &str) -> Vec<String> {
+        // Depois de |, ; e && vem outro comando
+        let before = line[..word_start].trim_end();
+        let is_command = before.is_empty() || before.ends_with(['|', ';', '&']);
+        if is_command && !word.contains('/') {
+            return self
+                .shell
+                .command_names(self)
+                .into_iter()
+                .filter(|name| name.starts_with(word))
+                .map(String::from)
+                .collect();
+        }
 
-        let candidates: Vec<String> = if current.is_empty() {
-            COMMANDS.iter().map(|&s| s.into()).collect()
+        let (dir, prefix) = match word.rfind('/') {
+            Some(slash) => word.split_at(slash + 1),
+            None => ("", word),
+        };
+        let path = if dir.is_empty() {
+            self.shell.cwd().to_string()
         } else {
-            COMMANDS
-                .iter()
-                .filter(|&&cmd| cmd.starts_with(current))
-                .map(|&s| s.into())
-                .collect()
+            self.shell.resolve_path(dir)
         };
-        
-        self.completion_cache = Some((current.clone(), candidates));
-        self.completion_index = 0;
+        let Ok(entries) = list_directory(&path) else {
+            return Vec::new();
+        };
+
+        let mut candidates: Vec<String> = entries
+            .into_iter()
+            .filter(|entry| entry.name != "." && entry.name != "..")
+            // Arquivos ocultos só quando o começo já tem o ponto
+            .filter(|entry| prefix.starts_with('.') || !entry.name.starts_with('.'))
+            .filter(|entry| entry.name.starts_with(prefix))
+            .map(|entry| {
+                let slash = if entry.is_directory { "/" } else { "" };
+                format!("{}{}{}", dir, entry.name, slash)
+            })
+            .collect();
+        candidates.sort();
+        candidates
     }
 
     fn complete_command(&mut self) {
-        self.update_completion_candidates();
-        
-        if let Some((_, candidates)) = &self.completion_cache {
-            if let Some(completion) = candidates.get(self.completion_index) {
-                self.current_command = completion.clone();
+        // Tab de novo na mesma linha passa para o próximo candidato
+        let continuing = self
+            .completion
+            .as_ref()
+            .is_some_and(|completion| completion.applied == self.current_command);
+
+        if !continuing {
+            let line = self.current_command.clone();
+            let cursor = self.command_cursor;
+            let word_start = line[..cursor].rfind(' ').map_or(0, |space| space + 1);
+            let word = &line[word_start..cursor];
+            let candidates = self.completion_candidates(&line, word_start, word);
+            self.completion = Some(Completion {
+                line,
+                cursor,
+                word_start,
+                candidates,
+                next: 0,
+                applied: String::new(),
+            });
+        }
+
+        let Some(completion) = self.completion.as_mut() else {
+            return;
+        };
+        let Some(candidate) = completion.candidates.get(completion.next) else {
+            return;
+        };
+
+        let line = &completion.line;
+        self.current_command = format!(
+            "{}{}{}",
+            &line[..completion.word_start],
+            candidate,
+            &line[completion.cursor..]
+        );
+        self.command_cursor = completion.word_start + candidate.len();
+        completion.next = (completion.next + 1) % completion.candidates.len();
+        completion.applied = self.current_command.clone();
+        self.redraw_input();
+    }
+
+    /// Ctrl+W: apaga a palavra antes do cursor e os espaços depois dela
+    fn delete_word_before_cursor(&mut self) {
+        let before = &self.current_command[..self.command_cursor];
+        let word_end = before.trim_end().len();
+        let word_start = before[..word_end].rfind(' ').map_or(0, |space| space + 1);
+        self.current_command.replace_range(word_start..self.command_cursor, "");
+        self.command_cursor = word_start;
+    }
+
+    /// Procura para trás a partir da entrada `before`, sem incluí-la
+    fn search_history(&mut self, before: usize) {
+        let Some(search) = self.history_search.as_mut() else {
+            return;
+        };
+        search.found = self.command_history[..before.min(self.command_history.len())]
+            .iter()
+            .rposition(|entry| entry.contains(search.query.as_str()));
+    }
+
+    /// Sai da busca deixando a entrada achada na linha de comando
+    fn accept_history_search(&mut self) {
+        if let Some(search) = self.history_search.take() {
+            self.current_command = match search.found {
+                Some(index) => self.command_history[index].clone(),
+                None => search.original,
+            };
+            self.command_cursor = self.current_command.len();
+            self.redraw_input();
+        }
+    }
+
+    /// Teclas durante o Ctrl+R. Devolve false quando a busca acabou e a tecla
+    /// ainda precisa do tratamento normal.
+    fn handle_search_char(&mut self, ch: char, ctrl_pressed: bool) -> bool {
+        let Some(search) = self.history_search.as_mut() else {
+            return false;
+        };
+
+        match ch {
+            'r' | 'R' if ctrl_pressed => {
+                // Próxima entrada mais antiga que também serve
+                let before = search.found.unwrap_or(self.command_history.len());
+                self.search_history(before);
+            }
+            'g' | 'G' | 'c' | 'C' if ctrl_pressed => {
+                let search = self.history_search.take();
+                self.current_command = search.map(|search| search.original).unwrap_or_default();
                 self.command_cursor = self.current_command.len();
-                self.completion_index = (self.completion_index + 1) % candidates.len();
-                self.update_display_lines();
-                self.content_changed = true;
-                self.needs_redraw = true;
             }
+            '\x08' if !ctrl_pressed => {
+                search.query.pop();
+                self.search_history(self.command_history.len());
+            }
+            ch if !ctrl_pressed && !ch.is_control() => {
+                // A entrada achada continua valendo se ainda contiver a busca
+                search.query.push(ch);
+                let before = search.found.map_or(self.command_history.len(), |index| index + 1);
+                self.search_history(before);
+            }
+            _ => {
+                self.accept_history_search();
+                return false;
+            }
+        }
+
+        self.redraw_input();
+        true
+    }
+
+    fn load_history(&mut self) {
+        let path = self.shell.resolve_path(HISTORY_FILE);
+        let Ok(text) = read_text_file(&path) else {
+            return;
+        };
+
+        let entries: Vec<&str> = text.lines().filter(|line| !line.is_empty()).collect();
+        let skip = entries.len().saturating_sub(COMMAND_HISTORY_CAPACITY);
+        self.command_history = entries[skip..].iter().map(|line| line.to_string()).collect();
+        self.history_index = self.command_history.len();
+    }
+
+    /// Acrescenta os comandos pendentes ao arquivo do histórico, de uma vez só.
+    /// O arquivo é relido antes, para não apagar o que outros terminais gravaram
+    /// desde que este abriu.
+    fn save_history(&mut self) {
+        if self.unsaved_history.is_empty() {
+            return;
         }
+
+        let path = self.shell.resolve_path(HISTORY_FILE);
+        let saved = read_text_file(&path).unwrap_or_default();
+        let mut lines: Vec<&str> = saved.lines().filter(|line| !line.is_empty()).collect();
+        lines.extend(self.unsaved_history.iter().map(String::as_str));
+
+        let skip = lines.len().saturating_sub(COMMAND_HISTORY_CAPACITY);
+        let mut text = lines[skip..].join("\n");
+        text.push('\n');
+        if let Err(e) = write_file(&path, text.as_bytes()) {
+            warn!("Could not save the command history: {}", e);
+        }
+        self.unsaved_history.clear();
     }
 
     fn navigate_history(&mut self, direction: isize) {
@@ -442,97 +851,189 @@ impl Terminal {
             String::new()
         };
         self.command_cursor = self.current_command.len();
-        self.update_display_lines();
-        self.content_changed = true;
-        self.needs_redraw = true;
+        self.redraw_input();
     }
 
     fn update_cursor_blink(&mut self, current_time: u64) {
         if current_time - self.cursor_blink_timer > CURSOR_BLINK_INTERVAL {
             self.cursor_blink_state = !self.cursor_blink_state;
             self.cursor_blink_timer = current_time;
-            self.content_changed = true;
-            self.needs_redraw = true;
         }
     }
 
+    /// Rola a visão pelo scrollback, negativo volta para linhas mais antigas
     fn scroll_lines(&mut self, lines: isize) {
-        let total_lines = self.display_lines.len();
-        if total_lines <= self.max_visible_lines {
-            return;
-        }
+        // A tela alternativa não tem scrollback
+        let max_offset = if self.screen.in_alternate_screen() {
+            0
+        } else {
+            self.screen.scrollback_len()
+        };
 
-        let max_offset = total_lines.saturating_sub(self.max_visible_lines);
-        let new_offset = match lines {
-            positive if positive > 0 => {
-                self.scroll_offset.saturating_add(positive as usize).min(max_offset)
-            }
-            negative if negative < 0 => {
-                self.scroll_offset.saturating_sub(negative.abs() as usize)
-            }
-            _ => self.scroll_offset,
+        let new_offset = if lines < 0 {
+            (self.view_offset + lines.unsigned_abs()).min(max_offset)
+        } else {
+            self.view_offset.saturating_sub(lines as usize)
         };
 
-        if new_offset != self.scroll_offset {
-            self.scroll_offset = new_offset;
-            self.content_changed = true;
-            self.needs_redraw = true;
+        if new_offset != self.view_offset {
+            self.view_offset = new_offset;
+            self.screen.mark_all_dirty();
         }
     }
 }
 
-impl Application for Terminal {
-    fn init(&mut self, surface: &mut Surface) {
-        self.text_area_idx = surface.add_shape(Shape::Text {
-            x: 5,
-            y: 5,
-            content: self.get_display_text(),
-            color: Color::WHITE,
-            background_color: Color::BLACK,
-            font_size: RasterHeight::Size16,
-            font_weight: FontWeight::Regular,
-            hide: false,
-        });
+impl Drop for Session {
+    // O painel fecha com a aba, com a janela ou sozinho
+    fn drop(&mut self) {
+        self.save_history();
+    }
+}
 
-        // Cursor visual como retângulo vertical (estilo Notepad)
-        self.cursor_idx = surface.add_shape(Shape::Rectangle {
-            x: 5,
-            y: 5,
-            width: 1, // Largura 1 pixel como no Notepad
-            height: 16,
-            color: Color::WHITE,
-            filled: true,
-            hide: false,
-        });
+/// Prompt mostrando o diretório atual, como `goofy:/docs> `
+fn prompt_for(cwd: &str) -> String {
+    format!("goofy:{}> ", cwd)
+}
+
+/// Colunas e linhas que cabem num painel
+fn grid_size(area: Rect) -> (usize, usize) {
+    let cols = area.width.saturating_sub(2 * MARGIN) / CELL_WIDTH;
+    let rows = area.height.saturating_sub(2 * MARGIN) / CELL_HEIGHT;
+    (cols, rows)
+}
+
+impl ShellHost for Session {
+    fn write(&mut self, text: &str) {
+        self.screen.write(text);
+    }
+
+    fn run_command(
+        &mut self,
+        shell: &mut Shell,
+        name: &str,
+        args: &[String],
+        io: &mut CommandIo,
+    ) -> Option<i32> {
+        // find/grep na tela rodam em segundo plano; em pipes e arquivos o shell roda direto
+        let on_screen = io.interactive && io.stdin.is_none();
+
+        match name {
+            "clear" => {
+                self.screen.clear();
+                self.selection = None;
+                Some(0)
+            }
+            "find" if on_screen => {
+                let (root, query_args) = split_find_args(shell, args);
+                Some(self.find_command(root, query_args, io))
+            }
+            "grep" if on_screen => {
+                let (options, targets) = GrepOptions::parse(args)?;
+                let target = match targets {
+                    [] => shell.cwd().to_string(),
+                    [target] => shell.resolve_path(target),
+                    _ => return None,
+                };
+                Some(self.grep_command(options, target, io))
+            }
+            _ => None,
+        }
+    }
+
+    fn commands(&self) -> &'static [(&'static str, &'static str)] {
+        HOST_COMMANDS
+    }
 
-        self.needs_redraw = true;
-        self.content_changed = true;
+    #[cfg(processes_enabled)]
+    fn execute(&mut self, path: &str, data: &[u8], io: &mut CommandIo) -> i32 {
+        // Em pipes e arquivos o shell precisa da saída inteira, e com mais comandos
+        // na linha precisa do código de saída (`$?`, `&&`, `||`), então espera.
+        // Também espera se já houver um programa, para não perder o rasto dele.
+        if !io.interactive || !io.last_on_line || self.program_job.is_some() {
+            return crate::shell::run_elf(path, data, io);
+        }
+
+        match process::spawn_elf(data) {
+            Ok(handle) => {
+                self.program_job = Some(ProgramJob {
+                    path: path.to_string(),
+                    handle,
+                });
+                0
+            }
+            Err(e) => {
+                io.error(&format!("{}: {}", path, e));
+                126
+            }
+        }
     }
+}
 
+// Entrada e desenho de um painel; o Terminal decide qual recebe o quê
+impl Session {
     fn handle_char_input(&mut self, ch: char, ctrl_pressed: bool, _surface: &mut Surface) {
         if self.mode != TerminalMode::Command {
             return;
         }
 
+        if ctrl_pressed && self.job_running() && (ch == 'c' || ch == 'C') {
+            self.cancel_jobs();
+            return;
+        }
+
+        // Input waits until the running find/grep or program is done
+        if self.job_running() {
+            return;
+        }
+
+        if self.history_search.is_some() && self.handle_search_char(ch, ctrl_pressed) {
+            return;
+        }
+
         if ctrl_pressed {
             match ch {
+                'a' | 'A' => {
+                    self.command_cursor = 0;
+                    self.redraw_input();
+                }
+                'e' | 'E' => {
+                    self.command_cursor = self.current_command.len();
+                    self.redraw_input();
+                }
+                'k' | 'K' => {
+                    self.current_command.truncate(self.command_cursor);
+                    self.redraw_input();
+                }
+                'u' | 'U' => {
+                    self.current_command.replace_range(..self.command_cursor, "");
+                    self.command_cursor = 0;
+                    self.redraw_input();
+                }
+                'w' | 'W' => {
+                    self.delete_word_before_cursor();
+                    self.redraw_input();
+                }
+                'r' | 'R' => {
+                    self.history_search = Some(HistorySearch {
+                        query: String::new(),
+                        found: None,
+                        original: self.current_command.clone(),
+                    });
+                    self.redraw_input();
+                }
                 'l' | 'L' => {
-                    self.output_lines.clear();
-                    self.display_lines.clear();
-                    self.command_lines.clear();
-                    self.output_lines.push("Terminal cleared".into());
-                    self.output_lines.push("".into());
-                    self.update_display_lines();
-                    self.content_changed = true;
-                    self.needs_redraw = true;
+                    self.screen.clear();
+                    self.selection = None;
+                    self.show_prompt();
                 }
                 'c' | 'C' => {
+                    // O comando abandonado fica na tela com ^C no fim
+                    self.command_cursor = self.current_command.len();
+                    self.redraw_input();
+                    self.screen.write("^C\n");
                     self.current_command.clear();
                     self.command_cursor = 0;
-                    self.output_lines.push("^C".into());
-                    self.update_display_lines();
-                    self.content_changed = true;
-                    self.needs_redraw = true;
+                    self.show_prompt();
                 }
                 _ => {}
             }
@@ -541,22 +1042,21 @@ impl Application for Terminal {
 
         match ch {
             '\r' | '\n' => {
+                // O comando fica na tela e a saída começa na linha de baixo
+                self.command_cursor = self.current_command.len();
+                self.redraw_input();
+                self.finish_line();
+                self.input_origin = None;
+
                 let command = core::mem::take(&mut self.current_command);
-                if !command.is_empty() {
-                    self.output_lines.push(format!("{}{}", self.prompt, command));
-                }
-                
-                self.execute_command(&command);
-                self.current_command.clear();
                 self.command_cursor = 0;
+                self.execute_command(&command);
             }
             '\x08' => { // Backspace
                 if self.command_cursor > 0 {
                     self.current_command.remove(self.command_cursor - 1);
                     self.command_cursor -= 1;
-                    self.update_display_lines();
-                    self.content_changed = true;
-                    self.needs_redraw = true;
+                    self.redraw_input();
                 }
             }
             '\t' => self.complete_command(),
@@ -565,32 +1065,40 @@ impl Application for Terminal {
                 if self.command_cursor <= self.current_command.len() {
                     self.current_command.insert(self.command_cursor, ch);
                     self.command_cursor += 1;
-                    self.update_display_lines();
-                    self.content_changed = true;
-                    self.needs_redraw = true;
+                    self.redraw_input();
                 }
             }
             _ => {}
         }
-        
-        self.update_scroll_if_needed();
     }
 
-    fn handle_key_input(&mut self, key: KeyCode, _surface: &mut Surface) {
+    fn handle_key_input(&mut self, key: KeyCode) {
         if self.mode != TerminalMode::Command {
             return;
         }
 
+        // Enquanto algo roda só dá para rolar a saída
+        let scrolling = matches!(key, KeyCode::PageUp | KeyCode::PageDown);
+        if self.job_running() && !scrolling {
+            return;
+        }
+
+        // Setas e afins terminam a busca no histórico, Esc só termina
+        if self.history_search.is_some() && !scrolling {
+            self.accept_history_search();
+            if key == KeyCode::Escape {
+                return;
+            }
+        }
+
         match key {
             KeyCode::ArrowLeft if self.command_cursor > 0 => {
                 self.command_cursor -= 1;
-                self.content_changed = true;
-                self.needs_redraw = true;
+                self.redraw_input();
             }
             KeyCode::ArrowRight if self.command_cursor < self.current_command.len() => {
                 self.command_cursor += 1;
-                self.content_changed = true;
-                self.needs_redraw = true;
+                self.redraw_input();
             }
             KeyCode::ArrowUp => {
                 if self.current_command.is_empty() {
@@ -608,68 +1116,550 @@ impl Application for Terminal {
             }
             KeyCode::Home => {
                 self.command_cursor = 0;
-                self.content_changed = true;
-                self.needs_redraw = true;
+                self.redraw_input();
             }
             KeyCode::End => {
                 self.command_cursor = self.current_command.len();
-                self.content_changed = true;
-                self.needs_redraw = true;
+                self.redraw_input();
             }
             KeyCode::Delete if self.command_cursor < self.current_command.len() => {
                 self.current_command.remove(self.command_cursor);
-                self.update_display_lines();
-                self.content_changed = true;
-                self.needs_redraw = true;
+                self.redraw_input();
             }
             KeyCode::PageUp => {
-                self.scroll_lines(-(self.max_visible_lines as isize) / 2);
+                self.scroll_lines(-(self.screen.rows() as isize) / 2);
             }
             KeyCode::PageDown => {
-                self.scroll_lines((self.max_visible_lines as isize) / 2);
+                self.scroll_lines((self.screen.rows() as isize) / 2);
             }
-            _ => return,
+            _ => {}
         }
-        
-        self.update_scroll_if_needed();
     }
 
-    fn render(&mut self, surface: &mut Surface) {
+    /// Anda com o que roda em segundo plano, também em abas escondidas
+    fn step_jobs(&mut self) {
+        self.step_search_job();
+        #[cfg(processes_enabled)]
+        self.step_program_job();
+    }
+
+    fn draw(&mut self, surface: &mut Surface, focused: bool) {
         let current_time = get_ms_since_epoch() as u64;
         self.update_cursor_blink(current_time);
 
-        if !self.needs_redraw && !self.content_changed {
+        let offset = self.visible_offset();
+
+        // Cursor é a célula sob ele com as cores trocadas, só no painel com o foco
+        let show_cursor =
+            focused && offset == 0 && self.screen.cursor_visible() && self.cursor_blink_state;
+        let cursor = show_cursor.then(|| self.screen.cursor());
+        if cursor != self.drawn_cursor {
+            for (row, _) in [self.drawn_cursor, cursor].into_iter().flatten() {
+                self.screen.mark_row_dirty(row);
+            }
+            self.drawn_cursor = cursor;
+        }
+
+        // Voltado no scrollback, qualquer mudança desloca tudo o que aparece
+        let rows = self.screen.rows();
+        let redraw_all = offset != 0 && (0..rows).any(|row| self.screen.is_row_dirty(row));
+        for row in 0..rows {
+            if redraw_all || self.screen.is_row_dirty(row) {
+                self.draw_row(surface, row, offset);
+            }
+        }
+        self.screen.mark_clean();
+    }
+
+    fn handle_mouse_event(&mut self, event: &MouseEvent) {
+        match event.kind {
+            MouseEventKind::Wheel(notches) => self.scroll_lines(notches as isize * WHEEL_LINES),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let point = self.selection_point(event.x, event.y);
+                self.set_selection(Some(Selection {
+                    anchor: point,
+                    head: point,
+                }));
+                self.selecting = true;
+            }
+            MouseEventKind::Move if self.selecting => {
+                let head = self.selection_point(event.x, event.y);
+                let selection = self.selection.map(|selection| Selection { head, ..selection });
+                self.set_selection(selection);
+            }
+            MouseEventKind::Up(MouseButton::Left) if self.selecting => {
+                self.selecting = false;
+                // Um clique sem arrastar só tira a seleção
+                if self.selected_text().is_none_or(|text| text.is_empty()) {
+                    self.set_selection(None);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn copy_selection(&self) {
+        if let Some(text) = self.selected_text().filter(|text| !text.is_empty()) {
+            clipboard::set_text(text);
+        }
+    }
+
+    /// Muda o painel de lugar ou de tamanho. Os textos da tela foram apagados
+    /// da janela, então tudo é desenhado de novo.
+    fn resize(&mut self, area: Rect) {
+        self.area = area;
+        let (cols, rows) = grid_size(area);
+        self.screen.resize(cols, rows);
+        self.screen.mark_all_dirty();
+
+        self.row_shapes.clear();
+        self.drawn_cursor = None;
+        self.redraw_input();
+    }
+}
+
+/// Como um painel se divide em dois, pela direção da linha entre eles:
+/// na horizontal um fica em cima do outro, na vertical lado a lado
+#[derive(Clone, Copy)]
+enum SplitDirection {
+    Horizontal,
+    Vertical,
+}
+
+/// Painéis de uma aba; os números são índices em `Tab::sessions`
+enum Layout {
+    Pane(usize),
+    Split(SplitDirection, Box<Layout>, Box<Layout>),
+}
+
+impl Layout {
+    /// Onde fica cada painel dentro de `area` e as linhas entre eles
+    fn areas(&self, area: Rect, panes: &mut Vec<(usize, Rect)>, dividers: &mut Vec<Rect>) {
+        match self {
+            Layout::Pane(index) => panes.push((*index, area)),
+            Layout::Split(direction, first, second) => {
+                let (first_area, divider, second_area) = split_area(area, *direction);
+                first.areas(first_area, panes, dividers);
+                dividers.push(divider);
+                second.areas(second_area, panes, dividers);
+            }
+        }
+    }
+
+    /// Divide o painel `pane`, com `new` na segunda metade
+    fn split(&mut self, pane: usize, direction: SplitDirection, new: usize) {
+        match self {
+            Layout::Pane(index) if *index == pane => {
+                let first = Box::new(Layout::Pane(pane));
+                *self = Layout::Split(direction, first, Box::new(Layout::Pane(new)));
+            }
+            Layout::Pane(_) => {}
+            Layout::Split(_, first, second) => {
+                first.split(pane, direction, new);
+                second.split(pane, direction, new);
+            }
+        }
+    }
+
+    /// Tira o painel `pane` e o vizinho fica com o lugar dele. Os índices
+    /// depois dele descem um, como em `Vec::remove`.
+    fn remove(&mut self, pane: usize) {
+        match self {
+            Layout::Pane(index) => {
+                if *index > pane {
+                    *index -= 1;
+                }
+            }
+            Layout::Split(_, first, second) => {
+                let sibling = if matches!(**first, Layout::Pane(index) if index == pane) {
+                    Some(core::mem::replace(&mut **second, Layout::Pane(0)))
+                } else if matches!(**second, Layout::Pane(index) if index == pane) {
+                    Some(core::mem::replace(&mut **first, Layout::Pane(0)))
+                } else {
+                    None
+                };
+
+                match sibling {
+                    Some(sibling) => {
+                        *self = sibling;
+                        self.remove(pane);
+                    }
+                    None => {
+                        first.remove(pane);
+                        second.remove(pane);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Metades de `area` e a linha entre elas
+fn split_area(area: Rect, direction: SplitDirection) -> (Rect, Rect, Rect) {
+    match direction {
+        SplitDirection::Horizontal => {
+            let top = area.height.saturating_sub(DIVIDER_WIDTH) / 2;
+            let bottom_y = area.y + top + DIVIDER_WIDTH;
+            let bottom = area.height.saturating_sub(top + DIVIDER_WIDTH);
+            (
+                Rect::new(area.x, area.y, area.width, top),
+                Rect::new(area.x, area.y + top, area.width, DIVIDER_WIDTH),
+                Rect::new(area.x, bottom_y, area.width, bottom),
+            )
+        }
+        SplitDirection::Vertical => {
+            let left = area.width.saturating_sub(DIVIDER_WIDTH) / 2;
+            let right_x = area.x + left + DIVIDER_WIDTH;
+            let right = area.width.saturating_sub(left + DIVIDER_WIDTH);
+            (
+                Rect::new(area.x, area.y, left, area.height),
+                Rect::new(area.x + left, area.y, DIVIDER_WIDTH, area.height),
+                Rect::new(right_x, area.y, right, area.height),
+            )
+        }
+    }
+}
+
+/// Uma aba: os shells dela e como os painéis estão divididos
+struct Tab {
+    sessions: Vec<Session>,
+    layout: Layout,
+    focused: usize,
+}
+
+impl Tab {
+    fn new(area: Rect) -> Self {
+        Tab {
+            sessions: vec![Session::new(area)],
+            layout: Layout::Pane(0),
+            focused: 0,
+        }
+    }
+
+    /// Texto na barra de abas: número e diretório do painel com o foco,
+    /// cortado pelo começo se não couber
+    fn label(&self, number: usize, max_chars: usize) -> String {
+        let label = format!("{}: {}", number, self.sessions[self.focused].shell.cwd());
+        let len = label.chars().count();
+        if len <= max_chars {
+            return label;
+        }
+
+        let keep = max_chars.saturating_sub(2);
+        let tail: String = label.chars().skip(len - keep).collect();
+        format!("..{}", tail)
+    }
+}
+
+/// Janela do terminal: abas, cada uma com um ou mais painéis, e em cada
+/// painel um shell com diretório e histórico próprios
+pub struct Terminal {
+    tabs: Vec<Tab>,
+    current: usize,
+    // Textos da barra de abas e o que mostram agora
+    tab_labels: Vec<(usize, String)>,
+    tab_width: usize,
+    // Painéis da aba atual, para saber qual está sob o mouse
+    pane_areas: Vec<(usize, Rect)>,
+    // Abas ou painéis mudaram e a janela precisa ser montada de novo
+    layout_changed: bool,
+}
+
+impl Terminal {
+    pub fn new(_args: Option<String>) -> Self {
+        let area = Rect::new(
+            0,
+            0,
+            DEFAULT_COLS * CELL_WIDTH + 2 * MARGIN,
+            DEFAULT_ROWS * CELL_HEIGHT + 2 * MARGIN,
+        );
+
+        Self {
+            tabs: vec![Tab::new(area)],
+            current: 0,
+            tab_labels: Vec::new(),
+            tab_width: MAX_TAB_WIDTH,
+            pane_areas: Vec::new(),
+            layout_changed: true,
+        }
+    }
+
+    fn focused_session(&mut self) -> &mut Session {
+        let tab = &mut self.tabs[self.current];
+        &mut tab.sessions[tab.focused]
+    }
+
+    /// Parte da janela para os painéis, abaixo da barra de abas quando há mais de uma
+    fn content_area(surface: &Surface, tab_count: usize) -> Rect {
+        let top = if tab_count > 1 { TAB_BAR_HEIGHT } else { 0 };
+        Rect::new(0, top, surface.width, surface.height.saturating_sub(top))
+    }
+
+    /// Uma sessão nova cabe no heap? Se não, avisa no painel com o foco
+    fn can_open_session(&mut self) -> bool {
+        let sessions: usize = self.tabs.iter().map(|tab| tab.sessions.len()).sum();
+        if sessions < MAX_SESSIONS {
+            return true;
+        }
+
+        let text = format!("terminal: at most {} shells can be open", MAX_SESSIONS);
+        self.focused_session().notice(&text);
+        false
+    }
+
+    /// Monta a janela de novo: barra de abas, linhas entre os painéis e cada
+    /// painel no seu lugar
+    fn layout(&mut self, surface: &mut Surface) {
+        surface.clear_all_shapes();
+        self.tab_labels.clear();
+        self.layout_changed = false;
+
+        if self.tabs.len() > 1 {
+            self.tab_width = (surface.width / self.tabs.len()).min(MAX_TAB_WIDTH);
+            for (i, tab) in self.tabs.iter().enumerate() {
+                let x = i * self.tab_width;
+                let background = if i == self.current {
+                    default_background()
+                } else {
+                    palette_color(8)
+                };
+
+                surface.add_shape(Shape::Rectangle {
+                    x,
+                    y: 0,
+                    width: self.tab_width.saturating_sub(DIVIDER_WIDTH),
+                    height: TAB_BAR_HEIGHT,
+                    color: background,
+                    filled: true,
+                    hide: false,
+                });
+                let max_chars = self.tab_width.saturating_sub(2 * TAB_PADDING) / CELL_WIDTH;
+                let label = tab.label(i + 1, max_chars);
+                let shape = surface.add_shape(Shape::Text {
+                    x: x + TAB_PADDING,
+                    y: (TAB_BAR_HEIGHT - CELL_HEIGHT) / 2,
+                    content: label.clone(),
+                    color: default_foreground(),
+                    background_color: background,
+                    font_size: RasterHeight::Size16,
+                    font_weight: FontWeight::Regular,
+                    hide: false,
+                });
+                self.tab_labels.push((shape, label));
+            }
+        }
+
+        let area = Self::content_area(surface, self.tabs.len());
+        let mut panes = Vec::new();
+        let mut dividers = Vec::new();
+        let tab = &mut self.tabs[self.current];
+        tab.layout.areas(area, &mut panes, &mut dividers);
+
+        for divider in dividers {
+            surface.add_shape(Shape::Rectangle {
+                x: divider.x,
+                y: divider.y,
+                width: divider.width,
+                height: divider.height,
+                color: palette_color(8),
+                filled: true,
+                hide: false,
+            });
+        }
+        for &(index, area) in &panes {
+            tab.sessions[index].resize(area);
+        }
+        self.pane_areas = panes;
+    }
+
+    /// Nomes das abas seguem o `cd` de cada uma
+    fn update_tab_labels(&mut self, surface: &mut Surface) {
+        let max_chars = self.tab_width.saturating_sub(2 * TAB_PADDING) / CELL_WIDTH;
+        for (i, (shape, shown)) in self.tab_labels.iter_mut().enumerate() {
+            let label = self.tabs[i].label(i + 1, max_chars);
+            if label != *shown {
+                surface.update_text_content(*shape, label.clone(), None);
+                *shown = label;
+            }
+        }
+    }
+
+    fn new_tab(&mut self, surface: &Surface) {
+        if !self.can_open_session() {
+            return;
+        }
+
+        let area = Self::content_area(surface, self.tabs.len() + 1);
+        self.tabs.push(Tab::new(area));
+        self.current = self.tabs.len() - 1;
+        self.layout_changed = true;
+    }
+
+    /// Fecha a aba atual. Sem abas sobra um shell novo; a janela fecha pelo botão.
+    fn close_tab(&mut self, surface: &Surface) {
+        let mut tab = self.tabs.remove(self.current);
+        for session in &mut tab.sessions {
+            session.stop_jobs();
+        }
+        drop(tab);
+
+        if self.tabs.is_empty() {
+            self.tabs.push(Tab::new(Self::content_area(surface, 1)));
+        }
+        self.current = self.current.min(self.tabs.len() - 1);
+        self.layout_changed = true;
+    }
+
+    fn switch_tab(&mut self, index: usize) {
+        if index < self.tabs.len() && index != self.current {
+            self.current = index;
+            self.layout_changed = true;
+        }
+    }
+
+    /// Divide o painel com o foco; o shell novo fica com a segunda metade e com o foco
+    fn split_pane(&mut self, direction: SplitDirection) {
+        if !self.can_open_session() {
             return;
         }
 
-        // Atualiza conteúdo do texto se necessário
-        let current_display = self.get_display_text();
-        if self.content_changed || current_display != self.previous_content {
-            surface.update_text_content(self.text_area_idx, current_display.clone(), None);
-            self.previous_content = current_display;
-            self.content_changed = false;
+        let tab = &mut self.tabs[self.current];
+        let area = self
+            .pane_areas
+            .iter()
+            .find(|(index, _)| *index == tab.focused)
+            .map_or(Rect::new(0, 0, 0, 0), |&(_, area)| area);
+        let (_, _, new_area) = split_area(area, direction);
+
+        let new = tab.sessions.len();
+        tab.sessions.push(Session::new(new_area));
+        tab.layout.split(tab.focused, direction, new);
+        tab.focused = new;
+        self.layout_changed = true;
+    }
+
+    /// Fecha o painel com o foco, ou a aba quando ele é o único
+    fn close_pane(&mut self, surface: &Surface) {
+        let tab = &mut self.tabs[self.current];
+        if tab.sessions.len() == 1 {
+            self.close_tab(surface);
+            return;
         }
 
-        // Atualiza posição do cursor
-        let (cursor_x, cursor_y) = self.get_cursor_visual_position();
-        if cursor_x != self.prev_cursor_x || cursor_y != self.prev_cursor_y {
-            surface.move_shape(self.cursor_idx, cursor_x, cursor_y);
-            self.prev_cursor_x = cursor_x;
-            self.prev_cursor_y = cursor_y;
+        let pane = tab.focused;
+        tab.sessions.remove(pane).stop_jobs();
+        tab.layout.remove(pane);
+        tab.focused = pane.min(tab.sessions.len() - 1);
+        self.layout_changed = true;
+    }
+
+    fn focus_next_pane(&mut self) {
+        let tab = &mut self.tabs[self.current];
+        tab.focused = (tab.focused + 1) % tab.sessions.len();
+    }
+}
+
+impl Application for Terminal {
+    fn init(&mut self, surface: &mut Surface) {
+        self.layout(surface);
+    }
+
+    fn handle_char_input(&mut self, ch: char, ctrl_pressed: bool, surface: &mut Surface) {
+        self.focused_session().handle_char_input(ch, ctrl_pressed, surface);
+    }
+
+    fn handle_key_input(&mut self, key: KeyCode, _surface: &mut Surface) {
+        self.focused_session().handle_key_input(key);
+    }
+
+    fn render(&mut self, surface: &mut Surface) {
+        for tab in &mut self.tabs {
+            for session in &mut tab.sessions {
+                session.step_jobs();
+            }
         }
 
-        // Controla visibilidade do cursor baseado no blink
-        surface.update_shape_visibility(self.cursor_idx, self.show_cursor && self.cursor_blink_state);
+        if self.layout_changed {
+            self.layout(surface);
+        }
+        self.update_tab_labels(surface);
 
-        self.needs_redraw = false;
-        self.last_render_time = current_time;
+        let tab = &mut self.tabs[self.current];
+        for (i, session) in tab.sessions.iter_mut().enumerate() {
+            session.draw(surface, i == tab.focused);
+        }
     }
 
     fn handle_mouse_click(&mut self, _x: usize, _y: usize, _surface: &mut Surface) {
         // Funcionalidade preservada
     }
 
+    fn handle_mouse_event(&mut self, event: &MouseEvent, _surface: &mut Surface) {
+        if let MouseEventKind::Down(button) = event.kind {
+            let (x, y) = (event.x.max(0) as usize, event.y.max(0) as usize);
+
+            // Clique na barra de abas troca de aba
+            if self.tabs.len() > 1 && y < TAB_BAR_HEIGHT {
+                if button == MouseButton::Left {
+                    self.switch_tab(x / self.tab_width.max(1));
+                }
+                return;
+            }
+
+            // Clicar num painel dá o foco a ele
+            let pane = self.pane_areas.iter().find(|(_, area)| area.contains_point(x, y));
+            if let Some(&(index, _)) = pane {
+                self.tabs[self.current].focused = index;
+            }
+        }
+
+        // O resto vai para o painel com o foco, que continua a seleção mesmo fora dele
+        self.focused_session().handle_mouse_event(event);
+    }
+
+    fn accelerators(&self) -> &'static [Accelerator] {
+        // Ctrl+C sozinho interrompe, então copiar e colar levam Shift
+        const ACCELERATORS: &[Accelerator] = &[
+            Accelerator::new(Modifiers::CTRL_SHIFT, KeyCode::C, "copy"),
+            Accelerator::new(Modifiers::CTRL_SHIFT, KeyCode::V, "paste"),
+            Accelerator::new(Modifiers::CTRL_SHIFT, KeyCode::T, "new_tab"),
+            Accelerator::new(Modifiers::CTRL_SHIFT, KeyCode::W, "close_tab"),
+            Accelerator::new(Modifiers::CTRL, KeyCode::PageUp, "previous_tab"),
+            Accelerator::new(Modifiers::CTRL, KeyCode::PageDown, "next_tab"),
+            Accelerator::new(Modifiers::CTRL_SHIFT, KeyCode::O, "split_horizontal"),
+            Accelerator::new(Modifiers::CTRL_SHIFT, KeyCode::E, "split_vertical"),
+            Accelerator::new(Modifiers::CTRL_SHIFT, KeyCode::N, "next_pane"),
+            Accelerator::new(Modifiers::CTRL_SHIFT, KeyCode::X, "close_pane"),
+        ];
+        ACCELERATORS
+    }
+
+    fn handle_accelerator(&mut self, action: &'static str, surface: &mut Surface) {
+        let tabs = self.tabs.len();
+        match action {
+            "copy" => self.focused_session().copy_selection(),
+            "paste" => self.focused_session().paste(surface),
+            "new_tab" => self.new_tab(surface),
+            "close_tab" => self.close_tab(surface),
+            "previous_tab" => self.switch_tab((self.current + tabs - 1) % tabs),
+            "next_tab" => self.switch_tab((self.current + 1) % tabs),
+            "split_horizontal" => self.split_pane(SplitDirection::Horizontal),
+            "split_vertical" => self.split_pane(SplitDirection::Vertical),
+            "next_pane" => self.focus_next_pane(),
+            "close_pane" => self.close_pane(surface),
+            _ => {}
+        }
+    }
+
+    fn handle_resize(&mut self, surface: &mut Surface) {
+        self.layout(surface);
+    }
+
+    fn handle_theme_change(&mut self, surface: &mut Surface) {
+        self.layout(surface);
+    }
+
     fn get_title(&self) -> Option<String> {
         Some("Terminal".into())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,237 @@
+//! Colors, fonts and a few metrics shared by the window manager, the desktop
+//! shell and the widgets.
+//!
+//! Everything that draws reads [`current`] when it builds its shapes, so a
+//! theme switch only needs the shapes to be built again. The desktop does
+//! that for the shell and calls `WindowManager::apply_theme` for the windows.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use noto_sans_mono_bitmap::{FontWeight, RasterHeight};
+
+use crate::framebuffer::Color;
+
+pub struct Theme {
+    /// Name stored in the settings file
+    pub name: &'static str,
+    pub display_name: &'static str,
+
+    // Desktop shell
+    pub desktop_background: Color,
+    pub taskbar: Color,
+    pub taskbar_text: Color,
+    pub taskbar_button: Color,
+    pub taskbar_button_focused: Color,
+    pub icon_label: Color,
+
+    // Window decorations
+    pub border: Color,
+    pub titlebar: Color,
+    pub title_text: Color,
+    pub title_button: Color,
+    pub title_button_glyph: Color,
+    pub title_button_disabled: Color,
+    pub close_button: Color,
+
+    // Window contents
+    pub window_background: Color,
+    pub text: Color,
+    pub disabled_text: Color,
+    pub error_text: Color,
+    pub control: Color,
+    pub control_text: Color,
+    pub control_border: Color,
+    pub input_background: Color,
+    pub selection: Color,
+    pub selection_text: Color,
+    pub focus: Color,
+    pub scroll_track: Color,
+    pub scroll_thumb: Color,
+
+    // Terminal and log viewer, for text without colors of its own
+    pub terminal_background: Color,
+    pub terminal_text: Color,
+
+    // Menus, dialogs and the Alt+Tab switcher
+    pub menu_background: Color,
+    pub separator: Color,
+    pub dialog_background: Color,
+    pub switcher_background: Color,
+
+    pub font_size: RasterHeight,
+    pub font_weight: FontWeight,
+    pub title_font_weight: FontWeight,
+
+    /// Thickness of the window outline
+    pub border_width: usize,
+    /// Opacity of window titlebars, 255 is solid
+    pub titlebar_alpha: u8,
+    /// How far the drop shadow reaches past a window, 0 for none
+    pub shadow_size: usize,
+}
+
+pub const LIGHT: Theme = Theme {
+    name: "light",
+    display_name: "Light",
+
+    desktop_background: Color::new(50, 111, 168),
+    taskbar: Color::new(175, 175, 175),
+    taskbar_text: Color::BLACK,
+    taskbar_button: Color::new(160, 160, 160),
+    taskbar_button_focused: Color::new(220, 220, 220),
+    icon_label: Color::WHITE,
+
+    border: Color::BLACK,
+    titlebar: Color::BLACK,
+    title_text: Color::WHITE,
+    title_button: Color::DARKGRAY,
+    title_button_glyph: Color::WHITE,
+    title_button_disabled: Color::GRAY,
+    close_button: Color::RED,
+
+    window_background: Color::new(240, 240, 240),
+    text: Color::BLACK,
+    disabled_text: Color::new(150, 150, 150),
+    error_text: Color::new(200, 0, 0),
+    control: Color::new(220, 220, 220),
+    control_text: Color::BLACK,
+    control_border: Color::BLACK,
+    input_background: Color::WHITE,
+    selection: Color::new(100, 140, 200),
+    selection_text: Color::WHITE,
+    focus: Color::new(50, 100, 220),
+    scroll_track: Color::new(230, 230, 230),
+    scroll_thumb: Color::new(160, 160, 160),
+
+    terminal_background: Color::BLACK,
+    terminal_text: Color::WHITE,
+
+    menu_background: Color::new(240, 240, 240),
+    separator: Color::new(180, 180, 180),
+    dialog_background: Color::new(230, 230, 230),
+    switcher_background: Color::new(60, 60, 60),
+
+    font_size: RasterHeight::Size16,
+    font_weight: FontWeight::Regular,
+    title_font_weight: FontWeight::Regular,
+
+    border_width: 1,
+    titlebar_alpha: 220,
+    shadow_size: 8,
+};
+
+pub const DARK: Theme = Theme {
+    name: "dark",
+    display_name: "Dark",
+
+    desktop_background: Color::new(32, 36, 44),
+    taskbar: Color::new(45, 45, 50),
+    taskbar_text: Color::new(230, 230, 230),
+    taskbar_button: Color::new(60, 60, 66),
+    taskbar_button_focused: Color::new(95, 95, 105),
+    icon_label: Color::new(230, 230, 230),
+
+    border: Color::new(15, 15, 15),
+    titlebar: Color::new(25, 25, 30),
+    title_text: Color::new(235, 235, 235),
+    title_button: Color::new(70, 70, 76),
+    title_button_glyph: Color::new(230, 230, 230),
+    title_button_disabled: Color::new(120, 120, 120),
+    close_button: Color::new(190, 50, 50),
+
+    window_background: Color::new(50, 50, 55),
+    text: Color::new(230, 230, 230),
+    disabled_text: Color::new(130, 130, 130),
+    error_text: Color::new(240, 100, 100),
+    control: Color::new(75, 75, 82),
+    control_text: Color::new(235, 235, 235),
+    control_border: Color::new(110, 110, 118),
+    input_background: Color::new(35, 35, 40),
+    selection: Color::new(60, 100, 165),
+    selection_text: Color::new(250, 250, 250),
+    focus: Color::new(90, 150, 250),
+    scroll_track: Color::new(40, 40, 45),
+    scroll_thumb: Color::new(100, 100, 108),
+
+    terminal_background: Color::new(20, 20, 24),
+    terminal_text: Color::new(220, 220, 220),
+
+    menu_background: Color::new(55, 55, 60),
+    separator: Color::new(90, 90, 96),
+    dialog_background: Color::new(60, 60, 66),
+    switcher_background: Color::new(30, 30, 34),
+
+    font_size: RasterHeight::Size16,
+    font_weight: FontWeight::Regular,
+    title_font_weight: FontWeight::Regular,
+
+    border_width: 1,
+    titlebar_alpha: 200,
+    shadow_size: 10,
+};
+
+pub const HIGH_CONTRAST: Theme = Theme {
+    name: "high-contrast",
+    display_name: "High contrast",
+
+    desktop_background: Color::BLACK,
+    taskbar: Color::BLACK,
+    taskbar_text: Color::WHITE,
+    taskbar_button: Color::BLACK,
+    taskbar_button_focused: Color::new(0, 0, 170),
+    icon_label: Color::new(255, 255, 0),
+
+    border: Color::WHITE,
+    titlebar: Color::new(0, 0, 170),
+    title_text: Color::WHITE,
+    title_button: Color::BLACK,
+    title_button_glyph: Color::WHITE,
+    title_button_disabled: Color::new(0, 200, 0),
+    close_button: Color::new(200, 0, 0),
+
+    window_background: Color::BLACK,
+    text: Color::WHITE,
+    disabled_text: Color::new(0, 200, 0),
+    error_text: Color::new(255, 80, 80),
+    control: Color::BLACK,
+    control_text: Color::WHITE,
+    control_border: Color::WHITE,
+    input_background: Color::BLACK,
+    selection: Color::new(0, 255, 255),
+    selection_text: Color::BLACK,
+    focus: Color::new(255, 255, 0),
+    scroll_track: Color::BLACK,
+    scroll_thumb: Color::WHITE,
+
+    terminal_background: Color::BLACK,
+    terminal_text: Color::WHITE,
+
+    menu_background: Color::BLACK,
+    separator: Color::WHITE,
+    dialog_background: Color::BLACK,
+    switcher_background: Color::BLACK,
+
+    font_size: RasterHeight::Size16,
+    font_weight: FontWeight::Bold,
+    title_font_weight: FontWeight::Bold,
+
+    border_width: 2,
+    titlebar_alpha: 255,
+    shadow_size: 0,
+};
+
+pub static THEMES: [&Theme; 3] = [&LIGHT, &DARK, &HIGH_CONTRAST];
+
+static CURRENT_THEME: AtomicUsize = AtomicUsize::new(0);
+
+pub fn current() -> &'static Theme {
+    THEMES[CURRENT_THEME.load(Ordering::Relaxed)]
+}
+
+/// Switch to the theme with the given settings name.
+/// Returns false if it is unknown or already in use.
+pub fn set_theme(name: &str) -> bool {
+    let Some(index) = THEMES.iter().position(|theme| theme.name == name) else {
+        return false;
+    };
+    CURRENT_THEME.swap(index, Ordering::Relaxed) != index
+}
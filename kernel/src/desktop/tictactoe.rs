@@ -5,9 +5,12 @@ use alloc::{
 use pc_keyboard::KeyCode;
 
 use crate::{
-    desktop::application::Application,
-    framebuffer::Color,
-    surface::{Shape, Surface},
+    desktop::{
+        application::Application,
+        theme,
+        widgets::{Button, Label, Ui, WidgetEvent, layout::Grid},
+    },
+    surface::{Rect, Surface},
 };
 use noto_sans_mono_bitmap::{FontWeight, RasterHeight};
 
@@ -59,15 +62,12 @@ pub struct TicTacToe {
     game_mode: GameMode,
     status_message: String,
 
-    // UI element indices
-    status_text_idx: Option<usize>,
-    cell_indices: [[Option<usize>; 3]; 3], // Background shapes for cells
-    cell_text_indices: [[Option<usize>; 3]; 3], // Text shapes for X/O
-
-    // Button indices
-    new_game_btn_idx: Option<usize>,
-    two_player_btn_idx: Option<usize>,
-    vs_bot_btn_idx: Option<usize>,
+    // Widget ids
+    ui: Ui<TicTacToe>,
+    status_label: usize,
+    cell_buttons: [[usize; 3]; 3],
+    two_player_btn: usize,
+    vs_bot_btn: usize,
 }
 
 impl TicTacToe {
@@ -78,220 +78,116 @@ impl TicTacToe {
             game_state: GameState::Playing,
             game_mode: GameMode::TwoPlayer,
             status_message: "Player X's turn".to_string(),
-            status_text_idx: None,
-            cell_indices: [[None; 3]; 3],
-            cell_text_indices: [[None; 3]; 3],
-            new_game_btn_idx: None,
-            two_player_btn_idx: None,
-            vs_bot_btn_idx: None,
+            ui: Ui::new(),
+            status_label: 0,
+            cell_buttons: [[0; 3]; 3],
+            two_player_btn: 0,
+            vs_bot_btn: 0,
         }
     }
 
     fn setup_ui(&mut self, surface: &mut Surface) {
-        surface.clear_all_shapes();
-
         let width = surface.width;
 
-        // Title
-        surface.add_shape(Shape::Text {
-            x: width / 2 - 50,
-            y: 20,
-            content: "Tic-Tac-Toe".to_string(),
-            color: Color::BLACK,
-            background_color: surface.background_color,
-            font_size: RasterHeight::Size24,
-            font_weight: FontWeight::Bold,
-            hide: false,
-        });
+        self.ui.add(
+            Label::new(width / 2 - 50, 20, "Tic-Tac-Toe")
+                .with_font(RasterHeight::Size24, FontWeight::Bold),
+            None,
+        );
 
         // Game mode buttons
-        let mode_color_2p = if self.game_mode == GameMode::TwoPlayer {
-            Color::new(200, 255, 200)
-        } else {
-            Color::new(220, 220, 220)
-        };
-
-        let mode_color_bot = if self.game_mode == GameMode::VsBot {
-            Color::new(200, 255, 200)
-        } else {
-            Color::new(220, 220, 220)
-        };
-
-        self.two_player_btn_idx = Some(surface.add_shape(Shape::Rectangle {
-            x: 50,
-            y: 50,
-            width: 80,
-            height: 30,
-            color: mode_color_2p,
-            filled: true,
-            hide: false,
-        }));
-
-        surface.add_shape(Shape::Rectangle {
-            x: 50,
-            y: 50,
-            width: 80,
-            height: 30,
-            color: Color::BLACK,
-            filled: false,
-            hide: false,
-        });
-
-        surface.add_shape(Shape::Text {
-            x: 63,
-            y: 58,
-            content: "2 Player".to_string(),
-            color: Color::BLACK,
-            background_color: mode_color_2p,
-            font_size: RasterHeight::Size16,
-            font_weight: FontWeight::Regular,
-            hide: false,
-        });
-
-        self.vs_bot_btn_idx = Some(surface.add_shape(Shape::Rectangle {
-            x: 140,
-            y: 50,
-            width: 80,
-            height: 30,
-            color: mode_color_bot,
-            filled: true,
-            hide: false,
-        }));
-
-        surface.add_shape(Shape::Rectangle {
-            x: 140,
-            y: 50,
-            width: 80,
-            height: 30,
-            color: Color::BLACK,
-            filled: false,
-            hide: false,
-        });
-
-        surface.add_shape(Shape::Text {
-            x: 160,
-            y: 58,
-            content: "vs Bot".to_string(),
-            color: Color::BLACK,
-            background_color: mode_color_bot,
-            font_size: RasterHeight::Size16,
-            font_weight: FontWeight::Regular,
-            hide: false,
-        });
+        self.two_player_btn = self.ui.add(
+            Button::new(Rect::new(50, 50, 80, 30), "2 Player"),
+            Some(Self::on_mode_button),
+        );
+        self.vs_bot_btn = self.ui.add(
+            Button::new(Rect::new(140, 50, 80, 30), "vs Bot"),
+            Some(Self::on_mode_button),
+        );
 
         // Game board - 3x3 grid
-        let board_start_x = 50;
-        let board_start_y = 100;
-        let cell_size = 60;
-        let cell_padding = 5;
-
+        let board = Grid::new(50, 100, 3, 60, 60, 5);
         for row in 0..3 {
             for col in 0..3 {
-                let x = board_start_x + col * (cell_size + cell_padding);
-                let y = board_start_y + row * (cell_size + cell_padding);
-
-                // Cell background
-                self.cell_indices[row][col] = Some(surface.add_shape(Shape::Rectangle {
-                    x,
-                    y,
-                    width: cell_size,
-                    height: cell_size,
-                    color: Color::WHITE,
-                    filled: true,
-                    hide: false,
-                }));
-
-                // Cell border
-                surface.add_shape(Shape::Rectangle {
-                    x,
-                    y,
-                    width: cell_size,
-                    height: cell_size,
-                    color: Color::BLACK,
-                    filled: false,
-                    hide: false,
-                });
-
-                // Cell text (X or O)
-                let text_content = match self.board[row][col] {
-                    Cell::Empty => " ".to_string(),
-                    Cell::Occupied(player) => player.to_string().to_string(),
-                };
+                self.cell_buttons[row][col] = self.ui.add(
+                    Button::new(board.cell(row, col), " ")
+                        .with_color(theme::current().input_background)
+                        .with_font(RasterHeight::Size24, FontWeight::Bold),
+                    Some(Self::on_cell),
+                );
+            }
+        }
+
+        self.status_label = self.ui.add(Label::new(50, 320, &self.status_message), None);
+
+        self.ui.add(
+            Button::new(Rect::new(80, 350, 100, 30), "New Game"),
+            Some(Self::on_new_game),
+        );
+
+        self.ui.attach(surface);
+        self.update_mode_buttons(surface);
+        self.update_board_display(surface);
+    }
 
-                self.cell_text_indices[row][col] = Some(surface.add_shape(Shape::Text {
-                    x: x + cell_size / 2 - 8,
-                    y: y + cell_size / 2 - 8,
-                    content: text_content,
-                    color: Color::BLACK,
-                    background_color: Color::WHITE,
-                    font_size: RasterHeight::Size24,
-                    font_weight: FontWeight::Bold,
-                    hide: false,
-                }));
+    fn update_mode_buttons(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
+        let selected = (theme.selection, theme.selection_text);
+        let unselected = (theme.control, theme.control_text);
+        let (two_player, vs_bot) = match self.game_mode {
+            GameMode::TwoPlayer => (selected, unselected),
+            GameMode::VsBot => (unselected, selected),
+        };
+
+        for (id, (color, text_color)) in
+            [(self.two_player_btn, two_player), (self.vs_bot_btn, vs_bot)]
+        {
+            if let Some(button) = self.ui.button(id) {
+                button.set_color(color, surface);
+                button.set_text_color(text_color, surface);
             }
         }
+    }
 
-        // Status message
-        self.status_text_idx = Some(surface.add_shape(Shape::Text {
-            x: 50,
-            y: 320,
-            content: self.status_message.clone(),
-            color: Color::BLACK,
-            background_color: surface.background_color,
-            font_size: RasterHeight::Size16,
-            font_weight: FontWeight::Regular,
-            hide: false,
-        }));
-
-        // New Game button
-        self.new_game_btn_idx = Some(surface.add_shape(Shape::Rectangle {
-            x: 80,
-            y: 350,
-            width: 100,
-            height: 30,
-            color: Color::new(200, 200, 255),
-            filled: true,
-            hide: false,
-        }));
-
-        surface.add_shape(Shape::Rectangle {
-            x: 80,
-            y: 350,
-            width: 100,
-            height: 30,
-            color: Color::BLACK,
-            filled: false,
-            hide: false,
-        });
-
-        surface.add_shape(Shape::Text {
-            x: 100,
-            y: 358,
-            content: "New Game".to_string(),
-            color: Color::BLACK,
-            background_color: Color::new(200, 200, 255),
-            font_size: RasterHeight::Size16,
-            font_weight: FontWeight::Regular,
-            hide: false,
-        });
+    fn on_mode_button(&mut self, id: usize, _event: WidgetEvent, surface: &mut Surface) {
+        let mode = if id == self.vs_bot_btn {
+            GameMode::VsBot
+        } else {
+            GameMode::TwoPlayer
+        };
+        self.set_game_mode(mode, surface);
+    }
+
+    fn on_new_game(&mut self, _id: usize, _event: WidgetEvent, surface: &mut Surface) {
+        self.new_game(surface);
+    }
+
+    fn on_cell(&mut self, id: usize, _event: WidgetEvent, surface: &mut Surface) {
+        let cell = (0..3)
+            .flat_map(|row| (0..3).map(move |col| (row, col)))
+            .find(|&(row, col)| self.cell_buttons[row][col] == id);
+
+        if let Some((row, col)) = cell {
+            self.handle_cell_click(row, col, surface);
+        }
     }
 
     fn update_status_message(&mut self, surface: &mut Surface, message: String) {
         self.status_message = message;
-        if let Some(idx) = self.status_text_idx {
-            surface.update_text_content(idx, self.status_message.clone(), None);
+        if let Some(label) = self.ui.label(self.status_label) {
+            label.set_text(&self.status_message, surface);
         }
     }
 
     fn update_board_display(&mut self, surface: &mut Surface) {
         for row in 0..3 {
             for col in 0..3 {
-                if let Some(text_idx) = self.cell_text_indices[row][col] {
-                    let text_content = match self.board[row][col] {
-                        Cell::Empty => " ".to_string(),
-                        Cell::Occupied(player) => player.to_string().to_string(),
-                    };
-                    surface.update_text_content(text_idx, text_content, None);
+                let text = match self.board[row][col] {
+                    Cell::Empty => " ",
+                    Cell::Occupied(player) => player.to_string(),
+                };
+                if let Some(button) = self.ui.button(self.cell_buttons[row][col]) {
+                    button.set_label(text, surface);
                 }
             }
         }
@@ -477,10 +373,10 @@ impl TicTacToe {
     fn set_game_mode(&mut self, mode: GameMode, surface: &mut Surface) {
         self.game_mode = mode;
         self.new_game(surface);
-        self.setup_ui(surface); // Refresh UI to update button colors
+        self.update_mode_buttons(surface);
     }
 
-    fn handle_cell_click(&mut self, x: usize, y: usize, surface: &mut Surface) -> bool {
+    fn handle_cell_click(&mut self, row: usize, col: usize, surface: &mut Surface) -> bool {
         if self.game_state != GameState::Playing {
             return false;
         }
@@ -490,35 +386,19 @@ impl TicTacToe {
             return false;
         }
 
-        let board_start_x = 50;
-        let board_start_y = 100;
-        let cell_size = 60;
-        let cell_padding = 5;
-
-        for row in 0..3 {
-            for col in 0..3 {
-                let cell_x = board_start_x + col * (cell_size + cell_padding);
-                let cell_y = board_start_y + row * (cell_size + cell_padding);
-
-                if x >= cell_x && x < cell_x + cell_size && y >= cell_y && y < cell_y + cell_size {
-                    return self.make_move(row, col, surface);
-                }
-            }
+        if !self.make_move(row, col, surface) {
+            return false;
         }
 
-        false
-    }
+        // If we're in bot mode and it's now the bot's turn, make bot move
+        if self.game_mode == GameMode::VsBot
+            && self.current_player == Player::O
+            && self.game_state == GameState::Playing
+        {
+            self.make_bot_move(surface);
+        }
 
-    fn is_button_clicked(
-        &self,
-        x: usize,
-        y: usize,
-        btn_x: usize,
-        btn_y: usize,
-        btn_width: usize,
-        btn_height: usize,
-    ) -> bool {
-        x >= btn_x && x < btn_x + btn_width && y >= btn_y && y < btn_y + btn_height
+        true
     }
 }
 
@@ -527,41 +407,26 @@ impl Application for TicTacToe {
         self.setup_ui(surface);
     }
 
-    fn handle_char_input(&mut self, _c: char, _ctrl_pressed: bool, _surface: &mut Surface) {
-        // TicTacToe doesn't need character input
+    fn handle_char_input(&mut self, c: char, _ctrl_pressed: bool, surface: &mut Surface) {
+        // Tab moves between the buttons, Enter/Space presses them
+        if let Some((callback, id, event)) = self.ui.handle_char(c, surface) {
+            callback(self, id, event, surface);
+        }
     }
 
     fn handle_key_input(&mut self, _key: KeyCode, _surface: &mut Surface) {
         // TicTacToe doesn't need keyboard input
     }
 
-    fn handle_mouse_click(&mut self, x: usize, y: usize, surface: &mut Surface) {
-        // Handle game mode buttons
-        if self.is_button_clicked(x, y, 50, 50, 80, 30) {
-            self.set_game_mode(GameMode::TwoPlayer, surface);
-            return;
-        }
-
-        if self.is_button_clicked(x, y, 140, 50, 80, 30) {
-            self.set_game_mode(GameMode::VsBot, surface);
-            return;
-        }
-
-        // Handle new game button
-        if self.is_button_clicked(x, y, 80, 350, 100, 30) {
-            self.new_game(surface);
-            return;
-        }
-
-        // Handle cell clicks
-        self.handle_cell_click(x, y, surface);
+    fn handle_theme_change(&mut self, surface: &mut Surface) {
+        surface.clear_all_shapes();
+        self.ui.clear();
+        self.setup_ui(surface);
+    }
 
-        // If we're in bot mode and it's now the bot's turn, make bot move
-        if self.game_mode == GameMode::VsBot
-            && self.current_player == Player::O
-            && self.game_state == GameState::Playing
-        {
-            self.make_bot_move(surface);
+    fn handle_mouse_click(&mut self, x: usize, y: usize, surface: &mut Surface) {
+        if let Some((callback, id, event)) = self.ui.handle_click(x, y, surface) {
+            callback(self, id, event, surface);
         }
     }
 
@@ -0,0 +1,1021 @@
+//! VT100/xterm subset behind the terminal window. Text goes through
+//! [`Screen::write`] and lands on a grid of cells, each with its own colors;
+//! escape sequences move the cursor, erase, scroll and recolor.
+//!
+//! Supported: the C0 controls BS, HT, LF, CR; ESC 7 8 D E M c; CSI cursor
+//! movement (A-H, d, f, s, u), erase (J, K, X), insert/delete (@, P, L, M),
+//! scrolling (S, T, r), SGR with 16 and 256 colors (24-bit ones are mapped
+//! into the 256-color cube), DECTCEM (?25) and the alternate screen (?47,
+//! ?1047, ?1049). OSC strings are skipped. LF also returns the carriage,
+//! there is no tty layer to translate it.
+
+use alloc::{collections::VecDeque, string::String, vec, vec::Vec};
+
+use crate::{desktop::theme, framebuffer::Color};
+
+/// Foreground for cells without an SGR color, from the current theme
+pub fn default_foreground() -> Color {
+    theme::current().terminal_text
+}
+
+/// Background for cells without an SGR color, from the current theme
+pub fn default_background() -> Color {
+    theme::current().terminal_background
+}
+
+/// Stored for characters the font can't draw, shown as U+FFFD
+const REPLACEMENT: u8 = 0x1a;
+const TAB_WIDTH: usize = 8;
+const MAX_PARAMS: usize = 16;
+
+// Style flags
+const DEFAULT_FG: u8 = 1 << 0;
+const DEFAULT_BG: u8 = 1 << 1;
+const BOLD: u8 = 1 << 2;
+const INVERSE: u8 = 1 << 3;
+
+/// xterm's colors for the 16 basic indices
+const BASIC_COLORS: [Color; 16] = [
+    Color::new(0, 0, 0),
+    Color::new(205, 0, 0),
+    Color::new(0, 205, 0),
+    Color::new(205, 205, 0),
+    Color::new(0, 0, 238),
+    Color::new(205, 0, 205),
+    Color::new(0, 205, 205),
+    Color::new(229, 229, 229),
+    Color::new(127, 127, 127),
+    Color::new(255, 0, 0),
+    Color::new(0, 255, 0),
+    Color::new(255, 255, 0),
+    Color::new(92, 92, 255),
+    Color::new(255, 0, 255),
+    Color::new(0, 255, 255),
+    Color::new(255, 255, 255),
+];
+/// Channel values of the 6x6x6 cube at indices 16-231
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// RGB value of a 256-color palette index
+pub fn palette_color(index: u8) -> Color {
+    match index {
+        0..=15 => BASIC_COLORS[index as usize],
+        16..=231 => {
+            let i = (index - 16) as usize;
+            Color::new(
+                CUBE_LEVELS[i / 36],
+                CUBE_LEVELS[i / 6 % 6],
+                CUBE_LEVELS[i % 6],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            Color::new(level, level, level)
+        }
+    }
+}
+
+/// Closest cube index for a 24-bit color
+fn cube_index(r: u8, g: u8, b: u8) -> u8 {
+    let level = |value: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| CUBE_LEVELS[i].abs_diff(value))
+            .unwrap_or(0) as u8
+    };
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+/// Colors and attributes of a cell. Colors are palette indices.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    fg: u8,
+    bg: u8,
+    flags: u8,
+}
+
+impl Style {
+    pub const DEFAULT: Style = Style {
+        fg: 7,
+        bg: 0,
+        flags: DEFAULT_FG | DEFAULT_BG,
+    };
+
+    /// Foreground and background to draw with
+    pub fn colors(&self) -> (Color, Color) {
+        let fg = if self.flags & DEFAULT_FG != 0 {
+            default_foreground()
+        } else if self.flags & BOLD != 0 && self.fg < 8 {
+            // Bold brightens the basic colors, like xterm does
+            palette_color(self.fg + 8)
+        } else {
+            palette_color(self.fg)
+        };
+        let bg = if self.flags & DEFAULT_BG != 0 {
+            default_background()
+        } else {
+            palette_color(self.bg)
+        };
+
+        if self.flags & INVERSE != 0 {
+            (bg, fg)
+        } else {
+            (fg, bg)
+        }
+    }
+
+    /// Nothing visible is drawn for a space in this style
+    fn is_plain_background(&self) -> bool {
+        self.flags & (DEFAULT_BG | INVERSE) == DEFAULT_BG
+    }
+}
+
+/// One character on the grid. The font only has ASCII, so one byte is
+/// enough and the grid stays small.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    ch: u8,
+    style: Style,
+}
+
+impl Cell {
+    pub const BLANK: Cell = Cell {
+        ch: b' ',
+        style: Style::DEFAULT,
+    };
+
+    pub fn char(&self) -> char {
+        match self.ch {
+            REPLACEMENT => '\u{FFFD}',
+            ch => ch as char,
+        }
+    }
+
+    /// The cell with its colors swapped, how the cursor is drawn
+    pub fn inverted(self) -> Cell {
+        Cell {
+            style: Style {
+                flags: self.style.flags ^ INVERSE,
+                ..self.style
+            },
+            ..self
+        }
+    }
+
+    fn is_blank(&self) -> bool {
+        self.ch == b' ' && self.style.is_plain_background()
+    }
+}
+
+/// Cells of a row grouped by style, for drawing each group in one go.
+/// Blanks at the end are left out.
+pub fn style_runs(cells: &[Cell]) -> Vec<(Style, String)> {
+    let end = cells
+        .iter()
+        .rposition(|cell| !cell.is_blank())
+        .map_or(0, |last| last + 1);
+
+    let mut runs: Vec<(Style, String)> = Vec::new();
+    for cell in &cells[..end] {
+        match runs.last_mut() {
+            Some((style, text)) if *style == cell.style => text.push(cell.char()),
+            _ => runs.push((cell.style, String::from(cell.char()))),
+        }
+    }
+    runs
+}
+
+#[derive(Clone, Copy)]
+struct SavedCursor {
+    row: usize,
+    col: usize,
+    style: Style,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Ground,
+    Escape,
+    /// ESC ( or ESC ), the next character picks a charset we don't have
+    Charset,
+    Csi,
+    Osc,
+    OscEscape,
+}
+
+pub struct Screen {
+    cols: usize,
+    rows: usize,
+    grid: Vec<Vec<Cell>>,
+    row: usize,
+    col: usize,
+    /// Last column was written, the next character goes on a new line
+    pending_wrap: bool,
+    style: Style,
+    saved: SavedCursor,
+    /// Scrolling region, both rows included
+    scroll_top: usize,
+    scroll_bottom: usize,
+    cursor_visible: bool,
+    /// Main screen and its cursor while the alternate one shows
+    primary: Option<(Vec<Vec<Cell>>, SavedCursor)>,
+    scrollback: VecDeque<Vec<Cell>>,
+    scrollback_limit: usize,
+    /// Heap the scrollback may take, and how much it takes now
+    scrollback_budget: usize,
+    scrollback_bytes: usize,
+    /// Lines scrolled off the top so far, to follow a position as it moves up
+    scrolled: usize,
+    dirty: Vec<bool>,
+    state: State,
+    params: [u16; MAX_PARAMS],
+    param_count: usize,
+    private: bool,
+}
+
+impl Screen {
+    /// Keeps at most `scrollback_limit` lines above the screen, fewer if they
+    /// would take more than `scrollback_budget` bytes of heap
+    pub fn new(
+        cols: usize,
+        rows: usize,
+        scrollback_limit: usize,
+        scrollback_budget: usize,
+    ) -> Self {
+        let cols = cols.max(1);
+        let rows = rows.max(1);
+        let cursor = SavedCursor {
+            row: 0,
+            col: 0,
+            style: Style::DEFAULT,
+        };
+
+        Self {
+            cols,
+            rows,
+            grid: vec![vec![Cell::BLANK; cols]; rows],
+            row: 0,
+            col: 0,
+            pending_wrap: false,
+            style: Style::DEFAULT,
+            saved: cursor,
+            scroll_top: 0,
+            scroll_bottom: rows - 1,
+            cursor_visible: true,
+            primary: None,
+            scrollback: VecDeque::new(),
+            scrollback_limit,
+            scrollback_budget,
+            scrollback_bytes: 0,
+            scrolled: 0,
+            dirty: vec![true; rows],
+            state: State::Ground,
+            params: [0; MAX_PARAMS],
+            param_count: 0,
+            private: false,
+        }
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Row and column of the cursor
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    pub fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    pub fn in_alternate_screen(&self) -> bool {
+        self.primary.is_some()
+    }
+
+    /// Lines scrolled off the top since the screen was made
+    pub fn scrolled(&self) -> usize {
+        self.scrolled
+    }
+
+    pub fn scrollback_len(&self) -> usize {
+        self.scrollback.len()
+    }
+
+    /// Row `row` of the view `offset` lines back into the scrollback.
+    /// Scrollback lines may be shorter than the screen.
+    pub fn view_row(&self, row: usize, offset: usize) -> &[Cell] {
+        let offset = offset.min(self.scrollback.len());
+        let line = self.scrollback.len() - offset + row;
+        match line.checked_sub(self.scrollback.len()) {
+            Some(screen_row) => &self.grid[screen_row],
+            None => &self.scrollback[line],
+        }
+    }
+
+    /// Line `line` counted the way [`Screen::scrolled`] does: the top row of
+    /// the screen is line `scrolled()` and the scrollback sits right above it.
+    /// None for lines that left the scrollback or lie below the screen.
+    pub fn line(&self, line: usize) -> Option<&[Cell]> {
+        match line.checked_sub(self.scrolled) {
+            Some(row) => self.grid.get(row).map(Vec::as_slice),
+            None => {
+                let back = self.scrolled - line;
+                let index = self.scrollback.len().checked_sub(back)?;
+                Some(&self.scrollback[index])
+            }
+        }
+    }
+
+    pub fn is_row_dirty(&self, row: usize) -> bool {
+        self.dirty.get(row).copied().unwrap_or(false)
+    }
+
+    pub fn mark_row_dirty(&mut self, row: usize) {
+        if let Some(dirty) = self.dirty.get_mut(row) {
+            *dirty = true;
+        }
+    }
+
+    pub fn mark_clean(&mut self) {
+        self.dirty.fill(false);
+    }
+
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty.fill(true);
+    }
+
+    /// Feed output to the screen, escape sequences may span several calls
+    pub fn write(&mut self, text: &str) {
+        for ch in text.chars() {
+            match self.state {
+                State::Ground => self.ground(ch),
+                State::Escape => self.escape(ch),
+                State::Charset => self.state = State::Ground,
+                State::Csi => self.csi(ch),
+                State::Osc => match ch {
+                    '\x07' => self.state = State::Ground,
+                    '\x1b' => self.state = State::OscEscape,
+                    _ => {}
+                },
+                // ESC \ ends the string
+                State::OscEscape => self.state = State::Ground,
+            }
+        }
+    }
+
+    /// Put the cursor at `row`, `col`, kept on the screen
+    pub fn set_cursor(&mut self, row: usize, col: usize) {
+        self.row = row.min(self.rows - 1);
+        self.col = col.min(self.cols - 1);
+        self.pending_wrap = false;
+    }
+
+    /// Blank everything from the cursor to the end of the screen
+    pub fn erase_below(&mut self) {
+        self.erase_in_display(0);
+    }
+
+    /// Blank the screen and forget the scrollback, cursor at the top left
+    pub fn clear(&mut self) {
+        self.erase_in_display(2);
+        self.erase_in_display(3);
+        self.set_cursor(0, 0);
+    }
+
+    /// Change the size, keeping what fits. Rows that no longer fit above the
+    /// cursor go into the scrollback.
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        let cols = cols.max(1);
+        let rows = rows.max(1);
+        if cols == self.cols && rows == self.rows {
+            return;
+        }
+
+        // Keep the cursor row on the screen
+        let overflow = (self.row + 1).saturating_sub(rows);
+        for _ in 0..overflow {
+            let line = self.grid.remove(0);
+            self.scrolled += 1;
+            if self.primary.is_none() {
+                self.push_scrollback(line);
+            }
+        }
+        self.row -= overflow;
+
+        resize_grid(&mut self.grid, cols, rows);
+        if let Some((grid, saved)) = self.primary.as_mut() {
+            resize_grid(grid, cols, rows);
+            saved.row = saved.row.min(rows - 1);
+            saved.col = saved.col.min(cols - 1);
+        }
+
+        self.cols = cols;
+        self.rows = rows;
+        self.scroll_top = 0;
+        self.scroll_bottom = rows - 1;
+        self.saved.row = self.saved.row.min(rows - 1);
+        self.saved.col = self.saved.col.min(cols - 1);
+        self.set_cursor(self.row, self.col);
+        self.dirty = vec![true; rows];
+    }
+
+    fn ground(&mut self, ch: char) {
+        match ch {
+            '\x1b' => self.state = State::Escape,
+            '\n' | '\x0b' | '\x0c' => {
+                self.carriage_return();
+                self.line_feed();
+            }
+            '\r' => self.carriage_return(),
+            '\x08' => self.set_cursor(self.row, self.col.saturating_sub(1)),
+            '\t' => {
+                let next = (self.col / TAB_WIDTH + 1) * TAB_WIDTH;
+                self.set_cursor(self.row, next);
+            }
+            ch if ch.is_control() => {}
+            ch => self.print(ch),
+        }
+    }
+
+    fn escape(&mut self, ch: char) {
+        self.state = State::Ground;
+        match ch {
+            '[' => {
+                self.params = [0; MAX_PARAMS];
+                self.param_count = 0;
+                self.private = false;
+                self.state = State::Csi;
+            }
+            ']' => self.state = State::Osc,
+            '(' | ')' => self.state = State::Charset,
+            '7' => self.save_cursor(),
+            '8' => self.restore_cursor(),
+            'D' => self.line_feed(),
+            'E' => {
+                self.carriage_return();
+                self.line_feed();
+            }
+            'M' => self.reverse_index(),
+            'c' => self.reset(),
+            _ => {}
+        }
+    }
+
+    fn csi(&mut self, ch: char) {
+        match ch {
+            '0'..='9' => {
+                if self.param_count == 0 {
+                    self.param_count = 1;
+                }
+                let digit = ch as u16 - '0' as u16;
+                let param = &mut self.params[self.param_count - 1];
+                *param = param.saturating_mul(10).saturating_add(digit);
+            }
+            ';' | ':' => {
+                if self.param_count == 0 {
+                    self.param_count = 1;
+                }
+                if self.param_count < MAX_PARAMS {
+                    self.param_count += 1;
+                }
+            }
+            '?' => self.private = true,
+            '\x1b' => self.state = State::Escape,
+            '\x40'..='\x7e' => {
+                self.state = State::Ground;
+                self.dispatch_csi(ch);
+            }
+            // Intermediate bytes and stray controls
+            _ => {}
+        }
+    }
+
+    /// Parameter `index`, or `default` when missing or zero
+    fn param(&self, index: usize, default: usize) -> usize {
+        match self.params[..self.param_count].get(index) {
+            Some(&value) if value != 0 => value as usize,
+            _ => default,
+        }
+    }
+
+    fn dispatch_csi(&mut self, command: char) {
+        if self.private {
+            match command {
+                'h' => self.set_private_modes(true),
+                'l' => self.set_private_modes(false),
+                _ => {}
+            }
+            return;
+        }
+
+        let count = self.param(0, 1);
+        match command {
+            'A' => self.set_cursor(self.row.saturating_sub(count), self.col),
+            'B' => self.set_cursor(self.row + count, self.col),
+            'C' => self.set_cursor(self.row, self.col + count),
+            'D' => self.set_cursor(self.row, self.col.saturating_sub(count)),
+            'E' => self.set_cursor(self.row + count, 0),
+            'F' => self.set_cursor(self.row.saturating_sub(count), 0),
+            'G' => self.set_cursor(self.row, count - 1),
+            'H' | 'f' => self.set_cursor(count - 1, self.param(1, 1) - 1),
+            'd' => self.set_cursor(count - 1, self.col),
+            'J' => self.erase_in_display(self.param(0, 0)),
+            'K' => self.erase_in_line(self.param(0, 0)),
+            'X' => {
+                let end = (self.col + count).min(self.cols);
+                self.blank_cells(self.row, self.col..end);
+            }
+            '@' => self.insert_cells(count),
+            'P' => self.delete_cells(count),
+            'L' => self.insert_lines(count),
+            'M' => self.delete_lines(count),
+            'S' => self.scroll_up(count),
+            'T' => self.scroll_down(count),
+            'r' => self.set_scroll_region(),
+            'm' => self.select_graphic_rendition(),
+            's' => self.save_cursor(),
+            'u' => self.restore_cursor(),
+            _ => {}
+        }
+    }
+
+    fn set_private_modes(&mut self, enable: bool) {
+        for index in 0..self.param_count {
+            match self.params[index] {
+                25 => self.cursor_visible = enable,
+                47 | 1047 | 1049 if enable => self.enter_alternate_screen(),
+                47 | 1047 | 1049 => self.leave_alternate_screen(),
+                _ => {}
+            }
+        }
+    }
+
+    fn select_graphic_rendition(&mut self) {
+        if self.param_count == 0 {
+            self.style = Style::DEFAULT;
+            return;
+        }
+
+        let params = self.params;
+        let mut i = 0;
+        while i < self.param_count {
+            match params[i] {
+                0 => self.style = Style::DEFAULT,
+                1 => self.style.flags |= BOLD,
+                22 => self.style.flags &= !BOLD,
+                7 => self.style.flags |= INVERSE,
+                27 => self.style.flags &= !INVERSE,
+                code @ 30..=37 => self.set_fg(code as u8 - 30),
+                code @ 90..=97 => self.set_fg(code as u8 - 90 + 8),
+                39 => self.style.flags |= DEFAULT_FG,
+                code @ 40..=47 => self.set_bg(code as u8 - 40),
+                code @ 100..=107 => self.set_bg(code as u8 - 100 + 8),
+                49 => self.style.flags |= DEFAULT_BG,
+                code @ (38 | 48) => {
+                    let (color, used) = self.extended_color(i + 1);
+                    i += used;
+                    match color {
+                        Some(color) if code == 38 => self.set_fg(color),
+                        Some(color) => self.set_bg(color),
+                        None => {}
+                    }
+                }
+                // Underline, italic, blink and the rest aren't drawn
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    /// Color after 38 or 48, `5;n` or `2;r;g;b`, and how many parameters it took
+    fn extended_color(&self, start: usize) -> (Option<u8>, usize) {
+        let params = &self.params[start.min(self.param_count)..self.param_count];
+        let channel = |value: u16| value.min(255) as u8;
+        match params {
+            [5, index, ..] => (Some(channel(*index)), 2),
+            [2, r, g, b, ..] => (Some(cube_index(channel(*r), channel(*g), channel(*b))), 4),
+            _ => (None, params.len()),
+        }
+    }
+
+    fn set_fg(&mut self, color: u8) {
+        self.style.fg = color;
+        self.style.flags &= !DEFAULT_FG;
+    }
+
+    fn set_bg(&mut self, color: u8) {
+        self.style.bg = color;
+        self.style.flags &= !DEFAULT_BG;
+    }
+
+    fn print(&mut self, ch: char) {
+        if self.pending_wrap {
+            self.carriage_return();
+            self.line_feed();
+        }
+
+        let ch = if ch.is_ascii() { ch as u8 } else { REPLACEMENT };
+        self.grid[self.row][self.col] = Cell {
+            ch,
+            style: self.style,
+        };
+        self.dirty[self.row] = true;
+
+        if self.col + 1 == self.cols {
+            self.pending_wrap = true;
+        } else {
+            self.col += 1;
+        }
+    }
+
+    fn carriage_return(&mut self) {
+        self.col = 0;
+        self.pending_wrap = false;
+    }
+
+    fn line_feed(&mut self) {
+        self.pending_wrap = false;
+        if self.row == self.scroll_bottom {
+            self.scroll_up(1);
+        } else if self.row + 1 < self.rows {
+            self.row += 1;
+        }
+    }
+
+    fn reverse_index(&mut self) {
+        self.pending_wrap = false;
+        if self.row == self.scroll_top {
+            self.scroll_down(1);
+        } else {
+            self.row = self.row.saturating_sub(1);
+        }
+    }
+
+    /// Blank cell in the current colors, erased areas keep the background
+    fn blank(&self) -> Cell {
+        Cell {
+            ch: b' ',
+            style: Style {
+                flags: self.style.flags & !(BOLD | INVERSE),
+                ..self.style
+            },
+        }
+    }
+
+    fn blank_cells(&mut self, row: usize, range: core::ops::Range<usize>) {
+        let blank = self.blank();
+        self.grid[row][range].fill(blank);
+        self.dirty[row] = true;
+    }
+
+    fn erase_in_line(&mut self, mode: usize) {
+        match mode {
+            0 => self.blank_cells(self.row, self.col..self.cols),
+            1 => self.blank_cells(self.row, 0..self.col + 1),
+            _ => self.blank_cells(self.row, 0..self.cols),
+        }
+    }
+
+    fn erase_in_display(&mut self, mode: usize) {
+        match mode {
+            0 => {
+                self.erase_in_line(0);
+                for row in self.row + 1..self.rows {
+                    self.blank_cells(row, 0..self.cols);
+                }
+            }
+            1 => {
+                for row in 0..self.row {
+                    self.blank_cells(row, 0..self.cols);
+                }
+                self.erase_in_line(1);
+            }
+            2 => {
+                for row in 0..self.rows {
+                    self.blank_cells(row, 0..self.cols);
+                }
+            }
+            3 => {
+                self.scrollback.clear();
+                self.scrollback_bytes = 0;
+            }
+            _ => {}
+        }
+    }
+
+    fn insert_cells(&mut self, count: usize) {
+        let count = count.min(self.cols - self.col);
+        let blank = self.blank();
+        let line = &mut self.grid[self.row];
+        line[self.col..].rotate_right(count);
+        line[self.col..self.col + count].fill(blank);
+        self.dirty[self.row] = true;
+    }
+
+    fn delete_cells(&mut self, count: usize) {
+        let count = count.min(self.cols - self.col);
+        let blank = self.blank();
+        let line = &mut self.grid[self.row];
+        line[self.col..].rotate_left(count);
+        let cols = self.cols;
+        line[cols - count..].fill(blank);
+        self.dirty[self.row] = true;
+    }
+
+    fn insert_lines(&mut self, count: usize) {
+        if (self.scroll_top..=self.scroll_bottom).contains(&self.row) {
+            let top = self.scroll_top;
+            self.scroll_top = self.row;
+            self.scroll_down(count);
+            self.scroll_top = top;
+            self.carriage_return();
+        }
+    }
+
+    fn delete_lines(&mut self, count: usize) {
+        if (self.scroll_top..=self.scroll_bottom).contains(&self.row) {
+            let top = self.scroll_top;
+            self.scroll_top = self.row;
+            self.scroll_region_up(count, false);
+            self.scroll_top = top;
+            self.carriage_return();
+        }
+    }
+
+    fn scroll_up(&mut self, count: usize) {
+        self.scroll_region_up(count, true);
+    }
+
+    /// Move the scrolling region up `count` lines. Lines leaving the top of
+    /// the main screen go into the scrollback when `keep` is set.
+    fn scroll_region_up(&mut self, count: usize, keep: bool) {
+        let count = count.min(self.scroll_bottom + 1 - self.scroll_top);
+        let blank = self.blank();
+        for _ in 0..count {
+            let mut line = self.grid.remove(self.scroll_top);
+            if self.scroll_top == 0 && keep {
+                self.scrolled += 1;
+                if self.primary.is_none() {
+                    self.push_scrollback(line.clone());
+                }
+            }
+            line.fill(blank);
+            self.grid.insert(self.scroll_bottom, line);
+        }
+        self.dirty[self.scroll_top..=self.scroll_bottom].fill(true);
+    }
+
+    fn scroll_down(&mut self, count: usize) {
+        let count = count.min(self.scroll_bottom + 1 - self.scroll_top);
+        let blank = self.blank();
+        for _ in 0..count {
+            let mut line = self.grid.remove(self.scroll_bottom);
+            line.fill(blank);
+            self.grid.insert(self.scroll_top, line);
+        }
+        self.dirty[self.scroll_top..=self.scroll_bottom].fill(true);
+    }
+
+    fn push_scrollback(&mut self, mut line: Vec<Cell>) {
+        if self.scrollback_limit == 0 {
+            return;
+        }
+
+        // Trailing blanks only cost memory
+        let end = line
+            .iter()
+            .rposition(|cell| !cell.is_blank())
+            .map_or(0, |last| last + 1);
+        line.truncate(end);
+        line.shrink_to_fit();
+
+        let size = line_size(&line);
+        if size > self.scrollback_budget {
+            return;
+        }
+        while self.scrollback.len() >= self.scrollback_limit
+            || self.scrollback_bytes + size > self.scrollback_budget
+        {
+            self.drop_oldest_line();
+        }
+        // With the heap full the line is lost, not the kernel
+        if self.scrollback.try_reserve(1).is_err() {
+            return;
+        }
+        self.scrollback_bytes += size;
+        self.scrollback.push_back(line);
+    }
+
+    fn drop_oldest_line(&mut self) {
+        if let Some(line) = self.scrollback.pop_front() {
+            self.scrollback_bytes -= line_size(&line);
+        }
+    }
+
+    fn set_scroll_region(&mut self) {
+        let top = self.param(0, 1) - 1;
+        let bottom = self.param(1, self.rows).min(self.rows) - 1;
+        if top < bottom {
+            self.scroll_top = top;
+            self.scroll_bottom = bottom;
+            self.set_cursor(0, 0);
+        }
+    }
+
+    fn save_cursor(&mut self) {
+        self.saved = SavedCursor {
+            row: self.row,
+            col: self.col,
+            style: self.style,
+        };
+    }
+
+    fn restore_cursor(&mut self) {
+        self.style = self.saved.style;
+        self.set_cursor(self.saved.row, self.saved.col);
+    }
+
+    fn enter_alternate_screen(&mut self) {
+        if self.primary.is_some() {
+            return;
+        }
+
+        let cursor = SavedCursor {
+            row: self.row,
+            col: self.col,
+            style: self.style,
+        };
+        let grid = core::mem::replace(
+            &mut self.grid,
+            vec![vec![Cell::BLANK; self.cols]; self.rows],
+        );
+        self.primary = Some((grid, cursor));
+        self.mark_all_dirty();
+    }
+
+    fn leave_alternate_screen(&mut self) {
+        let Some((grid, cursor)) = self.primary.take() else {
+            return;
+        };
+
+        self.grid = grid;
+        self.style = cursor.style;
+        self.set_cursor(cursor.row, cursor.col);
+        self.scroll_top = 0;
+        self.scroll_bottom = self.rows - 1;
+        self.mark_all_dirty();
+    }
+
+    /// ESC c: back to how the screen started, scrollback included
+    fn reset(&mut self) {
+        let scrolled = self.scrolled;
+        *self = Screen::new(
+            self.cols,
+            self.rows,
+            self.scrollback_limit,
+            self.scrollback_budget,
+        );
+        self.scrolled = scrolled;
+    }
+}
+
+/// Heap taken by a scrollback line
+fn line_size(line: &Vec<Cell>) -> usize {
+    size_of::<Vec<Cell>>() + line.capacity() * size_of::<Cell>()
+}
+
+fn resize_grid(grid: &mut Vec<Vec<Cell>>, cols: usize, rows: usize) {
+    grid.resize(rows, vec![Cell::BLANK; cols]);
+    for line in grid.iter_mut() {
+        line.resize(cols, Cell::BLANK);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn screen(cols: usize, rows: usize) -> Screen {
+        Screen::new(cols, rows, 10, 4096)
+    }
+
+    fn row_text(screen: &Screen, row: usize, offset: usize) -> String {
+        let text: String = screen
+            .view_row(row, offset)
+            .iter()
+            .map(Cell::char)
+            .collect();
+        text.trim_end().to_string()
+    }
+
+    #[test_case]
+    fn test_text_wraps_and_scrolls() {
+        let mut screen = screen(10, 4);
+        screen.write("0123456789");
+        // The last column is written, the wrap waits for the next character
+        assert_eq!(screen.cursor(), (0, 9));
+
+        screen.write("ab\nx\ny\nz");
+        assert_eq!(row_text(&screen, 0, 0), "ab");
+        assert_eq!(row_text(&screen, 3, 0), "z");
+        assert_eq!(screen.scrolled(), 1);
+        assert_eq!(screen.scrollback_len(), 1);
+        assert_eq!(row_text(&screen, 0, 1), "0123456789");
+    }
+
+    #[test_case]
+    fn test_cursor_movement_and_erase() {
+        let mut screen = screen(20, 4);
+        screen.write("hello world\x1b[1;7H\x1b[K");
+        assert_eq!(row_text(&screen, 0, 0), "hello");
+
+        screen.write("\x1b[2;3HX\x1b[A");
+        assert_eq!(row_text(&screen, 1, 0), "  X");
+        assert_eq!(screen.cursor(), (0, 3));
+
+        screen.write("\x1b[99;99H");
+        assert_eq!(screen.cursor(), (3, 19));
+
+        screen.write("\x1b[2J");
+        assert_eq!(row_text(&screen, 0, 0), "");
+        assert_eq!(row_text(&screen, 1, 0), "");
+    }
+
+    #[test_case]
+    fn test_graphic_rendition() {
+        let mut screen = screen(10, 2);
+        screen.write("\x1b[31mR\x1b[1;44mB\x1b[0mN\x1b[38;5;200mP\x1b[38;2;255;0;0mT");
+        let row = screen.view_row(0, 0);
+
+        assert_eq!(row[0].style.fg, 1);
+        assert_eq!(row[0].style.flags, DEFAULT_BG);
+        assert_eq!((row[1].style.fg, row[1].style.bg), (1, 4));
+        assert_eq!(row[1].style.flags, BOLD);
+        assert!(row[2].style == Style::DEFAULT);
+        assert_eq!(row[3].style.fg, 200);
+        // 24-bit colors land in the 256-color cube
+        assert_eq!(row[4].style.fg, 196);
+    }
+
+    #[test_case]
+    fn test_sequences_split_across_writes() {
+        let mut screen = screen(10, 2);
+        screen.write("\x1b[");
+        screen.write("3");
+        screen.write("2mA");
+        screen.write("\x1b]0;title\x07B\x1b]0;t\x1b\\C");
+
+        assert_eq!(row_text(&screen, 0, 0), "ABC");
+        assert_eq!(screen.view_row(0, 0)[0].style.fg, 2);
+    }
+
+    #[test_case]
+    fn test_alternate_screen() {
+        let mut screen = screen(10, 2);
+        screen.write("main\x1b[?1049h\x1b[?25l");
+        assert!(screen.in_alternate_screen());
+        assert!(!screen.cursor_visible());
+        assert_eq!(row_text(&screen, 0, 0), "");
+
+        screen.write("alt\x1b[?1049l");
+        assert!(!screen.in_alternate_screen());
+        assert_eq!(row_text(&screen, 0, 0), "main");
+        assert_eq!(screen.cursor(), (0, 4));
+    }
+
+    #[test_case]
+    fn test_scroll_region() {
+        let mut screen = screen(10, 4);
+        screen.write("a\nb\nc\nd\x1b[2;3r\x1b[2;1H\x1b[M");
+
+        assert_eq!(row_text(&screen, 0, 0), "a");
+        assert_eq!(row_text(&screen, 1, 0), "c");
+        assert_eq!(row_text(&screen, 2, 0), "");
+        assert_eq!(row_text(&screen, 3, 0), "d");
+        assert_eq!(screen.scrollback_len(), 0);
+    }
+
+    #[test_case]
+    fn test_scrollback_limits() {
+        let mut screen = Screen::new(10, 2, 3, 4096);
+        for i in 0..10 {
+            screen.write(&alloc::format!("line{}\n", i));
+        }
+        assert_eq!(screen.scrollback_len(), 3);
+        assert_eq!(row_text(&screen, 0, 3), "line6");
+
+        // Each scrolled line keeps only its five written cells
+        let line = size_of::<Vec<Cell>>() + 5 * size_of::<Cell>();
+        let mut screen = Screen::new(10, 2, 100, 2 * line);
+        for i in 0..10 {
+            screen.write(&alloc::format!("line{}\n", i));
+        }
+        assert_eq!(screen.scrollback_len(), 2);
+        assert_eq!(row_text(&screen, 0, 2), "line7");
+    }
+}
@@ -0,0 +1,41 @@
+//! Optional wallpaper for the desktop, read from a BMP on disk and tiled
+//! across the background.
+
+use alloc::vec::Vec;
+use embedded_graphics::{pixelcolor::Rgb888, prelude::RgbColor};
+use tinybmp::Bmp;
+
+use crate::{framebuffer::Color, fs::manager::read_file, surface::Wallpaper};
+
+pub const WALLPAPER_PATH: &str = "/wallpaper.bmp";
+
+/// The kernel heap is small, so only tile sized images are accepted
+const MAX_WALLPAPER_PIXELS: usize = 64 * 64;
+
+pub fn load_wallpaper(path: &str) -> Result<Wallpaper, &'static str> {
+    let data = read_file(path)?;
+    let bmp = Bmp::<Rgb888>::from_slice(&data).map_err(|_| "Not a 24-bit BMP image")?;
+
+    let size = bmp.as_raw().header().image_size;
+    let (width, height) = (size.width as usize, size.height as usize);
+    if width == 0 || height == 0 {
+        return Err("Wallpaper image is empty");
+    }
+    if width * height > MAX_WALLPAPER_PIXELS {
+        return Err("Wallpaper image is too large, it must fit in 64x64 pixels");
+    }
+
+    let pixels = bmp
+        .pixels()
+        .map(|pixel| Color::new(pixel.1.r(), pixel.1.g(), pixel.1.b()))
+        .collect::<Vec<_>>();
+    if pixels.len() != width * height {
+        return Err("Wallpaper image is truncated");
+    }
+
+    Ok(Wallpaper {
+        width,
+        height,
+        pixels,
+    })
+}
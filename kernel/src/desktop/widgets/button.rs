@@ -0,0 +1,177 @@
+use alloc::string::{String, ToString};
+use noto_sans_mono_bitmap::{FontWeight, RasterHeight};
+
+use super::{WidgetEvent, char_width};
+use crate::{
+    desktop::theme,
+    framebuffer::Color,
+    surface::{Rect, Shape, Surface},
+};
+
+pub struct Button {
+    bounds: Rect,
+    label: String,
+    color: Color,
+    text_color: Color,
+    font_size: RasterHeight,
+    font_weight: FontWeight,
+    focused: bool,
+    bg_idx: Option<usize>,
+    border_idx: Option<usize>,
+    text_idx: Option<usize>,
+}
+
+impl Button {
+    pub fn new(bounds: Rect, label: &str) -> Self {
+        Button {
+            bounds,
+            label: label.to_string(),
+            color: theme::current().control,
+            text_color: theme::current().control_text,
+            font_size: theme::current().font_size,
+            font_weight: theme::current().font_weight,
+            focused: false,
+            bg_idx: None,
+            border_idx: None,
+            text_idx: None,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_text_color(mut self, text_color: Color) -> Self {
+        self.text_color = text_color;
+        self
+    }
+
+    pub fn with_font(mut self, font_size: RasterHeight, font_weight: FontWeight) -> Self {
+        self.font_size = font_size;
+        self.font_weight = font_weight;
+        self
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Top-left of the label, centered in the button
+    fn text_position(&self) -> (usize, usize) {
+        let text_width = self.label.chars().count() * char_width(self.font_size, self.font_weight);
+        (
+            self.bounds.x + self.bounds.width.saturating_sub(text_width) / 2,
+            self.bounds.y + self.bounds.height.saturating_sub(self.font_size.val()) / 2,
+        )
+    }
+
+    fn border_color(&self) -> Color {
+        if self.focused {
+            theme::current().focus
+        } else {
+            theme::current().control_border
+        }
+    }
+
+    pub(super) fn attach(&mut self, surface: &mut Surface) {
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = self.bounds;
+
+        self.bg_idx = Some(surface.add_shape(Shape::Rectangle {
+            x,
+            y,
+            width,
+            height,
+            color: self.color,
+            filled: true,
+            hide: false,
+        }));
+        self.border_idx = Some(surface.add_shape(Shape::Rectangle {
+            x,
+            y,
+            width,
+            height,
+            color: self.border_color(),
+            filled: false,
+            hide: false,
+        }));
+
+        let (text_x, text_y) = self.text_position();
+        self.text_idx = Some(surface.add_shape(Shape::Text {
+            x: text_x,
+            y: text_y,
+            content: self.label.clone(),
+            color: self.text_color,
+            background_color: self.color,
+            font_size: self.font_size,
+            font_weight: self.font_weight,
+            hide: false,
+        }));
+    }
+
+    pub fn set_label(&mut self, label: &str, surface: &mut Surface) {
+        if self.label == label {
+            return;
+        }
+
+        self.label = label.to_string();
+        let (text_x, text_y) = self.text_position();
+        if let Some(idx) = self.text_idx {
+            // Clear the old text with the button color before the new one moves in
+            surface.update_text_content(idx, self.label.clone(), Some(self.bounds));
+            surface.move_shape(idx, text_x, text_y);
+        }
+    }
+
+    pub fn set_color(&mut self, color: Color, surface: &mut Surface) {
+        if self.color == color {
+            return;
+        }
+
+        self.color = color;
+        if let Some(idx) = self.bg_idx {
+            surface.update_rectangle_color(idx, color);
+        }
+        if let Some(idx) = self.text_idx {
+            surface.update_text_background(idx, color);
+        }
+    }
+
+    pub fn set_text_color(&mut self, text_color: Color, surface: &mut Surface) {
+        if self.text_color == text_color {
+            return;
+        }
+
+        self.text_color = text_color;
+        if let Some(idx) = self.text_idx {
+            surface.update_text_color(idx, text_color);
+        }
+    }
+
+    pub(super) fn set_focused(&mut self, focused: bool, surface: &mut Surface) {
+        self.focused = focused;
+        if let Some(idx) = self.border_idx {
+            surface.update_rectangle_color(idx, self.border_color());
+        }
+    }
+
+    pub(super) fn handle_click(&mut self) -> Option<WidgetEvent> {
+        Some(WidgetEvent::Clicked)
+    }
+
+    pub(super) fn handle_char(&mut self, c: char) -> Option<WidgetEvent> {
+        match c {
+            '\n' | '\r' | ' ' => Some(WidgetEvent::Clicked),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use alloc::string::{String, ToString};
+
+use super::{WidgetEvent, char_width};
+use crate::{
+    desktop::theme,
+    framebuffer::Color,
+    surface::{Rect, Shape, Surface},
+};
+
+const BOX_SIZE: usize = 14;
+const LABEL_GAP: usize = 6;
+
+pub struct Checkbox {
+    x: usize,
+    y: usize,
+    label: String,
+    checked: bool,
+    focused: bool,
+    border_idx: Option<usize>,
+    mark_idx: Option<usize>,
+}
+
+impl Checkbox {
+    pub fn new(x: usize, y: usize, label: &str, checked: bool) -> Self {
+        Checkbox {
+            x,
+            y,
+            label: label.to_string(),
+            checked,
+            focused: false,
+            border_idx: None,
+            mark_idx: None,
+        }
+    }
+
+    /// The box and its label are both clickable
+    pub fn bounds(&self) -> Rect {
+        let theme = theme::current();
+        let label_width =
+            self.label.chars().count() * char_width(theme.font_size, theme.font_weight);
+        Rect::new(self.x, self.y, BOX_SIZE + LABEL_GAP + label_width, 16)
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.checked
+    }
+
+    fn border_color(&self) -> Color {
+        if self.focused {
+            theme::current().focus
+        } else {
+            theme::current().control_border
+        }
+    }
+
+    pub(super) fn attach(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
+        surface.add_shape(Shape::Rectangle {
+            x: self.x,
+            y: self.y + 1,
+            width: BOX_SIZE,
+            height: BOX_SIZE,
+            color: theme.input_background,
+            filled: true,
+            hide: false,
+        });
+        self.border_idx = Some(surface.add_shape(Shape::Rectangle {
+            x: self.x,
+            y: self.y + 1,
+            width: BOX_SIZE,
+            height: BOX_SIZE,
+            color: self.border_color(),
+            filled: false,
+            hide: false,
+        }));
+        self.mark_idx = Some(surface.add_shape(Shape::Rectangle {
+            x: self.x + 3,
+            y: self.y + 4,
+            width: BOX_SIZE - 6,
+            height: BOX_SIZE - 6,
+            color: theme.text,
+            filled: true,
+            hide: !self.checked,
+        }));
+        surface.add_shape(Shape::Text {
+            x: self.x + BOX_SIZE + LABEL_GAP,
+            y: self.y,
+            content: self.label.clone(),
+            color: theme.text,
+            background_color: surface.background_color,
+            font_size: theme.font_size,
+            font_weight: theme.font_weight,
+            hide: false,
+        });
+    }
+
+    pub fn set_checked(&mut self, checked: bool, surface: &mut Surface) {
+        self.checked = checked;
+        if let Some(idx) = self.mark_idx {
+            surface.update_shape_visibility(idx, checked);
+        }
+    }
+
+    pub(super) fn set_focused(&mut self, focused: bool, surface: &mut Surface) {
+        self.focused = focused;
+        if let Some(idx) = self.border_idx {
+            surface.update_rectangle_color(idx, self.border_color());
+        }
+    }
+
+    pub(super) fn handle_click(&mut self, surface: &mut Surface) -> Option<WidgetEvent> {
+        self.set_checked(!self.checked, surface);
+        Some(WidgetEvent::Toggled(self.checked))
+    }
+
+    pub(super) fn handle_char(&mut self, c: char, surface: &mut Surface) -> Option<WidgetEvent> {
+        match c {
+            ' ' => self.handle_click(surface),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use alloc::string::{String, ToString};
+use noto_sans_mono_bitmap::{FontWeight, RasterHeight};
+
+use crate::{
+    desktop::theme,
+    framebuffer::Color,
+    surface::{Rect, Shape, Surface},
+};
+
+/// Text that apps update but users don't interact with. With `filled` it sits
+/// on its own box, like the calculator display.
+pub struct Label {
+    bounds: Rect,
+    text: String,
+    color: Color,
+    background: Option<Color>,
+    font_size: RasterHeight,
+    font_weight: FontWeight,
+    text_idx: Option<usize>,
+}
+
+impl Label {
+    pub fn new(x: usize, y: usize, text: &str) -> Self {
+        Label {
+            bounds: Rect::new(x, y, 0, 0),
+            text: text.to_string(),
+            color: theme::current().text,
+            background: None,
+            font_size: theme::current().font_size,
+            font_weight: theme::current().font_weight,
+            text_idx: None,
+        }
+    }
+
+    /// Draw a box of the given color behind the text; the text is vertically
+    /// centered and indented a little
+    pub fn filled(mut self, width: usize, height: usize, color: Color) -> Self {
+        self.bounds.width = width;
+        self.bounds.height = height;
+        self.background = Some(color);
+        self
+    }
+
+    pub fn with_font(mut self, font_size: RasterHeight, font_weight: FontWeight) -> Self {
+        self.font_size = font_size;
+        self.font_weight = font_weight;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub(super) fn attach(&mut self, surface: &mut Surface) {
+        let (text_x, text_y, background) = match self.background {
+            Some(color) => {
+                surface.add_shape(Shape::Rectangle {
+                    x: self.bounds.x,
+                    y: self.bounds.y,
+                    width: self.bounds.width,
+                    height: self.bounds.height,
+                    color,
+                    filled: true,
+                    hide: false,
+                });
+                (
+                    self.bounds.x + 5,
+                    self.bounds.y + self.bounds.height.saturating_sub(self.font_size.val()) / 2,
+                    color,
+                )
+            }
+            None => (self.bounds.x, self.bounds.y, surface.background_color),
+        };
+
+        self.text_idx = Some(surface.add_shape(Shape::Text {
+            x: text_x,
+            y: text_y,
+            content: self.text.clone(),
+            color: self.color,
+            background_color: background,
+            font_size: self.font_size,
+            font_weight: self.font_weight,
+            hide: false,
+        }));
+    }
+
+    pub fn set_text(&mut self, text: &str, surface: &mut Surface) {
+        if self.text == text {
+            return;
+        }
+
+        self.text = text.to_string();
+        if let Some(idx) = self.text_idx {
+            surface.update_text_content(idx, self.text.clone(), None);
+        }
+    }
+
+    pub fn set_color(&mut self, color: Color, surface: &mut Surface) {
+        self.color = color;
+        if let Some(idx) = self.text_idx {
+            surface.update_text_color(idx, color);
+        }
+    }
+}
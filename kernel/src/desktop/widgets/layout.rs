@@ -0,0 +1,104 @@
+//! Containers that hand out widget bounds so apps don't compute positions by hand
+
+use crate::surface::Rect;
+
+/// Stacks widgets top to bottom
+pub struct Column {
+    x: usize,
+    y: usize,
+    width: usize,
+    spacing: usize,
+}
+
+impl Column {
+    pub fn new(x: usize, y: usize, width: usize, spacing: usize) -> Self {
+        Column {
+            x,
+            y,
+            width,
+            spacing,
+        }
+    }
+
+    /// Bounds for the next widget, full width of the column
+    pub fn next(&mut self, height: usize) -> Rect {
+        let rect = Rect::new(self.x, self.y, self.width, height);
+        self.y += height + self.spacing;
+        rect
+    }
+
+    /// Leave an empty gap
+    pub fn skip(&mut self, height: usize) {
+        self.y += height;
+    }
+}
+
+/// Places widgets left to right
+pub struct Row {
+    x: usize,
+    y: usize,
+    height: usize,
+    spacing: usize,
+}
+
+impl Row {
+    pub fn new(x: usize, y: usize, height: usize, spacing: usize) -> Self {
+        Row {
+            x,
+            y,
+            height,
+            spacing,
+        }
+    }
+
+    /// Bounds for the next widget, full height of the row
+    pub fn next(&mut self, width: usize) -> Rect {
+        let rect = Rect::new(self.x, self.y, width, self.height);
+        self.x += width + self.spacing;
+        rect
+    }
+}
+
+/// Equal sized cells, filled row by row
+pub struct Grid {
+    x: usize,
+    y: usize,
+    columns: usize,
+    cell_width: usize,
+    cell_height: usize,
+    spacing: usize,
+}
+
+impl Grid {
+    pub fn new(
+        x: usize,
+        y: usize,
+        columns: usize,
+        cell_width: usize,
+        cell_height: usize,
+        spacing: usize,
+    ) -> Self {
+        Grid {
+            x,
+            y,
+            columns,
+            cell_width,
+            cell_height,
+            spacing,
+        }
+    }
+
+    pub fn cell(&self, row: usize, column: usize) -> Rect {
+        Rect::new(
+            self.x + column * (self.cell_width + self.spacing),
+            self.y + row * (self.cell_height + self.spacing),
+            self.cell_width,
+            self.cell_height,
+        )
+    }
+
+    /// Cell for the n-th widget, counting row by row
+    pub fn nth(&self, index: usize) -> Rect {
+        self.cell(index / self.columns, index % self.columns)
+    }
+}
@@ -0,0 +1,264 @@
+use alloc::{string::String, vec::Vec};
+use pc_keyboard::KeyCode;
+
+use super::{WidgetEvent, char_width};
+use crate::{
+    desktop::{
+        mouse::{MouseButton, MouseEvent, MouseEventKind},
+        theme,
+    },
+    framebuffer::Color,
+    surface::{Rect, Shape, Surface},
+};
+
+const ROW_HEIGHT: usize = 20;
+
+/// Scrolling list of text rows with one selected row. A fixed set of row
+/// shapes is created once and their text is swapped as the list scrolls.
+pub struct ListView {
+    bounds: Rect,
+    items: Vec<String>,
+    selected: Option<usize>,
+    scroll_offset: usize,
+    focused: bool,
+    border_idx: Option<usize>,
+    rows: Vec<(usize, usize)>, // (background, text) shape ids per visible row
+}
+
+impl ListView {
+    pub fn new(bounds: Rect) -> Self {
+        ListView {
+            bounds,
+            items: Vec::new(),
+            selected: None,
+            scroll_offset: 0,
+            focused: false,
+            border_idx: None,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    fn visible_rows(&self) -> usize {
+        self.bounds.height.saturating_sub(2) / ROW_HEIGHT
+    }
+
+    fn max_chars(&self) -> usize {
+        self.bounds.width.saturating_sub(10)
+            / char_width(theme::current().font_size, theme::current().font_weight)
+    }
+
+    fn row_text(&self, index: usize) -> String {
+        self.items
+            .get(index)
+            .map(|item| item.chars().take(self.max_chars()).collect())
+            .unwrap_or_default()
+    }
+
+    fn border_color(&self) -> Color {
+        if self.focused {
+            theme::current().focus
+        } else {
+            theme::current().control_border
+        }
+    }
+
+    pub(super) fn attach(&mut self, surface: &mut Surface) {
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = self.bounds;
+        let theme = theme::current();
+
+        surface.add_shape(Shape::Rectangle {
+            x,
+            y,
+            width,
+            height,
+            color: theme.input_background,
+            filled: true,
+            hide: false,
+        });
+        self.border_idx = Some(surface.add_shape(Shape::Rectangle {
+            x,
+            y,
+            width,
+            height,
+            color: self.border_color(),
+            filled: false,
+            hide: false,
+        }));
+
+        self.rows.clear();
+        for row in 0..self.visible_rows() {
+            let row_y = y + 1 + row * ROW_HEIGHT;
+            let bg = surface.add_shape(Shape::Rectangle {
+                x: x + 1,
+                y: row_y,
+                width: width.saturating_sub(2),
+                height: ROW_HEIGHT,
+                color: theme.input_background,
+                filled: true,
+                hide: false,
+            });
+            let text = surface.add_shape(Shape::Text {
+                x: x + 5,
+                y: row_y + 2,
+                content: String::new(),
+                color: theme.text,
+                background_color: theme.input_background,
+                font_size: theme.font_size,
+                font_weight: theme.font_weight,
+                hide: false,
+            });
+            self.rows.push((bg, text));
+        }
+
+        self.refresh_rows(surface);
+    }
+
+    fn refresh_rows(&mut self, surface: &mut Surface) {
+        let theme = theme::current();
+        for (row, &(bg, text)) in self.rows.iter().enumerate() {
+            let index = self.scroll_offset + row;
+            let (fg_color, bg_color) = if self.selected == Some(index) {
+                (theme.selection_text, theme.selection)
+            } else {
+                (theme.text, theme.input_background)
+            };
+
+            // Dirty the whole row so a shorter text doesn't leave the old one behind
+            let row_bounds = surface.get_shape_bounds(bg);
+            surface.update_rectangle_color(bg, bg_color);
+            surface.update_text_background(text, bg_color);
+            surface.update_text_color(text, fg_color);
+            surface.update_text_content(text, self.row_text(index), row_bounds);
+        }
+    }
+
+    pub fn set_items(&mut self, items: Vec<String>, surface: &mut Surface) {
+        self.items = items;
+        self.selected = self.selected.filter(|&i| i < self.items.len());
+        self.scroll_offset = self
+            .scroll_offset
+            .min(self.items.len().saturating_sub(self.visible_rows()));
+        self.refresh_rows(surface);
+    }
+
+    pub fn select(&mut self, index: Option<usize>, surface: &mut Surface) {
+        self.selected = index.filter(|&i| i < self.items.len());
+
+        // Keep the selection on screen
+        if let Some(index) = self.selected {
+            let visible = self.visible_rows().max(1);
+            if index < self.scroll_offset {
+                self.scroll_offset = index;
+            } else if index >= self.scroll_offset + visible {
+                self.scroll_offset = index + 1 - visible;
+            }
+        }
+        self.refresh_rows(surface);
+    }
+
+    pub fn scroll_by(&mut self, rows: isize, surface: &mut Surface) {
+        let max_offset = self.items.len().saturating_sub(self.visible_rows());
+        let offset = (self.scroll_offset as isize + rows).clamp(0, max_offset as isize) as usize;
+        if offset != self.scroll_offset {
+            self.scroll_offset = offset;
+            self.refresh_rows(surface);
+        }
+    }
+
+    fn index_at(&self, y: usize) -> Option<usize> {
+        let row = y.checked_sub(self.bounds.y + 1)? / ROW_HEIGHT;
+        let index = self.scroll_offset + row;
+        (row < self.visible_rows() && index < self.items.len()).then_some(index)
+    }
+
+    pub(super) fn set_focused(&mut self, focused: bool, surface: &mut Surface) {
+        self.focused = focused;
+        if let Some(idx) = self.border_idx {
+            surface.update_rectangle_color(idx, self.border_color());
+        }
+    }
+
+    pub(super) fn handle_click(
+        &mut self,
+        _x: usize,
+        y: usize,
+        surface: &mut Surface,
+    ) -> Option<WidgetEvent> {
+        let index = self.index_at(y)?;
+        self.select(Some(index), surface);
+        Some(WidgetEvent::Selected(index))
+    }
+
+    pub(super) fn handle_mouse_event(
+        &mut self,
+        event: &MouseEvent,
+        surface: &mut Surface,
+    ) -> Option<WidgetEvent> {
+        match event.kind {
+            MouseEventKind::Wheel(delta) => {
+                self.scroll_by(delta as isize * 3, surface);
+                None
+            }
+            MouseEventKind::DoubleClick(MouseButton::Left) => {
+                let index = self.index_at(event.y.max(0) as usize)?;
+                Some(WidgetEvent::Activated(index))
+            }
+            _ => None,
+        }
+    }
+
+    pub(super) fn handle_char(&mut self, c: char) -> Option<WidgetEvent> {
+        match c {
+            '\n' | '\r' => self.selected.map(WidgetEvent::Activated),
+            _ => None,
+        }
+    }
+
+    pub(super) fn handle_key(
+        &mut self,
+        key: KeyCode,
+        surface: &mut Surface,
+    ) -> Option<WidgetEvent> {
+        if self.items.is_empty() {
+            return None;
+        }
+
+        let last = self.items.len() - 1;
+        let page = self.visible_rows().max(1);
+        let index = match (key, self.selected) {
+            (KeyCode::ArrowUp, Some(i)) => i.saturating_sub(1),
+            (KeyCode::ArrowDown, Some(i)) => (i + 1).min(last),
+            (KeyCode::PageUp, Some(i)) => i.saturating_sub(page),
+            (KeyCode::PageDown, Some(i)) => (i + page).min(last),
+            (KeyCode::Home, _) => 0,
+            (KeyCode::End, _) => last,
+            (KeyCode::ArrowUp | KeyCode::ArrowDown | KeyCode::PageUp | KeyCode::PageDown, None) => {
+                0
+            }
+            _ => return None,
+        };
+
+        if self.selected == Some(index) {
+            return None;
+        }
+        self.select(Some(index), surface);
+        Some(WidgetEvent::Selected(index))
+    }
+}
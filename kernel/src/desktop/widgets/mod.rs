@@ -0,0 +1,335 @@
+//! Retained-mode widgets on top of surface shapes.
+//!
+//! Every widget owns the shapes it adds to the surface, keeps them up to date
+//! when its state changes and does its own hit testing. Apps keep a [`Ui`],
+//! add widgets with a callback and forward input to it:
+//!
+//! ```ignore
+//! if let Some((callback, id, event)) = self.ui.handle_click(x, y, surface) {
+//!     callback(self, id, event, surface);
+//! }
+//! ```
+
+use alloc::vec::Vec;
+use noto_sans_mono_bitmap::{FontWeight, RasterHeight, get_raster_width};
+use pc_keyboard::KeyCode;
+
+use crate::{
+    desktop::mouse::{MouseEvent, MouseEventKind},
+    surface::Surface,
+};
+
+mod button;
+mod checkbox;
+mod label;
+pub mod layout;
+mod list_view;
+mod scroll_bar;
+mod text_input;
+
+pub use button::Button;
+pub use checkbox::Checkbox;
+pub use label::Label;
+pub use list_view::ListView;
+pub use scroll_bar::ScrollBar;
+pub use text_input::TextInput;
+
+/// Width of one character of the given font
+pub fn char_width(font_size: RasterHeight, font_weight: FontWeight) -> usize {
+    get_raster_width(font_weight, font_size)
+}
+
+/// Something a widget reports back to its owner
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WidgetEvent {
+    /// Button pressed, by mouse or Enter/Space while focused
+    Clicked,
+    /// Checkbox flipped, carries the new state
+    Toggled(bool),
+    /// Text input content changed
+    Changed,
+    /// Enter pressed in a text input
+    Submitted,
+    /// List row selected
+    Selected(usize),
+    /// List row double-clicked or Enter pressed on it
+    Activated(usize),
+    /// Scroll bar moved, carries the new position
+    Scrolled(usize),
+}
+
+/// Called with the app, the id of the widget and what happened.
+/// A plain fn pointer so it can be copied out of the `Ui` before the app is borrowed.
+pub type Callback<A> = fn(&mut A, usize, WidgetEvent, &mut Surface);
+
+/// A callback to run, with the widget id and event to pass to it
+pub type Dispatch<A> = (Callback<A>, usize, WidgetEvent);
+
+pub enum Widget {
+    Button(Button),
+    Label(Label),
+    TextInput(TextInput),
+    ListView(ListView),
+    ScrollBar(ScrollBar),
+    Checkbox(Checkbox),
+}
+
+macro_rules! dispatch {
+    ($self:expr, $widget:ident => $body:expr) => {
+        match $self {
+            Widget::Button($widget) => $body,
+            Widget::Label($widget) => $body,
+            Widget::TextInput($widget) => $body,
+            Widget::ListView($widget) => $body,
+            Widget::ScrollBar($widget) => $body,
+            Widget::Checkbox($widget) => $body,
+        }
+    };
+}
+
+impl Widget {
+    fn attach(&mut self, surface: &mut Surface) {
+        dispatch!(self, widget => widget.attach(surface))
+    }
+
+    fn contains_point(&self, x: usize, y: usize) -> bool {
+        dispatch!(self, widget => widget.bounds().contains_point(x, y))
+    }
+
+    fn focusable(&self) -> bool {
+        !matches!(self, Widget::Label(_) | Widget::ScrollBar(_))
+    }
+
+    fn set_focused(&mut self, focused: bool, surface: &mut Surface) {
+        match self {
+            Widget::Button(button) => button.set_focused(focused, surface),
+            Widget::TextInput(input) => input.set_focused(focused, surface),
+            Widget::ListView(list) => list.set_focused(focused, surface),
+            Widget::Checkbox(checkbox) => checkbox.set_focused(focused, surface),
+            Widget::Label(_) | Widget::ScrollBar(_) => {}
+        }
+    }
+
+    fn handle_click(&mut self, x: usize, y: usize, surface: &mut Surface) -> Option<WidgetEvent> {
+        match self {
+            Widget::Button(button) => button.handle_click(),
+            Widget::Checkbox(checkbox) => checkbox.handle_click(surface),
+            Widget::ListView(list) => list.handle_click(x, y, surface),
+            Widget::ScrollBar(bar) => bar.handle_click(x, y, surface),
+            Widget::TextInput(_) | Widget::Label(_) => None,
+        }
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        event: &MouseEvent,
+        surface: &mut Surface,
+    ) -> Option<WidgetEvent> {
+        match self {
+            Widget::ListView(list) => list.handle_mouse_event(event, surface),
+            Widget::ScrollBar(bar) => bar.handle_mouse_event(event, surface),
+            _ => None,
+        }
+    }
+
+    fn handle_char(&mut self, c: char, surface: &mut Surface) -> Option<WidgetEvent> {
+        match self {
+            Widget::Button(button) => button.handle_char(c),
+            Widget::Checkbox(checkbox) => checkbox.handle_char(c, surface),
+            Widget::TextInput(input) => input.handle_char(c, surface),
+            Widget::ListView(list) => list.handle_char(c),
+            Widget::Label(_) | Widget::ScrollBar(_) => None,
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyCode, surface: &mut Surface) -> Option<WidgetEvent> {
+        match self {
+            Widget::ListView(list) => list.handle_key(key, surface),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! widget_from {
+    ($($kind:ident),*) => {
+        $(impl From<$kind> for Widget {
+            fn from(widget: $kind) -> Self {
+                Widget::$kind(widget)
+            }
+        })*
+    };
+}
+
+widget_from!(Button, Label, TextInput, ListView, ScrollBar, Checkbox);
+
+macro_rules! widget_getter {
+    ($($name:ident: $kind:ident),*) => {
+        $(pub fn $name(&mut self, id: usize) -> Option<&mut $kind> {
+            match self.widgets.get_mut(id) {
+                Some((Widget::$kind(widget), _)) => Some(widget),
+                _ => None,
+            }
+        })*
+    };
+}
+
+/// The widgets of one window, with keyboard focus and callbacks
+pub struct Ui<A> {
+    widgets: Vec<(Widget, Option<Callback<A>>)>,
+    focused: Option<usize>,
+    pressed: Option<usize>,
+}
+
+impl<A> Default for Ui<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A> Ui<A> {
+    pub fn new() -> Self {
+        Ui {
+            widgets: Vec::new(),
+            focused: None,
+            pressed: None,
+        }
+    }
+
+    /// Add a widget and return its id. Shapes are created by `attach`.
+    pub fn add(&mut self, widget: impl Into<Widget>, callback: Option<Callback<A>>) -> usize {
+        self.widgets.push((widget.into(), callback));
+        self.widgets.len() - 1
+    }
+
+    /// Create the shapes of every widget. Call on an empty surface, after
+    /// `clear_all_shapes`, since shape ids are positions in the shape list.
+    pub fn attach(&mut self, surface: &mut Surface) {
+        for (widget, _) in &mut self.widgets {
+            widget.attach(surface);
+        }
+
+        if let Some(id) = self.focused {
+            self.widgets[id].0.set_focused(true, surface);
+        }
+    }
+
+    /// Drop every widget, for apps that rebuild their layout
+    pub fn clear(&mut self) {
+        self.widgets.clear();
+        self.focused = None;
+        self.pressed = None;
+    }
+
+    widget_getter!(
+        button: Button,
+        label: Label,
+        text_input: TextInput,
+        list_view: ListView,
+        scroll_bar: ScrollBar,
+        checkbox: Checkbox
+    );
+
+    pub fn focused(&self) -> Option<usize> {
+        self.focused
+    }
+
+    pub fn focus(&mut self, id: usize, surface: &mut Surface) {
+        if self.focused == Some(id) || !self.widgets.get(id).is_some_and(|(w, _)| w.focusable()) {
+            return;
+        }
+
+        if let Some(old) = self.focused {
+            self.widgets[old].0.set_focused(false, surface);
+        }
+        self.widgets[id].0.set_focused(true, surface);
+        self.focused = Some(id);
+    }
+
+    /// Move focus to the next (or previous) focusable widget, wrapping around
+    pub fn focus_next(&mut self, forward: bool, surface: &mut Surface) {
+        let count = self.widgets.len();
+        if count == 0 {
+            return;
+        }
+
+        let start = self.focused.unwrap_or(if forward { count - 1 } else { 0 });
+        for step in 1..=count {
+            let id = if forward {
+                (start + step) % count
+            } else {
+                (start + count - step) % count
+            };
+            if self.widgets[id].0.focusable() {
+                self.focus(id, surface);
+                return;
+            }
+        }
+    }
+
+    fn with_callback(&self, id: usize, event: Option<WidgetEvent>) -> Option<Dispatch<A>> {
+        let callback = self.widgets[id].1?;
+        event.map(|event| (callback, id, event))
+    }
+
+    /// Click at a surface position; focuses the widget under it
+    pub fn handle_click(
+        &mut self,
+        x: usize,
+        y: usize,
+        surface: &mut Surface,
+    ) -> Option<Dispatch<A>> {
+        let id = self
+            .widgets
+            .iter()
+            .rposition(|(w, _)| w.contains_point(x, y))?;
+        self.focus(id, surface);
+
+        let event = self.widgets[id].0.handle_click(x, y, surface);
+        self.with_callback(id, event)
+    }
+
+    /// Wheel, double-click and drag input for the widget under the pointer
+    pub fn handle_mouse_event(
+        &mut self,
+        event: &MouseEvent,
+        surface: &mut Surface,
+    ) -> Option<Dispatch<A>> {
+        let id = match self.pressed {
+            Some(id) => id,
+            None => {
+                let (x, y) = event.position_in(surface.width, surface.height)?;
+                self.widgets
+                    .iter()
+                    .rposition(|(w, _)| w.contains_point(x, y))?
+            }
+        };
+
+        // The widget a button went down on gets the drag until the release
+        match event.kind {
+            MouseEventKind::Down(_) => self.pressed = Some(id),
+            MouseEventKind::Up(_) => self.pressed = None,
+            _ => {}
+        }
+
+        let event = self.widgets[id].0.handle_mouse_event(event, surface);
+        self.with_callback(id, event)
+    }
+
+    /// Typed character; Tab moves the focus, anything else goes to the focused widget
+    pub fn handle_char(&mut self, c: char, surface: &mut Surface) -> Option<Dispatch<A>> {
+        if c == '\t' {
+            self.focus_next(true, surface);
+            return None;
+        }
+
+        let id = self.focused?;
+        let event = self.widgets[id].0.handle_char(c, surface);
+        self.with_callback(id, event)
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode, surface: &mut Surface) -> Option<Dispatch<A>> {
+        let id = self.focused?;
+        let event = self.widgets[id].0.handle_key(key, surface);
+        self.with_callback(id, event)
+    }
+}
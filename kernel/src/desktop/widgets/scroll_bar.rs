@@ -0,0 +1,181 @@
+use super::WidgetEvent;
+use crate::{
+    desktop::{
+        mouse::{MouseButton, MouseEvent, MouseEventKind},
+        theme,
+    },
+    surface::{Rect, Shape, Surface},
+};
+
+const MIN_THUMB_HEIGHT: usize = 12;
+
+/// Vertical scroll bar over `total` units of which `visible` fit on screen.
+/// Clicking the track pages, dragging moves the thumb, the wheel steps.
+pub struct ScrollBar {
+    bounds: Rect,
+    total: usize,
+    visible: usize,
+    position: usize,
+    dragging: Option<i32>, // pointer offset inside the thumb while dragging
+    thumb_idx: Option<usize>,
+}
+
+impl ScrollBar {
+    pub fn new(bounds: Rect) -> Self {
+        ScrollBar {
+            bounds,
+            total: 0,
+            visible: 0,
+            position: 0,
+            dragging: None,
+            thumb_idx: None,
+        }
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    fn max_position(&self) -> usize {
+        self.total.saturating_sub(self.visible)
+    }
+
+    /// Thumb (y, height) in surface coordinates
+    fn thumb(&self) -> (usize, usize) {
+        let track = self.bounds.height;
+        if self.total <= self.visible || self.total == 0 {
+            return (self.bounds.y, track);
+        }
+
+        let height = (track * self.visible / self.total).clamp(MIN_THUMB_HEIGHT.min(track), track);
+        let offset = (track - height) * self.position / self.max_position();
+        (self.bounds.y + offset, height)
+    }
+
+    pub(super) fn attach(&mut self, surface: &mut Surface) {
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = self.bounds;
+
+        surface.add_shape(Shape::Rectangle {
+            x,
+            y,
+            width,
+            height,
+            color: theme::current().scroll_track,
+            filled: true,
+            hide: false,
+        });
+
+        let (thumb_y, thumb_height) = self.thumb();
+        self.thumb_idx = Some(surface.add_shape(Shape::Rectangle {
+            x: x + 1,
+            y: thumb_y,
+            width: width.saturating_sub(2),
+            height: thumb_height,
+            color: theme::current().scroll_thumb,
+            filled: true,
+            hide: false,
+        }));
+    }
+
+    fn refresh(&mut self, surface: &mut Surface) {
+        let Some(idx) = self.thumb_idx else {
+            return;
+        };
+
+        let (thumb_y, thumb_height) = self.thumb();
+        // The old thumb spot goes back to the track color
+        surface.force_dirty_region(
+            self.bounds.x,
+            self.bounds.y,
+            self.bounds.width,
+            self.bounds.height,
+        );
+        surface.move_shape(idx, self.bounds.x + 1, thumb_y);
+        surface.update_rectangle_size(idx, self.bounds.width.saturating_sub(2), thumb_height);
+    }
+
+    pub fn set_range(&mut self, total: usize, visible: usize, surface: &mut Surface) {
+        self.total = total;
+        self.visible = visible;
+        self.position = self.position.min(self.max_position());
+        self.refresh(surface);
+    }
+
+    pub fn set_position(&mut self, position: usize, surface: &mut Surface) {
+        let position = position.min(self.max_position());
+        if position != self.position {
+            self.position = position;
+            self.refresh(surface);
+        }
+    }
+
+    fn scroll_to(&mut self, position: usize, surface: &mut Surface) -> Option<WidgetEvent> {
+        let old = self.position;
+        self.set_position(position, surface);
+        (self.position != old).then_some(WidgetEvent::Scrolled(self.position))
+    }
+
+    pub(super) fn handle_click(
+        &mut self,
+        _x: usize,
+        y: usize,
+        surface: &mut Surface,
+    ) -> Option<WidgetEvent> {
+        let (thumb_y, thumb_height) = self.thumb();
+        let page = self.visible.max(1);
+
+        if y < thumb_y {
+            self.scroll_to(self.position.saturating_sub(page), surface)
+        } else if y >= thumb_y + thumb_height {
+            self.scroll_to(self.position + page, surface)
+        } else {
+            None
+        }
+    }
+
+    pub(super) fn handle_mouse_event(
+        &mut self,
+        event: &MouseEvent,
+        surface: &mut Surface,
+    ) -> Option<WidgetEvent> {
+        let (thumb_y, thumb_height) = self.thumb();
+
+        match event.kind {
+            MouseEventKind::Wheel(delta) => {
+                let position = (self.position as isize + delta as isize).max(0) as usize;
+                self.scroll_to(position, surface)
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                let y = event.y.max(0) as usize;
+                if y >= thumb_y && y < thumb_y + thumb_height {
+                    self.dragging = Some(event.y - thumb_y as i32);
+                }
+                None
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                self.dragging = None;
+                None
+            }
+            MouseEventKind::Move => {
+                let grab = self.dragging?;
+                let free_track = self.bounds.height.saturating_sub(thumb_height);
+                if free_track == 0 {
+                    return None;
+                }
+
+                let offset = (event.y - grab - self.bounds.y as i32).clamp(0, free_track as i32);
+                self.scroll_to(offset as usize * self.max_position() / free_track, surface)
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use alloc::string::{String, ToString};
+
+use super::{WidgetEvent, char_width};
+use crate::{
+    desktop::theme,
+    framebuffer::Color,
+    surface::{Rect, Shape, Surface},
+};
+
+/// Single line text field. Typing always appends; the view scrolls so the
+/// end of the text stays visible.
+pub struct TextInput {
+    bounds: Rect,
+    text: String,
+    max_length: usize,
+    focused: bool,
+    border_idx: Option<usize>,
+    text_idx: Option<usize>,
+}
+
+impl TextInput {
+    pub fn new(bounds: Rect) -> Self {
+        TextInput {
+            bounds,
+            text: String::new(),
+            max_length: 255,
+            focused: false,
+            border_idx: None,
+            text_idx: None,
+        }
+    }
+
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// What fits in the box, with a cursor while focused
+    fn visible_text(&self) -> String {
+        let max_chars = self.bounds.width.saturating_sub(10)
+            / char_width(theme::current().font_size, theme::current().font_weight);
+        let mut shown = self.text.clone();
+        if self.focused {
+            shown.push('_');
+        }
+
+        let count = shown.chars().count();
+        shown
+            .chars()
+            .skip(count.saturating_sub(max_chars))
+            .collect()
+    }
+
+    fn border_color(&self) -> Color {
+        if self.focused {
+            theme::current().focus
+        } else {
+            theme::current().control_border
+        }
+    }
+
+    pub(super) fn attach(&mut self, surface: &mut Surface) {
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = self.bounds;
+        let theme = theme::current();
+
+        surface.add_shape(Shape::Rectangle {
+            x,
+            y,
+            width,
+            height,
+            color: theme.input_background,
+            filled: true,
+            hide: false,
+        });
+        self.border_idx = Some(surface.add_shape(Shape::Rectangle {
+            x,
+            y,
+            width,
+            height,
+            color: self.border_color(),
+            filled: false,
+            hide: false,
+        }));
+        self.text_idx = Some(surface.add_shape(Shape::Text {
+            x: x + 5,
+            y: y + height.saturating_sub(16) / 2,
+            content: self.visible_text(),
+            color: theme.text,
+            background_color: theme.input_background,
+            font_size: theme.font_size,
+            font_weight: theme.font_weight,
+            hide: false,
+        }));
+    }
+
+    fn refresh(&mut self, surface: &mut Surface) {
+        if let Some(idx) = self.text_idx {
+            surface.update_text_content(idx, self.visible_text(), None);
+        }
+    }
+
+    pub fn set_text(&mut self, text: &str, surface: &mut Surface) {
+        self.text = text.to_string();
+        self.refresh(surface);
+    }
+
+    pub(super) fn set_focused(&mut self, focused: bool, surface: &mut Surface) {
+        self.focused = focused;
+        if let Some(idx) = self.border_idx {
+            surface.update_rectangle_color(idx, self.border_color());
+        }
+        self.refresh(surface);
+    }
+
+    pub(super) fn handle_char(&mut self, c: char, surface: &mut Surface) -> Option<WidgetEvent> {
+        match c {
+            '\n' | '\r' => return Some(WidgetEvent::Submitted),
+            '\x08' => {
+                self.text.pop()?;
+            }
+            c if !c.is_control() && self.text.chars().count() < self.max_length => {
+                self.text.push(c);
+            }
+            _ => return None,
+        }
+
+        self.refresh(surface);
+        Some(WidgetEvent::Changed)
+    }
+}
@@ -2,6 +2,7 @@ use crate::desktop::application::Application;
 use crate::desktop::terminal::Terminal;
 use alloc::vec;
 use alloc::{
+    boxed::Box,
     string::{String, ToString},
     vec::Vec,
 };
@@ -12,26 +13,102 @@ use tinybmp::Bmp;
 
 use crate::{
     desktop::{
-        calculator::Calculator, filemanager::FileManager, notepad::Notepad, sysinfo::SysInfo,
+        calculator::Calculator,
+        compositor,
+        context_menu::{ContextMenu, MenuItem, MenuOwner},
+        dialog::{CloseRequest, ModalDialog},
+        filemanager::FileManager,
+        logviewer::LogViewer,
+        main::TASKBAR_HEIGHT,
+        mouse::{MouseButton, MouseButtons, MouseEvent, MouseEventKind},
+        notepad::Notepad,
+        settings::Settings,
+        shortcuts::{Accelerator, Modifiers},
+        sysinfo::SysInfo,
+        theme,
         tictactoe::TicTacToe,
     },
-    framebuffer::{Color, FrameBufferWriter},
+    framebuffer::{Color, FrameBufferWriter, OffscreenBuffer, SCREEN_SIZE},
     surface::{Rect, Surface},
 };
 
+const TITLEBAR_HEIGHT: usize = 20;
+const TITLEBAR_BUTTON_WIDTH: usize = 20;
+/// How far outside the window frame an edge can be grabbed for resizing
+const RESIZE_BORDER: usize = 4;
+const MIN_WINDOW_WIDTH: usize = 200;
+const MIN_WINDOW_HEIGHT: usize = 120;
+/// Opacity of windows made translucent from the taskbar menu
+const TRANSLUCENT_OPACITY: u8 = 200;
+
 pub struct DragCache {
     background_buffer: Vec<u8>,
     cached_bounds: Rect,
     is_valid: bool,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TitleButton {
+    Minimize,
+    Maximize,
+    Close,
+}
+
+/// Window edges being dragged during a resize
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ResizeEdges {
+    pub left: bool,
+    pub right: bool,
+    pub top: bool,
+    pub bottom: bool,
+}
+
+struct ResizeState {
+    edges: ResizeEdges,
+    origin: (i16, i16),
+    start: Rect,
+}
+
 pub enum AppType {
     Calculator(Calculator),
-    FileManager(FileManager),
+    // Boxed: the file manager is several times bigger than the other apps
+    FileManager(Box<FileManager>),
     Notepad(Notepad),
     Terminal(Terminal),
     SysInfo(SysInfo),
+    LogViewer(LogViewer),
     TicTacToe(TicTacToe),
+    Settings(Settings),
+}
+
+impl AppType {
+    /// Name used for icons and `launch_app`
+    pub fn app_name(&self) -> &'static str {
+        match self {
+            AppType::Calculator(_) => "calculator",
+            AppType::FileManager(_) => "filemanager",
+            AppType::Notepad(_) => "notepad",
+            AppType::Terminal(_) => "terminal",
+            AppType::SysInfo(_) => "sysinfo",
+            AppType::LogViewer(_) => "logviewer",
+            AppType::TicTacToe(_) => "tictactoe",
+            AppType::Settings(_) => "settings",
+        }
+    }
+
+    /// Surface color behind the app. Apps built from widgets follow the theme,
+    /// the others keep their own look.
+    pub fn background_color(&self) -> Color {
+        match self {
+            AppType::Calculator(_)
+            | AppType::TicTacToe(_)
+            | AppType::Settings(_)
+            | AppType::FileManager(_)
+            | AppType::SysInfo(_) => theme::current().window_background,
+            AppType::Notepad(_) => theme::current().input_background,
+            AppType::Terminal(_) | AppType::LogViewer(_) => theme::current().terminal_background,
+        }
+    }
 }
 
 impl Application for AppType {
@@ -42,7 +119,9 @@ impl Application for AppType {
             AppType::Notepad(notepad) => notepad.init(surface),
             AppType::Terminal(terminal) => terminal.init(surface),
             AppType::SysInfo(sysinfo) => sysinfo.init(surface),
+            AppType::LogViewer(viewer) => viewer.init(surface),
             AppType::TicTacToe(tictactoe) => tictactoe.init(surface),
+            AppType::Settings(settings) => settings.init(surface),
         }
     }
 
@@ -53,7 +132,9 @@ impl Application for AppType {
             AppType::Notepad(notepad) => notepad.render(surface),
             AppType::Terminal(terminal) => terminal.render(surface),
             AppType::SysInfo(sysinfo) => sysinfo.render(surface),
+            AppType::LogViewer(viewer) => viewer.render(surface),
             AppType::TicTacToe(tictactoe) => tictactoe.render(surface),
+            AppType::Settings(settings) => settings.render(surface),
         }
     }
 
@@ -68,7 +149,9 @@ impl Application for AppType {
             AppType::Notepad(notepad) => notepad.handle_char_input(c, ctrl_pressed, surface),
             AppType::Terminal(terminal) => terminal.handle_char_input(c, ctrl_pressed, surface),
             AppType::SysInfo(sysinfo) => sysinfo.handle_char_input(c, ctrl_pressed, surface),
+            AppType::LogViewer(viewer) => viewer.handle_char_input(c, ctrl_pressed, surface),
             AppType::TicTacToe(tictactoe) => tictactoe.handle_char_input(c, ctrl_pressed, surface),
+            AppType::Settings(settings) => settings.handle_char_input(c, ctrl_pressed, surface),
         }
     }
 
@@ -79,7 +162,9 @@ impl Application for AppType {
             AppType::Notepad(notepad) => notepad.handle_key_input(key, surface),
             AppType::Terminal(terminal) => terminal.handle_key_input(key, surface),
             AppType::SysInfo(sysinfo) => sysinfo.handle_key_input(key, surface),
+            AppType::LogViewer(viewer) => viewer.handle_key_input(key, surface),
             AppType::TicTacToe(tictactoe) => tictactoe.handle_key_input(key, surface),
+            AppType::Settings(settings) => settings.handle_key_input(key, surface),
         }
     }
 
@@ -90,7 +175,158 @@ impl Application for AppType {
             AppType::Notepad(notepad) => notepad.handle_mouse_click(x, y, surface),
             AppType::Terminal(terminal) => terminal.handle_mouse_click(x, y, surface),
             AppType::SysInfo(sysinfo) => sysinfo.handle_mouse_click(x, y, surface),
+            AppType::LogViewer(viewer) => viewer.handle_mouse_click(x, y, surface),
             AppType::TicTacToe(tictactoe) => tictactoe.handle_mouse_click(x, y, surface),
+            AppType::Settings(settings) => settings.handle_mouse_click(x, y, surface),
+        }
+    }
+
+    fn handle_resize(&mut self, surface: &mut Surface) {
+        match self {
+            AppType::Calculator(calculator) => calculator.handle_resize(surface),
+            AppType::FileManager(filemanager) => filemanager.handle_resize(surface),
+            AppType::Notepad(notepad) => notepad.handle_resize(surface),
+            AppType::Terminal(terminal) => terminal.handle_resize(surface),
+            AppType::SysInfo(sysinfo) => sysinfo.handle_resize(surface),
+            AppType::LogViewer(viewer) => viewer.handle_resize(surface),
+            AppType::TicTacToe(tictactoe) => tictactoe.handle_resize(surface),
+            AppType::Settings(settings) => settings.handle_resize(surface),
+        }
+    }
+
+    fn handle_theme_change(&mut self, surface: &mut Surface) {
+        match self {
+            AppType::Calculator(calculator) => calculator.handle_theme_change(surface),
+            AppType::FileManager(filemanager) => filemanager.handle_theme_change(surface),
+            AppType::Notepad(notepad) => notepad.handle_theme_change(surface),
+            AppType::Terminal(terminal) => terminal.handle_theme_change(surface),
+            AppType::SysInfo(sysinfo) => sysinfo.handle_theme_change(surface),
+            AppType::LogViewer(viewer) => viewer.handle_theme_change(surface),
+            AppType::TicTacToe(tictactoe) => tictactoe.handle_theme_change(surface),
+            AppType::Settings(settings) => settings.handle_theme_change(surface),
+        }
+    }
+
+    fn handle_mouse_event(&mut self, event: &MouseEvent, surface: &mut Surface) {
+        match self {
+            AppType::Calculator(calculator) => calculator.handle_mouse_event(event, surface),
+            AppType::FileManager(filemanager) => filemanager.handle_mouse_event(event, surface),
+            AppType::Notepad(notepad) => notepad.handle_mouse_event(event, surface),
+            AppType::Terminal(terminal) => terminal.handle_mouse_event(event, surface),
+            AppType::SysInfo(sysinfo) => sysinfo.handle_mouse_event(event, surface),
+            AppType::LogViewer(viewer) => viewer.handle_mouse_event(event, surface),
+            AppType::TicTacToe(tictactoe) => tictactoe.handle_mouse_event(event, surface),
+            AppType::Settings(settings) => settings.handle_mouse_event(event, surface),
+        }
+    }
+
+    fn context_menu(&mut self, x: usize, y: usize, surface: &mut Surface) -> Option<ContextMenu> {
+        match self {
+            AppType::Calculator(calculator) => calculator.context_menu(x, y, surface),
+            AppType::FileManager(filemanager) => filemanager.context_menu(x, y, surface),
+            AppType::Notepad(notepad) => notepad.context_menu(x, y, surface),
+            AppType::Terminal(terminal) => terminal.context_menu(x, y, surface),
+            AppType::SysInfo(sysinfo) => sysinfo.context_menu(x, y, surface),
+            AppType::LogViewer(viewer) => viewer.context_menu(x, y, surface),
+            AppType::TicTacToe(tictactoe) => tictactoe.context_menu(x, y, surface),
+            AppType::Settings(settings) => settings.context_menu(x, y, surface),
+        }
+    }
+
+    fn handle_context_menu_action(&mut self, action: &'static str, surface: &mut Surface) {
+        match self {
+            AppType::Calculator(calculator) => {
+                calculator.handle_context_menu_action(action, surface)
+            }
+            AppType::FileManager(filemanager) => {
+                filemanager.handle_context_menu_action(action, surface)
+            }
+            AppType::Notepad(notepad) => notepad.handle_context_menu_action(action, surface),
+            AppType::Terminal(terminal) => terminal.handle_context_menu_action(action, surface),
+            AppType::SysInfo(sysinfo) => sysinfo.handle_context_menu_action(action, surface),
+            AppType::LogViewer(viewer) => viewer.handle_context_menu_action(action, surface),
+            AppType::TicTacToe(tictactoe) => tictactoe.handle_context_menu_action(action, surface),
+            AppType::Settings(settings) => settings.handle_context_menu_action(action, surface),
+        }
+    }
+
+    fn on_close_requested(&mut self, surface: &mut Surface) -> CloseRequest {
+        match self {
+            AppType::Calculator(calculator) => calculator.on_close_requested(surface),
+            AppType::FileManager(filemanager) => filemanager.on_close_requested(surface),
+            AppType::Notepad(notepad) => notepad.on_close_requested(surface),
+            AppType::Terminal(terminal) => terminal.on_close_requested(surface),
+            AppType::SysInfo(sysinfo) => sysinfo.on_close_requested(surface),
+            AppType::LogViewer(viewer) => viewer.on_close_requested(surface),
+            AppType::TicTacToe(tictactoe) => tictactoe.on_close_requested(surface),
+            AppType::Settings(settings) => settings.on_close_requested(surface),
+        }
+    }
+
+    fn on_close_dialog_result(&mut self, button: usize, surface: &mut Surface) -> bool {
+        match self {
+            AppType::Calculator(calculator) => calculator.on_close_dialog_result(button, surface),
+            AppType::FileManager(filemanager) => {
+                filemanager.on_close_dialog_result(button, surface)
+            }
+            AppType::Notepad(notepad) => notepad.on_close_dialog_result(button, surface),
+            AppType::Terminal(terminal) => terminal.on_close_dialog_result(button, surface),
+            AppType::SysInfo(sysinfo) => sysinfo.on_close_dialog_result(button, surface),
+            AppType::LogViewer(viewer) => viewer.on_close_dialog_result(button, surface),
+            AppType::TicTacToe(tictactoe) => tictactoe.on_close_dialog_result(button, surface),
+            AppType::Settings(settings) => settings.on_close_dialog_result(button, surface),
+        }
+    }
+
+    fn take_dialog(&mut self) -> Option<ModalDialog> {
+        match self {
+            AppType::Calculator(calculator) => calculator.take_dialog(),
+            AppType::FileManager(filemanager) => filemanager.take_dialog(),
+            AppType::Notepad(notepad) => notepad.take_dialog(),
+            AppType::Terminal(terminal) => terminal.take_dialog(),
+            AppType::SysInfo(sysinfo) => sysinfo.take_dialog(),
+            AppType::LogViewer(viewer) => viewer.take_dialog(),
+            AppType::TicTacToe(tictactoe) => tictactoe.take_dialog(),
+            AppType::Settings(settings) => settings.take_dialog(),
+        }
+    }
+
+    fn on_dialog_result(&mut self, button: usize, surface: &mut Surface) {
+        match self {
+            AppType::Calculator(calculator) => calculator.on_dialog_result(button, surface),
+            AppType::FileManager(filemanager) => filemanager.on_dialog_result(button, surface),
+            AppType::Notepad(notepad) => notepad.on_dialog_result(button, surface),
+            AppType::Terminal(terminal) => terminal.on_dialog_result(button, surface),
+            AppType::SysInfo(sysinfo) => sysinfo.on_dialog_result(button, surface),
+            AppType::LogViewer(viewer) => viewer.on_dialog_result(button, surface),
+            AppType::TicTacToe(tictactoe) => tictactoe.on_dialog_result(button, surface),
+            AppType::Settings(settings) => settings.on_dialog_result(button, surface),
+        }
+    }
+
+    fn accelerators(&self) -> &'static [Accelerator] {
+        match self {
+            AppType::Calculator(calculator) => calculator.accelerators(),
+            AppType::FileManager(filemanager) => filemanager.accelerators(),
+            AppType::Notepad(notepad) => notepad.accelerators(),
+            AppType::Terminal(terminal) => terminal.accelerators(),
+            AppType::SysInfo(sysinfo) => sysinfo.accelerators(),
+            AppType::LogViewer(viewer) => viewer.accelerators(),
+            AppType::TicTacToe(tictactoe) => tictactoe.accelerators(),
+            AppType::Settings(settings) => settings.accelerators(),
+        }
+    }
+
+    fn handle_accelerator(&mut self, action: &'static str, surface: &mut Surface) {
+        match self {
+            AppType::Calculator(calculator) => calculator.handle_accelerator(action, surface),
+            AppType::FileManager(filemanager) => filemanager.handle_accelerator(action, surface),
+            AppType::Notepad(notepad) => notepad.handle_accelerator(action, surface),
+            AppType::Terminal(terminal) => terminal.handle_accelerator(action, surface),
+            AppType::SysInfo(sysinfo) => sysinfo.handle_accelerator(action, surface),
+            AppType::LogViewer(viewer) => viewer.handle_accelerator(action, surface),
+            AppType::TicTacToe(tictactoe) => tictactoe.handle_accelerator(action, surface),
+            AppType::Settings(settings) => settings.handle_accelerator(action, surface),
         }
     }
 
@@ -101,7 +337,9 @@ impl Application for AppType {
             AppType::Notepad(notepad) => notepad.get_title(),
             AppType::Terminal(terminal) => terminal.get_title(),
             AppType::SysInfo(sysinfo) => sysinfo.get_title(),
+            AppType::LogViewer(viewer) => viewer.get_title(),
             AppType::TicTacToe(tictactoe) => tictactoe.get_title(),
+            AppType::Settings(settings) => settings.get_title(),
         }
     }
 }
@@ -119,7 +357,25 @@ pub struct Window {
     pub is_dragging: bool,
     pub drag_preview_x: usize,
     pub drag_preview_y: usize,
+    drag_preview_width: usize,
+    drag_preview_height: usize,
     drag_cache: Option<DragCache>,
+    resize: Option<ResizeState>,
+    pub resizable: bool,
+    pub minimized: bool,
+    /// Content bounds to go back to when a maximized window is restored
+    restore_bounds: Option<Rect>,
+    /// Modal dialog shown on top of the window; it takes all input while open
+    pub dialog: Option<ModalDialog>,
+    /// The open dialog came from `on_close_requested`, its answer may close the window
+    dialog_closes: bool,
+    /// 255 is fully opaque
+    pub opacity: u8,
+    /// Off-screen copy of the whole frame. None until the first render, or
+    /// when the pool ran out and the window draws on the screen directly.
+    buffer: Option<OffscreenBuffer>,
+    /// Titlebar has to be drawn into the buffer again
+    decorations_dirty: bool,
     pub application: AppType,
 }
 
@@ -133,24 +389,16 @@ impl Window {
         title: String,
         application: AppType,
     ) -> Self {
-        let background_color = match &application {
-            AppType::Calculator(_) => Color::GRAY,
-            AppType::FileManager(_) => Color::new(240, 240, 240),
-            AppType::Notepad(_) => Color::WHITE,
-            AppType::Terminal(_) => Color::BLACK,
-            AppType::SysInfo(_) => Color::DARKGRAY,
-            AppType::TicTacToe(_) => Color::new(250, 250, 250),
-        };
+        let background_color = application.background_color();
+
+        // Fixed layouts that don't adapt to a different window size
+        let resizable = !matches!(
+            &application,
+            AppType::Calculator(_) | AppType::TicTacToe(_) | AppType::Settings(_)
+        );
 
         let surface = Surface::new(width, height, background_color);
-        let icon = generate_icon_for_app_str::<16, 16>(match &application {
-            AppType::Calculator(_) => "calculator",
-            AppType::FileManager(_) => "filemanager",
-            AppType::Notepad(_) => "notepad",
-            AppType::Terminal(_) => "terminal",
-            AppType::SysInfo(_) => "sysinfo",
-            AppType::TicTacToe(_) => "tictactoe",
-        });
+        let icon = generate_icon_for_app_str::<16, 16>(application.app_name());
 
         Self {
             x,
@@ -166,7 +414,127 @@ impl Window {
             is_dragging: false,
             drag_preview_x: x,
             drag_preview_y: y,
+            drag_preview_width: width,
+            drag_preview_height: height,
             drag_cache: None,
+            resize: None,
+            resizable,
+            minimized: false,
+            restore_bounds: None,
+            dialog: None,
+            dialog_closes: false,
+            opacity: 255,
+            buffer: None,
+            decorations_dirty: true,
+        }
+    }
+
+    pub fn is_maximized(&self) -> bool {
+        self.restore_bounds.is_some()
+    }
+
+    /// Give the window a new content size. The surface is reallocated with the
+    /// existing shapes and the application gets a chance to lay them out again.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        if width == self.width && height == self.height {
+            return;
+        }
+
+        let mut surface = Surface::new(width, height, self.surface.background_color);
+        surface.just_fill_bg = self.surface.just_fill_bg;
+        surface.shapes = core::mem::take(&mut self.surface.shapes);
+        self.surface = surface;
+
+        self.width = width;
+        self.height = height;
+
+        self.application.handle_resize(&mut self.surface);
+        self.surface.force_full_redraw();
+    }
+
+    /// Move and resize in one go, returning the composite bounds before the change
+    fn set_bounds(&mut self, bounds: Rect) -> Rect {
+        let old_bounds = self.get_composite_bounds();
+        self.x = bounds.x;
+        self.y = bounds.y;
+        self.resize(bounds.width, bounds.height);
+        self.surface.is_dirty = true;
+        old_bounds
+    }
+
+    /// Maximize to `work_area`, or restore the previous bounds if already maximized.
+    /// Returns the composite bounds before the change so they can be redrawn.
+    pub fn toggle_maximize(&mut self, work_area: Rect) -> Rect {
+        self.decorations_dirty = true;
+        match self.restore_bounds.take() {
+            Some(bounds) => self.set_bounds(bounds),
+            None => {
+                self.restore_bounds = Some(self.get_content_bounds());
+                self.set_bounds(work_area)
+            }
+        }
+    }
+
+    fn title_button_x(&self, button: TitleButton) -> usize {
+        let slot = match button {
+            TitleButton::Close => 1,
+            TitleButton::Maximize => 2,
+            TitleButton::Minimize => 3,
+        };
+        self.x + self.width - slot * TITLEBAR_BUTTON_WIDTH
+    }
+
+    /// Titlebar button under the given screen position, if any
+    pub fn title_button_at(&self, x: usize, y: usize) -> Option<TitleButton> {
+        if y < self.y.saturating_sub(TITLEBAR_HEIGHT) || y > self.y {
+            return None;
+        }
+
+        [TitleButton::Close, TitleButton::Maximize, TitleButton::Minimize]
+            .into_iter()
+            .find(|&button| {
+                let button_x = self.title_button_x(button);
+                x >= button_x && x <= button_x + TITLEBAR_BUTTON_WIDTH
+            })
+    }
+
+    fn can_resize(&self) -> bool {
+        self.resizable && !self.minimized && !self.is_maximized()
+    }
+
+    /// Area that takes presses: the frame, plus the resize border sticking out
+    /// of it when the window can be resized
+    fn grab_area(&self) -> Rect {
+        let frame = self.get_full_bounds();
+        if !self.can_resize() {
+            return frame;
+        }
+
+        Rect::new(
+            frame.x.saturating_sub(RESIZE_BORDER),
+            frame.y.saturating_sub(RESIZE_BORDER),
+            frame.width + 2 * RESIZE_BORDER,
+            frame.height + 2 * RESIZE_BORDER,
+        )
+    }
+
+    /// Edges that would be dragged by pressing at the given screen position
+    pub fn resize_edges_at(&self, x: usize, y: usize) -> Option<ResizeEdges> {
+        if !self.can_resize() || !self.grab_area().contains_point(x, y) {
+            return None;
+        }
+
+        let edges = ResizeEdges {
+            left: x < self.x,
+            right: x >= self.x + self.width,
+            top: y < self.y.saturating_sub(TITLEBAR_HEIGHT),
+            bottom: y >= self.y + self.height,
+        };
+
+        if edges.left || edges.right || edges.top || edges.bottom {
+            Some(edges)
+        } else {
+            None
         }
     }
 
@@ -180,6 +548,20 @@ impl Window {
         )
     }
 
+    /// Full bounds plus the drop shadow: everything compositing the window touches
+    pub fn get_composite_bounds(&self) -> Rect {
+        compositor::shadow_bounds(self.get_full_bounds(), self.shadow_size())
+    }
+
+    /// Maximized windows have nothing around them to cast a shadow on
+    fn shadow_size(&self) -> usize {
+        if self.is_maximized() {
+            0
+        } else {
+            theme::current().shadow_size
+        }
+    }
+
     /// Get the window content bounds (just the surface area)
     pub fn get_content_bounds(&self) -> Rect {
         Rect::new(self.x, self.y, self.width, self.height)
@@ -193,56 +575,217 @@ impl Window {
             .any(|rect| rect.intersects(&window_bounds))
     }
 
-    pub fn render(&mut self, framebuffer: &mut FrameBufferWriter, force: bool) -> bool {
+    /// Let the application update its title and shapes before drawing
+    fn update(&mut self) {
         if let Some(title) = self.application.get_title() {
-            self.title = title;
+            if title != self.title {
+                self.title = title;
+                self.decorations_dirty = true;
+            }
         }
 
         self.application.render(&mut self.surface);
+    }
+
+    pub fn render(&mut self, framebuffer: &mut FrameBufferWriter, force: bool) -> bool {
+        self.update();
 
         return self.surface.render(framebuffer, self.x, self.y, force);
     }
 
-    pub fn render_decorations(&self, framebuffer: &mut FrameBufferWriter) {
-        // Window outline
-        framebuffer.draw_rect_outline(
-            (self.x - 1, self.y - 1),
-            (self.x + self.width, self.y + self.height),
-            Color::BLACK,
+    /// Bring the off-screen buffer up to date. Returns the screen areas that
+    /// changed, or None without a buffer, when the window draws on the screen.
+    pub fn render_offscreen(&mut self) -> Option<Vec<Rect>> {
+        let frame = self.get_full_bounds();
+        let fresh = !self
+            .buffer
+            .as_ref()
+            .is_some_and(|buffer| buffer.width() == frame.width && buffer.height() == frame.height);
+        if fresh {
+            // Give the old buffer back before asking for one of the new size
+            self.buffer = None;
+            self.buffer = OffscreenBuffer::new(frame.width, frame.height);
+            self.buffer.as_ref()?;
+            self.surface.force_full_redraw();
+        }
+
+        self.update();
+
+        let content = Rect::new(1, TITLEBAR_HEIGHT, self.width, self.height);
+        let mut damage: Vec<Rect> = if fresh {
+            vec![Rect::new(0, 0, frame.width, frame.height)]
+        } else if self.surface.is_dirty {
+            self.surface
+                .get_dirty_regions()
+                .iter()
+                .filter_map(|region| {
+                    let region = Rect::new(
+                        region.x + content.x,
+                        region.y + content.y,
+                        region.width,
+                        region.height,
+                    );
+                    region.intersection(&content)
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        // Out of `self` while drawing, `render_decorations` needs to borrow it
+        let mut buffer = self.buffer.take()?;
+        let rendered = self
+            .surface
+            .render(&mut buffer.writer, content.x, content.y, false);
+
+        // Content can cover the outline, so the frame goes on top every time
+        if rendered || self.decorations_dirty {
+            self.render_decorations(&mut buffer.writer, content.x, content.y);
+        }
+        if self.decorations_dirty {
+            damage.push(Rect::new(0, 0, frame.width, TITLEBAR_HEIGHT + 1));
+            self.decorations_dirty = false;
+        }
+
+        let dialog = self
+            .dialog
+            .as_mut()
+            .filter(|dialog| rendered || dialog.needs_redraw);
+        if let Some(dialog) = dialog {
+            dialog.render(&mut buffer.writer, content);
+            damage.push(dialog.bounds(content));
+        }
+        self.buffer = Some(buffer);
+
+        Some(
+            damage
+                .into_iter()
+                .map(|rect| Rect::new(rect.x + frame.x, rect.y + frame.y, rect.width, rect.height))
+                .collect(),
+        )
+    }
+
+    /// Draw the part of the window inside `clip` from its buffer, shadow first
+    fn composite(&self, framebuffer: &mut FrameBufferWriter, clip: Rect) {
+        let Some(buffer) = &self.buffer else {
+            return;
+        };
+        let theme = theme::current();
+        let frame = self.get_full_bounds();
+
+        compositor::draw_shadow(framebuffer, frame, self.shadow_size(), clip);
+
+        let titlebar_alpha = (self.opacity as usize * theme.titlebar_alpha as usize / 255) as u8;
+        compositor::composite(
+            framebuffer,
+            buffer,
+            (frame.x, frame.y),
+            clip,
+            self.opacity,
+            TITLEBAR_HEIGHT,
+            titlebar_alpha,
         );
+    }
+
+    /// Area that hides everything below it once composited, if any
+    fn opaque_bounds(&self) -> Option<Rect> {
+        if self.buffer.is_none() || self.minimized || self.opacity < 255 {
+            return None;
+        }
+
+        if theme::current().titlebar_alpha == 255 {
+            Some(self.get_full_bounds())
+        } else {
+            Some(self.get_content_bounds())
+        }
+    }
+
+    /// Draw the frame around content whose top left corner is at `(x, y)`:
+    /// the window position on screen, or where the content sits in the buffer.
+    pub fn render_decorations(&self, framebuffer: &mut FrameBufferWriter, x: usize, y: usize) {
+        let theme = theme::current();
+
+        // Window outline; a thicker one grows over the edge of the content
+        for inset in 0..theme.border_width.max(1) {
+            framebuffer.draw_rect_outline(
+                (x - 1 + inset, y - 1 + inset),
+                (x + self.width - inset, y + self.height - inset),
+                theme.border,
+            );
+        }
 
         // Titlebar
-        framebuffer.draw_rect(
-            (self.x - 1, self.y - 20),
-            (self.x + self.width, self.y),
-            Color::BLACK,
-        );
-        framebuffer.draw_raw_image(self.x + 2, self.y - 17, 16, &self.icon);
+        framebuffer.draw_rect((x - 1, y - 20), (x + self.width, y), theme.titlebar);
+        framebuffer.draw_raw_image(x + 2, y - 17, 16, &self.icon);
         framebuffer.draw_raw_text(
             &self.title,
-            self.x + 26,
-            self.y - 15,
-            Color::WHITE,
-            Color::BLACK,
-            noto_sans_mono_bitmap::FontWeight::Regular,
-            noto_sans_mono_bitmap::RasterHeight::Size16,
+            x + 26,
+            y - 15,
+            theme.title_text,
+            theme.titlebar,
+            theme.title_font_weight,
+            theme.font_size,
+        );
+
+        // Minimize button
+        let minimize_x = x + self.title_button_x(TitleButton::Minimize) - self.x;
+        framebuffer.draw_rect(
+            (minimize_x, y - 20),
+            (minimize_x + 20, y),
+            theme.title_button,
+        );
+        framebuffer.draw_line(
+            (minimize_x + 5, y - 6),
+            (minimize_x + 15, y - 6),
+            theme.title_button_glyph,
         );
 
+        // Maximize/restore button, greyed out for fixed-size windows
+        let maximize_x = x + self.title_button_x(TitleButton::Maximize) - self.x;
+        let maximize_fg = if self.resizable {
+            theme.title_button_glyph
+        } else {
+            theme.title_button_disabled
+        };
+        framebuffer.draw_rect(
+            (maximize_x, y - 20),
+            (maximize_x + 20, y),
+            theme.title_button,
+        );
+        if self.is_maximized() {
+            framebuffer.draw_rect_outline(
+                (maximize_x + 7, y - 16),
+                (maximize_x + 15, y - 8),
+                maximize_fg,
+            );
+            framebuffer.draw_rect_outline(
+                (maximize_x + 5, y - 13),
+                (maximize_x + 12, y - 5),
+                maximize_fg,
+            );
+        } else {
+            framebuffer.draw_rect_outline(
+                (maximize_x + 5, y - 15),
+                (maximize_x + 15, y - 5),
+                maximize_fg,
+            );
+        }
+
         // Close button
         framebuffer.draw_rect(
-            (self.x + self.width - 20, self.y - 20),
-            (self.x + self.width, self.y),
-            Color::RED,
+            (x + self.width - 20, y - 20),
+            (x + self.width, y),
+            theme.close_button,
         );
         framebuffer.draw_line(
-            (self.x + self.width - 15, self.y - 15),
-            (self.x + self.width - 5, self.y - 5),
-            Color::WHITE,
+            (x + self.width - 15, y - 15),
+            (x + self.width - 5, y - 5),
+            theme.title_button_glyph,
         );
         framebuffer.draw_line(
-            (self.x + self.width - 15, self.y - 5),
-            (self.x + self.width - 5, self.y - 15),
-            Color::WHITE,
+            (x + self.width - 15, y - 5),
+            (x + self.width - 5, y - 15),
+            theme.title_button_glyph,
         );
     }
 
@@ -251,8 +794,8 @@ impl Window {
         Rect::new(
             self.drag_preview_x.saturating_sub(2),
             self.drag_preview_y.saturating_sub(21),
-            self.width + 4,
-            self.height + 23,
+            self.drag_preview_width + 4,
+            self.drag_preview_height + 23,
         )
     }
 
@@ -340,7 +883,7 @@ impl Window {
         framebuffer.draw_rect_outline(
             (bounds.x, bounds.y),
             (bounds.x + bounds.width, bounds.y + bounds.height),
-            Color::BLACK,
+            theme::current().border,
         );
     }
 
@@ -349,6 +892,8 @@ impl Window {
         self.is_dragging = true;
         self.drag_preview_x = self.x;
         self.drag_preview_y = self.y;
+        self.drag_preview_width = self.width;
+        self.drag_preview_height = self.height;
         self.cache_background_under_outline(framebuffer);
     }
 
@@ -359,6 +904,17 @@ impl Window {
         new_x: usize,
         new_y: usize,
     ) {
+        let bounds = Rect::new(
+            new_x,
+            new_y,
+            self.drag_preview_width,
+            self.drag_preview_height,
+        );
+        self.update_preview_bounds(framebuffer, bounds);
+    }
+
+    /// Update the drag preview outline to new content bounds (position and size)
+    fn update_preview_bounds(&mut self, framebuffer: &mut FrameBufferWriter, bounds: Rect) {
         if !self.is_dragging {
             return;
         }
@@ -366,9 +922,11 @@ impl Window {
         // Restore background at old preview position
         self.restore_cached_background(framebuffer);
 
-        // Update preview position
-        self.drag_preview_x = new_x;
-        self.drag_preview_y = new_y;
+        // Update preview position and size
+        self.drag_preview_x = bounds.x;
+        self.drag_preview_y = bounds.y;
+        self.drag_preview_width = bounds.width;
+        self.drag_preview_height = bounds.height;
 
         // Cache background at new position and draw outline
         self.cache_background_under_outline(framebuffer);
@@ -378,21 +936,22 @@ impl Window {
     /// End dragging - commit to new position
     pub fn end_drag(&mut self, framebuffer: &mut FrameBufferWriter) -> (Rect, Rect) {
         if !self.is_dragging {
-            return (self.get_full_bounds(), self.get_full_bounds());
+            return (self.get_composite_bounds(), self.get_composite_bounds());
         }
 
         // Get old bounds for dirty region
-        let old_bounds = self.get_full_bounds();
+        let old_bounds = self.get_composite_bounds();
 
         // Restore background at preview position
         self.restore_cached_background(framebuffer);
 
-        // Update actual position to preview position
+        // Update actual position and size to the preview ones
         self.x = self.drag_preview_x;
         self.y = self.drag_preview_y;
+        self.resize(self.drag_preview_width, self.drag_preview_height);
 
         // Get new bounds for dirty region
-        let new_bounds = self.get_full_bounds();
+        let new_bounds = self.get_composite_bounds();
 
         // Exit drag mode
         self.is_dragging = false;
@@ -406,6 +965,15 @@ pub struct WindowManager {
     pub windows: Vec<Window>,
     window_order: Vec<usize>, // Window IDs from back to front (last is topmost)
     next_window_id: usize,
+    /// Window that got a button press; it keeps the mouse until every button is up
+    mouse_capture: Option<usize>,
+    context_menu: Option<(MenuOwner, ContextMenu)>,
+    /// A press closed or picked from the menu, swallow the rest of it
+    menu_grab: bool,
+    /// Item picked from a desktop menu, waiting for the desktop loop
+    desktop_action: Option<&'static str>,
+    /// Screen areas to composite again because windows changed places
+    damage: Vec<Rect>,
 }
 
 impl WindowManager {
@@ -414,6 +982,11 @@ impl WindowManager {
             windows: Vec::new(),
             window_order: Vec::new(),
             next_window_id: 1,
+            mouse_capture: None,
+            context_menu: None,
+            menu_grab: false,
+            desktop_action: None,
+            damage: Vec::new(),
         }
     }
 
@@ -430,6 +1003,16 @@ impl WindowManager {
     }
 
     pub fn focus_window(&mut self, window_id: usize) {
+        // Raising a window or bringing it back shows parts that were covered
+        let exposed = self
+            .windows
+            .iter()
+            .find(|w| w.id == window_id)
+            .filter(|w| w.minimized || self.window_order.last() != Some(&window_id));
+        if let Some(window) = exposed {
+            self.damage.push(window.get_composite_bounds());
+        }
+
         if self.windows.iter().any(|w| w.id == window_id) {
             // Remove window from current position in order
             self.window_order.retain(|&id| id != window_id);
@@ -439,12 +1022,76 @@ impl WindowManager {
 
         // Mark window as dirty to ensure it gets re-rendered
         if let Some(window) = self.windows.iter_mut().find(|w| w.id == window_id) {
+            window.minimized = false;
             window.surface.is_dirty = true;
         }
     }
 
+    /// Topmost window that isn't minimized
     pub fn get_focused_window_id(&self) -> Option<usize> {
-        self.window_order.last().copied()
+        self.window_order
+            .iter()
+            .rev()
+            .copied()
+            .find(|&id| self.windows.iter().any(|w| w.id == id && !w.minimized))
+    }
+
+    /// Screen area available to windows: everything above the taskbar
+    pub fn work_area() -> Rect {
+        let screen_size = *SCREEN_SIZE.get().unwrap();
+        let screen_width = screen_size.0 as usize;
+        let screen_height = screen_size.1 as usize;
+
+        // Leave room for the border and titlebar, and the line on top of the taskbar
+        Rect::new(
+            1,
+            TITLEBAR_HEIGHT,
+            screen_width - 2,
+            screen_height - TASKBAR_HEIGHT - TITLEBAR_HEIGHT - 2,
+        )
+    }
+
+    /// Hide a window to the taskbar. Returns the region it used to cover.
+    pub fn minimize_window(&mut self, window_id: usize) -> Option<Rect> {
+        let window = self.windows.iter_mut().find(|w| w.id == window_id)?;
+        if window.minimized {
+            return None;
+        }
+
+        window.minimized = true;
+        // Hand the memory to the windows still on screen, it's redrawn on restore
+        window.buffer = None;
+        Some(window.get_composite_bounds())
+    }
+
+    /// Toggle between maximized and normal size. Returns the region covered before.
+    pub fn toggle_maximize_window(&mut self, window_id: usize) -> Option<Rect> {
+        let window = self.windows.iter_mut().find(|w| w.id == window_id)?;
+        if !window.resizable {
+            return None;
+        }
+
+        let old_bounds = window.toggle_maximize(Self::work_area());
+        self.damage.push(window.get_composite_bounds());
+        self.focus_window(window_id);
+        Some(old_bounds)
+    }
+
+    /// Topmost visible window whose frame contains the given screen position
+    pub fn window_at(&self, x: usize, y: usize) -> Option<usize> {
+        self.window_order.iter().rev().copied().find(|&id| {
+            self.windows
+                .iter()
+                .any(|w| w.id == id && !w.minimized && w.get_full_bounds().contains_point(x, y))
+        })
+    }
+
+    /// App names of the open windows, oldest first
+    pub fn open_apps(&self) -> Vec<&'static str> {
+        self.windows
+            .iter()
+            .map(|window| window.application.app_name())
+            .collect()
     }
 
     pub fn get_windows_in_render_order(&self) -> Vec<&Window> {
@@ -472,6 +1119,61 @@ impl WindowManager {
             .collect()
     }
 
+    /// Rebuild and redraw every window after `theme::set_theme`
+    pub fn apply_theme(&mut self) {
+        for window in &mut self.windows {
+            window.surface.background_color = window.application.background_color();
+            window.application.handle_theme_change(&mut window.surface);
+            window.surface.force_full_redraw();
+            window.decorations_dirty = true;
+            if let Some(dialog) = &mut window.dialog {
+                dialog.needs_redraw = true;
+            }
+        }
+
+        if let Some((_, menu)) = &mut self.context_menu {
+            menu.needs_redraw = true;
+        }
+    }
+
+    /// Draw what changed in the window buffers since the last call, and
+    /// return the screen areas that need compositing again.
+    /// The desktop has to redraw them before [`WindowManager::render`].
+    pub fn take_damage(&mut self) -> Vec<Rect> {
+        let mut damage = core::mem::take(&mut self.damage);
+
+        for window in &mut self.windows {
+            // A dragged window keeps its old pixels until it is dropped
+            if window.minimized || window.is_dragging {
+                continue;
+            }
+
+            if window.dialog.is_none() {
+                window.dialog = window.application.take_dialog();
+            }
+
+            match window.render_offscreen() {
+                Some(rects) => damage.extend(rects),
+                // No buffer: it paints its whole frame, so everything on top has to follow
+                None => {
+                    let dialog_dirty = window.dialog.as_ref().is_some_and(|d| d.needs_redraw);
+                    if window.surface.is_dirty || dialog_dirty {
+                        damage.push(window.get_full_bounds());
+                    }
+                }
+            }
+        }
+
+        let screen_size = *SCREEN_SIZE.get().unwrap();
+        let screen = Rect::new(0, 0, screen_size.0 as usize, screen_size.1 as usize);
+        damage
+            .iter()
+            .filter_map(|rect| rect.intersection(&screen))
+            .collect()
+    }
+
+    /// Composite the windows over the desktop inside `desktop_dirty_regions`,
+    /// bottom to top. Windows hidden behind an opaque one are skipped.
     pub fn render(
         &mut self,
         framebuffer: &mut FrameBufferWriter,
@@ -486,26 +1188,74 @@ impl WindowManager {
             .map(|w| w.id)
             .collect();
 
-        for window_id in window_ids {
-            let window = self.windows.iter_mut().find(|w| w.id == window_id).unwrap();
+        // Lowest window that shows in each region
+        let mut regions: Vec<(Rect, usize)> = desktop_dirty_regions
+            .iter()
+            .map(|&region| {
+                let first = window_ids
+                    .iter()
+                    .rposition(|&id| {
+                        self.windows
+                            .iter()
+                            .find(|w| w.id == id)
+                            .and_then(|w| w.opaque_bounds())
+                            .is_some_and(|bounds| bounds.contains_rect(&region))
+                    })
+                    .unwrap_or(0);
+                (region, first)
+            })
+            .collect();
 
-            // Skip rendering if window is being dragged (only show drag preview)
-            if window.is_dragging {
+        for (index, &window_id) in window_ids.iter().enumerate() {
+            let window = self.windows.iter_mut().find(|w| w.id == window_id).unwrap();
+            if window.minimized {
                 continue;
             }
 
-            // Only render window if it intersects with dirty regions or window itself is dirty
-            let intersects_dirty = window.intersects_dirty_regions(desktop_dirty_regions);
-            let should_render = window.surface.is_dirty || intersects_dirty;
+            if window.buffer.is_some() {
+                let bounds = window.get_composite_bounds();
+                for &(region, first) in &regions {
+                    if index < first {
+                        continue;
+                    }
+                    if let Some(clip) = region.intersection(&bounds) {
+                        window.composite(framebuffer, clip);
+                        did_render = true;
+                    }
+                }
+                continue;
+            }
 
-            if window.render(framebuffer, should_render) {
-                did_render = true;
+            // Without a buffer the window draws itself on the screen, all of it
+            if window.is_dragging {
+                continue;
+            }
+            let dirty: Vec<Rect> = regions.iter().map(|&(region, _)| region).collect();
+            if !window.surface.is_dirty && !window.intersects_dirty_regions(&dirty) {
+                continue;
             }
 
-            if did_render {
-                // Always render decorations when we render the window
-                window.render_decorations(framebuffer);
+            window.render(framebuffer, true);
+            window.render_decorations(framebuffer, window.x, window.y);
+            let content_bounds = window.get_content_bounds();
+            if let Some(dialog) = &mut window.dialog {
+                dialog.render(framebuffer, content_bounds);
             }
+
+            // Whatever is above it has to be composited over the whole frame now
+            regions.push((window.get_full_bounds(), index + 1));
+            did_render = true;
+        }
+
+        // Context menus go above every window
+        let covered = did_render || !desktop_dirty_regions.is_empty();
+        let menu = self
+            .context_menu
+            .as_mut()
+            .filter(|(_, menu)| covered || menu.needs_redraw);
+        if let Some((_, menu)) = menu {
+            menu.render(framebuffer);
+            did_render = true;
         }
 
         did_render
@@ -518,108 +1268,446 @@ impl WindowManager {
         x: i16,
         y: i16,
     ) -> (bool, Option<(usize, usize, usize, usize)>) {
-        let mut window_to_focus: Option<usize> = None;
-        let mut window_to_remove: Option<usize> = None;
-        let mut remove_bounds: Option<(usize, usize, usize, usize)> = None;
-
-        // Check if the click was on the close button first
-        for window in &self.windows {
-            if x as usize >= window.x + window.width - 20
-                && x as usize <= window.x + window.width
-                && y as usize >= window.y - 20
-                && y as usize <= window.y
-            {
-                window_to_remove = Some(window.id);
-                remove_bounds = Some((
-                    window.x - 1,
-                    window.y - 20,
-                    window.width + 2,
-                    window.height + 21,
-                ));
-                break;
+        let (x, y) = (x as usize, y as usize);
+
+        // The press that closed a context menu doesn't click through
+        if self.menu_grab {
+            return (true, None);
+        }
+
+        let Some(window_id) = self.window_at(x, y) else {
+            return (false, None);
+        };
+
+        let window = self.windows.iter_mut().find(|w| w.id == window_id).unwrap();
+
+        // Titlebar buttons
+        if let Some(button) = window.title_button_at(x, y) {
+            let dirty = match button {
+                TitleButton::Close => self.request_close(window_id),
+                TitleButton::Minimize => self.minimize_window(window_id),
+                TitleButton::Maximize => self.toggle_maximize_window(window_id),
+            };
+
+            return (
+                true,
+                dirty.map(|rect| (rect.x, rect.y, rect.width, rect.height)),
+            );
+        }
+
+        // A modal dialog swallows every click on its parent
+        if let Some(dialog) = &window.dialog {
+            let answer = dialog.button_at(window.get_content_bounds(), x, y);
+            self.focus_window(window_id);
+
+            let dirty = answer.and_then(|button| self.answer_dialog(window_id, button));
+            return (
+                true,
+                dirty.map(|rect| (rect.x, rect.y, rect.width, rect.height)),
+            );
+        }
+
+        // Check for clicks on window content
+        if x >= window.x
+            && x <= window.x + window.width
+            && y >= window.y
+            && y <= window.y + window.height
+        {
+            let local_x = x.saturating_sub(window.x);
+            let local_y = y.saturating_sub(window.y);
+
+            window
+                .application
+                .handle_mouse_click(local_x, local_y, &mut window.surface);
+        }
+
+        // Any click on the frame or titlebar focuses the window
+        self.focus_window(window_id);
+
+        (true, None)
+    }
+
+    /// Deliver a pointer event to the window holding the mouse, or else to the
+    /// one whose content is under the pointer. Titlebar drags, resizes and
+    /// windows with an open dialog don't get events.
+    /// Returns true if a window took the event.
+    pub fn handle_mouse_event(
+        &mut self,
+        kind: MouseEventKind,
+        x: i16,
+        y: i16,
+        buttons: MouseButtons,
+        modifiers: Modifiers,
+    ) -> (bool, Option<(usize, usize, usize, usize)>) {
+        let (x, y) = (x as usize, y as usize);
+
+        if self.context_menu.is_some() {
+            let dirty = self.handle_context_menu_event(kind, x, y);
+            return (
+                true,
+                dirty.map(|rect| (rect.x, rect.y, rect.width, rect.height)),
+            );
+        }
+
+        if let MouseEventKind::Down(_) = kind {
+            self.menu_grab = false;
+        }
+        if self.menu_grab {
+            return (true, None);
+        }
+
+        let target = match self.mouse_capture {
+            Some(id) if self.windows.iter().any(|w| w.id == id) => Some(id),
+            _ => {
+                self.mouse_capture = None;
+                self.window_at(x, y).filter(|&id| {
+                    self.windows
+                        .iter()
+                        .any(|w| w.id == id && w.get_content_bounds().contains_point(x, y))
+                })
             }
+        };
+
+        let Some(window_id) = target else {
+            return (false, None);
+        };
+        let window = self.windows.iter_mut().find(|w| w.id == window_id).unwrap();
+
+        if window.is_dragging || window.resize.is_some() || window.dialog.is_some() {
+            return (false, None);
         }
 
-        if let Some(window_id) = window_to_remove {
-            self.windows.retain(|w| w.id != window_id);
-            self.window_order.retain(|&id| id != window_id);
+        match kind {
+            MouseEventKind::Down(_) => self.mouse_capture = Some(window_id),
+            MouseEventKind::Up(_) if !buttons.any() => self.mouse_capture = None,
+            _ => {}
+        }
+
+        let event = MouseEvent {
+            kind,
+            x: x as i32 - window.x as i32,
+            y: y as i32 - window.y as i32,
+            buttons,
+            modifiers,
+        };
+        window
+            .application
+            .handle_mouse_event(&event, &mut window.surface);
+
+        // Right button release over the content asks the app for its menu
+        let menu = if kind == MouseEventKind::Up(MouseButton::Right) {
+            event
+                .position_in(window.width, window.height)
+                .and_then(|(local_x, local_y)| {
+                    window
+                        .application
+                        .context_menu(local_x, local_y, &mut window.surface)
+                })
+        } else {
+            None
+        };
 
-            return (true, remove_bounds);
+        if let Some(menu) = menu {
+            self.focus_window(window_id);
+            let dirty = self.open_context_menu(MenuOwner::Window(window_id), menu, x, y);
+            return (
+                true,
+                dirty.map(|rect| (rect.x, rect.y, rect.width, rect.height)),
+            );
         }
 
-        // Check for clicks on window areas
-        for &window_id in self.window_order.iter().rev() {
-            let window = self.windows.iter_mut().find(|w| w.id == window_id).unwrap();
+        (true, None)
+    }
+
+    /// Show a context menu at a screen position, replacing any open one.
+    /// Returns the area of the replaced menu to redraw.
+    pub fn open_context_menu(
+        &mut self,
+        owner: MenuOwner,
+        mut menu: ContextMenu,
+        x: usize,
+        y: usize,
+    ) -> Option<Rect> {
+        let dirty = self.close_context_menu();
+        let screen_size = SCREEN_SIZE.get().unwrap();
+        menu.place(
+            x,
+            y,
+            screen_size.0 as usize,
+            screen_size.1 as usize - TASKBAR_HEIGHT,
+        );
+        self.context_menu = Some((owner, menu));
+        dirty
+    }
+
+    /// Menu for a window's taskbar button
+    pub fn open_taskbar_menu(&mut self, window_id: usize, x: usize, y: usize) -> Option<Rect> {
+        let window = self.windows.iter().find(|w| w.id == window_id)?;
 
-            // Check for clicks on title bar (for focusing)
-            if x as usize >= window.x - 1
-                && x as usize <= window.x + window.width
-                && y as usize >= window.y - 20
-                && y as usize <= window.y
-            {
-                window_to_focus = Some(window.id);
+        let mut items = Vec::new();
+        if window.minimized {
+            items.push(MenuItem::new("Restore", "restore"));
+        } else {
+            items.push(MenuItem::new("Minimize", "minimize"));
+        }
+        if window.resizable {
+            let label = if window.is_maximized() { "Restore size" } else { "Maximize" };
+            items.push(MenuItem::new(label, "maximize"));
+        }
+        let label = if window.opacity < 255 { "Make opaque" } else { "Make translucent" };
+        items.push(MenuItem::new(label, "opacity"));
+        items.push(MenuItem::separator());
+        items.push(MenuItem::new("Close", "close"));
+
+        let menu = ContextMenu::new(items);
+        // Taskbar menus open upwards, above the pointer
+        let height = menu.bounds().height;
+        self.open_context_menu(MenuOwner::Taskbar(window_id), menu, x, y.saturating_sub(height))
+    }
+
+    pub fn has_context_menu(&self) -> bool {
+        self.context_menu.is_some()
+    }
+
+    /// Returns the area the menu covered
+    pub fn close_context_menu(&mut self) -> Option<Rect> {
+        self.context_menu.take().map(|(_, menu)| menu.bounds())
+    }
+
+    /// Action picked from a desktop background menu since the last call
+    pub fn take_desktop_action(&mut self) -> Option<&'static str> {
+        self.desktop_action.take()
+    }
+
+    /// Pointer input while a menu is open. Any press starts a grab; releasing
+    /// over an item picks it, anywhere else closes the menu.
+    fn handle_context_menu_event(&mut self, kind: MouseEventKind, x: usize, y: usize) -> Option<Rect> {
+        let (_, menu) = self.context_menu.as_mut()?;
+
+        match kind {
+            MouseEventKind::Move => {
+                menu.update_hover(x, y);
+                None
+            }
+            MouseEventKind::Down(_) => {
+                self.menu_grab = true;
+                if menu.bounds().contains_point(x, y) {
+                    None
+                } else {
+                    self.close_context_menu()
+                }
             }
+            MouseEventKind::Up(_) if self.menu_grab => {
+                if !menu.bounds().contains_point(x, y) {
+                    return self.close_context_menu();
+                }
 
-            // Check for clicks on window content
-            if x as usize >= window.x
-                && x as usize <= window.x + window.width
-                && y as usize >= window.y
-                && y as usize <= window.y + window.height
-            {
-                window_to_focus = Some(window.id);
+                let action = menu.item_at(x, y).and_then(|i| menu.action(i));
+                let Some(action) = action else {
+                    // Separator or disabled item, keep the menu
+                    return None;
+                };
 
-                let local_x = (x as usize).saturating_sub(window.x);
-                let local_y = (y as usize).saturating_sub(window.y);
+                let (owner, menu) = self.context_menu.take()?;
+                let mut dirty = menu.bounds();
+                if let Some(rect) = self.run_menu_action(owner, action) {
+                    dirty = dirty.union(&rect);
+                }
+                Some(dirty)
+            }
+            _ => None,
+        }
+    }
 
+    fn run_menu_action(&mut self, owner: MenuOwner, action: &'static str) -> Option<Rect> {
+        match owner {
+            MenuOwner::Desktop => {
+                self.desktop_action = Some(action);
+                None
+            }
+            MenuOwner::Window(window_id) => {
+                let window = self.windows.iter_mut().find(|w| w.id == window_id)?;
                 window
                     .application
-                    .handle_mouse_click(local_x, local_y, &mut window.surface);
-
-                break; // Always break after handling content click
+                    .handle_context_menu_action(action, &mut window.surface);
+                None
             }
+            MenuOwner::Taskbar(window_id) => match action {
+                "restore" => {
+                    self.focus_window(window_id);
+                    None
+                }
+                "minimize" => self.minimize_window(window_id),
+                "maximize" => self.toggle_maximize_window(window_id),
+                "close" => self.request_close(window_id),
+                "opacity" => self.toggle_translucency(window_id),
+                _ => None,
+            },
         }
+    }
 
-        if let Some(window_id) = window_to_focus {
-            self.focus_window(window_id);
+    /// Switch a window between opaque and translucent. Returns the area to redraw.
+    pub fn toggle_translucency(&mut self, window_id: usize) -> Option<Rect> {
+        let window = self.windows.iter_mut().find(|w| w.id == window_id)?;
+        window.opacity = if window.opacity < 255 {
+            255
+        } else {
+            TRANSLUCENT_OPACITY
+        };
+        Some(window.get_composite_bounds())
+    }
+
+    fn remove_window(&mut self, window_id: usize) -> Option<Rect> {
+        let bounds = self
+            .windows
+            .iter()
+            .find(|w| w.id == window_id)?
+            .get_composite_bounds();
+
+        self.windows.retain(|w| w.id != window_id);
+        self.window_order.retain(|&id| id != window_id);
+        Some(bounds)
+    }
+
+    /// Ask a window's application whether it may close, and close it if so.
+    /// Returns the region to redraw when the window went away.
+    pub fn request_close(&mut self, window_id: usize) -> Option<Rect> {
+        let window = self.windows.iter_mut().find(|w| w.id == window_id)?;
+
+        // Already asking, the dialog has to be answered first
+        if window.dialog.is_some() {
+            return None;
         }
 
-        (window_to_focus.is_some(), None)
+        match window.application.on_close_requested(&mut window.surface) {
+            CloseRequest::Close => self.remove_window(window_id),
+            CloseRequest::Cancel => None,
+            CloseRequest::Ask(dialog) => {
+                window.dialog = Some(dialog);
+                window.dialog_closes = true;
+                self.focus_window(window_id);
+                None
+            }
+        }
     }
 
-    pub fn handle_taskbar_click(&mut self, window_id: usize) -> bool {
-        if self.windows.iter().any(|w| w.id == window_id) {
-            self.focus_window(window_id);
-            true
+    /// Close the dialog of a window with the given button, passing the answer to the app
+    fn answer_dialog(&mut self, window_id: usize, button: usize) -> Option<Rect> {
+        let window = self.windows.iter_mut().find(|w| w.id == window_id)?;
+        window.dialog.take()?;
+
+        let should_close = if core::mem::take(&mut window.dialog_closes) {
+            window
+                .application
+                .on_close_dialog_result(button, &mut window.surface)
         } else {
+            window
+                .application
+                .on_dialog_result(button, &mut window.surface);
             false
+        };
+
+        if should_close {
+            self.remove_window(window_id)
+        } else {
+            // Repaint the content the dialog was covering
+            window.surface.force_full_redraw();
+            None
         }
     }
 
+    /// Clicking the focused window's taskbar button minimizes it, any other brings it up.
+    /// Returns: (handled, dirty_region)
+    pub fn handle_taskbar_click(
+        &mut self,
+        window_id: usize,
+    ) -> (bool, Option<(usize, usize, usize, usize)>) {
+        if !self.windows.iter().any(|w| w.id == window_id) {
+            return (false, None);
+        }
+
+        if self.get_focused_window_id() == Some(window_id) {
+            let dirty = self.minimize_window(window_id);
+            return (
+                true,
+                dirty.map(|rect| (rect.x, rect.y, rect.width, rect.height)),
+            );
+        }
+
+        self.focus_window(window_id);
+        (true, None)
+    }
+
+    /// Open `file_path` in `app`, or just start the app when the path is empty
     pub fn open_app_handler(&mut self, file_path: String, app: String) {
+        if file_path.is_empty() {
+            launch_app(self, &app);
+            return;
+        }
+
         match app.as_str() {
             "notepad" => launch_notepad_with_file(self, file_path),
+            "filemanager" => launch_filemanager_at(self, file_path),
             "calculator" => launch_calculator(self), // Who tf opens his files in calculator?!
-            _ => {}
+            // The rest don't take files, start them anyway
+            _ => {
+                launch_app(self, &app);
+            }
         }
     }
 
     pub fn handle_mouse_down(&mut self, x: i16, y: i16, framebuffer: &FrameBufferWriter) {
-        for window in &mut self.windows {
-            if x as usize >= window.x
-                && x as usize <= window.x + window.width - 20
-                && y as usize >= window.y - 20
-                && y as usize <= window.y
-            {
-                window.dragging_offset = Some((x, y));
-                window.start_drag(framebuffer);
+        let (mouse_x, mouse_y) = (x as usize, y as usize);
 
-                return;
-            }
+        // Edges stick out of the frame a little, so check them before the content,
+        // but only on the topmost window there: covered edges can't be grabbed
+        let topmost = self.window_order.iter().rev().copied().find(|&id| {
+            self.windows.iter().any(|w| {
+                w.id == id && !w.minimized && w.grab_area().contains_point(mouse_x, mouse_y)
+            })
+        });
+        let grabbed = topmost
+            .and_then(|id| self.windows.iter_mut().find(|w| w.id == id))
+            .and_then(|window| {
+                let edges = window.resize_edges_at(mouse_x, mouse_y)?;
+                Some((window, edges))
+            });
+        if let Some((window, edges)) = grabbed {
+            window.resize = Some(ResizeState {
+                edges,
+                origin: (x, y),
+                start: window.get_content_bounds(),
+            });
+            window.start_drag(framebuffer);
+            return;
+        }
+
+        let Some(window_id) = self.window_at(mouse_x, mouse_y) else {
+            return;
+        };
+        let window = self.windows.iter_mut().find(|w| w.id == window_id).unwrap();
+
+        // Titlebar, minus the buttons. Maximized windows stay put.
+        if !window.is_maximized()
+            && mouse_x >= window.x
+            && mouse_x < window.title_button_x(TitleButton::Minimize)
+            && mouse_y >= window.y.saturating_sub(TITLEBAR_HEIGHT)
+            && mouse_y <= window.y
+        {
+            window.dragging_offset = Some((x, y));
+            window.start_drag(framebuffer);
         }
     }
 
     pub fn handle_mouse_move(&mut self, x: i16, y: i16, framebuffer: &mut FrameBufferWriter) {
         for window in &mut self.windows {
+            if let Some(resize) = &window.resize {
+                let bounds = resized_bounds(resize, x, y);
+                window.update_preview_bounds(framebuffer, bounds);
+
+                return;
+            }
+
             if let Some(offset) = window.dragging_offset {
                 let delta_x = x - offset.0;
                 let delta_y = y - offset.1;
@@ -649,8 +1737,9 @@ impl WindowManager {
         let mut dirty_regions = Vec::new();
 
         for window in &mut self.windows {
-            if window.dragging_offset.is_some() {
+            if window.dragging_offset.is_some() || window.resize.is_some() {
                 window.dragging_offset = None;
+                window.resize = None;
 
                 // End drag and get dirty regions
                 let (old_bounds, new_bounds) = window.end_drag(framebuffer);
@@ -676,32 +1765,87 @@ impl WindowManager {
         dirty_regions
     }
 
+    /// Returns the region to redraw if the input closed a window through its dialog
     pub fn handle_char_input(
         &mut self,
         ch: char,
         ctrl_pressed: bool,
         _alt_pressed: bool,
         _shift_pressed: bool,
-    ) {
+    ) -> Option<(usize, usize, usize, usize)> {
         // Send character input only to the focused window
-        if let Some(focused_id) = self.get_focused_window_id() {
-            if let Some(window) = self.windows.iter_mut().find(|w| w.id == focused_id) {
-                window
-                    .application
-                    .handle_char_input(ch, ctrl_pressed, &mut window.surface);
-            }
+        let focused_id = self.get_focused_window_id()?;
+        let window = self.windows.iter_mut().find(|w| w.id == focused_id)?;
+
+        if let Some(dialog) = &mut window.dialog {
+            let button = dialog.handle_char_input(ch)?;
+            return self
+                .answer_dialog(focused_id, button)
+                .map(|rect| (rect.x, rect.y, rect.width, rect.height));
         }
+
+        window
+            .application
+            .handle_char_input(ch, ctrl_pressed, &mut window.surface);
+        None
     }
 
-    pub fn handle_key_input(&mut self, key: KeyCode) {
+    /// Returns the region to redraw if the input closed a window through its dialog
+    pub fn handle_key_input(&mut self, key: KeyCode) -> Option<(usize, usize, usize, usize)> {
         // Handle key input only for the focused window
-        if let Some(focused_id) = self.get_focused_window_id() {
-            if let Some(window) = self.windows.iter_mut().find(|w| w.id == focused_id) {
-                window
-                    .application
-                    .handle_key_input(key, &mut window.surface);
-            }
+        let focused_id = self.get_focused_window_id()?;
+        let window = self.windows.iter_mut().find(|w| w.id == focused_id)?;
+
+        if let Some(dialog) = &mut window.dialog {
+            let button = dialog.handle_key_input(key)?;
+            return self
+                .answer_dialog(focused_id, button)
+                .map(|rect| (rect.x, rect.y, rect.width, rect.height));
         }
+
+        window
+            .application
+            .handle_key_input(key, &mut window.surface);
+        None
+    }
+
+    /// Offer a key press to the focused app's accelerators. Returns true if one took it.
+    pub fn handle_accelerator(&mut self, key: KeyCode, modifiers: Modifiers) -> bool {
+        let Some(focused_id) = self.get_focused_window_id() else {
+            return false;
+        };
+        let Some(window) = self.windows.iter_mut().find(|w| w.id == focused_id) else {
+            return false;
+        };
+
+        // Dialogs get their keys through the normal input path
+        if window.dialog.is_some() {
+            return false;
+        }
+
+        let Some(accelerator) = window
+            .application
+            .accelerators()
+            .iter()
+            .find(|a| a.shortcut.matches(key, modifiers))
+        else {
+            return false;
+        };
+
+        window
+            .application
+            .handle_accelerator(accelerator.action, &mut window.surface);
+        true
+    }
+
+    /// Windows for the Alt+Tab switcher, most recently focused first
+    pub fn get_switcher_windows(&self) -> Vec<(usize, String, Vec<Color>)> {
+        self.window_order
+            .iter()
+            .rev()
+            .filter_map(|&id| self.windows.iter().find(|w| w.id == id))
+            .map(|w| (w.id, w.title.clone(), w.icon.clone()))
+            .collect()
     }
 
     pub fn get_window_icon(&self, window_id: usize) -> Vec<Color> {
@@ -712,12 +1856,53 @@ impl WindowManager {
     }
 }
 
+/// Content bounds of a window being resized with the mouse at (x, y)
+fn resized_bounds(resize: &ResizeState, x: i16, y: i16) -> Rect {
+    let work_area = WindowManager::work_area();
+    let start = resize.start;
+    let delta_x = (x - resize.origin.0) as isize;
+    let delta_y = (y - resize.origin.1) as isize;
+
+    let mut width = start.width as isize;
+    let mut height = start.height as isize;
+
+    if resize.edges.right {
+        width += delta_x;
+    } else if resize.edges.left {
+        width -= delta_x.max(work_area.x as isize - start.x as isize);
+    }
+
+    if resize.edges.bottom {
+        height += delta_y;
+    } else if resize.edges.top {
+        height -= delta_y.max(work_area.y as isize - start.y as isize);
+    }
+
+    let width = (width.max(0) as usize).clamp(MIN_WINDOW_WIDTH, work_area.width);
+    let height = (height.max(0) as usize).clamp(MIN_WINDOW_HEIGHT, work_area.height);
+
+    // Dragging the left or top edge keeps the opposite edge in place
+    let x = if resize.edges.left {
+        (start.x + start.width).saturating_sub(width).max(work_area.x)
+    } else {
+        start.x
+    };
+    let y = if resize.edges.top {
+        (start.y + start.height).saturating_sub(height).max(work_area.y)
+    } else {
+        start.y
+    };
+
+    Rect::new(x, y, width, height)
+}
+
 const ICON_CALCULATOR: &[u8] = include_bytes!("../../../icons/calculator.bmp");
 const ICON_FILEMANAGER: &[u8] = include_bytes!("../../../icons/filemanager.bmp");
 const ICON_NOTEPAD: &[u8] = include_bytes!("../../../icons/notepad.bmp");
 const ICON_TERMINAL: &[u8] = include_bytes!("../../../icons/terminal.bmp");
 const ICON_SYSINFO: &[u8] = include_bytes!("../../../icons/sysinfo.bmp");
 const ICON_TICTACTOE: &[u8] = include_bytes!("../../../icons/tictactoe.bmp");
+const ICON_SETTINGS: &[u8] = include_bytes!("../../../icons/settings.bmp");
 const START_ICON: &[u8] = include_bytes!("../../../icons/start.bmp");
 
 pub fn launch_calculator(window_manager: &mut WindowManager) {
@@ -740,7 +1925,19 @@ pub fn launch_filemanager(window_manager: &mut WindowManager) {
         400,
         0, // Will be overridden by add_window
         "File Manager".to_string(),
-        AppType::FileManager(FileManager::new(None)),
+        AppType::FileManager(Box::new(FileManager::new(None))),
+    ));
+}
+
+pub fn launch_filemanager_at(window_manager: &mut WindowManager, path: String) {
+    window_manager.add_window(Window::new(
+        120,
+        80,
+        500,
+        400,
+        0, // Will be overridden by add_window
+        "File Manager".to_string(),
+        AppType::FileManager(Box::new(FileManager::new(Some(path)))),
     ));
 }
 
@@ -780,6 +1977,18 @@ pub fn launch_sysinfo(window_manager: &mut WindowManager) {
     ));
 }
 
+pub fn launch_logviewer(window_manager: &mut WindowManager) {
+    window_manager.add_window(Window::new(
+        140,
+        90,
+        640,
+        360,
+        0, // Will be overridden by add_window
+        "Log Viewer".to_string(),
+        AppType::LogViewer(LogViewer::new(None)),
+    ));
+}
+
 pub fn launch_tictactoe(window_manager: &mut WindowManager) {
     window_manager.add_window(Window::new(
         250,
@@ -804,14 +2013,56 @@ pub fn launch_terminal(window_manager: &mut WindowManager) {
     ));
 }
 
+pub fn launch_settings(window_manager: &mut WindowManager) {
+    window_manager.add_window(Window::new(
+        200,
+        120,
+        460,
+        320,
+        0, // Will be overridden by add_window
+        "Settings".to_string(),
+        AppType::Settings(Settings::new(None)),
+    ));
+}
+
+/// Start an app by its icon name, as used by `generate_icon_for_app_str`
+/// Names [`launch_app`] knows
+pub const APP_NAMES: &[&str] = &[
+    "calculator",
+    "filemanager",
+    "notepad",
+    "terminal",
+    "sysinfo",
+    "logviewer",
+    "tictactoe",
+    "settings",
+];
+
+pub fn launch_app(window_manager: &mut WindowManager, app: &str) -> bool {
+    match app {
+        "calculator" => launch_calculator(window_manager),
+        "filemanager" => launch_filemanager(window_manager),
+        "notepad" => launch_notepad(window_manager),
+        "terminal" => launch_terminal(window_manager),
+        "sysinfo" => launch_sysinfo(window_manager),
+        "logviewer" => launch_logviewer(window_manager),
+        "tictactoe" => launch_tictactoe(window_manager),
+        "settings" => launch_settings(window_manager),
+        _ => return false,
+    }
+    true
+}
+
 pub fn generate_icon_for_app_str<const W: usize, const H: usize>(app: &str) -> Vec<Color> {
     let data = match app {
         "calculator" => ICON_CALCULATOR.to_vec(),
         "filemanager" => ICON_FILEMANAGER.to_vec(),
         "notepad" => ICON_NOTEPAD.to_vec(),
         "terminal" => ICON_TERMINAL.to_vec(),
-        "sysinfo" => ICON_SYSINFO.to_vec(),
+        // No icon of its own yet
+        "sysinfo" | "logviewer" => ICON_SYSINFO.to_vec(),
         "tictactoe" => ICON_TICTACTOE.to_vec(),
+        "settings" => ICON_SETTINGS.to_vec(),
         "start_icon" => START_ICON.to_vec(),
         _ => vec![0; W * H * 3], // Default to blank icon
     };
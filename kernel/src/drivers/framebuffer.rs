@@ -16,7 +16,7 @@ use x86_64::{
     },
 };
 
-use crate::serial_println;
+use crate::{debug, error, serial_println, warn};
 
 pub static FRAMEBUFFER: OnceCell<Spinlock<FrameBufferWriter>> = OnceCell::uninit();
 pub static SCREEN_SIZE: OnceCell<(u16, u16)> = OnceCell::uninit();
@@ -118,10 +118,9 @@ impl DirtyTracker {
         let tiles_height = (screen_height + TILE_SIZE - 1) / TILE_SIZE;
         let total_tiles = tiles_width * tiles_height;
 
-        serial_println!(
+        debug!(
             "Framebuffer initialized with {}x{} tiles",
-            tiles_width,
-            tiles_height
+            tiles_width, tiles_height
         );
 
         Self {
@@ -267,6 +266,69 @@ impl Color {
     }
 }
 
+/// Virtual address of the backbuffer
+const BACKBUFFER_ADDR: u64 = 0xFFFF_8000_0000_0000;
+/// Virtual address of the pool for off-screen window buffers, well past the backbuffer
+const OFFSCREEN_POOL_ADDR: u64 = 0xFFFF_8000_4000_0000;
+/// Enough for a few maximized windows on a 1080p screen
+const OFFSCREEN_POOL_SIZE: usize = 32 * 1024 * 1024;
+
+const PAGE_SIZE_2MIB: usize = 2 * 1024 * 1024;
+
+/// Map fresh 2MiB frames at `virtual_addr` to hold at least `size` bytes.
+/// Returns the whole mapped region, zeroed.
+fn map_2mib_region(
+    virtual_addr: VirtAddr,
+    size: usize,
+    mapper: &mut impl Mapper<Size2MiB>,
+    frame_allocator: &mut (impl FrameAllocator<Size2MiB> + FrameAllocator<Size4KiB>),
+) -> Result<&'static mut [u8], MapToError<Size2MiB>> {
+    // Calculate how many 2MiB pages we need
+    let num_pages = size.div_ceil(PAGE_SIZE_2MIB);
+    let total_allocated_size = num_pages * PAGE_SIZE_2MIB;
+
+    debug!("Framebuffer: Need {} pages of 2MiB each", num_pages);
+
+    // Allocate multiple 2MiB frames
+    let mut frames = Vec::new();
+    for i in 0..num_pages {
+        let frame = frame_allocator
+            .allocate_frame()
+            .ok_or(MapToError::FrameAllocationFailed)?;
+
+        debug!(
+            "Framebuffer: Allocated 2MiB frame {} at {:?}",
+            i,
+            frame.start_address()
+        );
+        frames.push(frame);
+    }
+
+    let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
+
+    // Map all frames to consecutive virtual pages
+    for (i, frame) in frames.iter().enumerate() {
+        let page_addr = virtual_addr + (i * PAGE_SIZE_2MIB) as u64;
+        let page = Page::<Size2MiB>::containing_address(page_addr);
+
+        unsafe {
+            mapper.map_to(page, *frame, flags, frame_allocator)?.flush();
+        }
+
+        debug!(
+            "Framebuffer: Mapped frame {} to virtual address {:?}",
+            i, page_addr
+        );
+    }
+
+    // Create a slice over the mapped memory
+    let buffer =
+        unsafe { slice::from_raw_parts_mut(virtual_addr.as_mut_ptr::<u8>(), total_allocated_size) };
+    buffer.fill(0);
+
+    Ok(buffer)
+}
+
 /// BackBuffer using 2MiB memory pages for efficient double buffering
 pub struct BackBuffer {
     buffer: &'static mut [u8],
@@ -283,62 +345,16 @@ impl BackBuffer {
     ) -> Result<Self, MapToError<Size2MiB>> {
         // Calculate required buffer size
         let buffer_size = info.height * info.stride * info.bytes_per_pixel;
-        serial_println!("BackBuffer: Required size {} bytes", buffer_size);
-
-        // Calculate how many 2MiB pages we need
-        const PAGE_SIZE_2MIB: usize = 2 * 1024 * 1024;
-        let num_pages = (buffer_size + PAGE_SIZE_2MIB - 1) / PAGE_SIZE_2MIB; // Round up
-        let total_allocated_size = num_pages * PAGE_SIZE_2MIB;
-
-        serial_println!("BackBuffer: Need {} pages of 2MiB each", num_pages);
-
-        // Allocate multiple 2MiB frames
-        let mut frames = Vec::new();
-        for i in 0..num_pages {
-            let frame = frame_allocator
-                .allocate_frame()
-                .ok_or(MapToError::FrameAllocationFailed)?;
-
-            serial_println!(
-                "BackBuffer: Allocated 2MiB frame {} at {:?}",
-                i,
-                frame.start_address()
-            );
-            frames.push(frame);
-        }
+        debug!("BackBuffer: Required size {} bytes", buffer_size);
 
         // Use a high virtual address space for backbuffer (avoid conflicts)
-        let virtual_addr = VirtAddr::new(0xFFFF_8000_0000_0000u64);
-        let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
+        let virtual_addr = VirtAddr::new(BACKBUFFER_ADDR);
+        let buffer = map_2mib_region(virtual_addr, buffer_size, mapper, frame_allocator)?;
+        let total_allocated_size = buffer.len();
 
-        // Map all frames to consecutive virtual pages
-        for (i, frame) in frames.iter().enumerate() {
-            let page_addr = virtual_addr + (i * PAGE_SIZE_2MIB) as u64;
-            let page = Page::<Size2MiB>::containing_address(page_addr);
-
-            unsafe {
-                mapper.map_to(page, *frame, flags, frame_allocator)?.flush();
-            }
-
-            serial_println!(
-                "BackBuffer: Mapped frame {} to virtual address {:?}",
-                i,
-                page_addr
-            );
-        }
-
-        // Create a slice over the mapped memory
-        let buffer = unsafe {
-            slice::from_raw_parts_mut(virtual_addr.as_mut_ptr::<u8>(), total_allocated_size)
-        };
-
-        // Clear the buffer
-        buffer.fill(0);
-
-        serial_println!(
+        debug!(
             "BackBuffer: Successfully created at virtual address {:?} with {} bytes",
-            virtual_addr,
-            total_allocated_size
+            virtual_addr, total_allocated_size
         );
 
         Ok(BackBuffer {
@@ -381,6 +397,109 @@ impl BackBuffer {
     }
 }
 
+/// Memory for off-screen window buffers. It is mapped once at boot since
+/// window-sized buffers are far too big for the kernel heap.
+struct OffscreenPool {
+    base: usize,
+    info: FrameBufferInfo,
+    /// Free `(offset, len)` ranges sorted by offset
+    free: Vec<(usize, usize)>,
+}
+
+impl OffscreenPool {
+    /// First fit, returns the offset of the range
+    fn allocate(&mut self, len: usize) -> Option<usize> {
+        let len = (len + 63) & !63;
+        let index = self
+            .free
+            .iter()
+            .position(|&(_, free_len)| free_len >= len)?;
+        let (offset, free_len) = self.free[index];
+
+        if free_len == len {
+            self.free.remove(index);
+        } else {
+            self.free[index] = (offset + len, free_len - len);
+        }
+        Some(offset)
+    }
+
+    fn release(&mut self, offset: usize, len: usize) {
+        let len = (len + 63) & !63;
+        let index = self
+            .free
+            .iter()
+            .position(|&(free_offset, _)| free_offset > offset)
+            .unwrap_or(self.free.len());
+        self.free.insert(index, (offset, len));
+
+        // Merge with the following range, then with the previous one
+        if index + 1 < self.free.len() && offset + len == self.free[index + 1].0 {
+            self.free[index].1 += self.free[index + 1].1;
+            self.free.remove(index + 1);
+        }
+        if index > 0 && self.free[index - 1].0 + self.free[index - 1].1 == offset {
+            self.free[index - 1].1 += self.free[index].1;
+            self.free.remove(index);
+        }
+    }
+}
+
+static OFFSCREEN_POOL: OnceCell<Spinlock<OffscreenPool>> = OnceCell::uninit();
+
+/// Pixels drawn away from the screen, in the same pixel format as the screen so
+/// rows can be copied over as they are. The memory goes back to the pool on drop.
+pub struct OffscreenBuffer {
+    pub writer: FrameBufferWriter,
+    offset: usize,
+    len: usize,
+}
+
+impl OffscreenBuffer {
+    /// None when the pool couldn't be mapped or has no room left
+    pub fn new(width: usize, height: usize) -> Option<Self> {
+        let mut pool = OFFSCREEN_POOL.get()?.lock();
+        let screen = pool.info;
+        let len = width * height * screen.bytes_per_pixel;
+        if len == 0 {
+            return None;
+        }
+
+        let offset = pool.allocate(len)?;
+        let buffer = unsafe { slice::from_raw_parts_mut((pool.base + offset) as *mut u8, len) };
+        let info = FrameBufferInfo {
+            byte_len: len,
+            width,
+            height,
+            pixel_format: screen.pixel_format,
+            bytes_per_pixel: screen.bytes_per_pixel,
+            stride: width,
+        };
+
+        Some(Self {
+            writer: FrameBufferWriter::offscreen(buffer, info),
+            offset,
+            len,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.writer.info.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.writer.info.height
+    }
+}
+
+impl Drop for OffscreenBuffer {
+    fn drop(&mut self) {
+        if let Some(pool) = OFFSCREEN_POOL.get() {
+            pool.lock().release(self.offset, self.len);
+        }
+    }
+}
+
 struct CursorBackground {
     saved_pixels: [u8; CURSOR_BG_DATA_SIZE * 4],
     previous_pos: Option<(usize, usize)>,
@@ -389,7 +508,7 @@ struct CursorBackground {
 impl CursorBackground {
     fn new(bytes_per_pixel: usize) -> Self {
         if bytes_per_pixel > 4 {
-            serial_println!("Cursor background only supports 4 bytes per pixel (RGB).");
+            error!("Cursor background only supports 4 bytes per pixel (RGB).");
             panic!("Cursor background only supports 4 bytes per pixel (RGB).");
         }
 
@@ -442,6 +561,21 @@ impl FrameBufferWriter {
         logger
     }
 
+    /// A writer that draws straight into `buffer`, for off-screen rendering
+    fn offscreen(buffer: &'static mut [u8], info: FrameBufferInfo) -> Self {
+        buffer.fill(0);
+
+        Self {
+            framebuffer: buffer,
+            info,
+            x_pos: BORDER_PADDING,
+            y_pos: BORDER_PADDING,
+            cursor_background: CursorBackground::new(info.bytes_per_pixel),
+            backbuffer: None,
+            dirty_tracker: DirtyTracker::new(info.width, info.height),
+        }
+    }
+
     fn newline(&mut self) {
         self.y_pos += font_constants::CHAR_RASTER_HEIGHT.val() + LINE_SPACING;
         self.carriage_return()
@@ -489,6 +623,10 @@ impl FrameBufferWriter {
         (self.info.width, self.info.height)
     }
 
+    pub fn bytes_per_pixel(&self) -> usize {
+        self.info.bytes_per_pixel
+    }
+
     /// Writes a single char to the framebuffer. Takes care of special control characters, such as
     /// newlines and carriage returns.
     fn write_char(&mut self, c: char) {
@@ -1083,6 +1221,9 @@ pub fn init(
         (info.width as u16, info.height as u16)
     });
 
+    // The framebuffer keeps `frame` borrowed for good, read what the pool needs first
+    let screen_info = frame.info();
+
     FRAMEBUFFER.init_once(|| {
         let info = frame.info();
         let buffer = frame.buffer_mut();
@@ -1094,4 +1235,23 @@ pub fn init(
             mapper,
         ))
     });
+
+    // Windows fall back to drawing on the screen directly if this fails
+    match map_2mib_region(
+        VirtAddr::new(OFFSCREEN_POOL_ADDR),
+        OFFSCREEN_POOL_SIZE,
+        mapper,
+        frame_allocator,
+    ) {
+        Ok(memory) => {
+            OFFSCREEN_POOL.init_once(|| {
+                Spinlock::new(OffscreenPool {
+                    base: memory.as_mut_ptr() as usize,
+                    info: screen_info,
+                    free: vec![(0, memory.len())],
+                })
+            });
+        }
+        Err(err) => warn!("Offscreen pool unavailable: {:?}", err),
+    }
 }
@@ -1,2 +1,3 @@
 pub mod framebuffer;
+pub mod mouse;
 pub mod serial;
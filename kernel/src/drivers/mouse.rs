@@ -0,0 +1,149 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+use x86_64::instructions::port::Port;
+
+const DATA_PORT: u16 = 0x60;
+const STATUS_PORT: u16 = 0x64;
+const WRITE_TO_MOUSE: u8 = 0xD4;
+const SET_SAMPLE_RATE: u8 = 0xF3;
+const GET_DEVICE_ID: u8 = 0xF2;
+const ACK: u8 = 0xFA;
+const INTELLIMOUSE_ID: u8 = 3;
+const TIMEOUT: u32 = 100_000;
+
+/// Set once the mouse has switched to 4 byte IntelliMouse packets
+static WHEEL_ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn has_scroll_wheel() -> bool {
+    WHEEL_ENABLED.load(Ordering::Relaxed)
+}
+
+/// One decoded movement packet
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MousePacket {
+    pub dx: i16,
+    /// Positive is up, like the hardware reports it
+    pub dy: i16,
+    pub left: bool,
+    pub right: bool,
+    pub middle: bool,
+    /// Positive scrolls down
+    pub wheel: i8,
+}
+
+/// Collects the bytes of a packet as they arrive, one per interrupt
+pub struct PacketDecoder {
+    bytes: [u8; 4],
+    index: usize,
+}
+
+impl Default for PacketDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PacketDecoder {
+    pub const fn new() -> Self {
+        PacketDecoder {
+            bytes: [0; 4],
+            index: 0,
+        }
+    }
+
+    pub fn push(&mut self, byte: u8) -> Option<MousePacket> {
+        // Bit 3 of the first byte is always set, use it to get back in sync
+        if self.index == 0 && byte & 0x08 == 0 {
+            return None;
+        }
+
+        self.bytes[self.index] = byte;
+        self.index += 1;
+
+        let packet_size = if has_scroll_wheel() { 4 } else { 3 };
+        if self.index < packet_size {
+            return None;
+        }
+        self.index = 0;
+
+        let flags = self.bytes[0];
+        let overflow = flags & 0xC0 != 0;
+        let dx = self.bytes[1] as i16 - (((flags as i16) << 4) & 0x100);
+        let dy = self.bytes[2] as i16 - (((flags as i16) << 3) & 0x100);
+        // Only the low nibble is the wheel, the rest are buttons 4 and 5
+        let wheel = if packet_size == 4 {
+            ((self.bytes[3] << 4) as i8) >> 4
+        } else {
+            0
+        };
+
+        Some(MousePacket {
+            dx: if overflow { 0 } else { dx },
+            dy: if overflow { 0 } else { dy },
+            left: flags & 0x01 != 0,
+            right: flags & 0x02 != 0,
+            middle: flags & 0x04 != 0,
+            wheel,
+        })
+    }
+}
+
+fn wait_for_write() -> bool {
+    let mut status: Port<u8> = Port::new(STATUS_PORT);
+    (0..TIMEOUT).any(|_| unsafe { status.read() } & 0x02 == 0)
+}
+
+fn wait_for_read() -> bool {
+    let mut status: Port<u8> = Port::new(STATUS_PORT);
+    (0..TIMEOUT).any(|_| unsafe { status.read() } & 0x01 != 0)
+}
+
+fn read_data() -> Result<u8, &'static str> {
+    if !wait_for_read() {
+        return Err("Mouse did not answer");
+    }
+    let mut data: Port<u8> = Port::new(DATA_PORT);
+    Ok(unsafe { data.read() })
+}
+
+fn send_command(command: u8) -> Result<(), &'static str> {
+    let mut status: Port<u8> = Port::new(STATUS_PORT);
+    let mut data: Port<u8> = Port::new(DATA_PORT);
+
+    if !wait_for_write() {
+        return Err("Controller not ready");
+    }
+    unsafe { status.write(WRITE_TO_MOUSE) };
+    if !wait_for_write() {
+        return Err("Controller not ready");
+    }
+    unsafe { data.write(command) };
+
+    if read_data()? != ACK {
+        return Err("Mouse rejected command");
+    }
+    Ok(())
+}
+
+fn set_sample_rate(rate: u8) -> Result<(), &'static str> {
+    send_command(SET_SAMPLE_RATE)?;
+    send_command(rate)
+}
+
+/// Try the IntelliMouse knock sequence (sample rates 200, 100, 80). Mice
+/// with a wheel answer with device id 3 afterwards and start sending a
+/// fourth packet byte. Must run with interrupts off so the mouse handler
+/// doesn't eat the replies.
+pub fn enable_scroll_wheel() -> Result<bool, &'static str> {
+    for rate in [200, 100, 80] {
+        set_sample_rate(rate)?;
+    }
+
+    send_command(GET_DEVICE_ID)?;
+    let has_wheel = read_data()? == INTELLIMOUSE_ID;
+
+    // Back to the usual rate, the knock leaves it at 80
+    set_sample_rate(100)?;
+
+    WHEEL_ENABLED.store(has_wheel, Ordering::Relaxed);
+    Ok(has_wheel)
+}
@@ -1,6 +1,20 @@
+use conquer_once::spin::OnceCell;
+use crossbeam_queue::ArrayQueue;
 use lazy_static::lazy_static;
 use spin::Mutex;
 use uart_16550::SerialPort;
+use x86_64::instructions::port::Port;
+
+const COM1: u16 = 0x3F8;
+/// Interrupt enable register, bit 0 asks for an interrupt per received byte
+const INTERRUPT_ENABLE: u16 = COM1 + 1;
+/// Modem control register, OUT2 connects the UART to the interrupt line
+const MODEM_CONTROL: u16 = COM1 + 4;
+/// Line status register, bit 0 is set while a received byte waits
+const LINE_STATUS: u16 = COM1 + 5;
+
+/// Bytes received on COM1 that nobody read yet
+static RECEIVED: OnceCell<ArrayQueue<u8>> = OnceCell::uninit();
 
 lazy_static! {
     pub static ref SERIAL1: Mutex<SerialPort> = {
@@ -10,6 +24,38 @@ lazy_static! {
     };
 }
 
+/// Start taking input from COM1; until then received bytes are dropped
+pub fn enable_receive() {
+    RECEIVED.init_once(|| ArrayQueue::new(256));
+    lazy_static::initialize(&SERIAL1);
+
+    x86_64::instructions::interrupts::without_interrupts(|| unsafe {
+        // DTR, RTS and OUT2
+        Port::<u8>::new(MODEM_CONTROL).write(0x0B);
+        Port::<u8>::new(INTERRUPT_ENABLE).write(0x01);
+    });
+}
+
+/// Called from the COM1 interrupt, moves what the UART holds into the queue
+pub fn receive_interrupt() {
+    let mut line_status = Port::<u8>::new(LINE_STATUS);
+    let mut data = Port::<u8>::new(COM1);
+
+    // Reading the data register is what clears the interrupt, so drain it
+    // even when there is nowhere to put the bytes
+    while unsafe { line_status.read() } & 1 != 0 {
+        let byte = unsafe { data.read() };
+        if let Some(queue) = RECEIVED.get() {
+            let _ = queue.push(byte);
+        }
+    }
+}
+
+/// Next byte received on COM1
+pub fn read_byte() -> Option<u8> {
+    RECEIVED.get()?.pop()
+}
+
 #[doc(hidden)]
 pub fn _print(args: ::core::fmt::Arguments) {
     use core::fmt::Write;
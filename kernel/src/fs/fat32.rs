@@ -1,6 +1,7 @@
-use crate::serial_println;
+use crate::fs::journal::{self, JOURNAL_FILE_NAME, JournalEntry};
+use crate::{error, info, warn};
 use crate::sysinfo::FilesystemInfo;
-use crate::time::{Date, DateTime, Time, get_utc_time};
+use crate::time::{Date, DateTime, Time, get_local_time};
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use alloc::{format, vec};
@@ -105,6 +106,23 @@ pub struct FileEntry {
     pub created_at: DateTime,
     pub last_access_at: Date,
     pub last_write_at: DateTime,
+
+    /// Raw FAT attribute byte (see [`attributes`])
+    pub attributes: u8,
+}
+
+impl FileEntry {
+    pub fn is_read_only(&self) -> bool {
+        (self.attributes & attributes::READ_ONLY) != 0
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        (self.attributes & attributes::HIDDEN) != 0
+    }
+
+    pub fn is_system(&self) -> bool {
+        (self.attributes & attributes::SYSTEM) != 0
+    }
 }
 
 /// Trait for disk operations
@@ -121,8 +139,17 @@ pub struct Fat32FileSystem<D: DiskOperations> {
     data_start_sector: u64,
     sectors_per_cluster: u64,
     bytes_per_sector: u64,
+    /// Cluster of the intent log, None if it could not be set up
+    journal_cluster: Option<u32>,
+    /// Operation that failed partway and is still recorded in the journal. It is
+    /// finished before the next journaled operation, or by the replay at mount.
+    pending_journal: Option<JournalEntry>,
 }
 
+/// Error of an operation that failed partway and could not be rolled back. Its
+/// record stays in the journal, so callers must not try another way around it.
+pub const JOURNAL_PENDING: &str = "Operation left pending in the journal";
+
 impl<D: DiskOperations> Fat32FileSystem<D> {
     /// Create a new FAT32 filesystem instance
     pub fn new(mut disk: D) -> Result<Self, &'static str> {
@@ -144,14 +171,22 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
         let fat_size = boot_sector.sectors_per_fat_32 as u64;
         let data_start_sector = fat_start_sector + (boot_sector.fat_count as u64 * fat_size);
 
-        Ok(Fat32FileSystem {
+        let mut filesystem = Fat32FileSystem {
             disk,
             boot_sector,
             fat_start_sector,
             data_start_sector,
             sectors_per_cluster: boot_sector.sectors_per_cluster as u64,
             bytes_per_sector: boot_sector.bytes_per_sector as u64,
-        })
+            journal_cluster: None,
+            pending_journal: None,
+        };
+
+        if let Err(e) = filesystem.mount_journal() {
+            warn!("Journal unavailable, metadata updates are not crash-safe: {}", e);
+        }
+
+        Ok(filesystem)
     }
 
     /// Convert raw FAT date to Date struct
@@ -492,8 +527,8 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
         // Verify checksum consistency
         for lfn_entry in &sorted_entries {
             if lfn_entry.checksum != expected_checksum {
-                crate::serial_println!(
-                    "DEBUG: Checksum mismatch! Expected: 0x{:02X}, Got: 0x{:02X}",
+                warn!(
+                    "LFN checksum mismatch, expected 0x{:02X}, got 0x{:02X}",
                     expected_checksum,
                     lfn_entry.checksum
                 );
@@ -608,6 +643,7 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
             created_at: DateTime::from_date_and_time(created_date, created_time),
             last_access_at: last_access_date,
             last_write_at: DateTime::from_date_and_time(last_write_date, last_write_time),
+            attributes: entry.attributes,
         }
     }
 
@@ -753,6 +789,39 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
         Err("No free clusters available")
     }
 
+    /// Count the free clusters, reading the FAT one sector at a time
+    pub fn count_free_clusters(&mut self) -> Result<u32, &'static str> {
+        let max_clusters = (self.boot_sector.total_sectors_32 - self.data_start_sector as u32)
+            / self.boot_sector.sectors_per_cluster as u32;
+        let entries_per_sector = (self.bytes_per_sector / 4) as u32;
+
+        let mut sector_buffer = [0u8; 512];
+        let mut free = 0;
+        let mut cluster = 2u32;
+        while cluster < max_clusters {
+            let fat_sector = self.fat_start_sector + (cluster / entries_per_sector) as u64;
+            self.disk.read_sector(fat_sector, &mut sector_buffer)?;
+
+            let first = cluster % entries_per_sector;
+            let last = entries_per_sector.min(first + max_clusters - cluster);
+            for index in first..last {
+                let offset = index as usize * 4;
+                let entry = u32::from_le_bytes([
+                    sector_buffer[offset],
+                    sector_buffer[offset + 1],
+                    sector_buffer[offset + 2],
+                    sector_buffer[offset + 3],
+                ]) & cluster_values::MASK;
+                if entry == cluster_values::FREE {
+                    free += 1;
+                }
+            }
+            cluster += last - first;
+        }
+
+        Ok(free)
+    }
+
     /// Update a FAT entry
     fn update_fat_entry(&mut self, cluster: u32, value: u32) -> Result<(), &'static str> {
         let fat_offset = cluster * 4; // 4 bytes per FAT32 entry
@@ -835,6 +904,10 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
             return Err("Cannot update directory as file");
         }
 
+        if file_entry.is_read_only() {
+            return Err("File is read-only");
+        }
+
         let cluster_size = (self.sectors_per_cluster * self.bytes_per_sector) as usize;
         let old_clusters_needed = if file_entry.size == 0 {
             0
@@ -847,21 +920,29 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
             ((new_data.len() + cluster_size - 1) / cluster_size) as u32
         };
 
-        let new_first_cluster = if new_data.is_empty() {
+        // The data goes to disk first (into the existing chain or into freshly allocated,
+        // still unlinked clusters). The FAT and directory changes that make it visible
+        // are journaled, so a crash leaves them either fully applied or untouched.
+        let mut new_first_cluster = file_entry.first_cluster;
+        let mut link_from = 0;
+        let mut link_to = 0;
+        let mut truncate_at = 0;
+        let mut free_from = 0;
+
+        if new_data.is_empty() {
             // New file is empty, free all existing clusters
+            new_first_cluster = 0;
             if file_entry.first_cluster >= 2 {
-                self.free_cluster_chain(file_entry.first_cluster)?;
+                free_from = file_entry.first_cluster;
             }
-            0
         } else if file_entry.first_cluster == 0 {
             // File was empty, allocate new clusters
             let first_cluster = self.allocate_cluster_chain(new_clusters_needed)?;
             self.write_file(first_cluster, new_data)?;
-            first_cluster
+            new_first_cluster = first_cluster;
         } else if new_clusters_needed == old_clusters_needed {
             // Same number of clusters, just overwrite
             self.write_file(file_entry.first_cluster, new_data)?;
-            file_entry.first_cluster
         } else if new_clusters_needed < old_clusters_needed {
             // Fewer clusters needed, write data and free excess clusters
             self.write_file(file_entry.first_cluster, new_data)?;
@@ -875,15 +956,11 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
             // Get the next cluster (first one to free)
             let first_cluster_to_free = self.get_next_cluster(current_cluster)?;
 
-            // Mark the last kept cluster as end of chain
-            self.update_fat_entry(current_cluster, cluster_values::END_OF_CHAIN)?;
-
-            // Free the remaining clusters
-            if first_cluster_to_free < cluster_values::END_OF_CHAIN {
-                self.free_cluster_chain(first_cluster_to_free)?;
+            // The last kept cluster becomes the end of chain
+            truncate_at = current_cluster;
+            if (2..cluster_values::END_OF_CHAIN).contains(&first_cluster_to_free) {
+                free_from = first_cluster_to_free;
             }
-
-            file_entry.first_cluster
         } else {
             // More clusters needed, write to existing and allocate more
 
@@ -904,32 +981,31 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
                 let additional_clusters = new_clusters_needed - old_clusters_needed;
                 let new_clusters_start = self.allocate_cluster_chain(additional_clusters)?;
 
+                // Write remaining data to new clusters before they are linked in
+                let remaining_data = &new_data[bytes_in_existing_clusters..];
+                self.write_file(new_clusters_start, remaining_data)?;
+
                 // Find the last cluster in the existing chain
                 let mut last_existing_cluster = file_entry.first_cluster;
                 for _ in 1..old_clusters_needed {
                     last_existing_cluster = self.get_next_cluster(last_existing_cluster)?;
                 }
 
-                // Link the new clusters to the existing chain
-                self.update_fat_entry(last_existing_cluster, new_clusters_start)?;
-
-                // Write remaining data to new clusters
-                let remaining_data = &new_data[bytes_in_existing_clusters..];
-                self.write_file(new_clusters_start, remaining_data)?;
+                link_from = last_existing_cluster;
+                link_to = new_clusters_start;
             }
+        }
 
-            file_entry.first_cluster
-        };
-
-        // Update the directory entry with new file size and cluster
-        self.update_directory_entry(
+        self.run_journaled(JournalEntry::Update {
             dir_cluster,
-            filename,
+            name: filename.to_string(),
             new_first_cluster,
-            new_data.len() as u32,
-        )?;
-
-        Ok(())
+            new_size: new_data.len() as u32,
+            link_from,
+            link_to,
+            truncate_at,
+            free_from,
+        })
     }
 
     /// Write file data to allocated clusters
@@ -1297,7 +1373,7 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
             self.generate_short_name(dir_cluster, filename)?
         };
 
-        let now = get_utc_time(); // TODO: Use correct timezone
+        let now = get_local_time(); // FAT timestamps are local time
 
         let creation_date = self.date_to_raw_date(now.to_date());
         let creation_time = self.time_to_raw_time(now.to_time());
@@ -1362,6 +1438,30 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
         new_first_cluster: u32,
         new_file_size: u32,
     ) -> Result<(), &'static str> {
+        let now = get_local_time(); // FAT timestamps are local time
+        let raw_date = self.date_to_raw_date(now.to_date());
+        let raw_time = self.time_to_raw_time(now.to_time());
+
+        self.modify_directory_entry(dir_cluster, filename, |entry| {
+            entry.first_cluster_high = (new_first_cluster >> 16) as u16;
+            entry.first_cluster_low = (new_first_cluster & 0xFFFF) as u16;
+            entry.file_size = new_file_size;
+            entry.last_write_date = raw_date;
+            entry.last_write_time = raw_time;
+            entry.last_access_date = raw_date;
+        })
+    }
+
+    /// Find a directory entry by name and rewrite it in place with `modify`
+    fn modify_directory_entry<F>(
+        &mut self,
+        dir_cluster: u32,
+        filename: &str,
+        modify: F,
+    ) -> Result<(), &'static str>
+    where
+        F: FnOnce(&mut DirectoryEntry),
+    {
         let cluster_size = (self.sectors_per_cluster * self.bytes_per_sector) as usize;
         let entries_per_cluster = cluster_size / mem::size_of::<DirectoryEntry>();
         let mut current_cluster = dir_cluster;
@@ -1410,16 +1510,9 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
                 let entry_file = self.entry_to_file_entry_with_lfn(long_filename, &entry);
 
                 if entry_file.name.to_uppercase() == filename.to_uppercase() {
-                    let now = get_utc_time(); // TODO: Use correct timezone
-
                     // Create a mutable copy of the entry
                     let mut updated_entry = entry;
-                    updated_entry.first_cluster_high = (new_first_cluster >> 16) as u16;
-                    updated_entry.first_cluster_low = (new_first_cluster & 0xFFFF) as u16;
-                    updated_entry.file_size = new_file_size;
-                    updated_entry.last_write_date = self.date_to_raw_date(now.to_date());
-                    updated_entry.last_write_time = self.time_to_raw_time(now.to_time());
-                    updated_entry.last_access_date = self.date_to_raw_date(now.to_date());
+                    modify(&mut updated_entry);
 
                     // Write the updated entry back
                     let entry_bytes = unsafe {
@@ -1447,6 +1540,24 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
         Err("File not found in directory")
     }
 
+    /// Set the user-visible attribute bits (read-only, hidden, system, archive) of an entry.
+    /// The directory and volume bits are structural and are always preserved.
+    pub fn set_attributes(
+        &mut self,
+        dir_cluster: u32,
+        filename: &str,
+        attrs: u8,
+    ) -> Result<(), &'static str> {
+        let settable = attributes::READ_ONLY
+            | attributes::HIDDEN
+            | attributes::SYSTEM
+            | attributes::ARCHIVE;
+
+        self.modify_directory_entry(dir_cluster, filename, |entry| {
+            entry.attributes = (entry.attributes & !settable) | (attrs & settable);
+        })
+    }
+
     /// Update only the last access date for a file (for read operations)
     fn update_last_access_date(
         &mut self,
@@ -1501,7 +1612,7 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
                 let entry_file = self.entry_to_file_entry_with_lfn(long_filename, &entry);
 
                 if entry_file.name.to_uppercase() == filename.to_uppercase() {
-                    let now = get_utc_time(); // TODO: Use correct timezone
+                    let now = get_local_time(); // FAT timestamps are local time
 
                     // Create a mutable copy of the entry and only update last access date
                     let mut updated_entry = entry;
@@ -1545,15 +1656,15 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
             return Err("Cannot delete directory using delete_file");
         }
 
-        // Free the cluster chain (only if the file has allocated clusters)
-        if file_entry.first_cluster > 0 {
-            self.free_cluster_chain(file_entry.first_cluster)?;
+        if file_entry.is_read_only() {
+            return Err("File is read-only");
         }
 
-        // Mark directory entry as deleted
-        self.mark_directory_entry_deleted(dir_cluster, filename)?;
-
-        Ok(())
+        self.run_journaled(JournalEntry::Delete {
+            dir_cluster,
+            name: filename.to_string(),
+            first_cluster: file_entry.first_cluster,
+        })
     }
 
     /// Free a cluster chain
@@ -1570,8 +1681,8 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
 
             // Safety check to prevent infinite loops
             if iteration_count > 10000 {
-                serial_println!(
-                    "[ERROR] free_cluster_chain: Infinite loop detected! Breaking after {} iterations",
+                error!(
+                    "free_cluster_chain: cluster chain loops, stopped after {} iterations",
                     iteration_count
                 );
                 return Err("Infinite loop detected in cluster chain");
@@ -1829,18 +1940,20 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
             return Err("Not a directory");
         }
 
+        if dir_entry.is_read_only() {
+            return Err("Directory is read-only");
+        }
+
         // Check if directory is empty (only "." and ".." entries should exist)
         if !self.is_directory_empty(dir_entry.first_cluster)? {
             return Err("Directory not empty");
         }
 
-        // Free the cluster(s) used by the directory
-        self.free_cluster_chain(dir_entry.first_cluster)?;
-
-        // Mark directory entry as deleted in parent
-        self.mark_directory_entry_deleted(parent_cluster, dirname)?;
-
-        Ok(())
+        self.run_journaled(JournalEntry::Delete {
+            dir_cluster: parent_cluster,
+            name: dirname.to_string(),
+            first_cluster: dir_entry.first_cluster,
+        })
     }
 
     /// Check if a directory is empty (contains only "." and ".." entries)
@@ -1964,24 +2077,16 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
             return Err("Destination already exists");
         }
 
-        // Create the entry in the destination directory
-        self.create_directory_entry(
-            dest_dir_cluster,
-            dest_name,
-            source_entry.first_cluster,
-            source_entry.size,
-            source_entry.is_directory,
-        )?;
-
-        // If it's a directory, update the ".." entry to point to the new parent
-        if source_entry.is_directory && dest_dir_cluster != source_dir_cluster {
-            self.update_parent_directory_entry(source_entry.first_cluster, dest_dir_cluster)?;
-        }
-
-        // Delete the entry from the source directory
-        self.delete_file(source_dir_cluster, source_name)?;
-
-        Ok(())
+        self.run_journaled(JournalEntry::Move {
+            source_dir: source_dir_cluster,
+            source_name: source_name.to_string(),
+            dest_dir: dest_dir_cluster,
+            dest_name: dest_name.to_string(),
+            first_cluster: source_entry.first_cluster,
+            size: source_entry.size,
+            is_directory: source_entry.is_directory,
+            attributes: source_entry.attributes,
+        })
     }
 
     /// Update the ".." entry in a directory to point to a new parent
@@ -2023,6 +2128,228 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
         Err("Could not find '..' entry in directory")
     }
 
+    /// Find or create the intent log and replay an operation left over by a crash
+    fn mount_journal(&mut self) -> Result<(), &'static str> {
+        let root_cluster = self.boot_sector.root_cluster;
+        let cluster_size = (self.sectors_per_cluster * self.bytes_per_sector) as usize;
+
+        let entry = match self.find_file_in_directory(root_cluster, JOURNAL_FILE_NAME)? {
+            Some(entry) => entry,
+            None => {
+                let mut empty = vec![0u8; cluster_size];
+                empty[..journal::clean_header().len()].copy_from_slice(&journal::clean_header());
+                self.create_file(root_cluster, JOURNAL_FILE_NAME, &empty)?;
+                self.find_file_in_directory(root_cluster, JOURNAL_FILE_NAME)?
+                    .ok_or("Journal file vanished after creation")?
+            }
+        };
+
+        if entry.is_directory || entry.first_cluster < 2 {
+            return Err("Journal file is corrupt");
+        }
+
+        // Read-only so its cluster is never freed or reallocated under the cached journal_cluster;
+        // also fixes journals created before the flag was set
+        let journal_attributes = attributes::READ_ONLY | attributes::HIDDEN | attributes::SYSTEM;
+        if entry.attributes & journal_attributes != journal_attributes {
+            self.set_attributes(root_cluster, JOURNAL_FILE_NAME, journal_attributes)?;
+        }
+
+        self.journal_cluster = Some(entry.first_cluster);
+
+        let mut buffer = vec![0u8; cluster_size];
+        self.read_cluster(entry.first_cluster, &mut buffer)?;
+
+        if let Some(pending) = journal::decode(&buffer) {
+            info!("Replaying interrupted operation: {:?}", pending);
+            if let Err(e) = self.apply_journal_entry(&pending) {
+                error!("Journal replay failed: {}", e);
+                self.pending_journal = Some(pending);
+                return Ok(());
+            }
+            self.journal_commit()?;
+        }
+
+        Ok(())
+    }
+
+    /// Record an operation in the journal, perform it, then mark the journal clean.
+    /// A failed operation is rolled back when that can be done safely (moves) and
+    /// otherwise stays pending, so it is finished later instead of half-applied.
+    fn run_journaled(&mut self, entry: JournalEntry) -> Result<(), &'static str> {
+        // Writing a new record would drop the pending one
+        if let Some(pending) = self.pending_journal.take() {
+            if let Err(e) = self.apply_journal_entry(&pending) {
+                self.pending_journal = Some(pending);
+                return Err(e);
+            }
+            self.journal_commit()?;
+        }
+
+        if let Some(journal_cluster) = self.journal_cluster {
+            let cluster_size = (self.sectors_per_cluster * self.bytes_per_sector) as usize;
+            let record = journal::encode(&entry);
+            if record.len() > cluster_size {
+                return Err("Journal record too large");
+            }
+
+            let mut buffer = vec![0u8; cluster_size];
+            buffer[..record.len()].copy_from_slice(&record);
+            self.write_cluster(journal_cluster, &buffer)?;
+        }
+
+        if let Err(e) = self.apply_journal_entry(&entry) {
+            if self.undo_journal_entry(&entry).is_err() {
+                self.pending_journal = Some(entry);
+                return Err(JOURNAL_PENDING);
+            }
+            // Back to how it was before: callers may work around the error
+            // (move_item falls back to copy+delete) without a replay undoing that
+            self.journal_commit()?;
+            return Err(e);
+        }
+
+        self.journal_commit()
+    }
+
+    /// Undo the steps of an operation that failed partway. Only moves can be undone,
+    /// as long as the source entry is still there: the new entry is removed and ".."
+    /// points back at the old parent. The rest fails and stays pending.
+    fn undo_journal_entry(&mut self, entry: &JournalEntry) -> Result<(), &'static str> {
+        let JournalEntry::Move {
+            source_dir,
+            source_name,
+            dest_dir,
+            dest_name,
+            first_cluster,
+            is_directory,
+            ..
+        } = entry
+        else {
+            return Err("Operation cannot be rolled back");
+        };
+
+        if self
+            .find_file_in_directory(*source_dir, source_name)?
+            .is_none()
+        {
+            return Err("Source entry already removed");
+        }
+
+        if *is_directory && dest_dir != source_dir {
+            self.update_parent_directory_entry(*first_cluster, *source_dir)?;
+        }
+
+        if self
+            .find_file_in_directory(*dest_dir, dest_name)?
+            .is_some()
+        {
+            self.mark_directory_entry_deleted(*dest_dir, dest_name)?;
+        }
+
+        Ok(())
+    }
+
+    fn journal_commit(&mut self) -> Result<(), &'static str> {
+        let Some(journal_cluster) = self.journal_cluster else {
+            return Ok(());
+        };
+
+        let cluster_size = (self.sectors_per_cluster * self.bytes_per_sector) as usize;
+        let mut buffer = vec![0u8; cluster_size];
+        let header = journal::clean_header();
+        buffer[..header.len()].copy_from_slice(&header);
+        self.write_cluster(journal_cluster, &buffer)
+    }
+
+    /// Perform the steps of a journaled operation. Every step checks or overwrites
+    /// state, so running it again after a partial attempt rolls it forward; at worst
+    /// a half-freed chain leaks some clusters.
+    fn apply_journal_entry(&mut self, entry: &JournalEntry) -> Result<(), &'static str> {
+        match entry {
+            JournalEntry::Move {
+                source_dir,
+                source_name,
+                dest_dir,
+                dest_name,
+                first_cluster,
+                size,
+                is_directory,
+                attributes,
+            } => {
+                if self
+                    .find_file_in_directory(*dest_dir, dest_name)?
+                    .is_none()
+                {
+                    self.create_directory_entry(
+                        *dest_dir,
+                        dest_name,
+                        *first_cluster,
+                        *size,
+                        *is_directory,
+                    )?;
+                }
+
+                // Carry the attribute bits over, create_directory_entry only sets ARCHIVE/DIRECTORY
+                self.set_attributes(*dest_dir, dest_name, *attributes)?;
+
+                // If it's a directory, update the ".." entry to point to the new parent
+                if *is_directory && dest_dir != source_dir {
+                    self.update_parent_directory_entry(*first_cluster, *dest_dir)?;
+                }
+
+                // Remove the source entry only; the cluster chain now belongs to the destination
+                if self
+                    .find_file_in_directory(*source_dir, source_name)?
+                    .is_some()
+                {
+                    self.mark_directory_entry_deleted(*source_dir, source_name)?;
+                }
+            }
+            JournalEntry::Update {
+                dir_cluster,
+                name,
+                new_first_cluster,
+                new_size,
+                link_from,
+                link_to,
+                truncate_at,
+                free_from,
+            } => {
+                if *link_from >= 2 {
+                    self.update_fat_entry(*link_from, *link_to)?;
+                }
+                if *truncate_at >= 2 {
+                    self.update_fat_entry(*truncate_at, cluster_values::END_OF_CHAIN)?;
+                }
+
+                self.update_directory_entry(*dir_cluster, name, *new_first_cluster, *new_size)?;
+
+                // Only release clusters once nothing points at them any more
+                if *free_from >= 2 {
+                    self.free_cluster_chain(*free_from)?;
+                }
+            }
+            JournalEntry::Delete {
+                dir_cluster,
+                name,
+                first_cluster,
+            } => {
+                if self
+                    .find_file_in_directory(*dir_cluster, name)?
+                    .is_some()
+                {
+                    self.mark_directory_entry_deleted(*dir_cluster, name)?;
+                }
+                if *first_cluster >= 2 {
+                    self.free_cluster_chain(*first_cluster)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Get filesystem information for system monitoring
     pub fn get_filesystem_info(&self) -> FilesystemInfo {
         let boot_sector = &self.boot_sector;
@@ -2065,3 +2392,192 @@ impl<D: DiskOperations> Fat32FileSystem<D> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECTOR_SIZE: usize = 512;
+    const DISK_SECTORS: usize = 48;
+
+    /// Disk image in memory. Writes to `locked_sector` fail, to stop an operation halfway.
+    struct MemDisk {
+        data: Vec<u8>,
+        locked_sector: Option<u64>,
+    }
+
+    impl DiskOperations for MemDisk {
+        fn read_sector(&mut self, sector: u64, buffer: &mut [u8]) -> Result<(), &'static str> {
+            let start = sector as usize * SECTOR_SIZE;
+            let data = self
+                .data
+                .get(start..start + SECTOR_SIZE)
+                .ok_or("Sector out of range")?;
+            buffer[..SECTOR_SIZE].copy_from_slice(data);
+            Ok(())
+        }
+
+        fn write_sector(&mut self, sector: u64, buffer: &[u8]) -> Result<(), &'static str> {
+            if self.locked_sector == Some(sector) {
+                return Err("Sector is locked");
+            }
+
+            let start = sector as usize * SECTOR_SIZE;
+            let data = self
+                .data
+                .get_mut(start..start + SECTOR_SIZE)
+                .ok_or("Sector out of range")?;
+            data.copy_from_slice(&buffer[..SECTOR_SIZE]);
+            Ok(())
+        }
+    }
+
+    /// Empty volume: one sector per cluster, a single one-sector FAT, root in cluster 2
+    fn format() -> MemDisk {
+        let mut data = vec![0u8; DISK_SECTORS * SECTOR_SIZE];
+
+        let boot = &mut data[..SECTOR_SIZE];
+        boot[11..13].copy_from_slice(&(SECTOR_SIZE as u16).to_le_bytes());
+        boot[13] = 1; // sectors per cluster
+        boot[14..16].copy_from_slice(&1u16.to_le_bytes()); // reserved sectors
+        boot[16] = 1; // FAT count
+        boot[32..36].copy_from_slice(&(DISK_SECTORS as u32).to_le_bytes());
+        boot[36..40].copy_from_slice(&1u32.to_le_bytes()); // sectors per FAT
+        boot[44..48].copy_from_slice(&2u32.to_le_bytes()); // root cluster
+        boot[510..512].copy_from_slice(&0xAA55u16.to_le_bytes());
+
+        let fat = &mut data[SECTOR_SIZE..2 * SECTOR_SIZE];
+        fat[0..4].copy_from_slice(&0x0FFF_FFF8u32.to_le_bytes());
+        fat[4..8].copy_from_slice(&cluster_values::END_OF_CHAIN.to_le_bytes());
+        fat[8..12].copy_from_slice(&cluster_values::END_OF_CHAIN.to_le_bytes());
+
+        MemDisk {
+            data,
+            locked_sector: None,
+        }
+    }
+
+    fn mount(disk: MemDisk) -> Fat32FileSystem<MemDisk> {
+        Fat32FileSystem::new(disk).expect("mount failed")
+    }
+
+    /// Volume with `A.TXT` in the root and an empty `DEST` directory
+    fn sample_volume() -> (Fat32FileSystem<MemDisk>, FileEntry, u32) {
+        let mut fs = mount(format());
+        let root = fs.boot_sector.root_cluster;
+        fs.create_file(root, "A.TXT", b"hello").unwrap();
+        fs.create_directory(root, "DEST").unwrap();
+
+        let file = fs.find_file_in_directory(root, "A.TXT").unwrap().unwrap();
+        let dest = fs.find_file_in_directory(root, "DEST").unwrap().unwrap();
+        (fs, file, dest.first_cluster)
+    }
+
+    fn pending_record(fs: &mut Fat32FileSystem<MemDisk>) -> Option<JournalEntry> {
+        let mut buffer = vec![0u8; SECTOR_SIZE];
+        fs.read_cluster(fs.journal_cluster.unwrap(), &mut buffer).unwrap();
+        journal::decode(&buffer)
+    }
+
+    /// Leave a record in the journal as if the machine died right after writing it
+    fn write_record(fs: &mut Fat32FileSystem<MemDisk>, entry: &JournalEntry) {
+        let record = journal::encode(entry);
+        let mut buffer = vec![0u8; SECTOR_SIZE];
+        buffer[..record.len()].copy_from_slice(&record);
+        fs.write_cluster(fs.journal_cluster.unwrap(), &buffer).unwrap();
+    }
+
+    #[test_case]
+    fn test_replay_finishes_interrupted_move() {
+        let (mut fs, file, dest) = sample_volume();
+        let root = fs.boot_sector.root_cluster;
+        write_record(
+            &mut fs,
+            &JournalEntry::Move {
+                source_dir: root,
+                source_name: "A.TXT".to_string(),
+                dest_dir: dest,
+                dest_name: "B.TXT".to_string(),
+                first_cluster: file.first_cluster,
+                size: file.size,
+                is_directory: false,
+                attributes: file.attributes,
+            },
+        );
+
+        let mut fs = mount(fs.disk);
+        assert!(fs.find_file_in_directory(root, "A.TXT").unwrap().is_none());
+        let moved = fs.find_file_in_directory(dest, "B.TXT").unwrap().unwrap();
+        assert_eq!(moved.first_cluster, file.first_cluster);
+        assert_eq!(fs.read_file(moved.first_cluster, moved.size).unwrap(), b"hello");
+        assert_eq!(pending_record(&mut fs), None);
+    }
+
+    #[test_case]
+    fn test_replay_finishes_half_done_delete() {
+        let (mut fs, file, _) = sample_volume();
+        let root = fs.boot_sector.root_cluster;
+        write_record(
+            &mut fs,
+            &JournalEntry::Delete {
+                dir_cluster: root,
+                name: "A.TXT".to_string(),
+                first_cluster: file.first_cluster,
+            },
+        );
+        // The entry went away but the crash came before its clusters were freed
+        fs.mark_directory_entry_deleted(root, "A.TXT").unwrap();
+
+        let mut fs = mount(fs.disk);
+        assert!(fs.find_file_in_directory(root, "A.TXT").unwrap().is_none());
+        assert_eq!(
+            fs.get_next_cluster(file.first_cluster).unwrap(),
+            cluster_values::FREE
+        );
+        assert_eq!(pending_record(&mut fs), None);
+    }
+
+    #[test_case]
+    fn test_failed_move_is_rolled_back() {
+        let (mut fs, file, dest) = sample_volume();
+        let root = fs.boot_sector.root_cluster;
+
+        // Removing the source entry is the last step of a move
+        fs.disk.locked_sector = Some(fs.cluster_to_sector(root));
+        let result = fs.move_entry(root, "A.TXT", dest, "A.TXT");
+        fs.disk.locked_sector = None;
+
+        assert!(result.is_err());
+        assert_ne!(result, Err(JOURNAL_PENDING));
+        let source = fs.find_file_in_directory(root, "A.TXT").unwrap().unwrap();
+        assert_eq!(source.first_cluster, file.first_cluster);
+        assert!(fs.find_file_in_directory(dest, "A.TXT").unwrap().is_none());
+        assert_eq!(pending_record(&mut fs), None);
+        assert!(fs.pending_journal.is_none());
+    }
+
+    #[test_case]
+    fn test_failed_delete_stays_pending() {
+        let (mut fs, file, _) = sample_volume();
+        let root = fs.boot_sector.root_cluster;
+
+        // The entry is removed, then freeing the chain fails
+        fs.disk.locked_sector = Some(fs.fat_start_sector);
+        let result = fs.delete_file(root, "A.TXT");
+        fs.disk.locked_sector = None;
+
+        assert_eq!(result, Err(JOURNAL_PENDING));
+        assert!(matches!(
+            pending_record(&mut fs),
+            Some(JournalEntry::Delete { .. })
+        ));
+
+        let mut fs = mount(fs.disk);
+        assert!(fs.find_file_in_directory(root, "A.TXT").unwrap().is_none());
+        assert_eq!(
+            fs.get_next_cluster(file.first_cluster).unwrap(),
+            cluster_values::FREE
+        );
+        assert_eq!(pending_record(&mut fs), None);
+    }
+}
@@ -0,0 +1,299 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Reserved file in the root directory holding the intent log.
+/// It is a single preallocated cluster, written in place with raw cluster writes.
+pub const JOURNAL_FILE_NAME: &str = "JOURNAL.SYS";
+
+const JOURNAL_MAGIC: [u8; 4] = *b"GJNL";
+const HEADER_SIZE: usize = 12;
+
+const STATE_CLEAN: u8 = 0;
+const STATE_PENDING: u8 = 1;
+
+const OP_MOVE: u8 = 1;
+const OP_UPDATE: u8 = 2;
+const OP_DELETE: u8 = 3;
+
+/// A multi-step metadata operation, recorded before any of its steps hit the disk.
+/// Every variant carries enough to be replayed from scratch at mount time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JournalEntry {
+    /// Directory entry moved from one directory (or name) to another
+    Move {
+        source_dir: u32,
+        source_name: String,
+        dest_dir: u32,
+        dest_name: String,
+        first_cluster: u32,
+        size: u32,
+        is_directory: bool,
+        attributes: u8,
+    },
+    /// File contents rewritten; the new data is already on disk, only the
+    /// FAT chain and directory entry remain. Cluster fields are 0 when unused.
+    Update {
+        dir_cluster: u32,
+        name: String,
+        new_first_cluster: u32,
+        new_size: u32,
+        link_from: u32,
+        link_to: u32,
+        truncate_at: u32,
+        free_from: u32,
+    },
+    /// Entry removed and its cluster chain released
+    Delete {
+        dir_cluster: u32,
+        name: String,
+        first_cluster: u32,
+    },
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_str(out: &mut Vec<u8>, value: &str) {
+    let bytes = value.as_bytes();
+    out.extend_from_slice(&(bytes.len() as u16).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn u8(&mut self) -> Option<u8> {
+        let value = *self.data.get(self.pos)?;
+        self.pos += 1;
+        Some(value)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let bytes = self.data.get(self.pos..self.pos + 4)?;
+        self.pos += 4;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn str(&mut self) -> Option<String> {
+        let len_bytes = self.data.get(self.pos..self.pos + 2)?;
+        let len = u16::from_le_bytes([len_bytes[0], len_bytes[1]]) as usize;
+        self.pos += 2;
+
+        let bytes = self.data.get(self.pos..self.pos + len)?;
+        self.pos += len;
+        core::str::from_utf8(bytes).ok().map(|s| s.to_string())
+    }
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.iter()
+        .fold(0u32, |acc, &b| acc.rotate_left(5) ^ (b as u32))
+}
+
+/// Serialize a pending record.
+/// Layout: magic, state, op, payload length (u16), checksum (u32), payload.
+pub fn encode(entry: &JournalEntry) -> Vec<u8> {
+    let mut payload = Vec::new();
+
+    let op = match entry {
+        JournalEntry::Move {
+            source_dir,
+            source_name,
+            dest_dir,
+            dest_name,
+            first_cluster,
+            size,
+            is_directory,
+            attributes,
+        } => {
+            put_u32(&mut payload, *source_dir);
+            put_str(&mut payload, source_name);
+            put_u32(&mut payload, *dest_dir);
+            put_str(&mut payload, dest_name);
+            put_u32(&mut payload, *first_cluster);
+            put_u32(&mut payload, *size);
+            payload.push(*is_directory as u8);
+            payload.push(*attributes);
+            OP_MOVE
+        }
+        JournalEntry::Update {
+            dir_cluster,
+            name,
+            new_first_cluster,
+            new_size,
+            link_from,
+            link_to,
+            truncate_at,
+            free_from,
+        } => {
+            put_u32(&mut payload, *dir_cluster);
+            put_str(&mut payload, name);
+            put_u32(&mut payload, *new_first_cluster);
+            put_u32(&mut payload, *new_size);
+            put_u32(&mut payload, *link_from);
+            put_u32(&mut payload, *link_to);
+            put_u32(&mut payload, *truncate_at);
+            put_u32(&mut payload, *free_from);
+            OP_UPDATE
+        }
+        JournalEntry::Delete {
+            dir_cluster,
+            name,
+            first_cluster,
+        } => {
+            put_u32(&mut payload, *dir_cluster);
+            put_str(&mut payload, name);
+            put_u32(&mut payload, *first_cluster);
+            OP_DELETE
+        }
+    };
+
+    let mut record = Vec::with_capacity(HEADER_SIZE + payload.len());
+    record.extend_from_slice(&JOURNAL_MAGIC);
+    record.push(STATE_PENDING);
+    record.push(op);
+    record.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+    put_u32(&mut record, checksum(&payload));
+    record.extend_from_slice(&payload);
+    record
+}
+
+/// Header marking the journal as clean (no operation in flight)
+pub fn clean_header() -> [u8; HEADER_SIZE] {
+    let mut header = [0u8; HEADER_SIZE];
+    header[..4].copy_from_slice(&JOURNAL_MAGIC);
+    header[4] = STATE_CLEAN;
+    header
+}
+
+/// Decode the pending record of a journal cluster.
+/// Returns None when the journal is clean, uninitialized or torn.
+pub fn decode(data: &[u8]) -> Option<JournalEntry> {
+    if data.len() < HEADER_SIZE || data[..4] != JOURNAL_MAGIC || data[4] != STATE_PENDING {
+        return None;
+    }
+
+    let op = data[5];
+    let len = u16::from_le_bytes([data[6], data[7]]) as usize;
+    let expected = u32::from_le_bytes([data[8], data[9], data[10], data[11]]);
+    let payload = data.get(HEADER_SIZE..HEADER_SIZE + len)?;
+
+    if checksum(payload) != expected {
+        return None;
+    }
+
+    let mut r = Reader {
+        data: payload,
+        pos: 0,
+    };
+
+    match op {
+        OP_MOVE => Some(JournalEntry::Move {
+            source_dir: r.u32()?,
+            source_name: r.str()?,
+            dest_dir: r.u32()?,
+            dest_name: r.str()?,
+            first_cluster: r.u32()?,
+            size: r.u32()?,
+            is_directory: r.u8()? != 0,
+            attributes: r.u8()?,
+        }),
+        OP_UPDATE => Some(JournalEntry::Update {
+            dir_cluster: r.u32()?,
+            name: r.str()?,
+            new_first_cluster: r.u32()?,
+            new_size: r.u32()?,
+            link_from: r.u32()?,
+            link_to: r.u32()?,
+            truncate_at: r.u32()?,
+            free_from: r.u32()?,
+        }),
+        OP_DELETE => Some(JournalEntry::Delete {
+            dir_cluster: r.u32()?,
+            name: r.str()?,
+            first_cluster: r.u32()?,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn sample_entries() -> Vec<JournalEntry> {
+        vec![
+            JournalEntry::Move {
+                source_dir: 2,
+                source_name: "notes.txt".to_string(),
+                dest_dir: 7,
+                dest_name: "Old Notes.txt".to_string(),
+                first_cluster: 12,
+                size: 4100,
+                is_directory: false,
+                attributes: 0x21,
+            },
+            JournalEntry::Update {
+                dir_cluster: 5,
+                name: "log.txt".to_string(),
+                new_first_cluster: 30,
+                new_size: 9000,
+                link_from: 31,
+                link_to: 40,
+                truncate_at: 0,
+                free_from: 0,
+            },
+            JournalEntry::Delete {
+                dir_cluster: 2,
+                name: "ção.txt".to_string(),
+                first_cluster: 0,
+            },
+        ]
+    }
+
+    #[test_case]
+    fn test_encode_decode_round_trip() {
+        for entry in sample_entries() {
+            assert_eq!(decode(&encode(&entry)), Some(entry));
+        }
+    }
+
+    #[test_case]
+    fn test_decode_pending_record_from_cluster() {
+        // At mount the record is read back with the rest of its cluster
+        for entry in sample_entries() {
+            let record = encode(&entry);
+            let mut cluster = vec![0u8; 512];
+            cluster[..record.len()].copy_from_slice(&record);
+            assert_eq!(decode(&cluster), Some(entry));
+        }
+    }
+
+    #[test_case]
+    fn test_clean_journal_has_nothing_to_replay() {
+        assert_eq!(decode(&clean_header()), None);
+        assert_eq!(decode(&[0u8; 512]), None);
+        assert_eq!(decode(&[]), None);
+    }
+
+    #[test_case]
+    fn test_torn_record_is_ignored() {
+        let record = encode(&sample_entries()[0]);
+
+        let mut corrupted = record.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0xFF;
+        assert_eq!(decode(&corrupted), None);
+
+        assert_eq!(decode(&record[..record.len() - 1]), None);
+
+        let mut bad_magic = record.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(decode(&bad_magic), None);
+    }
+}
@@ -1,56 +1,74 @@
 use crate::fs::disk::AtaDisk;
-use crate::fs::fat32::{Fat32FileSystem, FileEntry};
+use crate::fs::fat32::{Fat32FileSystem, FileEntry, JOURNAL_PENDING, attributes};
+use crate::fs::journal::JOURNAL_FILE_NAME;
+use crate::fs::metadata::{
+    self, DEFAULT_DIR_MODE, DEFAULT_FILE_MODE, DEFAULT_SYMLINK_MODE, FileStat, FileType,
+    MetadataRecord, SIDECAR_NAME,
+};
+use crate::fs::search::{FindQuery, Finder};
+use crate::{debug, info, warn};
 use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use crossbeam_queue::ArrayQueue;
 use lazy_static::lazy_static;
 use spin::Mutex;
 use x86_64::instructions::interrupts::{self, without_interrupts};
 
 lazy_static! {
     pub static ref FILESYSTEM: Mutex<Option<Fat32FileSystem<AtaDisk>>> = Mutex::new(None);
+    static ref WATCHES: Mutex<Vec<Watch>> = Mutex::new(Vec::new());
 }
 
+static NEXT_WATCH_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Events kept per watcher before the oldest ones start being dropped
+const WATCH_QUEUE_CAPACITY: usize = 16;
+
+/// Maximum number of symbolic links followed while resolving a path
+const MAX_SYMLINK_DEPTH: usize = 8;
+
 /// Initialize the filesystem
 pub fn init_filesystem() -> Result<(), &'static str> {
-    crate::serial_println!("Initializing filesystem...");
+    info!("Initializing filesystem...");
 
     // Try primary master first (drive 0)
-    crate::serial_println!("Trying primary master drive (0)...");
+    debug!("Trying primary master drive (0)...");
     let mut disk = AtaDisk::new_primary(0);
     if let Ok(_) = disk.init() {
-        crate::serial_println!("Primary master initialized successfully");
+        debug!("Primary master initialized successfully");
         match Fat32FileSystem::new(disk) {
             Ok(filesystem) => {
-                crate::serial_println!("FAT32 filesystem found on primary master");
+                info!("FAT32 filesystem found on primary master");
                 *FILESYSTEM.lock() = Some(filesystem);
                 return Ok(());
             }
             Err(e) => {
-                crate::serial_println!("Primary master is not FAT32: {}", e);
+                warn!("Primary master is not FAT32: {}", e);
             }
         }
     } else {
-        crate::serial_println!("Failed to initialize primary master");
+        warn!("Failed to initialize primary master");
     }
 
     // Try primary slave (drive 1)
-    crate::serial_println!("Trying primary slave drive (1)...");
+    debug!("Trying primary slave drive (1)...");
     let mut disk = AtaDisk::new_primary(1);
     if let Ok(_) = disk.init() {
-        crate::serial_println!("Primary slave initialized successfully");
+        debug!("Primary slave initialized successfully");
         match Fat32FileSystem::new(disk) {
             Ok(filesystem) => {
-                crate::serial_println!("FAT32 filesystem found on primary slave");
+                info!("FAT32 filesystem found on primary slave");
                 *FILESYSTEM.lock() = Some(filesystem);
                 return Ok(());
             }
             Err(e) => {
-                crate::serial_println!("Primary slave is not FAT32: {}", e);
+                warn!("Primary slave is not FAT32: {}", e);
             }
         }
     } else {
-        crate::serial_println!("Failed to initialize primary slave");
+        warn!("Failed to initialize primary slave");
     }
 
     Err("No FAT32 filesystem found on any drive")
@@ -118,7 +136,7 @@ fn resolve_path_for_operation(
 
 /// List files in a directory (path-based)
 pub fn list_directory(path: &str) -> Result<Vec<FileEntry>, &'static str> {
-    let (dir_cluster, filename) = resolve_path(path)?;
+    let (dir_cluster, filename) = resolve_path(&follow_symlinks(path)?)?;
 
     // If filename is provided, we're looking for a specific file
     if let Some(_) = filename {
@@ -126,16 +144,23 @@ pub fn list_directory(path: &str) -> Result<Vec<FileEntry>, &'static str> {
     }
 
     let mut fs_guard = FILESYSTEM.lock();
-    match fs_guard.as_mut() {
+    let (entries, is_root) = match fs_guard.as_mut() {
         Some(fs) => {
             if fs.is_root_directory(dir_cluster) {
-                fs.list_root_directory()
+                (fs.list_root_directory()?, true)
             } else {
-                fs.list_directory(dir_cluster)
+                (fs.list_directory(dir_cluster)?, false)
             }
         }
-        None => Err("Filesystem not initialized"),
-    }
+        None => return Err("Filesystem not initialized"),
+    };
+
+    // The metadata sidecar and the journal are implementation details, never show them
+    Ok(entries
+        .into_iter()
+        .filter(|entry| entry.name != SIDECAR_NAME)
+        .filter(|entry| !(is_root && entry.name == JOURNAL_FILE_NAME))
+        .collect())
 }
 
 /// Find a file by path
@@ -161,6 +186,7 @@ pub fn find_file(path: &str) -> Result<Option<FileEntry>, &'static str> {
 
 /// Read a file's content by path
 pub fn read_file(path: &str) -> Result<Vec<u8>, &'static str> {
+    let path = &follow_symlinks(path)?;
     let file_entry = find_file(path)?.ok_or("File not found")?;
 
     if file_entry.is_directory {
@@ -186,8 +212,8 @@ pub fn read_file(path: &str) -> Result<Vec<u8>, &'static str> {
                 // We ignore the error here since read operation was successful
                 // but log if there's an issue updating access time
                 if let Err(_) = fs.update_file_last_access(dir_cluster, &filename) {
-                    crate::serial_println!(
-                        "Warning: Failed to update last access time for file: {}",
+                    warn!(
+                        "Failed to update last access time for file: {}",
                         path
                     );
                 }
@@ -222,7 +248,10 @@ pub fn create_file(path: &str, data: &[u8]) -> Result<(), &'static str> {
             Some(fs) => fs.create_file(dir_cluster, &filename, data),
             None => Err("Filesystem not initialized"),
         }
-    })
+    })?;
+
+    notify(path, FsEventKind::Created);
+    Ok(())
 }
 
 /// Create a text file with path-based addressing
@@ -232,6 +261,8 @@ pub fn create_text_file(path: &str, content: &str) -> Result<(), &'static str> {
 
 /// Delete a file by path
 pub fn delete_file(path: &str) -> Result<(), &'static str> {
+    check_not_reserved(path)?;
+
     let (dir_cluster, filename) = resolve_path(path)?;
 
     let filename = match filename {
@@ -245,7 +276,11 @@ pub fn delete_file(path: &str) -> Result<(), &'static str> {
             Some(fs) => fs.delete_file(dir_cluster, &filename),
             None => Err("Filesystem not initialized"),
         }
-    })
+    })?;
+
+    forget_metadata(path);
+    notify(path, FsEventKind::Deleted);
+    Ok(())
 }
 
 /// Create a new directory with path-based addressing
@@ -263,7 +298,10 @@ pub fn create_directory(path: &str) -> Result<(), &'static str> {
             Some(fs) => fs.create_directory(parent_cluster, &dirname),
             None => Err("Filesystem not initialized"),
         }
-    })
+    })?;
+
+    notify(path, FsEventKind::Created);
+    Ok(())
 }
 
 /// Delete a directory by path
@@ -275,17 +313,34 @@ pub fn delete_directory(path: &str) -> Result<(), &'static str> {
         None => return Err("Cannot delete root directory"),
     };
 
+    // A sidecar left behind by chmod/chown would otherwise keep the directory "not empty"
+    let only_dots = list_directory(path)
+        .is_ok_and(|entries| entries.iter().all(|e| e.name == "." || e.name == ".."));
+    if only_dots {
+        let sidecar_path = join_path(path, SIDECAR_NAME);
+        if let Ok(Some(_)) = find_file(&sidecar_path) {
+            delete_file(&sidecar_path)?;
+        }
+    }
+
     interrupts::without_interrupts(|| {
         let mut fs_guard = FILESYSTEM.lock();
         match fs_guard.as_mut() {
             Some(fs) => fs.delete_directory(parent_cluster, &dirname),
             None => Err("Filesystem not initialized"),
         }
-    })
+    })?;
+
+    forget_metadata(path);
+    notify(path, FsEventKind::Deleted);
+    Ok(())
 }
 
 /// Write data to an existing file by path
 pub fn write_file(path: &str, data: &[u8]) -> Result<(), &'static str> {
+    let path = &follow_symlinks(path)?;
+    check_not_reserved(path)?;
+
     if let Some(file_entry) = find_file(path)? {
         if file_entry.is_directory {
             return Err("Path points to a directory, not a file");
@@ -305,7 +360,10 @@ pub fn write_file(path: &str, data: &[u8]) -> Result<(), &'static str> {
                 }
                 None => Err("Filesystem not initialized"),
             }
-        })
+        })?;
+
+        notify(path, FsEventKind::Modified);
+        Ok(())
     } else {
         // File doesn't exist, create it
         create_file(path, data)
@@ -422,6 +480,9 @@ pub fn copy_directory(source_path: &str, dest_path: &str) -> Result<(), &'static
 
 /// Move a file or directory from source path to destination path
 pub fn move_item(source_path: &str, dest_path: &str) -> Result<(), &'static str> {
+    check_not_reserved(source_path)?;
+    check_not_reserved(dest_path)?;
+
     // Check if source exists
     let is_directory = match path_exists(source_path)? {
         Some(is_dir) => is_dir,
@@ -433,6 +494,10 @@ pub fn move_item(source_path: &str, dest_path: &str) -> Result<(), &'static str>
         return Err("Destination already exists");
     }
 
+    // Remember the source metadata so it can follow the entry
+    let (source_parent, source_name) = split_parent(source_path);
+    let source_record = metadata::find_record(&read_sidecar(&source_parent), &source_name).cloned();
+
     // Resolve source and destination paths
     let (source_dir_cluster, source_filename) = resolve_path(source_path)?;
     let source_filename = source_filename.ok_or("Invalid source path")?;
@@ -455,10 +520,12 @@ pub fn move_item(source_path: &str, dest_path: &str) -> Result<(), &'static str>
     });
 
     match optimized_result {
-        Ok(()) => Ok(()),
+        Ok(()) => {}
+        // The move will be finished from the journal, a copy would duplicate it
+        Err(e) if e == JOURNAL_PENDING => return Err(e),
         Err(_) => {
             // Fall back to copy+delete if optimized move fails
-            crate::serial_println!("Optimized move failed, falling back to copy+delete");
+            warn!("Optimized move failed, falling back to copy+delete");
 
             // Copy the item first
             if is_directory {
@@ -470,9 +537,28 @@ pub fn move_item(source_path: &str, dest_path: &str) -> Result<(), &'static str>
                 // Delete the source file
                 delete_file(source_path)?;
             }
-            Ok(())
         }
     }
+
+    forget_metadata(source_path);
+    if let Some(mut record) = source_record {
+        let (dest_parent, dest_name) = split_parent(dest_path);
+        record.name = dest_name;
+
+        let mut records = read_sidecar(&dest_parent);
+        metadata::upsert_record(&mut records, record);
+        if let Err(e) = write_sidecar(&dest_parent, &records) {
+            warn!("Failed to move metadata to {}: {}", dest_path, e);
+        }
+    }
+
+    notify(
+        source_path,
+        FsEventKind::Renamed {
+            to: dest_path.to_string(),
+        },
+    );
+    Ok(())
 }
 
 /// Rename a file or directory (move within the same parent directory)
@@ -504,3 +590,548 @@ pub fn rename_item(old_path: &str, new_name: &str) -> Result<(), &'static str> {
     // Use move_item to perform the rename
     move_item(old_path, &new_path)
 }
+
+/// Join a directory path and an entry name
+fn join_path(dir: &str, name: &str) -> String {
+    if dir == "/" || dir.is_empty() {
+        format!("/{}", name)
+    } else {
+        format!("{}/{}", dir.trim_end_matches('/'), name)
+    }
+}
+
+/// Split a path into its parent directory and final component
+fn split_parent(path: &str) -> (String, String) {
+    let trimmed = path.trim_end_matches('/');
+    match trimmed.rfind('/') {
+        Some(0) => ("/".to_string(), trimmed[1..].to_string()),
+        Some(last_slash) => (
+            trimmed[..last_slash].to_string(),
+            trimmed[last_slash + 1..].to_string(),
+        ),
+        None => ("/".to_string(), trimmed.to_string()),
+    }
+}
+
+fn is_root_path(path: &str) -> bool {
+    path.trim_end_matches('/').is_empty()
+}
+
+/// The journal is written in place by the filesystem, freeing or reallocating
+/// its cluster would corrupt whatever file reuses it
+fn check_not_reserved(path: &str) -> Result<(), &'static str> {
+    let (parent, name) = split_parent(path);
+    if is_root_path(&parent) && name.eq_ignore_ascii_case(JOURNAL_FILE_NAME) {
+        return Err("JOURNAL.SYS is reserved by the filesystem");
+    }
+    Ok(())
+}
+
+/// Find the directory entry for a path, which may be a file or a directory
+fn find_entry(path: &str) -> Result<Option<FileEntry>, &'static str> {
+    let (parent_cluster, name) = resolve_path_for_operation(path, true)?;
+
+    let name = match name {
+        Some(name) => name,
+        None => return Ok(None), // The root has no entry of its own
+    };
+
+    without_interrupts(|| {
+        let mut fs_guard = FILESYSTEM.lock();
+        match fs_guard.as_mut() {
+            Some(fs) => fs.find_file_in_directory(parent_cluster, &name),
+            None => Err("Filesystem not initialized"),
+        }
+    })
+}
+
+/// Read the metadata records of a directory, empty if it has no sidecar
+fn read_sidecar(dir_path: &str) -> Vec<MetadataRecord> {
+    let sidecar_path = join_path(dir_path, SIDECAR_NAME);
+
+    let entry = match find_file(&sidecar_path) {
+        Ok(Some(entry)) if !entry.is_directory => entry,
+        _ => return Vec::new(),
+    };
+
+    // Read directly so stat() does not touch the sidecar's access date
+    let data = without_interrupts(|| {
+        let mut fs_guard = FILESYSTEM.lock();
+        match fs_guard.as_mut() {
+            Some(fs) => fs.read_file(entry.first_cluster, entry.size),
+            None => Err("Filesystem not initialized"),
+        }
+    });
+
+    match data.ok().and_then(|data| String::from_utf8(data).ok()) {
+        Some(text) => metadata::parse_sidecar(&text),
+        None => Vec::new(),
+    }
+}
+
+/// Write the metadata records of a directory, removing the sidecar when there are none left
+fn write_sidecar(dir_path: &str, records: &[MetadataRecord]) -> Result<(), &'static str> {
+    let sidecar_path = join_path(dir_path, SIDECAR_NAME);
+
+    if records.is_empty() {
+        return match find_file(&sidecar_path)? {
+            Some(_) => delete_file(&sidecar_path),
+            None => Ok(()),
+        };
+    }
+
+    write_file(
+        &sidecar_path,
+        metadata::serialize_sidecar(records).as_bytes(),
+    )?;
+    set_attributes(&sidecar_path, attributes::HIDDEN | attributes::SYSTEM)
+}
+
+/// Drop the metadata record of a path that no longer exists
+fn forget_metadata(path: &str) {
+    let (parent, name) = split_parent(path);
+    if name == SIDECAR_NAME {
+        return;
+    }
+
+    let mut records = read_sidecar(&parent);
+    if metadata::remove_record(&mut records, &name).is_none() {
+        return;
+    }
+
+    if let Err(e) = write_sidecar(&parent, &records) {
+        warn!("Failed to drop metadata for {}: {}", path, e);
+    }
+}
+
+/// Get Unix-like information about a file, directory or symlink.
+/// A symlink in the last component is not followed, use [`follow_symlinks`]
+/// first for that.
+pub fn stat(path: &str) -> Result<FileStat, &'static str> {
+    stat_entry(&resolve_symlinks(path, false)?)
+}
+
+/// [`stat`] for a path without symlinks in its directories
+fn stat_entry(path: &str) -> Result<FileStat, &'static str> {
+    if is_root_path(path) {
+        let fs_guard = FILESYSTEM.lock();
+        return match fs_guard.as_ref() {
+            Some(fs) => Ok(FileStat::root(fs.get_root_cluster())),
+            None => Err("Filesystem not initialized"),
+        };
+    }
+
+    let entry = find_entry(path)?.ok_or("File not found")?;
+    let (parent, name) = split_parent(path);
+    let records = read_sidecar(&parent);
+
+    Ok(FileStat::from_entry(
+        &entry,
+        metadata::find_record(&records, &name),
+    ))
+}
+
+/// Replace the FAT attribute bits (read-only, hidden, system, archive) of a path
+pub fn set_attributes(path: &str, attrs: u8) -> Result<(), &'static str> {
+    check_not_reserved(path)?;
+
+    let (parent_cluster, name) = resolve_path_for_operation(path, true)?;
+    let name = name.ok_or("Cannot change attributes of the root directory")?;
+
+    without_interrupts(|| {
+        let mut fs_guard = FILESYSTEM.lock();
+        match fs_guard.as_mut() {
+            Some(fs) => fs.set_attributes(parent_cluster, &name, attrs),
+            None => Err("Filesystem not initialized"),
+        }
+    })?;
+
+    notify(path, FsEventKind::Modified);
+    Ok(())
+}
+
+pub fn set_read_only(path: &str, read_only: bool) -> Result<(), &'static str> {
+    let entry = find_entry(path)?.ok_or("File not found")?;
+    let attrs = if read_only {
+        entry.attributes | attributes::READ_ONLY
+    } else {
+        entry.attributes & !attributes::READ_ONLY
+    };
+    set_attributes(path, attrs)
+}
+
+pub fn set_hidden(path: &str, hidden: bool) -> Result<(), &'static str> {
+    let entry = find_entry(path)?.ok_or("File not found")?;
+    let attrs = if hidden {
+        entry.attributes | attributes::HIDDEN
+    } else {
+        entry.attributes & !attributes::HIDDEN
+    };
+    set_attributes(path, attrs)
+}
+
+/// Get the sidecar record for a path, or a fresh one with the default mode
+fn record_for(entry: &FileEntry, records: &[MetadataRecord]) -> MetadataRecord {
+    match metadata::find_record(records, &entry.name) {
+        Some(record) => record.clone(),
+        None => MetadataRecord::new(
+            &entry.name,
+            if entry.is_directory {
+                DEFAULT_DIR_MODE
+            } else {
+                DEFAULT_FILE_MODE
+            },
+        ),
+    }
+}
+
+/// Change the permission bits of a path. Clearing the owner write bit marks
+/// the entry read-only on disk, setting it clears the read-only attribute.
+pub fn chmod(path: &str, mode: u16) -> Result<(), &'static str> {
+    let entry = find_entry(path)?.ok_or("File not found")?;
+    let (parent, _) = split_parent(path);
+
+    let mut records = read_sidecar(&parent);
+    let mut record = record_for(&entry, &records);
+    record.mode = mode & 0o7777;
+    metadata::upsert_record(&mut records, record);
+    write_sidecar(&parent, &records)?;
+
+    set_read_only(path, mode & 0o200 == 0)
+}
+
+/// Change the owner and group of a path
+pub fn chown(path: &str, uid: u16, gid: u16) -> Result<(), &'static str> {
+    let entry = find_entry(path)?.ok_or("File not found")?;
+    let (parent, _) = split_parent(path);
+
+    let mut records = read_sidecar(&parent);
+    let mut record = record_for(&entry, &records);
+    record.uid = uid;
+    record.gid = gid;
+    metadata::upsert_record(&mut records, record);
+    write_sidecar(&parent, &records)?;
+
+    notify(path, FsEventKind::Modified);
+    Ok(())
+}
+
+/// Create a symbolic link at `link_path` pointing to `target`.
+/// The link is stored as a small file containing the target, so it stays readable
+/// on systems that don't know about the sidecar.
+pub fn create_symlink(target: &str, link_path: &str) -> Result<(), &'static str> {
+    if path_exists(link_path)?.is_some() {
+        return Err("Destination already exists");
+    }
+
+    create_file(link_path, target.as_bytes())?;
+
+    let (parent, name) = split_parent(link_path);
+    let mut records = read_sidecar(&parent);
+    let mut record = MetadataRecord::new(&name, DEFAULT_SYMLINK_MODE);
+    record.link_target = Some(target.to_string());
+    metadata::upsert_record(&mut records, record);
+    write_sidecar(&parent, &records)
+}
+
+/// Get the target of a symbolic link
+pub fn read_link(path: &str) -> Result<String, &'static str> {
+    stat(path)?.link_target.ok_or("Not a symbolic link")
+}
+
+/// Resolve a path through any symbolic links. Parts that don't exist are
+/// kept as they are so the result can be used to create files.
+pub fn follow_symlinks(path: &str) -> Result<String, &'static str> {
+    resolve_symlinks(path, true)
+}
+
+/// Resolve the symbolic links in the directories of `path`, and in its last
+/// component when `follow_last` is set. `.` and `..` are resolved on the way.
+fn resolve_symlinks(path: &str, follow_last: bool) -> Result<String, &'static str> {
+    // Components left to look at, the next one last
+    let mut pending: Vec<String> = path
+        .split('/')
+        .rev()
+        .filter(|c| !c.is_empty())
+        .map(|c| c.to_string())
+        .collect();
+    let mut resolved = "/".to_string();
+    let mut links = 0;
+
+    while let Some(component) = pending.pop() {
+        match component.as_str() {
+            "." => continue,
+            ".." => {
+                resolved = split_parent(&resolved).0;
+                continue;
+            }
+            _ => {}
+        }
+
+        let candidate = join_path(&resolved, &component);
+        let follow = follow_last || !pending.is_empty();
+        let target = match stat_entry(&candidate) {
+            Ok(info) if follow && info.file_type == FileType::Symlink => {
+                info.link_target.unwrap_or_default()
+            }
+            _ => {
+                resolved = candidate;
+                continue;
+            }
+        };
+
+        links += 1;
+        if links > MAX_SYMLINK_DEPTH {
+            return Err("Too many levels of symbolic links");
+        }
+
+        // The target replaces the link, relative ones start from its directory
+        if target.starts_with('/') {
+            resolved = "/".to_string();
+        }
+        pending.extend(
+            target
+                .split('/')
+                .rev()
+                .filter(|c| !c.is_empty())
+                .map(|c| c.to_string()),
+        );
+    }
+
+    Ok(resolved)
+}
+
+/// Kind of change reported to filesystem watchers
+#[derive(Debug, Clone, PartialEq)]
+pub enum FsEventKind {
+    Created,
+    Modified,
+    Deleted,
+    /// The entry was moved or renamed, the event path is the old location
+    Renamed { to: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FsEvent {
+    pub path: String,
+    pub kind: FsEventKind,
+}
+
+pub type WatchId = usize;
+
+struct Watch {
+    id: WatchId,
+    path: String,
+    // The path as given, to report the new location when a parent directory moves
+    original: String,
+    queue: ArrayQueue<FsEvent>,
+}
+
+fn trim_watch_path(path: &str) -> String {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        "/".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Normalize a path for watch matching (FAT names are case-insensitive)
+fn normalize_watch_path(path: &str) -> String {
+    trim_watch_path(path).to_uppercase()
+}
+
+/// A watch on a directory sees its direct children, a watch on a file sees the file itself
+fn watch_matches(watch_path: &str, event_path: &str) -> bool {
+    let event_path = normalize_watch_path(event_path);
+    if event_path == watch_path {
+        return true;
+    }
+
+    split_parent(&event_path).0 == watch_path
+}
+
+/// Where a watched entry ends up when `moved` (an ancestor directory) goes to `to`,
+/// or None if the watch is not below `moved`
+fn moved_watch_path(watch: &Watch, moved: &str, to: &str) -> Option<String> {
+    let moved = normalize_watch_path(moved);
+    if moved == "/" || !watch.path.starts_with(&moved) {
+        return None;
+    }
+    if watch.path.as_bytes().get(moved.len()) != Some(&b'/') {
+        return None;
+    }
+
+    let rest = watch.original.get(moved.len()..)?;
+    Some(format!("{}{}", to.trim_end_matches('/'), rest))
+}
+
+/// Subscribe to changes of a file or of the direct children of a directory.
+/// Moving a directory above the watched path is reported as a rename of the
+/// watched path itself. Events are collected with [`poll_events`] and the watch
+/// released with [`unwatch`].
+pub fn watch(path: &str) -> WatchId {
+    let id = NEXT_WATCH_ID.fetch_add(1, Ordering::Relaxed);
+
+    without_interrupts(|| {
+        WATCHES.lock().push(Watch {
+            id,
+            path: normalize_watch_path(path),
+            original: trim_watch_path(path),
+            queue: ArrayQueue::new(WATCH_QUEUE_CAPACITY),
+        });
+    });
+
+    id
+}
+
+pub fn unwatch(id: WatchId) {
+    without_interrupts(|| {
+        WATCHES.lock().retain(|watch| watch.id != id);
+    });
+}
+
+/// Point an existing watch at a different path, dropping any pending events
+pub fn rewatch(id: WatchId, path: &str) {
+    without_interrupts(|| {
+        if let Some(watch) = WATCHES.lock().iter_mut().find(|watch| watch.id == id) {
+            watch.path = normalize_watch_path(path);
+            watch.original = trim_watch_path(path);
+            while watch.queue.pop().is_some() {}
+        }
+    });
+}
+
+/// Drain the pending events of a watch
+pub fn poll_events(id: WatchId) -> Vec<FsEvent> {
+    without_interrupts(|| {
+        let watches = WATCHES.lock();
+        let mut events = Vec::new();
+
+        if let Some(watch) = watches.iter().find(|watch| watch.id == id) {
+            while let Some(event) = watch.queue.pop() {
+                events.push(event);
+            }
+        }
+
+        events
+    })
+}
+
+/// Deliver an event to every matching watch
+fn notify(path: &str, kind: FsEventKind) {
+    if split_parent(path).1 == SIDECAR_NAME {
+        return;
+    }
+
+    without_interrupts(|| {
+        let watches = WATCHES.lock();
+
+        for watch in watches.iter() {
+            let matches = watch_matches(&watch.path, path)
+                || match &kind {
+                    FsEventKind::Renamed { to } => watch_matches(&watch.path, to),
+                    _ => false,
+                };
+
+            let event = if matches {
+                FsEvent {
+                    path: path.to_string(),
+                    kind: kind.clone(),
+                }
+            } else if let FsEventKind::Renamed { to } = &kind {
+                match moved_watch_path(watch, path, to) {
+                    Some(new_path) => FsEvent {
+                        path: watch.original.clone(),
+                        kind: FsEventKind::Renamed { to: new_path },
+                    },
+                    None => continue,
+                }
+            } else {
+                continue;
+            };
+
+            // A slow consumer loses the oldest events rather than the newest
+            watch.queue.force_push(event);
+        }
+    });
+}
+
+/// Recursively search `root` for entries matching a glob pattern (`*`, `?`, `**`)
+/// and returns their paths. For size/date filters or to get results incrementally,
+/// use [`crate::fs::search::Finder`] directly.
+pub fn find(root: &str, pattern: &str) -> Result<Vec<String>, &'static str> {
+    find_with_query(root, FindQuery::new(pattern))
+}
+
+pub fn find_with_query(root: &str, query: FindQuery) -> Result<Vec<String>, &'static str> {
+    match path_exists(root)? {
+        Some(true) => {}
+        Some(false) => return Err("Search root is not a directory"),
+        None => return Err("Search root not found"),
+    }
+
+    let mut finder = Finder::new(root, query);
+    let mut results = Vec::new();
+    while let Some(matches) = finder.step() {
+        results.extend(matches.into_iter().map(|m| m.path));
+    }
+
+    Ok(results)
+}
+
+/// Delete a file, or a directory together with everything below it
+pub fn delete_recursive(path: &str) -> Result<(), &'static str> {
+    match path_exists(path)? {
+        Some(true) => {
+            if is_root_path(path) {
+                return Err("Cannot delete root directory");
+            }
+
+            for entry in list_directory(path)? {
+                if entry.name == "." || entry.name == ".." {
+                    continue;
+                }
+                delete_recursive(&join_path(path, &entry.name))?;
+            }
+            delete_directory(path)
+        }
+        Some(false) => delete_file(path),
+        None => Err("Path not found"),
+    }
+}
+
+/// Total size in bytes of a file, or of all files below a directory
+pub fn disk_usage(path: &str) -> Result<u64, &'static str> {
+    match path_exists(path)? {
+        Some(true) => {
+            let mut total = 0;
+            for entry in list_directory(path)? {
+                if entry.name == "." || entry.name == ".." {
+                    continue;
+                }
+                total += if entry.is_directory {
+                    disk_usage(&join_path(path, &entry.name))?
+                } else {
+                    entry.size as u64
+                };
+            }
+            Ok(total)
+        }
+        Some(false) => Ok(find_file(path)?.map(|e| e.size as u64).unwrap_or(0)),
+        None => Err("Path not found"),
+    }
+}
+
+/// Size of the filesystem and the space still free on it, both in bytes
+pub fn disk_space() -> Result<(u64, u64), &'static str> {
+    interrupts::without_interrupts(|| {
+        let mut fs_guard = FILESYSTEM.lock();
+        let fs = fs_guard.as_mut().ok_or("Filesystem not initialized")?;
+
+        let info = fs.get_filesystem_info();
+        let cluster_size = info.bytes_per_sector as u64 * info.sectors_per_cluster as u64;
+        let free = fs.count_free_clusters()? as u64 * cluster_size;
+        Ok((info.total_clusters as u64 * cluster_size, free))
+    })
+}
@@ -0,0 +1,284 @@
+use crate::fs::fat32::{FileEntry, attributes};
+use crate::time::{Date, DateTime};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Name of the per-directory sidecar file holding Unix-like metadata.
+/// Entries without a record fall back to values derived from the FAT attributes.
+pub const SIDECAR_NAME: &str = ".goofymeta";
+
+pub const DEFAULT_FILE_MODE: u16 = 0o644;
+pub const DEFAULT_DIR_MODE: u16 = 0o755;
+pub const DEFAULT_SYMLINK_MODE: u16 = 0o777;
+
+const WRITE_BITS: u16 = 0o222;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileType {
+    Regular,
+    Directory,
+    Symlink,
+}
+
+/// One line of a sidecar file: `name<TAB>mode<TAB>uid<TAB>gid[<TAB>link target]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataRecord {
+    pub name: String,
+    pub mode: u16,
+    pub uid: u16,
+    pub gid: u16,
+    pub link_target: Option<String>,
+}
+
+impl MetadataRecord {
+    pub fn new(name: &str, mode: u16) -> Self {
+        MetadataRecord {
+            name: name.to_string(),
+            mode,
+            uid: 0,
+            gid: 0,
+            link_target: None,
+        }
+    }
+}
+
+/// POSIX-ish view of a file, combining the FAT entry with its sidecar record
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileStat {
+    pub name: String,
+    pub file_type: FileType,
+    pub mode: u16,
+    pub uid: u16,
+    pub gid: u16,
+    pub size: u32,
+    pub first_cluster: u32,
+    pub attributes: u8,
+    pub link_target: Option<String>,
+
+    pub created_at: DateTime,
+    pub accessed_at: Date,
+    pub modified_at: DateTime,
+}
+
+impl FileStat {
+    pub fn from_entry(entry: &FileEntry, record: Option<&MetadataRecord>) -> Self {
+        let link_target = record.and_then(|r| r.link_target.clone());
+
+        let file_type = if entry.is_directory {
+            FileType::Directory
+        } else if link_target.is_some() {
+            FileType::Symlink
+        } else {
+            FileType::Regular
+        };
+
+        let mut mode = match (record, file_type) {
+            (Some(r), _) => r.mode,
+            (None, FileType::Directory) => DEFAULT_DIR_MODE,
+            (None, FileType::Symlink) => DEFAULT_SYMLINK_MODE,
+            (None, FileType::Regular) => DEFAULT_FILE_MODE,
+        };
+
+        // The FAT read-only bit always wins, it is what the filesystem enforces
+        if entry.is_read_only() {
+            mode &= !WRITE_BITS;
+        }
+
+        FileStat {
+            name: entry.name.clone(),
+            file_type,
+            mode,
+            uid: record.map(|r| r.uid).unwrap_or(0),
+            gid: record.map(|r| r.gid).unwrap_or(0),
+            size: entry.size,
+            first_cluster: entry.first_cluster,
+            attributes: entry.attributes,
+            link_target,
+            created_at: entry.created_at,
+            accessed_at: entry.last_access_at,
+            modified_at: entry.last_write_at,
+        }
+    }
+
+    /// Stat for the root directory, which has no directory entry of its own
+    pub fn root(root_cluster: u32) -> Self {
+        let epoch = DateTime {
+            millis: 0,
+            seconds: 0,
+            minutes: 0,
+            hours: 0,
+            day: 1,
+            month: 1,
+            year: 1980,
+        };
+
+        FileStat {
+            name: "/".to_string(),
+            file_type: FileType::Directory,
+            mode: DEFAULT_DIR_MODE,
+            uid: 0,
+            gid: 0,
+            size: 0,
+            first_cluster: root_cluster,
+            attributes: attributes::DIRECTORY,
+            link_target: None,
+            created_at: epoch,
+            accessed_at: epoch.to_date(),
+            modified_at: epoch,
+        }
+    }
+
+    /// Render the mode like `ls -l` does, e.g. `drwxr-xr-x`
+    pub fn mode_string(&self) -> String {
+        let mut out = String::with_capacity(10);
+        out.push(match self.file_type {
+            FileType::Directory => 'd',
+            FileType::Symlink => 'l',
+            FileType::Regular => '-',
+        });
+
+        for shift in [6, 3, 0] {
+            let bits = (self.mode >> shift) & 0o7;
+            out.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+            out.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+            out.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+        }
+
+        out
+    }
+}
+
+/// Parse the content of a sidecar file, skipping malformed lines
+pub fn parse_sidecar(text: &str) -> Vec<MetadataRecord> {
+    let mut records = Vec::new();
+
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 4 || fields[0].is_empty() {
+            continue;
+        }
+
+        let (Ok(mode), Ok(uid), Ok(gid)) = (
+            u16::from_str_radix(fields[1], 8),
+            fields[2].parse::<u16>(),
+            fields[3].parse::<u16>(),
+        ) else {
+            continue;
+        };
+
+        records.push(MetadataRecord {
+            name: fields[0].to_string(),
+            mode: mode & 0o7777,
+            uid,
+            gid,
+            link_target: fields.get(4).filter(|t| !t.is_empty()).map(|t| t.to_string()),
+        });
+    }
+
+    records
+}
+
+pub fn serialize_sidecar(records: &[MetadataRecord]) -> String {
+    let mut out = String::new();
+
+    for record in records {
+        out.push_str(&format!(
+            "{}\t{:o}\t{}\t{}",
+            record.name, record.mode, record.uid, record.gid
+        ));
+        if let Some(target) = &record.link_target {
+            out.push('\t');
+            out.push_str(target);
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Look up a record by name (FAT names are case-insensitive)
+pub fn find_record<'a>(records: &'a [MetadataRecord], name: &str) -> Option<&'a MetadataRecord> {
+    records
+        .iter()
+        .find(|r| r.name.to_uppercase() == name.to_uppercase())
+}
+
+pub fn remove_record(records: &mut Vec<MetadataRecord>, name: &str) -> Option<MetadataRecord> {
+    let index = records
+        .iter()
+        .position(|r| r.name.to_uppercase() == name.to_uppercase())?;
+    Some(records.remove(index))
+}
+
+/// Insert or replace the record for `record.name`
+pub fn upsert_record(records: &mut Vec<MetadataRecord>, record: MetadataRecord) {
+    remove_record(records, &record.name);
+    records.push(record);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test_case]
+    fn test_parse_sidecar() {
+        let records =
+            parse_sidecar("notes.txt\t600\t1000\t100\nlink\t777\t0\t0\t/docs/notes.txt\n");
+        assert_eq!(
+            records,
+            vec![
+                MetadataRecord {
+                    name: "notes.txt".to_string(),
+                    mode: 0o600,
+                    uid: 1000,
+                    gid: 100,
+                    link_target: None,
+                },
+                MetadataRecord {
+                    name: "link".to_string(),
+                    mode: 0o777,
+                    uid: 0,
+                    gid: 0,
+                    link_target: Some("/docs/notes.txt".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test_case]
+    fn test_parse_sidecar_skips_malformed_lines() {
+        let text =
+            "\nshort\t644\t0\n\t644\t0\t0\nbad\t999\t0\t0\nuid\t644\tx\t0\nok\t17755\t1\t2\t\n";
+        let records = parse_sidecar(text);
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].name, "ok");
+        // Only permission, setuid/setgid and sticky bits are kept
+        assert_eq!(records[0].mode, 0o7755);
+        assert_eq!(records[0].link_target, None);
+    }
+
+    #[test_case]
+    fn test_sidecar_round_trip() {
+        let mut link = MetadataRecord::new("link", DEFAULT_SYMLINK_MODE);
+        link.link_target = Some("../target".to_string());
+        let records = vec![MetadataRecord::new("a.txt", DEFAULT_FILE_MODE), link];
+
+        assert_eq!(parse_sidecar(&serialize_sidecar(&records)), records);
+    }
+
+    #[test_case]
+    fn test_records_are_found_case_insensitively() {
+        let mut records = vec![MetadataRecord::new("Notes.TXT", 0o600)];
+        assert!(find_record(&records, "notes.txt").is_some());
+
+        upsert_record(&mut records, MetadataRecord::new("NOTES.txt", 0o644));
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].mode, 0o644);
+
+        assert!(remove_record(&mut records, "notes.TXT").is_some());
+        assert!(records.is_empty());
+    }
+}
@@ -1,3 +1,7 @@
 pub mod disk;
 pub mod fat32;
+pub mod journal;
 pub mod manager;
+pub mod metadata;
+pub mod search;
+pub mod trash;
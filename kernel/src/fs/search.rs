@@ -0,0 +1,377 @@
+use crate::fs::fat32::FileEntry;
+use crate::fs::manager::list_directory;
+use crate::time::Date;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// What to look for in a [`Finder`] walk
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FindQuery {
+    /// Glob matched against the name, or against the path relative to the
+    /// search root when it contains a `/`. Empty matches everything.
+    pub pattern: String,
+    pub min_size: Option<u32>,
+    pub max_size: Option<u32>,
+    pub modified_after: Option<Date>,
+    pub modified_before: Option<Date>,
+    pub files_only: bool,
+    pub dirs_only: bool,
+}
+
+impl FindQuery {
+    pub fn new(pattern: &str) -> Self {
+        FindQuery {
+            pattern: pattern.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Parse a search string such as `*.txt size>1k after:2024-01-31 type:f`
+    pub fn parse(input: &str) -> Result<Self, &'static str> {
+        let mut query = FindQuery::default();
+        let mut patterns = Vec::new();
+
+        for token in input.split_whitespace() {
+            if let Some(size) = token.strip_prefix("size>") {
+                query.min_size = Some(parse_size(size)?);
+            } else if let Some(size) = token.strip_prefix("size<") {
+                query.max_size = Some(parse_size(size)?);
+            } else if let Some(date) = token.strip_prefix("after:") {
+                query.modified_after = Some(parse_date(date)?);
+            } else if let Some(date) = token.strip_prefix("before:") {
+                query.modified_before = Some(parse_date(date)?);
+            } else if let Some(kind) = token.strip_prefix("type:") {
+                match kind {
+                    "f" | "file" => query.files_only = true,
+                    "d" | "dir" => query.dirs_only = true,
+                    _ => return Err("type must be f or d"),
+                }
+            } else {
+                patterns.push(token);
+            }
+        }
+
+        if patterns.len() > 1 {
+            return Err("Only one name pattern is supported");
+        }
+
+        query.pattern = patterns.first().map(|p| p.to_string()).unwrap_or_default();
+        Ok(query)
+    }
+
+    /// Check an entry, `relative_path` being its path below the search root
+    pub fn matches(&self, relative_path: &str, entry: &FileEntry) -> bool {
+        if self.files_only && entry.is_directory {
+            return false;
+        }
+        if self.dirs_only && !entry.is_directory {
+            return false;
+        }
+
+        if self
+            .min_size
+            .is_some_and(|min| entry.is_directory || entry.size <= min)
+        {
+            return false;
+        }
+        if self
+            .max_size
+            .is_some_and(|max| entry.is_directory || entry.size >= max)
+        {
+            return false;
+        }
+
+        let modified = date_key(&entry.last_write_at.to_date());
+        if self
+            .modified_after
+            .as_ref()
+            .is_some_and(|after| modified < date_key(after))
+        {
+            return false;
+        }
+        if self
+            .modified_before
+            .as_ref()
+            .is_some_and(|before| modified > date_key(before))
+        {
+            return false;
+        }
+
+        if self.pattern.is_empty() {
+            true
+        } else if self.pattern.contains('/') {
+            glob_match(&self.pattern, relative_path)
+        } else {
+            glob_match(&self.pattern, &entry.name)
+        }
+    }
+}
+
+fn date_key(date: &Date) -> (u16, u8, u8) {
+    (date.year, date.month, date.day)
+}
+
+/// Parse a byte count with an optional k/m suffix
+pub fn parse_size(text: &str) -> Result<u32, &'static str> {
+    let lower = text.to_lowercase();
+    let (digits, multiplier) = if let Some(n) = lower.strip_suffix('k') {
+        (n.to_string(), 1024)
+    } else if let Some(n) = lower.strip_suffix('m') {
+        (n.to_string(), 1024 * 1024)
+    } else {
+        (lower, 1)
+    };
+
+    digits
+        .parse::<u32>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or("Invalid size")
+}
+
+/// Parse a YYYY-MM-DD date
+pub fn parse_date(text: &str) -> Result<Date, &'static str> {
+    let mut parts = text.split('-');
+    let (Some(year), Some(month), Some(day), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err("Dates must be YYYY-MM-DD");
+    };
+
+    match (year.parse::<u16>(), month.parse::<u8>(), day.parse::<u8>()) {
+        (Ok(year), Ok(month), Ok(day)) if (1..=12).contains(&month) && (1..=31).contains(&day) => {
+            Ok(Date { day, month, year })
+        }
+        _ => Err("Dates must be YYYY-MM-DD"),
+    }
+}
+
+/// Match a path against a glob. `*` and `?` stay within one path component,
+/// `**` spans any number of components. Matching is case-insensitive like FAT.
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|p| !p.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+    match_components(&pattern, &path)
+}
+
+fn match_components(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some(&"**") => (0..=path.len()).any(|skip| match_components(&pattern[1..], &path[skip..])),
+        Some(first) => {
+            !path.is_empty()
+                && match_component(first, path[0])
+                && match_components(&pattern[1..], &path[1..])
+        }
+    }
+}
+
+fn match_component(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let name: Vec<char> = name.chars().map(|c| c.to_ascii_lowercase()).collect();
+
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<usize> = None;
+    let mut star_n = 0;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some(p);
+            star_n = n;
+            p += 1;
+        } else if let Some(star_p) = star {
+            // Let the last star swallow one more character
+            p = star_p + 1;
+            star_n += 1;
+            n = star_n;
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// True if the text contains glob wildcards
+pub fn has_wildcards(text: &str) -> bool {
+    text.contains('*') || text.contains('?')
+}
+
+/// Split an argument like `/docs/**/*.txt` into the directory to walk (`/docs`)
+/// and the glob to apply below it (`**/*.txt`)
+pub fn split_glob_root(arg: &str) -> (String, String) {
+    let components: Vec<&str> = arg.split('/').filter(|c| !c.is_empty()).collect();
+    let first_glob = components
+        .iter()
+        .position(|c| has_wildcards(c))
+        .unwrap_or(components.len());
+
+    let root = format!("/{}", components[..first_glob].join("/"));
+    let pattern = components[first_glob..].join("/");
+    (root, pattern)
+}
+
+#[derive(Debug, Clone)]
+pub struct FindMatch {
+    pub path: String,
+    pub entry: FileEntry,
+}
+
+/// Incremental recursive search. Each [`Finder::step`] scans a single directory,
+/// so callers can show results as they come in without stalling the desktop.
+pub struct Finder {
+    root: String,
+    query: FindQuery,
+    pending_dirs: Vec<String>,
+    scanned_dirs: usize,
+}
+
+impl Finder {
+    pub fn new(root: &str, query: FindQuery) -> Self {
+        let root = match root.trim_end_matches('/') {
+            "" => "/".to_string(),
+            trimmed => trimmed.to_string(),
+        };
+
+        Finder {
+            pending_dirs: alloc::vec![root.clone()],
+            root,
+            query,
+            scanned_dirs: 0,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pending_dirs.is_empty()
+    }
+
+    pub fn scanned_dirs(&self) -> usize {
+        self.scanned_dirs
+    }
+
+    /// Scan the next directory and return its matches, None once the walk is over
+    pub fn step(&mut self) -> Option<Vec<FindMatch>> {
+        let dir = self.pending_dirs.pop()?;
+        self.scanned_dirs += 1;
+
+        let entries = match list_directory(&dir) {
+            Ok(entries) => entries,
+            Err(_) => return Some(Vec::new()), // Unreadable directories are skipped
+        };
+
+        let mut matches = Vec::new();
+        let mut subdirs = Vec::new();
+
+        for entry in entries {
+            if entry.name == "." || entry.name == ".." {
+                continue;
+            }
+
+            let path = if dir == "/" {
+                format!("/{}", entry.name)
+            } else {
+                format!("{}/{}", dir, entry.name)
+            };
+
+            let relative = if self.root == "/" {
+                &path[1..]
+            } else {
+                &path[self.root.len() + 1..]
+            };
+
+            if entry.is_directory {
+                subdirs.push(path.clone());
+            }
+
+            if self.query.matches(relative, &entry) {
+                matches.push(FindMatch { path, entry });
+            }
+        }
+
+        // Pushed in reverse so directories are visited in listing order
+        self.pending_dirs.extend(subdirs.into_iter().rev());
+
+        Some(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn test_glob_within_component() {
+        assert!(glob_match("*.txt", "notes.txt"));
+        assert!(glob_match("*.TXT", "Notes.txt"));
+        assert!(glob_match("n?tes.*", "notes.md"));
+        assert!(glob_match("*a*b*", "xaxxbx"));
+        assert!(!glob_match("*.txt", "notes.txt.bak"));
+        assert!(!glob_match("?.txt", "ab.txt"));
+        // A single star does not cross a `/`
+        assert!(!glob_match("*.txt", "docs/notes.txt"));
+    }
+
+    #[test_case]
+    fn test_glob_double_star() {
+        assert!(glob_match("**/*.txt", "notes.txt"));
+        assert!(glob_match("**/*.txt", "docs/old/notes.txt"));
+        assert!(glob_match("docs/**", "docs"));
+        assert!(glob_match("docs/**/b", "docs/a/b"));
+        assert!(!glob_match("docs/**/b", "other/a/b"));
+        assert!(glob_match("", ""));
+    }
+
+    #[test_case]
+    fn test_split_glob_root() {
+        assert_eq!(
+            split_glob_root("/docs/**/*.txt"),
+            ("/docs".to_string(), "**/*.txt".to_string())
+        );
+        assert_eq!(
+            split_glob_root("*.md"),
+            ("/".to_string(), "*.md".to_string())
+        );
+        assert_eq!(
+            split_glob_root("/docs/a"),
+            ("/docs/a".to_string(), String::new())
+        );
+    }
+
+    #[test_case]
+    fn test_parse_query() {
+        let query = FindQuery::parse("*.txt size>1k size<2m after:2024-01-31 type:f").unwrap();
+        assert_eq!(
+            query,
+            FindQuery {
+                pattern: "*.txt".to_string(),
+                min_size: Some(1024),
+                max_size: Some(2 * 1024 * 1024),
+                modified_after: Some(Date {
+                    day: 31,
+                    month: 1,
+                    year: 2024
+                }),
+                modified_before: None,
+                files_only: true,
+                dirs_only: false,
+            }
+        );
+
+        assert_eq!(FindQuery::parse("").unwrap(), FindQuery::default());
+        assert!(FindQuery::parse("type:d").unwrap().dirs_only);
+    }
+
+    #[test_case]
+    fn test_parse_query_errors() {
+        assert!(FindQuery::parse("a b").is_err());
+        assert!(FindQuery::parse("type:x").is_err());
+        assert!(FindQuery::parse("size>lots").is_err());
+        assert!(FindQuery::parse("before:2024-13-01").is_err());
+        assert!(FindQuery::parse("after:yesterday").is_err());
+        assert_eq!(parse_size("4294967295k"), Err("Invalid size"));
+    }
+}
@@ -0,0 +1,214 @@
+use crate::fs::manager::{
+    create_directory, delete_recursive, disk_usage, move_item, path_exists, read_text_file,
+    set_hidden, write_file,
+};
+use crate::info;
+use crate::time::get_ms_since_epoch;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+pub const TRASH_DIR: &str = "/.trash";
+
+/// Index of trashed items: `trash name<TAB>original path<TAB>deleted at (ms)<TAB>size`
+const INDEX_PATH: &str = "/.trash/.index";
+
+/// Oldest items are purged once the trash holds more than this
+pub const MAX_TRASH_BYTES: u64 = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrashEntry {
+    /// Name of the item inside the trash directory
+    pub trash_name: String,
+    pub original_path: String,
+    pub deleted_at: i64,
+    pub size: u64,
+}
+
+impl TrashEntry {
+    pub fn trash_path(&self) -> String {
+        format!("{}/{}", TRASH_DIR, self.trash_name)
+    }
+
+    /// Name the item had before it was trashed
+    pub fn original_name(&self) -> &str {
+        self.original_path.rsplit('/').next().unwrap_or(&self.original_path)
+    }
+}
+
+fn ensure_trash_dir() -> Result<(), &'static str> {
+    if path_exists(TRASH_DIR)?.is_none() {
+        create_directory(TRASH_DIR)?;
+        set_hidden(TRASH_DIR, true)?;
+    }
+    Ok(())
+}
+
+fn read_index() -> Vec<TrashEntry> {
+    let Ok(text) = read_text_file(INDEX_PATH) else {
+        return Vec::new();
+    };
+
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != 4 {
+                return None;
+            }
+            Some(TrashEntry {
+                trash_name: fields[0].to_string(),
+                original_path: fields[1].to_string(),
+                deleted_at: fields[2].parse().ok()?,
+                size: fields[3].parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+fn write_index(entries: &[TrashEntry]) -> Result<(), &'static str> {
+    let mut text = String::new();
+    for entry in entries {
+        text.push_str(&format!(
+            "{}\t{}\t{}\t{}\n",
+            entry.trash_name, entry.original_path, entry.deleted_at, entry.size
+        ));
+    }
+
+    let created = path_exists(INDEX_PATH)?.is_none();
+    write_file(INDEX_PATH, text.as_bytes())?;
+    if created {
+        set_hidden(INDEX_PATH, true)?;
+    }
+    Ok(())
+}
+
+/// Items currently in the trash, most recently deleted first
+pub fn list_trash() -> Vec<TrashEntry> {
+    let mut entries = read_index();
+    entries.sort_by_key(|e| Reverse(e.deleted_at));
+    entries
+}
+
+pub fn trash_size() -> u64 {
+    read_index().iter().map(|e| e.size).sum()
+}
+
+/// Move a file or directory into the trash, remembering where it came from
+pub fn move_to_trash(path: &str) -> Result<(), &'static str> {
+    let path = path.trim_end_matches('/');
+    if path.is_empty() {
+        return Err("Cannot trash the root directory");
+    }
+    if path == TRASH_DIR || path.starts_with("/.trash/") {
+        return Err("Item is already in the trash");
+    }
+
+    ensure_trash_dir()?;
+
+    let size = disk_usage(path)?;
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let deleted_at = get_ms_since_epoch();
+
+    // Prefix with the deletion time so equal names from different folders don't clash
+    let mut trash_name = format!("{}_{}", deleted_at, name);
+    let mut suffix = 1;
+    while path_exists(&format!("{}/{}", TRASH_DIR, trash_name))?.is_some() {
+        trash_name = format!("{}-{}_{}", deleted_at, suffix, name);
+        suffix += 1;
+    }
+
+    move_item(path, &format!("{}/{}", TRASH_DIR, trash_name))?;
+
+    let mut entries = read_index();
+    entries.push(TrashEntry {
+        trash_name,
+        original_path: path.to_string(),
+        deleted_at,
+        size,
+    });
+    write_index(&entries)?;
+
+    auto_purge()
+}
+
+/// Put an item back where it was deleted from. Returns the restored path.
+pub fn restore(trash_name: &str) -> Result<String, &'static str> {
+    let mut entries = read_index();
+    let index = entries
+        .iter()
+        .position(|e| e.trash_name == trash_name)
+        .ok_or("Item not found in trash")?;
+    let entry = entries[index].clone();
+
+    if path_exists(&entry.original_path)?.is_some() {
+        return Err("An item with the original name already exists");
+    }
+
+    // Recreate missing parent folders
+    let mut parent = String::new();
+    let components: Vec<&str> = entry.original_path.split('/').filter(|c| !c.is_empty()).collect();
+    for component in &components[..components.len().saturating_sub(1)] {
+        parent = format!("{}/{}", parent, component);
+        if path_exists(&parent)?.is_none() {
+            create_directory(&parent)?;
+        }
+    }
+
+    move_item(&entry.trash_path(), &entry.original_path)?;
+
+    entries.remove(index);
+    write_index(&entries)?;
+    Ok(entry.original_path)
+}
+
+/// Permanently delete a single trashed item
+pub fn delete_permanently(trash_name: &str) -> Result<(), &'static str> {
+    let mut entries = read_index();
+    let index = entries
+        .iter()
+        .position(|e| e.trash_name == trash_name)
+        .ok_or("Item not found in trash")?;
+
+    let entry = entries.remove(index);
+    if path_exists(&entry.trash_path())?.is_some() {
+        delete_recursive(&entry.trash_path())?;
+    }
+    write_index(&entries)
+}
+
+/// Permanently delete everything in the trash
+pub fn empty_trash() -> Result<(), &'static str> {
+    for entry in read_index() {
+        if path_exists(&entry.trash_path())?.is_some() {
+            delete_recursive(&entry.trash_path())?;
+        }
+    }
+    write_index(&[])
+}
+
+/// Drop the oldest items until the trash fits in [`MAX_TRASH_BYTES`]
+pub fn auto_purge() -> Result<(), &'static str> {
+    let mut entries = read_index();
+    entries.sort_by_key(|e| e.deleted_at);
+
+    let mut total: u64 = entries.iter().map(|e| e.size).sum();
+    let mut purged = 0;
+
+    // Always keep the newest item, even if it alone is over the limit
+    while total > MAX_TRASH_BYTES && entries.len() > 1 {
+        let oldest = entries.remove(0);
+        if path_exists(&oldest.trash_path())?.is_some() {
+            delete_recursive(&oldest.trash_path())?;
+        }
+        total -= oldest.size;
+        purged += 1;
+    }
+
+    if purged > 0 {
+        info!("Trash: purged {} old item(s)", purged);
+        write_index(&entries)?;
+    }
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use crate::{
-    BOOT_IST_STACK, BootStack, INTERRUPT_STACK_SIZE, KERNEL_STACK, allocator,
+    BOOT_IST_STACK, BootStack, INTERRUPT_STACK_SIZE, KERNEL_STACK, allocator, error,
     gdt::STACK_SIZE,
-    memory,
+    info, memory,
     memory::BootInfoFrameAllocator,
     println, serial_println,
     sysinfo::{STACK_BASE, get_stack_pointer},
@@ -41,7 +41,7 @@ pub fn start_kernel(boot_info: &'static mut BootInfo) -> (VirtAddr, BootInfoFram
     let mut frame_allocator = unsafe { BootInfoFrameAllocator::init(&boot_info.memory_regions) };
 
     match allocator::init_heap(&mut mapper, &mut frame_allocator, HEAP_START, HEAP_SIZE) {
-        Ok(_) => serial_println!("Heap initialized"),
+        Ok(_) => info!("Heap initialized"),
         Err(e) => panic!("Heap initialization failed: {:?}", e),
     }
 
@@ -61,11 +61,11 @@ pub fn start_kernel(boot_info: &'static mut BootInfo) -> (VirtAddr, BootInfoFram
 
     match crate::fs::manager::init_filesystem() {
         Ok(_) => {
-            serial_println!("Filesystem initialized successfully!");
+            info!("Filesystem initialized successfully!");
             println!("Filesystem ready!");
         }
         Err(e) => {
-            serial_println!("Failed to initialize filesystem: {}", e);
+            error!("Failed to initialize filesystem: {}", e);
             println!("Filesystem initialization failed: {}", e);
         }
     }
@@ -3,7 +3,7 @@ use pc_keyboard::{DecodedKey, HandleControl, Keyboard, ScancodeSet1, layouts};
 use conquer_once::spin::OnceCell;
 use crossbeam_queue::ArrayQueue;
 
-use crate::{print, println, serial_println};
+use crate::{debug, info, print, println};
 
 static SCANCODE_QUEUE: OnceCell<ArrayQueue<u8>> = OnceCell::uninit();
 
@@ -21,13 +21,13 @@ pub fn add_scancode(scancode: u8) {
 }
 
 pub fn init_scancode_queue() {
-    serial_println!("Initializing scancode queue...");
+    debug!("Initializing scancode queue...");
 
     SCANCODE_QUEUE
         .try_init_once(|| ArrayQueue::new(100))
         .expect("ScancodeStream::new should only be called once");
 
-    serial_println!("Scancode queue initialized successfully!");
+    debug!("Scancode queue initialized successfully!");
 }
 
 pub fn print_keypresses() -> ! {
@@ -37,7 +37,7 @@ pub fn print_keypresses() -> ! {
         .try_get()
         .expect("Scancode queue not initialized");
 
-    serial_println!("[KEYBOARD] Starting to print keypresses...");
+    info!("Starting to print keypresses...");
     println!("[KEYBOARD] Press keys to see their output. Press ESC to exit.");
 
     loop {
@@ -41,7 +41,10 @@ pub mod errno;
 pub mod exit;
 pub mod fs;
 pub mod init;
+pub mod log;
 pub mod mm;
+pub mod settings;
+pub mod shell;
 pub mod surface;
 pub mod sysinfo;
 pub mod time;
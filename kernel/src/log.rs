@@ -0,0 +1,206 @@
+//! Kernel log with levels.
+//!
+//! The [`error!`], [`warn!`], [`info!`], [`debug!`] and [`trace!`] macros
+//! tag each message with the module it comes from, print it on the serial
+//! port and keep it in a ring buffer, so `dmesg` and the log viewer can show
+//! it without a serial capture. Only the last [`CAPACITY`] records are kept.
+//!
+//! Logging allocates, so it must not be used from interrupt handlers.
+
+use alloc::{collections::VecDeque, string::String};
+use core::{
+    fmt::{self, Write},
+    sync::atomic::{AtomicU8, Ordering},
+};
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+
+use crate::{serial_println, time::get_local_time};
+
+/// Records kept for `dmesg`, older ones are dropped
+pub const CAPACITY: usize = 128;
+/// Longer messages are cut, the heap is small
+const MAX_MESSAGE: usize = 200;
+
+/// How serious a record is, most serious first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub const ALL: [Level; 5] = [
+        Level::Error,
+        Level::Warn,
+        Level::Info,
+        Level::Debug,
+        Level::Trace,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Level> {
+        match name.trim() {
+            "warning" => Some(Level::Warn),
+            name => Level::ALL.into_iter().find(|level| level.name() == name),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Record {
+    /// Counts up from boot, never reused even after [`clear`]
+    pub sequence: u64,
+    /// Local time as (hours, minutes, seconds)
+    pub time: (u8, u8, u8),
+    pub level: Level,
+    /// `module_path!()` of the caller
+    pub module: &'static str,
+    pub message: String,
+}
+
+impl Record {
+    /// Module path without the crate name, like `desktop::terminal`
+    pub fn tag(&self) -> &'static str {
+        self.module
+            .split_once("::")
+            .map_or(self.module, |(_, path)| path)
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (hours, minutes, seconds) = self.time;
+        write!(
+            f,
+            "[{:02}:{:02}:{:02}] {:<5} {}: {}",
+            hours,
+            minutes,
+            seconds,
+            self.level.name(),
+            self.tag(),
+            self.message
+        )
+    }
+}
+
+struct LogBuffer {
+    records: VecDeque<Record>,
+    next_sequence: u64,
+}
+
+static LOG: Mutex<LogBuffer> = Mutex::new(LogBuffer {
+    records: VecDeque::new(),
+    next_sequence: 0,
+});
+
+/// Least serious level that is still logged
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Debug as u8);
+
+pub fn set_max_level(level: Level) {
+    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn max_level() -> Level {
+    Level::ALL[MAX_LEVEL.load(Ordering::Relaxed) as usize]
+}
+
+#[doc(hidden)]
+pub fn _log(level: Level, module: &'static str, args: fmt::Arguments) {
+    if level > max_level() {
+        return;
+    }
+
+    let mut message = String::new();
+    let _ = message.write_fmt(args);
+    if message.len() > MAX_MESSAGE {
+        let mut end = MAX_MESSAGE;
+        while !message.is_char_boundary(end) {
+            end -= 1;
+        }
+        message.truncate(end);
+    }
+
+    let now = get_local_time();
+    let mut record = Record {
+        sequence: 0,
+        time: (now.hours, now.minutes, now.seconds),
+        level,
+        module,
+        message,
+    };
+    serial_println!("{}", record);
+
+    interrupts::without_interrupts(|| {
+        let mut log = LOG.lock();
+        record.sequence = log.next_sequence;
+        log.next_sequence += 1;
+        if log.records.len() == CAPACITY {
+            log.records.pop_front();
+        }
+        log.records.push_back(record);
+    });
+}
+
+/// Call `f` on every kept record, oldest first. `f` must not log.
+pub fn for_each(mut f: impl FnMut(&Record)) {
+    interrupts::without_interrupts(|| {
+        for record in &LOG.lock().records {
+            f(record);
+        }
+    });
+}
+
+/// Sequence the next record will get; changes whenever something is logged
+pub fn next_sequence() -> u64 {
+    interrupts::without_interrupts(|| LOG.lock().next_sequence)
+}
+
+/// Drop every kept record
+pub fn clear() {
+    interrupts::without_interrupts(|| LOG.lock().records.clear());
+}
+
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)*) => {
+        $crate::log::_log($level, module_path!(), format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => ($crate::log!($crate::log::Level::Error, $($arg)*));
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => ($crate::log!($crate::log::Level::Warn, $($arg)*));
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => ($crate::log!($crate::log::Level::Info, $($arg)*));
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => ($crate::log!($crate::log::Level::Debug, $($arg)*));
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => ($crate::log!($crate::log::Level::Trace, $($arg)*));
+}
@@ -19,7 +19,7 @@ use kernel::tasks::task::NORMAL_PRIORITY;
 use kernel::tasks::{init, jump_to_user_land, scheduler, spawn_process};
 
 use kernel::user_program_loader;
-use kernel::{println, serial_println};
+use kernel::{debug, info, println, serial_println};
 use x86_64::VirtAddr;
 use x86_64::instructions::interrupts;
 
@@ -42,7 +42,7 @@ extern "C" fn start_user_program() {
 #[cfg(processes_enabled)]
 extern "C" fn foo() {
     for _ in 0..20 {
-        serial_println!("hello from task {}", scheduler::get_current_taskid());
+        debug!("hello from task {}", scheduler::get_current_taskid());
     }
 }
 
@@ -61,9 +61,9 @@ fn kernel_main(boot_info: &'static mut BootInfo) -> ! {
 
     #[cfg(processes_enabled)]
     {
-        serial_println!("Init scheduler...");
+        info!("Init scheduler...");
         init();
-        serial_println!("Spawn tasks...");
+        info!("Spawn tasks...");
 
         scheduler::spawn(foo, NORMAL_PRIORITY).unwrap();
 
@@ -82,13 +82,13 @@ fn kernel_main(boot_info: &'static mut BootInfo) -> ! {
         )
         .unwrap();
 
-        serial_println!("Reschedule...");
+        info!("Reschedule...");
 
         kernel::interrupts::init_mouse();
         interrupts::enable();
 
         scheduler::schedule();
-        serial_println!("Returned to kernel_main!");
+        info!("Returned to kernel_main!");
     }
 
     #[cfg(not(processes_enabled))]
@@ -97,6 +97,9 @@ fn kernel_main(boot_info: &'static mut BootInfo) -> ! {
         interrupts::enable();
     }
 
+    // Programs started later (from the terminal) load through the global allocator
+    kernel::memory::init_frame_allocator(frame_allocator);
+
     // Some tests for the heap allocator
     let heap_value = alloc::boxed::Box::new(41);
     println!("heap_value at {:p}", heap_value);
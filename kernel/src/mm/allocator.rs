@@ -10,7 +10,7 @@ use x86_64::{
     },
 };
 
-use crate::serial_println;
+use crate::info;
 
 #[global_allocator]
 pub static mut ALLOCATOR: CountingAllocator = CountingAllocator::empty();
@@ -40,10 +40,9 @@ pub fn init_heap(
         init_allocator(&raw mut ALLOCATOR, heap_start, heap_size);
     }
 
-    serial_println!(
+    info!(
         "Heap initialized successfully at {:#x} with size {} bytes",
-        heap_start,
-        heap_size
+        heap_start, heap_size
     );
 
     Ok(())
@@ -1,20 +1,32 @@
 use x86_64::{
     PhysAddr, VirtAddr,
     structures::paging::{
-        FrameAllocator, Mapper, OffsetPageTable, PageTable, PageTableFlags, PhysFrame, Size2MiB,
-        Size4KiB, mapper::MapToError,
+        FrameAllocator, FrameDeallocator, Mapper, OffsetPageTable, PageTable, PageTableFlags,
+        PhysFrame, Size2MiB, Size4KiB,
+        mapper::{CleanUp, MapToError},
+        page::PageRangeInclusive,
     },
 };
 
 // use bootloader::bootinfo::{MemoryMap, MemoryRegionType};
 use bootloader_api::info::{MemoryRegionKind, MemoryRegions};
 
-use crate::serial_println;
+use conquer_once::spin::OnceCell;
+use spinning_top::Spinlock;
+
+use crate::{debug, error};
+
+/// Frame allocator handed over by `kernel_main` once booting is done, for
+/// code that needs frames later on, like loading user programs
+pub static FRAME_ALLOCATOR: OnceCell<Spinlock<BootInfoFrameAllocator>> = OnceCell::uninit();
 
 /// A FrameAllocator that returns usable frames from the bootloader's memory map.
 pub struct BootInfoFrameAllocator {
     memory_map: &'static MemoryRegions,
     next: usize,
+    /// Frames given back, each one holds the address of the next (0 ends the
+    /// list, frame 0 is never usable)
+    free_list: Option<PhysFrame>,
 }
 
 impl BootInfoFrameAllocator {
@@ -27,6 +39,7 @@ impl BootInfoFrameAllocator {
         BootInfoFrameAllocator {
             memory_map,
             next: 0,
+            free_list: None,
         }
     }
 
@@ -44,6 +57,15 @@ impl BootInfoFrameAllocator {
     }
 }
 
+// The memory map is never written after boot, so the allocator can move
+// into the global one
+unsafe impl Send for BootInfoFrameAllocator {}
+
+/// Make `frame_allocator` available through [`FRAME_ALLOCATOR`]
+pub fn init_frame_allocator(frame_allocator: BootInfoFrameAllocator) {
+    FRAME_ALLOCATOR.init_once(|| Spinlock::new(frame_allocator));
+}
+
 /// Initialize a new OffsetPageTable.
 ///
 /// This function is unsafe because the caller must guarantee that the
@@ -75,6 +97,52 @@ unsafe fn active_level_4_table(physical_memory_offset: VirtAddr) -> &'static mut
     unsafe { &mut *page_table_ptr }
 }
 
+/// Whether all of `start..start + len` is mapped and user accessible in the
+/// active address space, so a system call can read it without faulting.
+/// Only reads the page tables, nothing is borrowed mutably.
+pub fn is_user_memory(start: VirtAddr, len: u64) -> bool {
+    use x86_64::{registers::control::Cr3, structures::paging::Page};
+
+    let Some(&physical_memory_offset) = crate::PHYSICAL_MEMORY_OFFSET.get() else {
+        return false;
+    };
+    if len == 0 {
+        return true;
+    }
+    let Some(last) = start.as_u64().checked_add(len - 1) else {
+        return false;
+    };
+
+    let (level_4_table_frame, _) = Cr3::read();
+    let table_at = |frame: PhysAddr| -> &PageTable {
+        let virt = physical_memory_offset + frame.as_u64();
+        unsafe { &*virt.as_ptr() }
+    };
+
+    let first = Page::<Size4KiB>::containing_address(start);
+    let last = Page::<Size4KiB>::containing_address(VirtAddr::new(last));
+    Page::range_inclusive(first, last).all(|page| {
+        let addr = page.start_address();
+        let mut table = table_at(level_4_table_frame.start_address());
+        for (level, index) in [addr.p4_index(), addr.p3_index(), addr.p2_index(), addr.p1_index()]
+            .into_iter()
+            .enumerate()
+        {
+            let entry = &table[index];
+            let flags = entry.flags();
+            // Every level has to allow user access, not only the last one
+            if !flags.contains(PageTableFlags::PRESENT | PageTableFlags::USER_ACCESSIBLE) {
+                return false;
+            }
+            if level == 3 || flags.contains(PageTableFlags::HUGE_PAGE) {
+                return true;
+            }
+            table = table_at(entry.addr());
+        }
+        false
+    })
+}
+
 /// A FrameAllocator that always returns `None`.
 pub struct EmptyFrameAllocator;
 
@@ -86,12 +154,25 @@ unsafe impl FrameAllocator<Size4KiB> for EmptyFrameAllocator {
 
 unsafe impl FrameAllocator<Size4KiB> for BootInfoFrameAllocator {
     fn allocate_frame(&mut self) -> Option<PhysFrame> {
+        if let Some(frame) = self.free_list {
+            // Only filled once the physical memory is mapped, see deallocate_frame
+            let offset = *crate::PHYSICAL_MEMORY_OFFSET.get()?;
+            let next = unsafe {
+                (offset + frame.start_address().as_u64())
+                    .as_ptr::<u64>()
+                    .read()
+            };
+            self.free_list =
+                (next != 0).then(|| PhysFrame::containing_address(PhysAddr::new(next)));
+            return Some(frame);
+        }
+
         let frame = self.usable_frames().nth(self.next);
         if frame.is_none() {
-            serial_println!("Frame allocation failed at index {}", self.next);
+            error!("Frame allocation failed at index {}", self.next);
             // Count total available frames for debugging
             let total_frames = self.usable_frames().count();
-            serial_println!(
+            error!(
                 "Total usable frames: {}, requested index: {}",
                 total_frames,
                 self.next
@@ -102,6 +183,24 @@ unsafe impl FrameAllocator<Size4KiB> for BootInfoFrameAllocator {
     }
 }
 
+impl FrameDeallocator<Size4KiB> for BootInfoFrameAllocator {
+    /// The memory map can't take frames back, so they go to a list threaded
+    /// through the frames themselves, which allocate_frame uses first
+    unsafe fn deallocate_frame(&mut self, frame: PhysFrame) {
+        let Some(&offset) = crate::PHYSICAL_MEMORY_OFFSET.get() else {
+            return;
+        };
+
+        let next = self.free_list.map_or(0, |f| f.start_address().as_u64());
+        unsafe {
+            (offset + frame.start_address().as_u64())
+                .as_mut_ptr::<u64>()
+                .write(next);
+        }
+        self.free_list = Some(frame);
+    }
+}
+
 unsafe impl FrameAllocator<Size2MiB> for BootInfoFrameAllocator {
     fn allocate_frame(&mut self) -> Option<PhysFrame<Size2MiB>> {
         // Find an aligned 2MiB frame by skipping to next aligned frame
@@ -159,11 +258,11 @@ unsafe impl FrameAllocator<Size2MiB> for BootInfoFrameAllocator {
             // Reserve all 512 frames by advancing next pointer
             self.next = found_index + 512;
             let addr = frame.start_address().as_u64();
-            serial_println!("2MiB frame allocated at address 0x{:x}", addr);
+            debug!("2MiB frame allocated at address 0x{:x}", addr);
             return Some(PhysFrame::containing_address(PhysAddr::new(addr)));
         }
 
-        serial_println!("2MiB frame allocation failed - no suitable aligned region found");
+        error!("2MiB frame allocation failed - no suitable aligned region found");
         None
     }
 }
@@ -251,8 +350,43 @@ impl ProcessAddressSpace {
         Ok(())
     }
 
+    /// Unmap `pages` and give their frames back, along with the page tables
+    /// left empty and the level 4 table itself.
+    ///
+    /// # Safety
+    ///
+    /// No task may run on this address space anymore.
+    pub unsafe fn release(
+        &mut self,
+        pages: &[PageRangeInclusive],
+        frame_allocator: &mut impl FrameDeallocator<Size4KiB>,
+    ) {
+        let page_table_virt =
+            self.physical_memory_offset + self.page_table_frame.start_address().as_u64();
+        let page_table_ptr: *mut PageTable = page_table_virt.as_mut_ptr();
+
+        unsafe {
+            let page_table_ref = &mut *page_table_ptr;
+            let mut mapper = OffsetPageTable::new(page_table_ref, self.physical_memory_offset);
+
+            for &range in pages {
+                for page in range {
+                    // The lower tables are shared with the kernel's, so the
+                    // mapping may be cached in the active address space too
+                    if let Ok((frame, flush)) = mapper.unmap(page) {
+                        flush.flush();
+                        frame_allocator.deallocate_frame(frame);
+                    }
+                }
+                mapper.clean_up_addr_range(range, frame_allocator);
+            }
+
+            frame_allocator.deallocate_frame(self.page_table_frame);
+        }
+    }
+
     pub fn cleanup(&mut self) {
-        serial_println!(
+        debug!(
             "Cleaning up address space for page table frame: {:?}",
             self.page_table_frame.start_address()
         );
@@ -0,0 +1,204 @@
+//! Persistent settings stored in `/etc/goofy.conf`.
+//!
+//! The file holds one `key = value` per line; blank lines and lines starting
+//! with `#` are ignored. Keys nobody knows about are kept as they are, so
+//! saving never drops what a newer build wrote.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use spin::Mutex;
+
+use crate::{
+    desktop::{keyboard::set_keyboard_layout, theme},
+    framebuffer::Color,
+    fs::manager::{create_directory, path_exists, read_text_file, write_file},
+    log::{self, Level},
+    time::{parse_timezone, set_timezone_offset},
+    warn,
+};
+
+pub const SETTINGS_PATH: &str = "/etc/goofy.conf";
+const SETTINGS_DIR: &str = "/etc";
+
+pub const KEYBOARD_LAYOUT: &str = "keyboard.layout";
+pub const DISPLAY_BACKGROUND: &str = "display.background";
+pub const DISPLAY_WALLPAPER: &str = "display.wallpaper";
+pub const THEME: &str = "theme";
+pub const TIMEZONE: &str = "time.zone";
+pub const SESSION_RESTORE: &str = "session.restore";
+pub const SESSION_WINDOWS: &str = "session.windows";
+pub const TERMINAL_SCROLLBACK: &str = "terminal.scrollback";
+pub const LOG_LEVEL: &str = "log.level";
+
+/// Parsed contents of the settings file, in file order
+pub struct SettingsStore {
+    entries: Vec<(String, String)>,
+}
+
+impl Default for SettingsStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SettingsStore {
+    pub const fn new() -> Self {
+        SettingsStore {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut store = SettingsStore::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .unwrap_or(value);
+                store.set(key.trim(), value);
+            }
+        }
+        store
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns false if the key already had this value
+    pub fn set(&mut self, key: &str, value: &str) -> bool {
+        match self.entries.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) if v == value => false,
+            Some((_, v)) => {
+                *v = value.to_string();
+                true
+            }
+            None => {
+                self.entries.push((key.to_string(), value.to_string()));
+                true
+            }
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# Goofy OS settings\n");
+        for (key, value) in &self.entries {
+            text.push_str(key);
+            text.push_str(" = ");
+            text.push_str(value);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+static SETTINGS: Mutex<SettingsStore> = Mutex::new(SettingsStore::new());
+
+/// Bumped on every change so the desktop knows when to apply them again
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Read the settings file. A missing file just means every default applies.
+pub fn load() -> Result<(), &'static str> {
+    if path_exists(SETTINGS_PATH)?.is_none() {
+        return Ok(());
+    }
+
+    *SETTINGS.lock() = SettingsStore::parse(&read_text_file(SETTINGS_PATH)?);
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+    Ok(())
+}
+
+pub fn save() -> Result<(), &'static str> {
+    if path_exists(SETTINGS_DIR)?.is_none() {
+        create_directory(SETTINGS_DIR)?;
+    }
+
+    let text = SETTINGS.lock().to_text();
+    write_file(SETTINGS_PATH, text.as_bytes())
+}
+
+pub fn get(key: &str) -> Option<String> {
+    SETTINGS.lock().get(key).map(|value| value.to_string())
+}
+
+pub fn get_bool(key: &str, default: bool) -> bool {
+    match get(key).as_deref() {
+        Some("true" | "yes" | "1") => true,
+        Some("false" | "no" | "0") => false,
+        _ => default,
+    }
+}
+
+/// Change a setting in memory; call [`save`] to write it out.
+/// Returns false if nothing changed.
+pub fn set(key: &str, value: &str) -> bool {
+    let changed = SETTINGS.lock().set(key, value);
+    if changed {
+        GENERATION.fetch_add(1, Ordering::Relaxed);
+    }
+    changed
+}
+
+pub fn generation() -> usize {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+/// Push the settings that live outside the desktop into the kernel
+pub fn apply() {
+    if let Some(layout) = get(KEYBOARD_LAYOUT) {
+        set_keyboard_layout(&layout);
+    }
+
+    match get(TIMEZONE).map(|zone| (parse_timezone(&zone), zone)) {
+        Some((Some(offset), _)) => set_timezone_offset(offset),
+        Some((None, zone)) => warn!("Unknown time zone: {}", zone),
+        None => set_timezone_offset(0),
+    }
+
+    match get(LOG_LEVEL).map(|name| (Level::parse(&name), name)) {
+        Some((Some(level), _)) => log::set_max_level(level),
+        Some((None, name)) => warn!("Unknown log level: {}", name),
+        None => log::set_max_level(Level::Debug),
+    }
+
+    apply_theme();
+}
+
+/// Switch to the saved theme; returns true if that changed the theme in use
+pub fn apply_theme() -> bool {
+    let name = get(THEME).unwrap_or_else(|| theme::LIGHT.name.to_string());
+    if !theme::THEMES.iter().any(|theme| theme.name == name) {
+        warn!("Unknown theme: {}", name);
+        return false;
+    }
+    theme::set_theme(&name)
+}
+
+/// Parse a `#rrggbb` color
+pub fn parse_color(text: &str) -> Option<Color> {
+    let hex = text.trim().strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some(Color::new(channel(0)?, channel(2)?, channel(4)?))
+}
+
+pub fn format_color(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
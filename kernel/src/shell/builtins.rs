@@ -0,0 +1,851 @@
+//! Commands built into the shell. They read `io.stdin` when given no files,
+//! so they can all sit in the middle of a pipeline.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use super::{CommandIo, Shell, ShellHost, coreutils, parser::is_name};
+use crate::{
+    desktop::{
+        filemanager::recommended_apps,
+        input::add_file_open_request,
+        keyboard::{get_current_layout, set_keyboard_layout},
+        window_manager::APP_NAMES,
+    },
+    fs::{
+        manager::{find_with_query, list_directory, path_exists, read_text_file},
+        search::{FindQuery, has_wildcards, split_glob_root},
+    },
+    log::{self, Level},
+    time::{format_timezone, get_local_time, get_ms_since_epoch, get_timezone_offset},
+};
+
+pub struct Builtin {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub run: fn(&mut Shell, &[String], &mut CommandIo, &mut dyn ShellHost) -> i32,
+}
+
+pub static BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "help",
+        help: "Show this help",
+        run: help,
+    },
+    Builtin {
+        name: "echo",
+        help: "echo [-n] [text...]",
+        run: echo,
+    },
+    Builtin {
+        name: "cat",
+        help: "cat [file...] - Show file contents",
+        run: cat,
+    },
+    Builtin {
+        name: "ls",
+        help: "ls [dir] - List files",
+        run: ls,
+    },
+    Builtin {
+        name: "cd",
+        help: "cd [dir|-] - Change the working directory",
+        run: coreutils::cd,
+    },
+    Builtin {
+        name: "pwd",
+        help: "Show the working directory",
+        run: coreutils::pwd,
+    },
+    Builtin {
+        name: "mkdir",
+        help: "mkdir [-p] <dir>... - Create directories",
+        run: coreutils::mkdir,
+    },
+    Builtin {
+        name: "rmdir",
+        help: "rmdir <dir>... - Remove empty directories",
+        run: coreutils::rmdir,
+    },
+    Builtin {
+        name: "rm",
+        help: "rm [-r] [-f] <path>... - Remove files or directories",
+        run: coreutils::rm,
+    },
+    Builtin {
+        name: "cp",
+        help: "cp [-r] <source>... <dest> - Copy files or directories",
+        run: coreutils::cp,
+    },
+    Builtin {
+        name: "mv",
+        help: "mv <source>... <dest> - Move or rename",
+        run: coreutils::mv,
+    },
+    Builtin {
+        name: "touch",
+        help: "touch <file>... - Create files or update their time",
+        run: coreutils::touch,
+    },
+    Builtin {
+        name: "stat",
+        help: "stat <path>... - Show file details",
+        run: coreutils::stat,
+    },
+    Builtin {
+        name: "ln",
+        help: "ln -s <target> <link> - Create a symbolic link",
+        run: coreutils::ln,
+    },
+    Builtin {
+        name: "chmod",
+        help: "chmod <octal mode> <path>... - Change permissions",
+        run: coreutils::chmod,
+    },
+    Builtin {
+        name: "chown",
+        help: "chown <uid>[:<gid>] <path>... - Change owner and group",
+        run: coreutils::chown,
+    },
+    Builtin {
+        name: "du",
+        help: "du [-s] [path...] - Show disk usage",
+        run: coreutils::du,
+    },
+    Builtin {
+        name: "df",
+        help: "Show free disk space",
+        run: coreutils::df,
+    },
+    Builtin {
+        name: "head",
+        help: "head [-n N] [file...] - Show the first lines",
+        run: coreutils::head,
+    },
+    Builtin {
+        name: "tail",
+        help: "tail [-n N] [file...] - Show the last lines",
+        run: coreutils::tail,
+    },
+    Builtin {
+        name: "find",
+        help: "find [dir] [glob] [size>N] [size<N] [after:D] [before:D] [type:f|d]",
+        run: find,
+    },
+    Builtin {
+        name: "grep",
+        help: "grep [-i] <text> [file|dir|glob...]",
+        run: grep,
+    },
+    Builtin {
+        name: "wc",
+        help: "wc [file...] - Count lines, words and bytes",
+        run: wc,
+    },
+    Builtin {
+        name: "set",
+        help: "set [NAME=value | NAME value] - Show or set variables",
+        run: set,
+    },
+    Builtin {
+        name: "export",
+        help: "export [NAME[=value]...] - Show or export variables",
+        run: export,
+    },
+    Builtin {
+        name: "unset",
+        help: "unset NAME... - Remove variables",
+        run: unset,
+    },
+    Builtin {
+        name: "env",
+        help: "Show exported variables",
+        run: env,
+    },
+    Builtin {
+        name: "source",
+        help: "source <file> [args...] - Run a script in this shell",
+        run: source,
+    },
+    Builtin {
+        name: ".",
+        help: "Same as source",
+        run: source,
+    },
+    Builtin {
+        name: "sh",
+        help: "sh <file> [args...] - Run a script in a new shell",
+        run: sh,
+    },
+    Builtin {
+        name: "test",
+        help: "test <expr> - Check files, strings and numbers",
+        run: test,
+    },
+    Builtin {
+        name: "[",
+        help: "[ <expr> ] - Same as test",
+        run: bracket,
+    },
+    Builtin {
+        name: "true",
+        help: "Do nothing, successfully",
+        run: |_, _, _, _| 0,
+    },
+    Builtin {
+        name: "false",
+        help: "Do nothing, unsuccessfully",
+        run: |_, _, _, _| 1,
+    },
+    Builtin {
+        name: "date",
+        help: "Show current date/time",
+        run: date,
+    },
+    Builtin {
+        name: "time",
+        help: "Show current time",
+        run: time,
+    },
+    Builtin {
+        name: "uptime",
+        help: "Show system uptime",
+        run: uptime,
+    },
+    Builtin {
+        name: "dmesg",
+        help: "dmesg [-l level] [-m module] [-C] - Show the kernel log",
+        run: dmesg,
+    },
+    Builtin {
+        name: "version",
+        help: "Show OS version",
+        run: |_, _, io, _| {
+            io.print("GoofyOS v0.1.0 - Built with Rust");
+            0
+        },
+    },
+    Builtin {
+        name: "open",
+        help: "open <path>... - Open files in their app, folders in the file manager",
+        run: open,
+    },
+    Builtin {
+        name: "run",
+        help: "run <app> [file] - Start a desktop app",
+        run: run_app,
+    },
+    Builtin {
+        name: "setkeyboard",
+        help: "setkeyboard [layout] - Change keyboard layout",
+        run: setkeyboard,
+    },
+];
+
+fn help(_shell: &mut Shell, _args: &[String], io: &mut CommandIo, host: &mut dyn ShellHost) -> i32 {
+    io.print("Available commands:");
+    let host_commands = host.commands().iter().copied();
+    let builtins = BUILTINS.iter().map(|builtin| (builtin.name, builtin.help));
+    for (name, help) in builtins.chain(host_commands) {
+        io.print(&format!("  {:<12}- {}", name, help));
+    }
+    io.print("");
+    io.print("Commands can be chained with ; && || and |, and redirected with > >> <.");
+    io.print("Use \"quotes\" or 'quotes' for spaces and $NAME for variables.");
+    io.print("Scripts run with source <file> or ./file.sh and can use if, for and while.");
+    io.print("Programs (ELF files) run with ./program, apps start with open and run.");
+    0
+}
+
+fn echo(_shell: &mut Shell, args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    match args.split_first() {
+        Some((flag, rest)) if flag == "-n" => io.stdout.push_str(&rest.join(" ")),
+        _ => io.print(&args.join(" ")),
+    }
+    0
+}
+
+/// Text of the files named in `args`, or stdin without any.
+/// Unreadable files are reported and skipped, the flag says if any were.
+fn read_inputs(
+    shell: &Shell,
+    command: &str,
+    args: &[String],
+    io: &mut CommandIo,
+) -> (Vec<(Option<String>, String)>, bool) {
+    if args.is_empty() {
+        return match io.stdin.take() {
+            Some(text) => (alloc::vec![(None, text)], false),
+            None => {
+                io.error(&format!(
+                    "{}: no input, give a file or pipe into it",
+                    command
+                ));
+                (Vec::new(), true)
+            }
+        };
+    }
+
+    let mut inputs = Vec::new();
+    let mut failed = false;
+    for arg in args {
+        let path = shell.resolve_path(arg);
+        match read_text_file(&path) {
+            Ok(text) => inputs.push((Some(path), text)),
+            Err(e) => {
+                io.error(&format!("{}: {}: {}", command, arg, e));
+                failed = true;
+            }
+        }
+    }
+    (inputs, failed)
+}
+
+fn cat(shell: &mut Shell, args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    let (inputs, failed) = read_inputs(shell, "cat", args, io);
+    for (_, text) in inputs {
+        io.stdout.push_str(&text);
+        if !text.is_empty() && !text.ends_with('\n') {
+            io.stdout.push('\n');
+        }
+    }
+    failed as i32
+}
+
+fn ls(shell: &mut Shell, args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    let path = shell.resolve_path(args.first().map(String::as_str).unwrap_or("."));
+    match list_directory(&path) {
+        Ok(entries) => {
+            for entry in entries {
+                if entry.is_directory {
+                    io.print(&format!("{}/", entry.name));
+                } else {
+                    io.print(&entry.name);
+                }
+            }
+            0
+        }
+        Err(e) => {
+            io.error(&format!("ls: {}: {}", path, e));
+            1
+        }
+    }
+}
+
+/// Directory to search and the query words of `find`'s arguments
+pub fn split_find_args<'a>(shell: &Shell, args: &'a [String]) -> (String, &'a [String]) {
+    // A leading path is the directory to search, the rest is the query
+    match args.first() {
+        Some(first)
+            if !has_wildcards(first)
+                && (first.contains('/') || matches!(first.as_str(), "." | ".." | "~")) =>
+        {
+            (shell.resolve_path(first), &args[1..])
+        }
+        _ => (shell.cwd().to_string(), args),
+    }
+}
+
+fn find(shell: &mut Shell, args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    let (root, query_args) = split_find_args(shell, args);
+
+    let query = match FindQuery::parse(&query_args.join(" ")) {
+        Ok(query) => query,
+        Err(e) => {
+            io.error(&format!("find: {}", e));
+            return 2;
+        }
+    };
+
+    match find_with_query(&root, query) {
+        Ok(paths) => {
+            for path in paths {
+                io.print(&path);
+            }
+            0
+        }
+        Err(e) => {
+            io.error(&format!("find: {}: {}", root, e));
+            1
+        }
+    }
+}
+
+pub struct GrepOptions {
+    pub needle: String,
+    pub ignore_case: bool,
+}
+
+impl GrepOptions {
+    /// Split `[-i] <text> [targets...]`, None without a text to look for
+    pub fn parse(args: &[String]) -> Option<(Self, &[String])> {
+        let (ignore_case, args) = match args.first() {
+            Some(flag) if flag == "-i" => (true, &args[1..]),
+            _ => (false, args),
+        };
+        let (needle, targets) = args.split_first()?;
+
+        let options = GrepOptions {
+            needle: if ignore_case {
+                needle.to_lowercase()
+            } else {
+                needle.clone()
+            },
+            ignore_case,
+        };
+        Some((options, targets))
+    }
+
+    pub fn matches(&self, line: &str) -> bool {
+        if self.ignore_case {
+            line.to_lowercase().contains(&self.needle)
+        } else {
+            line.contains(&self.needle)
+        }
+    }
+}
+
+/// Matching lines of a file as `path:line: text`
+pub fn grep_file(path: &str, options: &GrepOptions) -> Vec<String> {
+    // Binary files simply don't match
+    let Ok(content) = read_text_file(path) else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| options.matches(line))
+        .map(|(number, line)| format!("{}:{}: {}", path, number + 1, line))
+        .collect()
+}
+
+fn grep(shell: &mut Shell, args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    let Some((options, targets)) = GrepOptions::parse(args) else {
+        io.error("Usage: grep [-i] <text> [file|dir|glob...]");
+        return 2;
+    };
+
+    let mut found = 0;
+    if targets.is_empty() {
+        let Some(text) = io.stdin.take() else {
+            io.error("grep: no input, give a file or pipe into it");
+            return 2;
+        };
+        for line in text.lines().filter(|line| options.matches(line)) {
+            io.print(line);
+            found += 1;
+        }
+        return if found > 0 { 0 } else { 1 };
+    }
+
+    for target in targets {
+        let target = shell.resolve_path(target);
+        let files = if has_wildcards(&target) {
+            let (root, pattern) = split_glob_root(&target);
+            let mut query = FindQuery::new(&pattern);
+            query.files_only = true;
+            find_with_query(&root, query)
+        } else {
+            match path_exists(&target) {
+                Ok(Some(true)) => {
+                    let mut query = FindQuery::new("");
+                    query.files_only = true;
+                    find_with_query(&target, query)
+                }
+                Ok(Some(false)) => Ok(alloc::vec![target.clone()]),
+                _ => Err("No such file or directory"),
+            }
+        };
+
+        match files {
+            Ok(files) => {
+                for file in files {
+                    for hit in grep_file(&file, &options) {
+                        io.print(&hit);
+                        found += 1;
+                    }
+                }
+            }
+            Err(e) => io.error(&format!("grep: {}: {}", target, e)),
+        }
+    }
+
+    if found > 0 { 0 } else { 1 }
+}
+
+fn wc(shell: &mut Shell, args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    let (inputs, failed) = read_inputs(shell, "wc", args, io);
+    for (name, text) in inputs {
+        let counts = format!(
+            "{:>6} {:>6} {:>6}",
+            text.lines().count(),
+            text.split_whitespace().count(),
+            text.len()
+        );
+        match name {
+            Some(name) => io.print(&format!("{} {}", counts, name)),
+            None => io.print(&counts),
+        }
+    }
+    failed as i32
+}
+
+/// `NAME=value`, or None with the error already reported
+fn split_assignment<'a>(arg: &'a str, io: &mut CommandIo) -> Option<(&'a str, Option<&'a str>)> {
+    let (name, value) = match arg.split_once('=') {
+        Some((name, value)) => (name, Some(value)),
+        None => (arg, None),
+    };
+
+    if is_name(name) {
+        Some((name, value))
+    } else {
+        io.error(&format!("'{}' is not a valid variable name", name));
+        None
+    }
+}
+
+fn set(shell: &mut Shell, args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    match args {
+        [] => {
+            for (name, value, _) in shell.variables() {
+                io.print(&format!("{}={}", name, value));
+            }
+            0
+        }
+        // `set NAME value`
+        [name, value] if !name.contains('=') => match split_assignment(name, io) {
+            Some((name, _)) => {
+                shell.set_var(name, value);
+                0
+            }
+            None => 1,
+        },
+        _ => {
+            let mut status = 0;
+            for arg in args {
+                match split_assignment(arg, io) {
+                    Some((name, Some(value))) => shell.set_var(name, value),
+                    Some((name, None)) => {
+                        io.error(&format!("set: {}: missing '=value'", name));
+                        status = 1;
+                    }
+                    None => status = 1,
+                }
+            }
+            status
+        }
+    }
+}
+
+fn export(shell: &mut Shell, args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    if args.is_empty() {
+        for (name, value, _) in shell.variables().into_iter().filter(|var| var.2) {
+            io.print(&format!("export {}={}", name, value));
+        }
+        return 0;
+    }
+
+    let mut status = 0;
+    for arg in args {
+        match split_assignment(arg, io) {
+            Some((name, Some(value))) => shell.export(name, value),
+            Some((name, None)) => {
+                // Exporting an unknown name creates it empty, like other shells
+                if !shell.mark_exported(name) {
+                    shell.export(name, "");
+                }
+            }
+            None => status = 1,
+        }
+    }
+    status
+}
+
+fn unset(shell: &mut Shell, args: &[String], _io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    for name in args {
+        shell.unset_var(name);
+    }
+    0
+}
+
+fn env(shell: &mut Shell, _args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    for (name, value, _) in shell.variables().into_iter().filter(|var| var.2) {
+        io.print(&format!("{}={}", name, value));
+    }
+    0
+}
+
+fn source(shell: &mut Shell, args: &[String], io: &mut CommandIo, host: &mut dyn ShellHost) -> i32 {
+    match args.split_first() {
+        Some((path, args)) => shell.source(path, args, host),
+        None => {
+            io.error("Usage: source <file> [args...]");
+            2
+        }
+    }
+}
+
+fn sh(shell: &mut Shell, args: &[String], io: &mut CommandIo, host: &mut dyn ShellHost) -> i32 {
+    match args.split_first() {
+        Some((path, args)) => shell.run_script(path, args, host),
+        None => {
+            io.error("Usage: sh <file> [args...]");
+            2
+        }
+    }
+}
+
+fn test(shell: &mut Shell, args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    match evaluate_test(shell, args) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(e) => {
+            io.error(&format!("test: {}", e));
+            2
+        }
+    }
+}
+
+fn bracket(
+    shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    host: &mut dyn ShellHost,
+) -> i32 {
+    match args.split_last() {
+        Some((last, args)) if last == "]" => test(shell, args, io, host),
+        _ => {
+            io.error("[: missing ']'");
+            2
+        }
+    }
+}
+
+/// `test`'s expressions: `! expr`, `-e/-f/-d path`, `-n/-z text`, and
+/// `a = b`, `a != b` or numbers compared with `-eq`, `-ne`, `-lt`, `-le`,
+/// `-gt` and `-ge`
+fn evaluate_test(shell: &Shell, args: &[String]) -> Result<bool, &'static str> {
+    let number = |text: &str| text.trim().parse::<i64>().map_err(|_| "integer expected");
+
+    match args {
+        [] => Ok(false),
+        [not, rest @ ..] if not == "!" => evaluate_test(shell, rest).map(|result| !result),
+        [text] => Ok(!text.is_empty()),
+        [op, operand] => match op.as_str() {
+            "-n" => Ok(!operand.is_empty()),
+            "-z" => Ok(operand.is_empty()),
+            "-e" | "-f" | "-d" => {
+                let kind = path_exists(&shell.resolve_path(operand)).unwrap_or(None);
+                Ok(match op.as_str() {
+                    "-e" => kind.is_some(),
+                    "-f" => kind == Some(false),
+                    _ => kind == Some(true),
+                })
+            }
+            _ => Err("unknown unary operator"),
+        },
+        [left, op, right] => match op.as_str() {
+            "=" | "==" => Ok(left == right),
+            "!=" => Ok(left != right),
+            "-eq" => Ok(number(left)? == number(right)?),
+            "-ne" => Ok(number(left)? != number(right)?),
+            "-lt" => Ok(number(left)? < number(right)?),
+            "-le" => Ok(number(left)? <= number(right)?),
+            "-gt" => Ok(number(left)? > number(right)?),
+            "-ge" => Ok(number(left)? >= number(right)?),
+            _ => Err("unknown binary operator"),
+        },
+        _ => Err("too many arguments"),
+    }
+}
+
+fn date(_shell: &mut Shell, _args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    let datetime = get_local_time();
+    io.print(&format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} {}",
+        datetime.year,
+        datetime.month,
+        datetime.day,
+        datetime.hours,
+        datetime.minutes,
+        datetime.seconds,
+        format_timezone(get_timezone_offset())
+    ));
+    0
+}
+
+fn time(_shell: &mut Shell, _args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    let datetime = get_local_time();
+    io.print(&format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        datetime.hours, datetime.minutes, datetime.seconds, datetime.millis
+    ));
+    0
+}
+
+fn uptime(_shell: &mut Shell, _args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    let uptime_seconds = get_ms_since_epoch() as u64 / 1000;
+    let hours = uptime_seconds / 3600;
+    let minutes = (uptime_seconds % 3600) / 60;
+    let seconds = uptime_seconds % 60;
+    io.print(&format!(
+        "Uptime: {:02}:{:02}:{:02}",
+        hours, minutes, seconds
+    ));
+    0
+}
+
+/// Kernel log records; `-l` keeps that level and the more serious ones,
+/// `-m` the modules whose path starts with the given one
+fn dmesg(_shell: &mut Shell, args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    let mut level = Level::Trace;
+    let mut module: Option<&str> = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-l" => match args.next().and_then(|name| Level::parse(name)) {
+                Some(parsed) => level = parsed,
+                None => {
+                    io.error("dmesg: -l takes error, warn, info, debug or trace");
+                    return 1;
+                }
+            },
+            "-m" => match args.next() {
+                Some(name) => module = Some(name),
+                None => {
+                    io.error("dmesg: -m takes a module path");
+                    return 1;
+                }
+            },
+            "-C" => {
+                log::clear();
+                return 0;
+            }
+            other => {
+                io.error(&format!("dmesg: unknown option {}", other));
+                return 1;
+            }
+        }
+    }
+
+    let mut lines = Vec::new();
+    log::for_each(|record| {
+        let in_module = module.is_none_or(|module| record.tag().starts_with(module));
+        if record.level <= level && in_module {
+            lines.push(record.to_string());
+        }
+    });
+    for line in lines {
+        io.print(&line);
+    }
+    0
+}
+
+/// Hand files to their app through the desktop, like double clicking them
+fn open(shell: &mut Shell, args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    if args.is_empty() {
+        io.error("Usage: open <path>...");
+        return 2;
+    }
+
+    let mut status = 0;
+    for arg in args {
+        let path = shell.resolve_path(arg);
+        let app = match path_exists(&path) {
+            Ok(Some(true)) => "filemanager",
+            Ok(Some(false)) => match recommended_apps(&path) {
+                (Some(app), _) => app,
+                (None, others) => {
+                    io.error(&format!(
+                        "open: {}: no default app, use run <{}> {}",
+                        arg,
+                        others.join("|"),
+                        arg
+                    ));
+                    status = 1;
+                    continue;
+                }
+            },
+            _ => {
+                io.error(&format!("open: {}: No such file or directory", arg));
+                status = 1;
+                continue;
+            }
+        };
+        add_file_open_request(path, app.to_string());
+    }
+    status
+}
+
+fn run_app(shell: &mut Shell, args: &[String], io: &mut CommandIo, _: &mut dyn ShellHost) -> i32 {
+    let Some((app, rest)) = args.split_first() else {
+        io.error("Usage: run <app> [file]");
+        io.error(&format!("Apps: {}", APP_NAMES.join(", ")));
+        return 2;
+    };
+
+    if !APP_NAMES.contains(&app.as_str()) {
+        io.error(&format!(
+            "run: {}: unknown app, try one of: {}",
+            app,
+            APP_NAMES.join(", ")
+        ));
+        return 127;
+    }
+
+    // An empty path just starts the app
+    let path = match rest {
+        [] => String::new(),
+        [file] => {
+            let path = shell.resolve_path(file);
+            if !matches!(path_exists(&path), Ok(Some(_))) {
+                io.error(&format!("run: {}: No such file or directory", file));
+                return 1;
+            }
+            path
+        }
+        _ => {
+            io.error("Usage: run <app> [file]");
+            return 2;
+        }
+    };
+
+    add_file_open_request(path, app.clone());
+    0
+}
+
+fn setkeyboard(
+    _shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    match args.first() {
+        Some(layout_name) => {
+            if set_keyboard_layout(layout_name) {
+                io.print(&format!("Keyboard layout changed to: {}", layout_name));
+                0
+            } else {
+                io.error(&format!(
+                    "Failed to change keyboard layout to: {}",
+                    layout_name
+                ));
+                1
+            }
+        }
+        None => {
+            io.print(&format!(
+                "Current keyboard layout: {:?}",
+                get_current_layout()
+            ));
+            io.print("Usage: setkeyboard <layout>");
+            0
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! The shell on COM1, for driving the system without a display
+//! (`-serial stdio` in QEMU). Input arrives through the UART receive
+//! interrupt and is picked up by [`SerialConsole::poll`] from the desktop
+//! loop, so a command runs between two frames. Kernel logs go to the same
+//! port and may show up in the middle of a line.
+
+use alloc::string::String;
+
+use super::{CommandIo, Shell, ShellHost};
+use crate::{serial, serial_print};
+
+/// Longest line the console keeps, the rest of it is dropped
+const MAX_LINE: usize = 256;
+
+static HOST_COMMANDS: &[(&str, &str)] = &[("clear", "Clear the screen")];
+
+/// Where we are in an escape sequence sent by the other side (arrow keys
+/// and the like), which the console skips
+#[derive(Clone, Copy, PartialEq)]
+enum Escape {
+    None,
+    Started,
+    Csi,
+}
+
+pub struct SerialConsole {
+    shell: Shell,
+    line: String,
+    escape: Escape,
+    /// Terminals send CR LF for Enter, the LF must not run an empty line
+    last_was_cr: bool,
+    /// Output ended in the middle of a line, the prompt goes below it
+    line_open: bool,
+}
+
+impl SerialConsole {
+    /// Start listening on COM1 and show the first prompt
+    pub fn start() -> Self {
+        serial::enable_receive();
+
+        let mut console = SerialConsole {
+            shell: Shell::new(),
+            line: String::new(),
+            escape: Escape::None,
+            last_was_cr: false,
+            line_open: false,
+        };
+        console.write("GoofyOS serial console, type 'help' for available commands\n");
+        console.show_prompt();
+        console
+    }
+
+    /// Handle what was typed since the last call
+    pub fn poll(&mut self) {
+        while let Some(byte) = serial::read_byte() {
+            self.handle_byte(byte);
+        }
+    }
+
+    fn handle_byte(&mut self, byte: u8) {
+        let last_was_cr = core::mem::replace(&mut self.last_was_cr, byte == b'\r');
+
+        match self.escape {
+            Escape::Started => {
+                self.escape = if byte == b'[' {
+                    Escape::Csi
+                } else {
+                    Escape::None
+                };
+                return;
+            }
+            Escape::Csi => {
+                // Parameters until the final byte
+                if (0x40..=0x7e).contains(&byte) {
+                    self.escape = Escape::None;
+                }
+                return;
+            }
+            Escape::None => {}
+        }
+
+        match byte {
+            b'\n' if last_was_cr => {}
+            b'\r' | b'\n' => {
+                serial_print!("\r\n");
+                let line = core::mem::take(&mut self.line);
+                let mut shell = core::mem::take(&mut self.shell);
+                shell.run(&line, self);
+                self.shell = shell;
+                self.show_prompt();
+            }
+            // Backspace and DEL, terminals send either
+            0x08 | 0x7f if !self.line.is_empty() => {
+                self.line.pop();
+                serial_print!("\x08 \x08");
+            }
+            // Ctrl+C drops the line
+            0x03 => {
+                self.line.clear();
+                serial_print!("^C\r\n");
+                self.show_prompt();
+            }
+            0x1b => self.escape = Escape::Started,
+            0x20..=0x7e if self.line.len() < MAX_LINE => {
+                self.line.push(byte as char);
+                serial_print!("{}", byte as char);
+            }
+            _ => {}
+        }
+    }
+
+    fn show_prompt(&mut self) {
+        if core::mem::take(&mut self.line_open) {
+            serial_print!("\r\n");
+        }
+        serial_print!("goofy:{}> ", self.shell.cwd());
+    }
+}
+
+impl ShellHost for SerialConsole {
+    fn write(&mut self, text: &str) {
+        // The other side expects CR LF
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                serial_print!("\r\n");
+            }
+            serial_print!("{}", line);
+        }
+        if !text.is_empty() {
+            self.line_open = !text.ends_with('\n');
+        }
+    }
+
+    fn run_command(
+        &mut self,
+        _shell: &mut Shell,
+        name: &str,
+        _args: &[String],
+        _io: &mut CommandIo,
+    ) -> Option<i32> {
+        match name {
+            "clear" => {
+                serial_print!("\x1b[2J\x1b[H");
+                Some(0)
+            }
+            _ => None,
+        }
+    }
+
+    fn commands(&self) -> &'static [(&'static str, &'static str)] {
+        HOST_COMMANDS
+    }
+}
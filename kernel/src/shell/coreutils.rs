@@ -0,0 +1,695 @@
+//! File and directory commands, on top of [`crate::fs::manager`].
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use super::{CommandIo, Shell, ShellHost};
+use crate::fs::{
+    manager::{
+        chmod as chmod_path, chown as chown_path, copy_directory, copy_file, create_directory,
+        create_symlink, delete_directory, delete_file, delete_recursive, disk_space, disk_usage,
+        list_directory, move_item, path_exists, read_file, read_text_file, stat as stat_path,
+        write_file,
+    },
+    metadata::FileType,
+};
+
+/// Lines `head` and `tail` show without `-n`
+const DEFAULT_LINES: usize = 10;
+
+/// Split leading single-letter flags like `-r` or `-rf` off `args`. Returns
+/// None, with the error reported, on a flag not in `allowed`.
+fn parse_flags<'a>(
+    command: &str,
+    args: &'a [String],
+    allowed: &str,
+    io: &mut CommandIo,
+) -> Option<(Vec<char>, &'a [String])> {
+    let mut flags = Vec::new();
+    let mut rest = args;
+    while let Some((arg, tail)) = rest.split_first() {
+        if arg == "--" {
+            rest = tail;
+            break;
+        }
+        if arg.len() < 2 || !arg.starts_with('-') {
+            break;
+        }
+        for flag in arg.chars().skip(1) {
+            if !allowed.contains(flag) {
+                io.error(&format!("{}: unknown option -{}", command, flag));
+                return None;
+            }
+            flags.push(flag);
+        }
+        rest = tail;
+    }
+    Some((flags, rest))
+}
+
+/// Last component of a path
+fn base_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn join(dir: &str, name: &str) -> String {
+    if dir == "/" {
+        format!("/{}", name)
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{}B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{}K", bytes.div_ceil(1024))
+    } else {
+        format!("{}M", bytes.div_ceil(1024 * 1024))
+    }
+}
+
+pub(super) fn cd(
+    shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    let target = match args.first().map(String::as_str) {
+        None => shell.get_var("HOME").unwrap_or("/").to_string(),
+        Some("-") => {
+            let previous = shell.get_var("OLDPWD").unwrap_or("/").to_string();
+            io.print(&previous);
+            previous
+        }
+        Some(path) => path.to_string(),
+    };
+
+    match shell.change_dir(&target) {
+        Ok(()) => 0,
+        Err(e) => {
+            io.error(&format!("cd: {}: {}", target, e));
+            1
+        }
+    }
+}
+
+pub(super) fn pwd(
+    shell: &mut Shell,
+    _args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    io.print(shell.cwd());
+    0
+}
+
+pub(super) fn mkdir(
+    shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    let Some((flags, paths)) = parse_flags("mkdir", args, "p", io) else {
+        return 2;
+    };
+    let parents = flags.contains(&'p');
+    if paths.is_empty() {
+        io.error("Usage: mkdir [-p] <dir>...");
+        return 2;
+    }
+
+    let mut status = 0;
+    for arg in paths {
+        let path = shell.resolve_path(arg);
+        let result = if parents {
+            create_parents(&path)
+        } else {
+            match path_exists(&path) {
+                Ok(Some(_)) => Err("File exists"),
+                _ => create_directory(&path),
+            }
+        };
+        if let Err(e) = result {
+            io.error(&format!("mkdir: {}: {}", arg, e));
+            status = 1;
+        }
+    }
+    status
+}
+
+/// `mkdir -p`: create every missing directory along `path`
+fn create_parents(path: &str) -> Result<(), &'static str> {
+    let mut current = String::new();
+    for component in path.split('/').filter(|c| !c.is_empty()) {
+        current.push('/');
+        current.push_str(component);
+        match path_exists(&current)? {
+            Some(true) => {}
+            Some(false) => return Err("Not a directory"),
+            None => create_directory(&current)?,
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn rmdir(
+    shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    if args.is_empty() {
+        io.error("Usage: rmdir <dir>...");
+        return 2;
+    }
+
+    let mut status = 0;
+    for arg in args {
+        let path = shell.resolve_path(arg);
+        let result = match path_exists(&path) {
+            Ok(Some(true)) => delete_directory(&path),
+            Ok(Some(false)) => Err("Not a directory"),
+            Ok(None) => Err("No such file or directory"),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            io.error(&format!("rmdir: {}: {}", arg, e));
+            status = 1;
+        }
+    }
+    status
+}
+
+pub(super) fn rm(
+    shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    let Some((flags, paths)) = parse_flags("rm", args, "rRf", io) else {
+        return 2;
+    };
+    let recursive = flags.contains(&'r') || flags.contains(&'R');
+    let force = flags.contains(&'f');
+    if paths.is_empty() && !force {
+        io.error("Usage: rm [-r] [-f] <path>...");
+        return 2;
+    }
+
+    let mut status = 0;
+    for arg in paths {
+        let path = shell.resolve_path(arg);
+        let result = match path_exists(&path) {
+            Ok(Some(true)) if path == "/" => Err("Cannot delete root directory"),
+            Ok(Some(true)) if recursive => delete_recursive(&path),
+            Ok(Some(true)) => Err("Is a directory"),
+            Ok(Some(false)) => delete_file(&path),
+            Ok(None) if force => Ok(()),
+            Ok(None) => Err("No such file or directory"),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            io.error(&format!("rm: {}: {}", arg, e));
+            status = 1;
+        }
+    }
+    status
+}
+
+/// Sources and destination of `cp` and `mv`. With several sources, or when
+/// the destination is a directory, each source goes inside it.
+fn copy_targets(
+    command: &str,
+    shell: &Shell,
+    args: &[String],
+    io: &mut CommandIo,
+) -> Option<Vec<(String, String)>> {
+    let Some((dest, sources)) = args.split_last().filter(|(_, sources)| !sources.is_empty()) else {
+        io.error(&format!("Usage: {} <source>... <dest>", command));
+        return None;
+    };
+
+    let dest = shell.resolve_path(dest);
+    let into_dir = matches!(path_exists(&dest), Ok(Some(true)));
+    if sources.len() > 1 && !into_dir {
+        io.error(&format!("{}: {}: Not a directory", command, dest));
+        return None;
+    }
+
+    Some(
+        sources
+            .iter()
+            .map(|source| {
+                let source = shell.resolve_path(source);
+                let target = if into_dir {
+                    join(&dest, base_name(&source))
+                } else {
+                    dest.clone()
+                };
+                (source, target)
+            })
+            .collect(),
+    )
+}
+
+pub(super) fn cp(
+    shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    let Some((flags, args)) = parse_flags("cp", args, "rR", io) else {
+        return 2;
+    };
+    let recursive = !flags.is_empty();
+    let Some(targets) = copy_targets("cp", shell, args, io) else {
+        return 2;
+    };
+
+    let mut status = 0;
+    for (source, dest) in targets {
+        let result = match path_exists(&source) {
+            // Copying a directory into itself would never end
+            Ok(Some(true)) if dest.starts_with(&join(&source, "")) => {
+                Err("Cannot copy a directory into itself")
+            }
+            Ok(Some(true)) if recursive => copy_directory(&source, &dest),
+            Ok(Some(true)) => Err("Is a directory, use -r"),
+            Ok(Some(false)) => copy_file(&source, &dest),
+            Ok(None) => Err("No such file or directory"),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            io.error(&format!("cp: {}: {}", source, e));
+            status = 1;
+        }
+    }
+    status
+}
+
+pub(super) fn mv(
+    shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    let Some(targets) = copy_targets("mv", shell, args, io) else {
+        return 2;
+    };
+
+    let mut status = 0;
+    for (source, dest) in targets {
+        let result = if source == "/" {
+            Err("Cannot move root directory")
+        } else if dest.starts_with(&join(&source, "")) {
+            Err("Cannot move a directory into itself")
+        } else {
+            move_item(&source, &dest)
+        };
+        if let Err(e) = result {
+            io.error(&format!("mv: {}: {}", source, e));
+            status = 1;
+        }
+    }
+    status
+}
+
+pub(super) fn touch(
+    shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    if args.is_empty() {
+        io.error("Usage: touch <file>...");
+        return 2;
+    }
+
+    let mut status = 0;
+    for arg in args {
+        let path = shell.resolve_path(arg);
+        let result = match path_exists(&path) {
+            Ok(Some(true)) => Ok(()),
+            // Writing the same data back updates the modification time
+            Ok(Some(false)) => read_file(&path).and_then(|data| write_file(&path, &data)),
+            Ok(None) => write_file(&path, &[]),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            io.error(&format!("touch: {}: {}", arg, e));
+            status = 1;
+        }
+    }
+    status
+}
+
+pub(super) fn stat(
+    shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    if args.is_empty() {
+        io.error("Usage: stat <path>...");
+        return 2;
+    }
+
+    let mut status = 0;
+    for arg in args {
+        let path = shell.resolve_path(arg);
+        let info = match stat_path(&path) {
+            Ok(info) => info,
+            Err(e) => {
+                io.error(&format!("stat: {}: {}", arg, e));
+                status = 1;
+                continue;
+            }
+        };
+
+        let kind = match info.file_type {
+            FileType::Regular => "regular file",
+            FileType::Directory => "directory",
+            FileType::Symlink => "symbolic link",
+        };
+        match &info.link_target {
+            Some(target) => io.print(&format!("  File: {} -> {}", path, target)),
+            None => io.print(&format!("  File: {}", path)),
+        }
+        io.print(&format!("  Size: {:<10} Type: {}", info.size, kind));
+        io.print(&format!(
+            "Access: ({:04o}/{})  Uid: {}  Gid: {}",
+            info.mode,
+            info.mode_string(),
+            info.uid,
+            info.gid
+        ));
+
+        let created = &info.created_at;
+        let modified = &info.modified_at;
+        let accessed = &info.accessed_at;
+        io.print(&format!(
+            "Access: {:04}-{:02}-{:02}",
+            accessed.year, accessed.month, accessed.day
+        ));
+        io.print(&format!(
+            "Modify: {:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            modified.year,
+            modified.month,
+            modified.day,
+            modified.hours,
+            modified.minutes,
+            modified.seconds
+        ));
+        io.print(&format!(
+            " Birth: {:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            created.year,
+            created.month,
+            created.day,
+            created.hours,
+            created.minutes,
+            created.seconds
+        ));
+    }
+    status
+}
+
+pub(super) fn ln(
+    shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    let Some((flags, paths)) = parse_flags("ln", args, "s", io) else {
+        return 2;
+    };
+    if !flags.contains(&'s') {
+        io.error("ln: only symbolic links are supported, use -s");
+        return 2;
+    }
+    let [target, link] = paths else {
+        io.error("Usage: ln -s <target> <link>");
+        return 2;
+    };
+
+    // The target is stored as given, relative ones resolve from the link's directory
+    let mut link_path = shell.resolve_path(link);
+    if path_exists(&link_path) == Ok(Some(true)) {
+        link_path = join(&link_path, base_name(target.trim_end_matches('/')));
+    }
+
+    match create_symlink(target, &link_path) {
+        Ok(()) => 0,
+        Err(e) => {
+            io.error(&format!("ln: {}: {}", link, e));
+            1
+        }
+    }
+}
+
+pub(super) fn chmod(
+    shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    let Some((mode, paths)) = args.split_first().filter(|(_, paths)| !paths.is_empty()) else {
+        io.error("Usage: chmod <octal mode> <path>...");
+        return 2;
+    };
+    let Some(mode) = u16::from_str_radix(mode, 8).ok().filter(|&m| m <= 0o7777) else {
+        io.error(&format!("chmod: invalid mode: {}", mode));
+        return 2;
+    };
+
+    let mut status = 0;
+    for arg in paths {
+        if let Err(e) = chmod_path(&shell.resolve_path(arg), mode) {
+            io.error(&format!("chmod: {}: {}", arg, e));
+            status = 1;
+        }
+    }
+    status
+}
+
+pub(super) fn chown(
+    shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    let Some((owner, paths)) = args.split_first().filter(|(_, paths)| !paths.is_empty()) else {
+        io.error("Usage: chown <uid>[:<gid>] <path>...");
+        return 2;
+    };
+
+    // Ids are numeric, there are no user names
+    let (uid, gid) = match owner.split_once(':') {
+        Some((uid, gid)) => (uid.parse::<u16>(), gid.parse::<u16>().map(Some)),
+        None => (owner.parse::<u16>(), Ok(None)),
+    };
+    let (Ok(uid), Ok(gid)) = (uid, gid) else {
+        io.error(&format!("chown: invalid owner: {}", owner));
+        return 2;
+    };
+
+    let mut status = 0;
+    for arg in paths {
+        let path = shell.resolve_path(arg);
+        // Without a group, the current one is kept
+        let result = match gid {
+            Some(gid) => chown_path(&path, uid, gid),
+            None => stat_path(&path).and_then(|info| chown_path(&path, uid, info.gid)),
+        };
+        if let Err(e) = result {
+            io.error(&format!("chown: {}: {}", arg, e));
+            status = 1;
+        }
+    }
+    status
+}
+
+pub(super) fn du(
+    shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    let Some((flags, paths)) = parse_flags("du", args, "s", io) else {
+        return 2;
+    };
+    let summarize = flags.contains(&'s');
+    let current = [".".to_string()];
+    let paths = if paths.is_empty() {
+        &current[..]
+    } else {
+        paths
+    };
+
+    let mut status = 0;
+    for arg in paths {
+        let path = shell.resolve_path(arg);
+        let result = if summarize {
+            disk_usage(&path).map(|total| io.print(&format!("{}\t{}", format_size(total), path)))
+        } else {
+            usage_tree(&path, io).map(|_| ())
+        };
+        if let Err(e) = result {
+            io.error(&format!("du: {}: {}", arg, e));
+            status = 1;
+        }
+    }
+    status
+}
+
+/// Print the usage of every directory below `path`, deepest first, and
+/// return the total
+fn usage_tree(path: &str, io: &mut CommandIo) -> Result<u64, &'static str> {
+    if path_exists(path)? != Some(true) {
+        let total = disk_usage(path)?;
+        io.print(&format!("{}\t{}", format_size(total), path));
+        return Ok(total);
+    }
+
+    let mut total = 0;
+    for entry in list_directory(path)? {
+        if entry.name == "." || entry.name == ".." {
+            continue;
+        }
+        total += if entry.is_directory {
+            usage_tree(&join(path, &entry.name), io)?
+        } else {
+            entry.size as u64
+        };
+    }
+    io.print(&format!("{}\t{}", format_size(total), path));
+    Ok(total)
+}
+
+pub(super) fn df(
+    _shell: &mut Shell,
+    _args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    match disk_space() {
+        Ok((total, free)) => {
+            let used = total - free;
+            let percent = (used * 100).checked_div(total).unwrap_or(0);
+            io.print("Filesystem  Size    Used    Avail   Use%  Mounted on");
+            io.print(&format!(
+                "{:<11} {:<7} {:<7} {:<7} {:>3}%  /",
+                "fat32",
+                format_size(total),
+                format_size(used),
+                format_size(free),
+                percent
+            ));
+            0
+        }
+        Err(e) => {
+            io.error(&format!("df: {}", e));
+            1
+        }
+    }
+}
+
+/// `-n N` and the files of `head`/`tail`
+fn parse_line_count<'a>(
+    command: &str,
+    args: &'a [String],
+    io: &mut CommandIo,
+) -> Option<(usize, &'a [String])> {
+    match args {
+        [flag, count, rest @ ..] if flag == "-n" => match count.parse() {
+            Ok(count) => Some((count, rest)),
+            Err(_) => {
+                io.error(&format!("{}: invalid number of lines: {}", command, count));
+                None
+            }
+        },
+        _ => Some((DEFAULT_LINES, args)),
+    }
+}
+
+/// Shared by `head` and `tail`, which only differ in which lines they keep
+fn print_lines(
+    command: &str,
+    shell: &Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    keep: fn(Vec<&str>, usize) -> Vec<&str>,
+) -> i32 {
+    let Some((count, files)) = parse_line_count(command, args, io) else {
+        return 2;
+    };
+
+    let inputs: Vec<(Option<&String>, Result<String, &'static str>)> = if files.is_empty() {
+        match io.stdin.take() {
+            Some(text) => alloc::vec![(None, Ok(text))],
+            None => {
+                io.error(&format!(
+                    "{}: no input, give a file or pipe into it",
+                    command
+                ));
+                return 2;
+            }
+        }
+    } else {
+        files
+            .iter()
+            .map(|file| (Some(file), read_text_file(&shell.resolve_path(file))))
+            .collect()
+    };
+
+    let mut status = 0;
+    let show_names = inputs.len() > 1;
+    for (i, (name, text)) in inputs.iter().enumerate() {
+        let text = match text {
+            Ok(text) => text,
+            Err(e) => {
+                io.error(&format!("{}: {}: {}", command, name.map_or("-", |n| n), e));
+                status = 1;
+                continue;
+            }
+        };
+
+        if show_names {
+            if i > 0 {
+                io.print("");
+            }
+            io.print(&format!("==> {} <==", name.map_or("-", |n| n)));
+        }
+        for line in keep(text.lines().collect(), count) {
+            io.print(line);
+        }
+    }
+    status
+}
+
+pub(super) fn head(
+    shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    print_lines("head", shell, args, io, |mut lines, count| {
+        lines.truncate(count);
+        lines
+    })
+}
+
+pub(super) fn tail(
+    shell: &mut Shell,
+    args: &[String],
+    io: &mut CommandIo,
+    _: &mut dyn ShellHost,
+) -> i32 {
+    print_lines("tail", shell, args, io, |lines, count| {
+        lines[lines.len().saturating_sub(count)..].to_vec()
+    })
+}
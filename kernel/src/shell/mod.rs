@@ -0,0 +1,729 @@
+//! Command language shared by everything that takes typed commands.
+//!
+//! A line is parsed into pipelines joined by `;`, `&&` and `||`, with `if`,
+//! `for` and `while` on top. Commands in a pipeline hand their output to the
+//! next one as text, and `>`, `>>` and `<` redirect to files through
+//! [`crate::fs::manager`]. Scripts are the same language read from a file, and
+//! a path to an ELF file starts it as a user program. Each front end (the
+//! Terminal window, the serial console in [`console`]) implements
+//! [`ShellHost`] to show the output and to add commands that only make sense
+//! there.
+
+pub mod builtins;
+pub mod console;
+mod coreutils;
+pub mod parser;
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::fs::manager::{path_exists, read_file, read_text_file, write_file};
+use parser::{
+    Command, CommandList, CompoundCommand, Connector, Pipeline, Redirect, RedirectKind,
+    SimpleCommand, Word, WordPart,
+};
+
+/// How deep `source` and scripts may nest, each level uses kernel stack
+const MAX_SCRIPT_DEPTH: usize = 8;
+/// A `while` loop that runs longer than this is assumed to be stuck
+const MAX_LOOP_ITERATIONS: usize = 10_000;
+/// How long a program whose output goes into a pipe or a file may run
+#[cfg(processes_enabled)]
+const PROGRAM_TIMEOUT_MS: i64 = 5_000;
+/// How long a killed program gets to go away, the scheduler stops it on a
+/// timer tick that finds it in user code
+#[cfg(processes_enabled)]
+const KILL_TIMEOUT_MS: i64 = 100;
+/// Exit status when a program outlived its time and couldn't be stopped
+#[cfg(processes_enabled)]
+const TIMED_OUT_STATUS: i32 = 124;
+/// First bytes of an ELF file, programs start with them and scripts don't
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+
+/// Standard streams of one command
+pub struct CommandIo {
+    /// Text piped or redirected in, None when nothing was
+    pub stdin: Option<String>,
+    pub stdout: String,
+    pub stderr: String,
+    /// Output goes straight to the console, not into a pipe or a file
+    pub interactive: bool,
+    /// Nothing else on the command line runs after this command, so the host
+    /// may leave a program running and report its status when it ends
+    pub last_on_line: bool,
+}
+
+impl CommandIo {
+    pub fn print(&mut self, text: &str) {
+        self.stdout.push_str(text);
+        self.stdout.push('\n');
+    }
+
+    pub fn error(&mut self, text: &str) {
+        self.stderr.push_str(text);
+        self.stderr.push('\n');
+    }
+}
+
+/// Front end a [`Shell`] runs in
+pub trait ShellHost {
+    /// Show text on the console
+    fn write(&mut self, text: &str);
+
+    /// Show an error message, on the console unless the host keeps them apart
+    fn write_error(&mut self, text: &str) {
+        self.write(text);
+    }
+
+    /// Run a command only the host knows, or one it handles better when
+    /// `io.interactive`. Returns None to leave it to the shell.
+    fn run_command(
+        &mut self,
+        _shell: &mut Shell,
+        _name: &str,
+        _args: &[String],
+        _io: &mut CommandIo,
+    ) -> Option<i32> {
+        None
+    }
+
+    /// Name and description of the host's own commands, for `help`
+    fn commands(&self) -> &'static [(&'static str, &'static str)] {
+        &[]
+    }
+
+    /// Run the user program in `data`, an ELF image read from `path`.
+    /// By default it waits for the program and collects its output in `io`.
+    fn execute(&mut self, path: &str, data: &[u8], io: &mut CommandIo) -> i32 {
+        run_elf(path, data, io)
+    }
+}
+
+struct Variable {
+    name: String,
+    value: String,
+    exported: bool,
+}
+
+pub struct Shell {
+    variables: Vec<Variable>,
+    /// Directory relative paths start from, always absolute and normalized
+    cwd: String,
+    /// `$0`, `$1`... of the running script
+    args: Vec<String>,
+    /// Exit status of the last pipeline, `$?`
+    pub status: i32,
+    /// Scripts running inside each other right now
+    depth: usize,
+}
+
+impl Default for Shell {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shell {
+    pub fn new() -> Self {
+        let mut shell = Self {
+            variables: Vec::new(),
+            cwd: "/".to_string(),
+            args: alloc::vec!["sh".to_string()],
+            status: 0,
+            depth: 0,
+        };
+        shell.export("HOME", "/");
+        shell.export("USER", "goofy");
+        shell.export("SHELL", "sh");
+        shell.export("PWD", "/");
+        shell
+    }
+
+    pub fn cwd(&self) -> &str {
+        &self.cwd
+    }
+
+    /// Make `path` the working directory, `cd` without the special cases
+    pub fn change_dir(&mut self, path: &str) -> Result<(), &'static str> {
+        let path = self.resolve_path(path);
+        match path_exists(&path)? {
+            Some(true) => {}
+            Some(false) => return Err("Not a directory"),
+            None => return Err("No such file or directory"),
+        }
+
+        let previous = core::mem::replace(&mut self.cwd, path);
+        self.export("OLDPWD", &previous);
+        let cwd = self.cwd.clone();
+        self.export("PWD", &cwd);
+        Ok(())
+    }
+
+    pub fn get_var(&self, name: &str) -> Option<&str> {
+        self.variables
+            .iter()
+            .find(|var| var.name == name)
+            .map(|var| var.value.as_str())
+    }
+
+    pub fn set_var(&mut self, name: &str, value: &str) {
+        match self.variables.iter_mut().find(|var| var.name == name) {
+            Some(var) => var.value = value.to_string(),
+            None => self.variables.push(Variable {
+                name: name.to_string(),
+                value: value.to_string(),
+                exported: false,
+            }),
+        }
+    }
+
+    pub fn export(&mut self, name: &str, value: &str) {
+        self.set_var(name, value);
+        self.mark_exported(name);
+    }
+
+    /// Returns false if there is no such variable
+    pub fn mark_exported(&mut self, name: &str) -> bool {
+        match self.variables.iter_mut().find(|var| var.name == name) {
+            Some(var) => {
+                var.exported = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn unset_var(&mut self, name: &str) {
+        self.variables.retain(|var| var.name != name);
+    }
+
+    /// Every variable as `(name, value, exported)`, sorted by name
+    pub fn variables(&self) -> Vec<(&str, &str, bool)> {
+        let mut variables: Vec<_> = self
+            .variables
+            .iter()
+            .map(|var| (var.name.as_str(), var.value.as_str(), var.exported))
+            .collect();
+        variables.sort_by(|a, b| a.0.cmp(b.0));
+        variables
+    }
+
+    /// Make a path typed by the user absolute: relative paths start at the
+    /// working directory, `~` is `$HOME`, and `.` and `..` are folded away.
+    pub fn resolve_path(&self, path: &str) -> String {
+        let home = self.get_var("HOME").unwrap_or("/");
+        let full = if path == "~" {
+            home.to_string()
+        } else if let Some(rest) = path.strip_prefix("~/") {
+            format!("{}/{}", home, rest)
+        } else if path.starts_with('/') {
+            path.to_string()
+        } else {
+            format!("{}/{}", self.cwd, path)
+        };
+
+        let mut components: Vec<&str> = Vec::new();
+        for component in full.split('/') {
+            match component {
+                "" | "." => {}
+                // The parent of the root is the root
+                ".." => {
+                    components.pop();
+                }
+                component => components.push(component),
+            }
+        }
+        format!("/{}", components.join("/"))
+    }
+
+    /// Names of everything that can be run, for completion
+    pub fn command_names(&self, host: &dyn ShellHost) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = builtins::BUILTINS
+            .iter()
+            .map(|builtin| builtin.name)
+            .chain(host.commands().iter().map(|(name, _)| *name))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Run a command line and return its exit status
+    pub fn run(&mut self, line: &str, host: &mut dyn ShellHost) -> i32 {
+        match parser::parse(line) {
+            Ok(list) => self.run_list(&list, host, self.depth == 0),
+            Err(e) => {
+                host.write_error(&format!("sh: {}\n", e));
+                self.status = 2;
+                self.status
+            }
+        }
+    }
+
+    /// Run the script at `path` in this shell, so the variables and working
+    /// directory it sets stay set. With `args` they replace `$1`, `$2`...
+    /// for the duration of the script.
+    pub fn source(&mut self, path: &str, args: &[String], host: &mut dyn ShellHost) -> i32 {
+        if self.depth >= MAX_SCRIPT_DEPTH {
+            host.write_error(&format!("sh: {}: scripts nested too deeply\n", path));
+            return 1;
+        }
+        let path = self.resolve_path(path);
+        let script = match read_text_file(&path) {
+            Ok(script) => script,
+            Err(e) => {
+                host.write_error(&format!("sh: {}: {}\n", path, e));
+                return 1;
+            }
+        };
+
+        let saved_args = if args.is_empty() {
+            None
+        } else {
+            let mut new_args = alloc::vec![path.clone()];
+            new_args.extend_from_slice(args);
+            Some(core::mem::replace(&mut self.args, new_args))
+        };
+
+        self.depth += 1;
+        let status = self.run(&script, host);
+        self.depth -= 1;
+
+        if let Some(args) = saved_args {
+            self.args = args;
+        }
+        status
+    }
+
+    /// Run the script at `path` in a new shell that only inherits the exported
+    /// variables and the working directory, like `./script.sh` does
+    pub fn run_script(&mut self, path: &str, args: &[String], host: &mut dyn ShellHost) -> i32 {
+        let path = self.resolve_path(path);
+        let mut child = Shell {
+            variables: Vec::new(),
+            cwd: self.cwd.clone(),
+            args: alloc::vec![path.clone()],
+            status: 0,
+            depth: self.depth,
+        };
+        for var in self.variables.iter().filter(|var| var.exported) {
+            child.export(&var.name, &var.value);
+        }
+        child.args.extend_from_slice(args);
+
+        child.source(&path, &[], host)
+    }
+
+    /// `last_on_line` is false when more commands may follow the list, like
+    /// the rest of a script or of an `if`
+    fn run_list(
+        &mut self,
+        list: &CommandList,
+        host: &mut dyn ShellHost,
+        last_on_line: bool,
+    ) -> i32 {
+        for (i, (connector, pipeline)) in list.items.iter().enumerate() {
+            let skip = match connector {
+                Connector::Always => false,
+                Connector::And => self.status != 0,
+                Connector::Or => self.status == 0,
+            };
+            if !skip {
+                let last = last_on_line && i + 1 == list.items.len();
+                self.status = self.run_pipeline(pipeline, host, last);
+            }
+        }
+        self.status
+    }
+
+    fn run_pipeline(
+        &mut self,
+        pipeline: &Pipeline,
+        host: &mut dyn ShellHost,
+        last_on_line: bool,
+    ) -> i32 {
+        let mut input = None;
+        let mut status = 0;
+
+        for (i, command) in pipeline.commands.iter().enumerate() {
+            let last = i + 1 == pipeline.commands.len();
+            let mut io = CommandIo {
+                stdin: input.take(),
+                stdout: String::new(),
+                stderr: String::new(),
+                interactive: last,
+                last_on_line: last_on_line && last,
+            };
+
+            status = match command {
+                Command::Simple(command) => self.run_command(command, &mut io, host),
+                Command::Compound(command, redirects) => {
+                    self.run_compound(command, redirects, &mut io, host)
+                }
+            };
+
+            if !io.stderr.is_empty() {
+                host.write_error(&io.stderr);
+            }
+            // Output sent to a file is gone by now and leaves the next command nothing
+            if last {
+                if !io.stdout.is_empty() {
+                    host.write(&io.stdout);
+                }
+            } else {
+                input = Some(io.stdout);
+            }
+        }
+
+        status
+    }
+
+    /// Open the files of `<` into `io.stdin` and return where `>` or `>>`
+    /// want the output, with whether to append. None once an error is reported.
+    fn apply_redirects(
+        &self,
+        redirects: &[Redirect],
+        io: &mut CommandIo,
+    ) -> Option<Option<(String, bool)>> {
+        let mut output = None;
+        for redirect in redirects {
+            let path = self.resolve_path(&self.expand(&redirect.target));
+            match redirect.kind {
+                RedirectKind::Input => match read_text_file(&path) {
+                    Ok(text) => io.stdin = Some(text),
+                    Err(e) => {
+                        io.error(&format!("sh: {}: {}", path, e));
+                        return None;
+                    }
+                },
+                kind => output = Some((path, kind == RedirectKind::Append)),
+            }
+        }
+
+        if output.is_some() {
+            io.interactive = false;
+        }
+        Some(output)
+    }
+
+    /// Write what a command printed to the file of its `>` or `>>`
+    fn finish_redirect(output: Option<(String, bool)>, io: &mut CommandIo, status: i32) -> i32 {
+        let Some((path, append)) = output else {
+            return status;
+        };
+
+        let written = write_output(&path, &io.stdout, append);
+        io.stdout.clear();
+        match written {
+            Ok(()) => status,
+            Err(e) => {
+                io.error(&format!("sh: {}: {}", path, e));
+                1
+            }
+        }
+    }
+
+    /// Expand, apply the redirections and run one command
+    fn run_command(
+        &mut self,
+        command: &SimpleCommand,
+        io: &mut CommandIo,
+        host: &mut dyn ShellHost,
+    ) -> i32 {
+        // On its own, `NAME=value` sets a variable
+        if !command.words.is_empty()
+            && command.redirects.is_empty()
+            && command.words.iter().all(|word| word.assignment().is_some())
+        {
+            for word in &command.words {
+                let (name, rest, value) = word.assignment().unwrap();
+                let value = format!("{}{}", value, self.expand_parts(rest));
+                self.set_var(name, &value);
+            }
+            return 0;
+        }
+
+        let args: Vec<String> = command.words.iter().map(|w| self.expand(w)).collect();
+        let Some(output) = self.apply_redirects(&command.redirects, io) else {
+            return 1;
+        };
+
+        let status = match args.split_first() {
+            Some((name, args)) => self.run_program(name, args, io, host),
+            // Only redirections, like `> file` to empty it
+            None => 0,
+        };
+
+        Self::finish_redirect(output, io, status)
+    }
+
+    /// Run `if`, `for` or `while`. Unless it writes to the console, whatever
+    /// its commands print is collected as its own output.
+    fn run_compound(
+        &mut self,
+        command: &CompoundCommand,
+        redirects: &[Redirect],
+        io: &mut CommandIo,
+        host: &mut dyn ShellHost,
+    ) -> i32 {
+        let Some(output) = self.apply_redirects(redirects, io) else {
+            return 1;
+        };
+
+        let status = if io.interactive {
+            self.run_compound_body(command, host)
+        } else {
+            let mut capture = Capture {
+                host,
+                output: String::new(),
+            };
+            let status = self.run_compound_body(command, &mut capture);
+            io.stdout = capture.output;
+            status
+        };
+
+        Self::finish_redirect(output, io, status)
+    }
+
+    fn run_compound_body(&mut self, command: &CompoundCommand, host: &mut dyn ShellHost) -> i32 {
+        match command {
+            CompoundCommand::If {
+                branches,
+                otherwise,
+            } => {
+                for (condition, body) in branches {
+                    if self.run_list(condition, host, false) == 0 {
+                        return self.run_list(body, host, false);
+                    }
+                }
+                match otherwise {
+                    Some(otherwise) => self.run_list(otherwise, host, false),
+                    None => 0,
+                }
+            }
+            CompoundCommand::For {
+                variable,
+                words,
+                body,
+            } => {
+                let mut values = Vec::new();
+                for word in words {
+                    let value = self.expand(word);
+                    // Unquoted variables split into words, so `for f in $FILES` works
+                    let quoted = word.parts.iter().any(|p| matches!(p, WordPart::Quoted(_)));
+                    let has_var = word.parts.iter().any(|p| matches!(p, WordPart::Var(_)));
+                    if has_var && !quoted {
+                        values.extend(value.split_whitespace().map(String::from));
+                    } else {
+                        values.push(value);
+                    }
+                }
+
+                let mut status = 0;
+                for value in values {
+                    self.set_var(variable, &value);
+                    status = self.run_list(body, host, false);
+                }
+                status
+            }
+            CompoundCommand::While { condition, body } => {
+                let mut status = 0;
+                let mut iterations = 0;
+                while self.run_list(condition, host, false) == 0 {
+                    // Nothing else runs until the loop ends, so don't let it hang the system
+                    iterations += 1;
+                    if iterations > MAX_LOOP_ITERATIONS {
+                        host.write_error(&format!(
+                            "sh: while: stopped after {} iterations\n",
+                            MAX_LOOP_ITERATIONS
+                        ));
+                        return 1;
+                    }
+                    status = self.run_list(body, host, false);
+                }
+                status
+            }
+        }
+    }
+
+    fn run_program(
+        &mut self,
+        name: &str,
+        args: &[String],
+        io: &mut CommandIo,
+        host: &mut dyn ShellHost,
+    ) -> i32 {
+        if let Some(status) = host.run_command(self, name, args, io) {
+            return status;
+        }
+
+        // A path runs the program or script there, `./hello` or `./setup.sh`
+        if name.contains('/') {
+            let path = self.resolve_path(name);
+            return match path_exists(&path) {
+                Ok(Some(false)) => match read_file(&path) {
+                    Ok(data) if data.starts_with(ELF_MAGIC) => host.execute(&path, &data, io),
+                    Ok(_) => self.run_script(name, args, host),
+                    Err(e) => {
+                        io.error(&format!("sh: {}: {}", name, e));
+                        126
+                    }
+                },
+                Ok(Some(true)) => {
+                    io.error(&format!("sh: {}: Is a directory", name));
+                    126
+                }
+                _ => {
+                    io.error(&format!("sh: {}: No such file or directory", name));
+                    127
+                }
+            };
+        }
+
+        match builtins::BUILTINS
+            .iter()
+            .find(|builtin| builtin.name == name)
+        {
+            Some(builtin) => (builtin.run)(self, args, io, host),
+            None => {
+                io.error(&format!("sh: {}: command not found", name));
+                127
+            }
+        }
+    }
+
+    fn expand(&self, word: &Word) -> String {
+        self.expand_parts(&word.parts)
+    }
+
+    fn expand_parts(&self, parts: &[WordPart]) -> String {
+        let mut text = String::new();
+        for part in parts {
+            match part {
+                WordPart::Text(part) | WordPart::Quoted(part) => text.push_str(part),
+                WordPart::Var(name) if name == "?" => text.push_str(&self.status.to_string()),
+                WordPart::Var(name) if name == "#" => {
+                    text.push_str(&self.args.len().saturating_sub(1).to_string())
+                }
+                WordPart::Var(name) if name == "@" => {
+                    text.push_str(&self.args.get(1..).unwrap_or_default().join(" "))
+                }
+                WordPart::Var(name) if parser::is_special_name(name) => {
+                    let index: usize = name.parse().unwrap_or(usize::MAX);
+                    text.push_str(self.args.get(index).map_or("", String::as_str))
+                }
+                WordPart::Var(name) => text.push_str(self.get_var(name).unwrap_or("")),
+            }
+        }
+        text
+    }
+}
+
+/// Stands in for the host while the output of an `if`, `for` or `while` goes
+/// into a pipe or a file. Errors and host commands still reach the real host.
+struct Capture<'a> {
+    host: &'a mut dyn ShellHost,
+    output: String,
+}
+
+impl ShellHost for Capture<'_> {
+    fn write(&mut self, text: &str) {
+        self.output.push_str(text);
+    }
+
+    fn write_error(&mut self, text: &str) {
+        self.host.write_error(text);
+    }
+
+    fn run_command(
+        &mut self,
+        shell: &mut Shell,
+        name: &str,
+        args: &[String],
+        io: &mut CommandIo,
+    ) -> Option<i32> {
+        // Whatever the command prints has to end up in `output`
+        io.interactive = false;
+        self.host.run_command(shell, name, args, io)
+    }
+
+    fn commands(&self) -> &'static [(&'static str, &'static str)] {
+        self.host.commands()
+    }
+
+    fn execute(&mut self, path: &str, data: &[u8], io: &mut CommandIo) -> i32 {
+        io.interactive = false;
+        self.host.execute(path, data, io)
+    }
+}
+
+/// Start the user program in `data` and wait for it to end, with what it
+/// wrote going to `io`. One that runs too long is stopped.
+#[cfg(processes_enabled)]
+pub fn run_elf(path: &str, data: &[u8], io: &mut CommandIo) -> i32 {
+    use crate::tasks::process;
+
+    let handle = match process::spawn_elf(data) {
+        Ok(handle) => handle,
+        Err(e) => {
+            io.error(&format!("sh: {}: {}", path, e));
+            return 126;
+        }
+    };
+
+    let mut exit_code = process::wait(&handle, PROGRAM_TIMEOUT_MS);
+    let timed_out = exit_code.is_none();
+    if timed_out {
+        process::kill(&handle);
+        exit_code = process::wait(&handle, KILL_TIMEOUT_MS);
+    }
+
+    let (stdout, stderr) = process::take_output(&handle);
+    io.stdout.push_str(&stdout);
+    io.stderr.push_str(&stderr);
+
+    match exit_code {
+        Some(code) => {
+            if timed_out {
+                io.error(&format!(
+                    "sh: {}: still running after {} ms, stopped",
+                    path, PROGRAM_TIMEOUT_MS
+                ));
+            }
+            code
+        }
+        None => {
+            io.error(&format!(
+                "sh: {}: still running after {} ms and could not be stopped yet",
+                path,
+                PROGRAM_TIMEOUT_MS + KILL_TIMEOUT_MS
+            ));
+            TIMED_OUT_STATUS
+        }
+    }
+}
+
+/// Without processes there is nothing to run programs in
+#[cfg(not(processes_enabled))]
+pub fn run_elf(path: &str, _data: &[u8], io: &mut CommandIo) -> i32 {
+    io.error(&format!("sh: {}: cannot execute binary file", path));
+    126
+}
+
+/// Write a command's output to a file for `>` and `>>`
+fn write_output(path: &str, text: &str, append: bool) -> Result<(), &'static str> {
+    if path_exists(path)? == Some(true) {
+        return Err("Is a directory");
+    }
+
+    if append && path_exists(path)?.is_some() {
+        let mut data = read_file(path)?;
+        data.extend_from_slice(text.as_bytes());
+        write_file(path, &data)
+    } else {
+        write_file(path, text.as_bytes())
+    }
+}
@@ -0,0 +1,786 @@
+//! Turns a command line into a [`CommandList`].
+//!
+//! Words keep their variable references, they are expanded when the command
+//! runs so `X=1; echo $X` sees the new value.
+
+use alloc::{string::String, vec::Vec};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WordPart {
+    /// Plain text outside quotes
+    Text(String),
+    /// Text from single or double quotes
+    Quoted(String),
+    /// `$NAME`, `${NAME}`, or a special one like `$?` or `$1`
+    Var(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Word {
+    pub parts: Vec<WordPart>,
+}
+
+impl Word {
+    /// `NAME=value` with an unquoted name, as the command itself
+    pub fn assignment(&self) -> Option<(&str, &[WordPart], &str)> {
+        let Some(WordPart::Text(first)) = self.parts.first() else {
+            return None;
+        };
+        let (name, value) = first.split_once('=')?;
+        is_name(name).then_some((name, &self.parts[1..], value))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RedirectKind {
+    /// `> file`
+    Output,
+    /// `>> file`
+    Append,
+    /// `< file`
+    Input,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Redirect {
+    pub kind: RedirectKind,
+    pub target: Word,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SimpleCommand {
+    pub words: Vec<Word>,
+    pub redirects: Vec<Redirect>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CompoundCommand {
+    /// `if list; then list; [elif list; then list;]... [else list;] fi`
+    If {
+        branches: Vec<(CommandList, CommandList)>,
+        otherwise: Option<CommandList>,
+    },
+    /// `for NAME in words...; do list; done`
+    For {
+        variable: String,
+        words: Vec<Word>,
+        body: CommandList,
+    },
+    /// `while list; do list; done`
+    While {
+        condition: CommandList,
+        body: CommandList,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Simple(SimpleCommand),
+    /// A compound command and the redirections after its closing keyword
+    Compound(CompoundCommand, Vec<Redirect>),
+}
+
+/// Commands joined by `|`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pipeline {
+    pub commands: Vec<Command>,
+}
+
+/// How a pipeline depends on the one before it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Connector {
+    /// First pipeline, or after `;` or a newline
+    Always,
+    /// After `&&`
+    And,
+    /// After `||`
+    Or,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CommandList {
+    pub items: Vec<(Connector, Pipeline)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(Word),
+    Semicolon,
+    And,
+    Or,
+    Pipe,
+    Redirect(RedirectKind),
+}
+
+/// Words that open or close a compound command where a command could start
+const KEYWORDS: &[&str] = &[
+    "if", "then", "elif", "else", "fi", "for", "in", "do", "done", "while",
+];
+
+/// Letters, digits and `_`, not starting with a digit
+pub fn is_name(text: &str) -> bool {
+    let mut chars = text.chars();
+    matches!(chars.next(), Some(c) if c == '_' || c.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+pub fn parse(line: &str) -> Result<CommandList, &'static str> {
+    let mut parser = Parser {
+        tokens: tokenize(line)?,
+        pos: 0,
+    };
+    let list = parser.list(&[])?;
+    match parser.peek() {
+        None => Ok(list),
+        Some(_) => Err("syntax error near unexpected keyword"),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// The next token if it is a keyword: one plain, unquoted word
+    fn peek_keyword(&self) -> Option<&'static str> {
+        let Some(Token::Word(word)) = self.peek() else {
+            return None;
+        };
+        match word.parts.as_slice() {
+            [WordPart::Text(text)] => KEYWORDS.iter().copied().find(|k| k == text),
+            _ => None,
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while self.peek() == Some(&Token::Semicolon) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str, error: &'static str) -> Result<(), &'static str> {
+        self.skip_separators();
+        if self.peek_keyword() == Some(keyword) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+
+    /// Pipelines up to the end of the input or one of `terminators`, which is
+    /// left for the caller
+    fn list(&mut self, terminators: &[&str]) -> Result<CommandList, &'static str> {
+        let mut list = CommandList::default();
+        let mut connector = Connector::Always;
+
+        loop {
+            // Empty lines and stray `;` are fine, a dangling `&&` is not
+            if connector == Connector::Always {
+                self.skip_separators();
+            }
+            let at_end = match self.peek_keyword() {
+                Some(keyword) => terminators.contains(&keyword),
+                None => self.peek().is_none(),
+            };
+            if at_end {
+                if connector != Connector::Always {
+                    return Err("missing command after '&&' or '||'");
+                }
+                return Ok(list);
+            }
+
+            list.items.push((connector, self.pipeline()?));
+
+            connector = match self.peek() {
+                Some(Token::And) => Connector::And,
+                Some(Token::Or) => Connector::Or,
+                Some(Token::Semicolon) => Connector::Always,
+                None => return Ok(list),
+                Some(_) => return Err("syntax error near unexpected word"),
+            };
+            self.pos += 1;
+        }
+    }
+
+    fn pipeline(&mut self) -> Result<Pipeline, &'static str> {
+        let mut commands = alloc::vec![self.command()?];
+        while self.peek() == Some(&Token::Pipe) {
+            self.pos += 1;
+            if self.peek().is_none() {
+                return Err("missing command after '|'");
+            }
+            commands.push(self.command()?);
+        }
+        Ok(Pipeline { commands })
+    }
+
+    fn command(&mut self) -> Result<Command, &'static str> {
+        let compound = match self.peek_keyword() {
+            Some("if") => {
+                self.pos += 1;
+                self.if_clause()?
+            }
+            Some("for") => {
+                self.pos += 1;
+                self.for_clause()?
+            }
+            Some("while") => {
+                self.pos += 1;
+                let condition = self.condition("do")?;
+                let body = self.body()?;
+                CompoundCommand::While { condition, body }
+            }
+            // `in` is only special inside `for`
+            Some(keyword) if keyword != "in" => {
+                return Err("syntax error near unexpected keyword");
+            }
+            _ => return self.simple_command().map(Command::Simple),
+        };
+
+        let mut redirects = Vec::new();
+        while let Some(Token::Redirect(kind)) = self.peek() {
+            let kind = *kind;
+            self.pos += 1;
+            redirects.push(Redirect {
+                kind,
+                target: self.redirect_target()?,
+            });
+        }
+        Ok(Command::Compound(compound, redirects))
+    }
+
+    fn simple_command(&mut self) -> Result<SimpleCommand, &'static str> {
+        let mut command = SimpleCommand::default();
+        loop {
+            match self.peek() {
+                Some(Token::Word(word)) => {
+                    command.words.push(word.clone());
+                    self.pos += 1;
+                }
+                Some(Token::Redirect(kind)) => {
+                    let kind = *kind;
+                    self.pos += 1;
+                    command.redirects.push(Redirect {
+                        kind,
+                        target: self.redirect_target()?,
+                    });
+                }
+                _ => break,
+            }
+        }
+
+        if command == SimpleCommand::default() {
+            return Err(match self.peek() {
+                Some(Token::Pipe) => "syntax error near '|'",
+                _ => "syntax error near '&&', '||' or ';'",
+            });
+        }
+        Ok(command)
+    }
+
+    fn redirect_target(&mut self) -> Result<Word, &'static str> {
+        match self.next() {
+            Some(Token::Word(target)) => Ok(target),
+            _ => Err("missing file name after redirection"),
+        }
+    }
+
+    /// A list ended by `keyword`, which is consumed
+    fn condition(&mut self, keyword: &'static str) -> Result<CommandList, &'static str> {
+        let condition = self.list(&[keyword])?;
+        if condition.items.is_empty() {
+            return Err("missing condition");
+        }
+        self.expect_keyword(
+            keyword,
+            match keyword {
+                "then" => "expected 'then'",
+                _ => "expected 'do'",
+            },
+        )?;
+        Ok(condition)
+    }
+
+    /// The body of a loop, up to and including `done`
+    fn body(&mut self) -> Result<CommandList, &'static str> {
+        let body = self.list(&["done"])?;
+        self.expect_keyword("done", "expected 'done'")?;
+        Ok(body)
+    }
+
+    fn if_clause(&mut self) -> Result<CompoundCommand, &'static str> {
+        let mut branches = Vec::new();
+        loop {
+            let condition = self.condition("then")?;
+            let body = self.list(&["elif", "else", "fi"])?;
+            branches.push((condition, body));
+
+            match self.next_keyword() {
+                Some("elif") => continue,
+                Some("else") => {
+                    let otherwise = self.list(&["fi"])?;
+                    self.expect_keyword("fi", "expected 'fi'")?;
+                    return Ok(CompoundCommand::If {
+                        branches,
+                        otherwise: Some(otherwise),
+                    });
+                }
+                Some("fi") => {
+                    return Ok(CompoundCommand::If {
+                        branches,
+                        otherwise: None,
+                    });
+                }
+                _ => return Err("expected 'fi'"),
+            }
+        }
+    }
+
+    fn next_keyword(&mut self) -> Option<&'static str> {
+        let keyword = self.peek_keyword()?;
+        self.pos += 1;
+        Some(keyword)
+    }
+
+    fn for_clause(&mut self) -> Result<CompoundCommand, &'static str> {
+        let variable = match self.next() {
+            Some(Token::Word(word)) => match word.parts.as_slice() {
+                [WordPart::Text(name)] if is_name(name) => name.clone(),
+                _ => return Err("bad for loop variable"),
+            },
+            _ => return Err("missing for loop variable"),
+        };
+
+        if self.next_keyword() != Some("in") {
+            return Err("expected 'in'");
+        }
+        let mut words = Vec::new();
+        while let Some(Token::Word(word)) = self.peek() {
+            words.push(word.clone());
+            self.pos += 1;
+        }
+
+        self.expect_keyword("do", "expected 'do'")?;
+        let body = self.body()?;
+        Ok(CompoundCommand::For {
+            variable,
+            words,
+            body,
+        })
+    }
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>, &'static str> {
+    let mut tokens = Vec::new();
+    let mut parts: Vec<WordPart> = Vec::new();
+    let mut text = String::new();
+    // A word can be empty (`""`), so track whether one was started
+    let mut in_word = false;
+    let mut chars = line.chars().peekable();
+
+    // Move the current word, if any, into the token list
+    fn finish_word(
+        tokens: &mut Vec<Token>,
+        parts: &mut Vec<WordPart>,
+        text: &mut String,
+        in_word: &mut bool,
+    ) {
+        if !text.is_empty() {
+            parts.push(WordPart::Text(core::mem::take(text)));
+        }
+        if *in_word {
+            tokens.push(Token::Word(Word {
+                parts: core::mem::take(parts),
+            }));
+        }
+        *in_word = false;
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\r' => finish_word(&mut tokens, &mut parts, &mut text, &mut in_word),
+            '\n' | ';' => {
+                finish_word(&mut tokens, &mut parts, &mut text, &mut in_word);
+                tokens.push(Token::Semicolon);
+            }
+            '#' if !in_word => {
+                // Comment up to the end of the line
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            '|' | '&' | '>' | '<' => {
+                finish_word(&mut tokens, &mut parts, &mut text, &mut in_word);
+                let doubled = chars.peek() == Some(&c);
+                let token = match (c, doubled) {
+                    ('|', true) => Token::Or,
+                    ('|', false) => Token::Pipe,
+                    ('&', true) => Token::And,
+                    ('&', false) => return Err("background jobs are not supported"),
+                    ('>', true) => Token::Redirect(RedirectKind::Append),
+                    ('>', false) => Token::Redirect(RedirectKind::Output),
+                    ('<', true) => return Err("here-documents are not supported"),
+                    _ => Token::Redirect(RedirectKind::Input),
+                };
+                if doubled {
+                    chars.next();
+                }
+                tokens.push(token);
+            }
+            '\\' => {
+                in_word = true;
+                // A backslash before the newline joins the lines
+                match chars.next() {
+                    Some('\n') | None => {}
+                    Some(escaped) => text.push(escaped),
+                }
+            }
+            '\'' => {
+                in_word = true;
+                flush_text(&mut parts, &mut text);
+                let mut quoted = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => quoted.push(c),
+                        None => return Err("unterminated single quote"),
+                    }
+                }
+                parts.push(WordPart::Quoted(quoted));
+            }
+            '"' => {
+                in_word = true;
+                flush_text(&mut parts, &mut text);
+                let mut quoted = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => quoted.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                quoted.push('\\');
+                                quoted.push(c);
+                            }
+                            None => return Err("unterminated double quote"),
+                        },
+                        Some('$') => match read_variable(&mut chars)? {
+                            Some(name) => {
+                                if !quoted.is_empty() {
+                                    parts.push(WordPart::Quoted(core::mem::take(&mut quoted)));
+                                }
+                                parts.push(WordPart::Var(name));
+                            }
+                            None => quoted.push('$'),
+                        },
+                        Some(c) => quoted.push(c),
+                        None => return Err("unterminated double quote"),
+                    }
+                }
+                parts.push(WordPart::Quoted(quoted));
+            }
+            '$' => {
+                in_word = true;
+                match read_variable(&mut chars)? {
+                    Some(name) => {
+                        flush_text(&mut parts, &mut text);
+                        parts.push(WordPart::Var(name));
+                    }
+                    None => text.push('$'),
+                }
+            }
+            c => {
+                in_word = true;
+                text.push(c);
+            }
+        }
+    }
+
+    finish_word(&mut tokens, &mut parts, &mut text, &mut in_word);
+    Ok(tokens)
+}
+
+fn flush_text(parts: &mut Vec<WordPart>, text: &mut String) {
+    if !text.is_empty() {
+        parts.push(WordPart::Text(core::mem::take(text)));
+    }
+}
+
+/// `$?`, `$#`, `$@` and the positional parameters like `$1`
+pub fn is_special_name(name: &str) -> bool {
+    matches!(name, "?" | "#" | "@")
+        || (!name.is_empty() && name.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Name of the variable after a `$`, or None if the `$` is just a dollar sign
+fn read_variable(
+    chars: &mut core::iter::Peekable<core::str::Chars>,
+) -> Result<Option<String>, &'static str> {
+    match chars.peek() {
+        Some(&c @ ('?' | '#' | '@' | '0'..='9')) => {
+            chars.next();
+            Ok(Some(c.into()))
+        }
+        Some('{') => {
+            chars.next();
+            let mut name = String::new();
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some(c) => name.push(c),
+                    None => return Err("missing '}'"),
+                }
+            }
+            if is_special_name(&name) || is_name(&name) {
+                Ok(Some(name))
+            } else {
+                Err("bad variable name")
+            }
+        }
+        Some(&c) if c == '_' || c.is_ascii_alphabetic() => {
+            let mut name = String::new();
+            while let Some(&c) = chars.peek() {
+                if c != '_' && !c.is_ascii_alphanumeric() {
+                    break;
+                }
+                name.push(c);
+                chars.next();
+            }
+            Ok(Some(name))
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::ToString, vec};
+
+    fn word(text: &str) -> Word {
+        Word {
+            parts: vec![WordPart::Text(text.to_string())],
+        }
+    }
+
+    fn simple(words: &[&str]) -> Command {
+        Command::Simple(SimpleCommand {
+            words: words.iter().map(|w| word(w)).collect(),
+            redirects: Vec::new(),
+        })
+    }
+
+    fn list(items: Vec<(Connector, Vec<Command>)>) -> CommandList {
+        CommandList {
+            items: items
+                .into_iter()
+                .map(|(connector, commands)| (connector, Pipeline { commands }))
+                .collect(),
+        }
+    }
+
+    /// A list with one simple command
+    fn single(words: &[&str]) -> CommandList {
+        list(vec![(Connector::Always, vec![simple(words)])])
+    }
+
+    fn words_of(line: &str) -> Vec<Word> {
+        match &parse(line).unwrap().items[0].1.commands[0] {
+            Command::Simple(command) => command.words.clone(),
+            _ => panic!("not a simple command"),
+        }
+    }
+
+    #[test_case]
+    fn test_quotes_and_variables() {
+        assert_eq!(
+            words_of(r#"echo 'a $b' "x $HOME y" $1 ${NAME}x \$z"#),
+            vec![
+                word("echo"),
+                Word {
+                    parts: vec![WordPart::Quoted("a $b".to_string())],
+                },
+                Word {
+                    parts: vec![
+                        WordPart::Quoted("x ".to_string()),
+                        WordPart::Var("HOME".to_string()),
+                        WordPart::Quoted(" y".to_string()),
+                    ],
+                },
+                Word {
+                    parts: vec![WordPart::Var("1".to_string())],
+                },
+                Word {
+                    parts: vec![
+                        WordPart::Var("NAME".to_string()),
+                        WordPart::Text("x".to_string()),
+                    ],
+                },
+                word("$z"),
+            ]
+        );
+    }
+
+    #[test_case]
+    fn test_empty_words_and_comments() {
+        assert_eq!(
+            words_of(r#"echo "" a#b # the rest"#),
+            vec![
+                word("echo"),
+                Word {
+                    parts: vec![WordPart::Quoted(String::new())],
+                },
+                word("a#b"),
+            ]
+        );
+        assert_eq!(parse("  # only a comment").unwrap(), CommandList::default());
+    }
+
+    #[test_case]
+    fn test_pipelines_and_connectors() {
+        assert_eq!(
+            parse("a | b && c || d; e\nf").unwrap(),
+            list(vec![
+                (Connector::Always, vec![simple(&["a"]), simple(&["b"])]),
+                (Connector::And, vec![simple(&["c"])]),
+                (Connector::Or, vec![simple(&["d"])]),
+                (Connector::Always, vec![simple(&["e"])]),
+                (Connector::Always, vec![simple(&["f"])]),
+            ])
+        );
+    }
+
+    #[test_case]
+    fn test_redirects() {
+        assert_eq!(
+            parse("sort < in > out >> log").unwrap(),
+            list(vec![(
+                Connector::Always,
+                vec![Command::Simple(SimpleCommand {
+                    words: vec![word("sort")],
+                    redirects: vec![
+                        Redirect {
+                            kind: RedirectKind::Input,
+                            target: word("in"),
+                        },
+                        Redirect {
+                            kind: RedirectKind::Output,
+                            target: word("out"),
+                        },
+                        Redirect {
+                            kind: RedirectKind::Append,
+                            target: word("log"),
+                        },
+                    ],
+                })],
+            )])
+        );
+    }
+
+    #[test_case]
+    fn test_compound_commands() {
+        assert_eq!(
+            parse("if a; then b; elif c; then d; else e; fi > out").unwrap(),
+            list(vec![(
+                Connector::Always,
+                vec![Command::Compound(
+                    CompoundCommand::If {
+                        branches: vec![
+                            (single(&["a"]), single(&["b"])),
+                            (single(&["c"]), single(&["d"]))
+                        ],
+                        otherwise: Some(single(&["e"])),
+                    },
+                    vec![Redirect {
+                        kind: RedirectKind::Output,
+                        target: word("out"),
+                    }],
+                )],
+            )])
+        );
+
+        assert_eq!(
+            parse("for x in 1 2; do b; done").unwrap(),
+            list(vec![(
+                Connector::Always,
+                vec![Command::Compound(
+                    CompoundCommand::For {
+                        variable: "x".to_string(),
+                        words: vec![word("1"), word("2")],
+                        body: single(&["b"]),
+                    },
+                    Vec::new(),
+                )],
+            )])
+        );
+
+        assert_eq!(
+            parse("while a\ndo\nb\ndone").unwrap(),
+            list(vec![(
+                Connector::Always,
+                vec![Command::Compound(
+                    CompoundCommand::While {
+                        condition: single(&["a"]),
+                        body: single(&["b"]),
+                    },
+                    Vec::new(),
+                )],
+            )])
+        );
+    }
+
+    #[test_case]
+    fn test_keywords_only_where_a_command_starts() {
+        assert_eq!(parse("echo if fi").unwrap(), single(&["echo", "if", "fi"]));
+        assert_eq!(
+            words_of(r#""if" x"#),
+            vec![
+                Word {
+                    parts: vec![WordPart::Quoted("if".to_string())],
+                },
+                word("x"),
+            ]
+        );
+    }
+
+    #[test_case]
+    fn test_assignment() {
+        assert_eq!(word("X=1").assignment(), Some(("X", &[][..], "1")));
+        assert_eq!(word("1X=1").assignment(), None);
+        assert_eq!(words_of(r#""X"=1"#)[0].assignment(), None);
+    }
+
+    #[test_case]
+    fn test_syntax_errors() {
+        for line in [
+            "echo 'x",
+            "echo \"x",
+            "echo ${",
+            "a &&",
+            "a &",
+            "| b",
+            "a |",
+            "a >",
+            "cat <<EOF",
+            "fi",
+            "if a; then b",
+            "if; then b; fi",
+            "for 1 in x; do a; done",
+            "while a; do b",
+        ] {
+            assert!(parse(line).is_err(), "{} should not parse", line);
+        }
+    }
+}
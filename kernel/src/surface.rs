@@ -4,8 +4,8 @@ use embedded_graphics::{pixelcolor::Rgb888, prelude::RgbColor};
 use noto_sans_mono_bitmap::{FontWeight, RasterHeight};
 use tinybmp::{Bmp, RawBmp};
 
+use crate::debug;
 use crate::framebuffer::{Color, FrameBufferWriter};
-use crate::serial_println;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Rect {
@@ -46,6 +46,26 @@ impl Rect {
         }
     }
 
+    /// Overlapping part of two rectangles, None if they don't touch
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let x1 = self.x.max(other.x);
+        let y1 = self.y.max(other.y);
+        let x2 = (self.x + self.width).min(other.x + other.width);
+        let y2 = (self.y + self.height).min(other.y + other.height);
+
+        if x1 >= x2 || y1 >= y2 {
+            return None;
+        }
+        Some(Rect::new(x1, y1, x2 - x1, y2 - y1))
+    }
+
+    pub fn contains_rect(&self, other: &Rect) -> bool {
+        other.x >= self.x
+            && other.y >= self.y
+            && other.x + other.width <= self.x + self.width
+            && other.y + other.height <= self.y + self.height
+    }
+
     pub fn contains_point(&self, x: usize, y: usize) -> bool {
         x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
     }
@@ -342,10 +362,24 @@ impl Shape {
     }
 }
 
+/// Image repeated across a surface's background instead of the flat color
+pub struct Wallpaper {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Color>,
+}
+
+impl Wallpaper {
+    fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[(y % self.height) * self.width + x % self.width]
+    }
+}
+
 pub struct Surface {
     pub width: usize,
     pub height: usize,
     pub background_color: Color,
+    pub wallpaper: Option<Wallpaper>,
     pub just_fill_bg: bool,
     pub shapes: Vec<Shape>,
     pub is_dirty: bool,
@@ -358,6 +392,7 @@ impl Surface {
             width,
             height,
             background_color,
+            wallpaper: None,
             just_fill_bg: false,
             shapes: Vec::new(),
             is_dirty: true,
@@ -577,6 +612,31 @@ impl Surface {
         }
     }
 
+    pub fn update_text_background(&mut self, shape_id: usize, new_color: Color) -> bool {
+        if let Some(Shape::Text {
+            background_color, ..
+        }) = self.shapes.get_mut(shape_id)
+        {
+            *background_color = new_color;
+            let bounds = self.shapes[shape_id].get_bounds();
+            self.mark_region_dirty(bounds);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn update_image_data(&mut self, shape_id: usize, new_data: Vec<Color>) -> bool {
+        if let Some(Shape::RawImage { data, .. }) = self.shapes.get_mut(shape_id) {
+            *data = new_data;
+            let bounds = self.shapes[shape_id].get_bounds();
+            self.mark_region_dirty(bounds);
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn update_shape_visibility(&mut self, shape_id: usize, visible: bool) -> bool {
         if let Some(shape) = self.shapes.get_mut(shape_id) {
             let dirty_region = shape.set_visibility(visible);
@@ -653,8 +713,14 @@ impl Surface {
         // Render each dirty region
         for region in &self.dirty_regions {
             // Clear the dirty region with background
-            // Use just_fill_bg mode only if we're doing a full redraw OR if there are no shapes
-            if self.just_fill_bg && (force || self.shapes.is_empty()) {
+            if let Some(wallpaper) = &self.wallpaper {
+                for y in region.y..(region.y + region.height).min(self.height) {
+                    for x in region.x..(region.x + region.width).min(self.width) {
+                        framebuffer.write_pixel(x + offset_x, y + offset_y, wallpaper.pixel(x, y));
+                    }
+                }
+            } else if self.just_fill_bg && (force || self.shapes.is_empty()) {
+                // Use just_fill_bg mode only if we're doing a full redraw OR if there are no shapes
                 // For just_fill_bg mode with full redraw or no shapes
                 for y in region.y..(region.y + region.height) {
                     for x in region.x..(region.x + region.width) {
@@ -715,7 +781,7 @@ impl Surface {
 }
 
 pub fn create_image_shape(x: usize, y: usize, bmp_data: &'static [u8]) -> Option<Shape> {
-    serial_println!(
+    debug!(
         "Creating image shape from BMP data of size {}",
         bmp_data.len()
     );
@@ -725,7 +791,7 @@ pub fn create_image_shape(x: usize, y: usize, bmp_data: &'static [u8]) -> Option
         let height = size.height as usize;
         let width = size.width as usize;
 
-        serial_println!("BMP dimensions: {}x{}", width, height);
+        debug!("BMP dimensions: {}x{}", width, height);
 
         Some(Shape::BmpImage {
             x,
@@ -1,7 +1,7 @@
-use crate::{serial_println, tasks::scheduler::do_exit};
+use crate::tasks::{process, scheduler::do_exit};
 
 #[unsafe(no_mangle)]
-pub extern "C" fn sys_exit() {
-    serial_println!("enter syscall exit");
+pub extern "C" fn sys_exit(code: i32) {
+    process::exit(code);
     do_exit();
 }
@@ -1,7 +1,12 @@
 pub mod exit;
+pub mod stat;
 pub mod write;
 
+use x86_64::VirtAddr;
+
+use crate::memory::is_user_memory;
 use crate::syscalls::exit::sys_exit;
+use crate::syscalls::stat::sys_stat;
 use crate::syscalls::write::sys_write;
 
 /// number of the system call `exit`
@@ -10,8 +15,14 @@ pub const SYSNO_EXIT: usize = 0;
 /// number of the system call `write`
 pub const SYSNO_WRITE: usize = 1;
 
+/// number of the system call `stat`
+pub const SYSNO_STAT: usize = 2;
+
 /// total number of system calls
-pub const NO_SYSCALLS: usize = 2;
+pub const NO_SYSCALLS: usize = 3;
+
+/// Lowest address of the kernel half, user buffers must end below it
+const USER_SPACE_END: usize = 0x0000_8000_0000_0000;
 
 #[repr(align(64))]
 #[repr(C)]
@@ -22,7 +33,11 @@ pub(crate) struct SyscallTable {
 impl SyscallTable {
     pub const fn new() -> Self {
         SyscallTable {
-            handle: [sys_exit as *const _, sys_write as *const _],
+            handle: [
+                sys_exit as *const _,
+                sys_write as *const _,
+                sys_stat as *const _,
+            ],
         }
     }
 }
@@ -30,5 +45,15 @@ impl SyscallTable {
 unsafe impl Send for SyscallTable {}
 unsafe impl Sync for SyscallTable {}
 
+/// Whether `buf..buf + len` is in the caller's address space and mapped,
+/// so a system call can use it without faulting inside the kernel
+pub(crate) fn is_user_buffer(buf: usize, len: usize) -> bool {
+    let end = buf.checked_add(len);
+    if buf == 0 || end.is_none_or(|end| end > USER_SPACE_END) {
+        return false;
+    }
+    is_user_memory(VirtAddr::new(buf as u64), len as u64)
+}
+
 #[unsafe(no_mangle)]
 pub(crate) static SYSHANDLER_TABLE: SyscallTable = SyscallTable::new();
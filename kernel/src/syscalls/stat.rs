@@ -0,0 +1,79 @@
+use crate::fs::manager::{follow_symlinks, stat};
+use crate::fs::metadata::{FileStat, FileType};
+use crate::syscalls::is_user_buffer;
+use crate::time::{DateTime, Time, get_timezone_offset};
+
+/// File type bits of [`Stat::mode`], as in POSIX `st_mode`
+pub const S_IFDIR: u32 = 0o040000;
+pub const S_IFREG: u32 = 0o100000;
+pub const S_IFLNK: u32 = 0o120000;
+
+/// What `stat` writes to the caller's buffer
+#[repr(C)]
+pub struct Stat {
+    /// File type and permission bits
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub size: u64,
+    /// Times in seconds since 1970-01-01 UTC
+    pub accessed_at: i64,
+    pub modified_at: i64,
+    pub created_at: i64,
+}
+
+/// FAT stores local time
+fn seconds_since_epoch(time: &DateTime) -> i64 {
+    time.ms_since_epoch() / 1000 - get_timezone_offset() as i64 * 60
+}
+
+impl From<&FileStat> for Stat {
+    fn from(info: &FileStat) -> Self {
+        let file_type = match info.file_type {
+            FileType::Directory => S_IFDIR,
+            FileType::Regular => S_IFREG,
+            FileType::Symlink => S_IFLNK,
+        };
+        let midnight = Time {
+            millis: 0,
+            seconds: 0,
+            minutes: 0,
+            hours: 0,
+        };
+        let accessed_at = DateTime::from_date_and_time(info.accessed_at, midnight);
+
+        Stat {
+            mode: file_type | info.mode as u32,
+            uid: info.uid as u32,
+            gid: info.gid as u32,
+            size: info.size as u64,
+            accessed_at: seconds_since_epoch(&accessed_at),
+            modified_at: seconds_since_epoch(&info.modified_at),
+            created_at: seconds_since_epoch(&info.created_at),
+        }
+    }
+}
+
+/// `path` is `len` bytes of UTF-8, `buf` has room for a [`Stat`]. Symlinks
+/// are followed. Returns 0, or -1 on a bad argument or a missing file.
+#[unsafe(no_mangle)]
+pub extern "C" fn sys_stat(path: usize, len: usize, buf: usize) -> isize {
+    if !is_user_buffer(path, len) || !is_user_buffer(buf, size_of::<Stat>()) {
+        return -1;
+    }
+    if !buf.is_multiple_of(align_of::<Stat>()) {
+        return -1;
+    }
+
+    let bytes = unsafe { core::slice::from_raw_parts(path as *const u8, len) };
+    let Ok(path) = core::str::from_utf8(bytes) else {
+        return -1;
+    };
+
+    let Ok(info) = follow_symlinks(path).and_then(|path| stat(&path)) else {
+        return -1;
+    };
+
+    unsafe { (buf as *mut Stat).write(Stat::from(&info)) };
+    0
+}
@@ -1,13 +1,29 @@
 use alloc::string::String;
 
 use crate::serial_println;
+use crate::syscalls::is_user_buffer;
+use crate::tasks::process;
+use crate::tasks::scheduler::do_exit;
 
+/// `buf` is an address in the caller's address space, checked before it is read
 #[unsafe(no_mangle)]
-pub extern "C" fn sys_write(s: *mut u8, len: usize) -> isize {
-    serial_println!("enter syscall write");
-    let str = unsafe { String::from_raw_parts(s, len, len) };
-    serial_println!("[sys_write] {}", str);
-    core::mem::forget(str);
+pub extern "C" fn sys_write(fd: usize, buf: usize, len: usize) -> isize {
+    if process::killed() {
+        process::exit(process::KILLED_EXIT_CODE);
+        do_exit();
+    }
+
+    if !is_user_buffer(buf, len) {
+        return -1;
+    }
+
+    let bytes = unsafe { core::slice::from_raw_parts(buf as *const u8, len) };
+    let text = String::from_utf8_lossy(bytes);
+
+    // Programs started from the terminal write there, the rest to serial
+    if !process::write_output(fd, &text) {
+        serial_println!("[sys_write] {}", text);
+    }
 
     len as isize
 }
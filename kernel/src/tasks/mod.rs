@@ -1,3 +1,4 @@
+pub mod process;
 pub mod scheduler;
 pub mod state;
 pub mod switch;
@@ -0,0 +1,231 @@
+//! User programs started after boot. Whatever they write goes into a
+//! [`ProcessOutput`] shared with the code that started them, so a front end
+//! like the terminal can show it.
+
+use alloc::{collections::BTreeMap, string::String, sync::Arc, vec::Vec};
+use spinning_top::Spinlock;
+use x86_64::{
+    VirtAddr,
+    instructions::{hlt, interrupts::without_interrupts},
+    structures::paging::page::PageRangeInclusive,
+};
+
+use crate::memory::{FRAME_ALLOCATOR, ProcessAddressSpace};
+use crate::tasks::task::{LOW_PRIORITY, TaskId};
+use crate::tasks::{jump_to_user_land, scheduler, spawn_process};
+use crate::time::get_ms_since_epoch;
+use crate::user_program_loader::load_elf;
+
+/// Exit code of a program stopped with [`kill`]
+pub const KILLED_EXIT_CODE: i32 = 130;
+
+/// What a program wrote so far and how it ended
+#[derive(Default)]
+pub struct ProcessOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// None while the program runs
+    pub exit_code: Option<i32>,
+    killed: bool,
+}
+
+impl ProcessOutput {
+    /// Hand over what was written since the last call, as (stdout, stderr)
+    pub fn take(&mut self) -> (String, String) {
+        (
+            core::mem::take(&mut self.stdout),
+            core::mem::take(&mut self.stderr),
+        )
+    }
+}
+
+/// Shared with the task running the program. Lock it with interrupts off,
+/// the task may be holding it when the timer fires.
+pub type ProcessHandle = Arc<Spinlock<ProcessOutput>>;
+
+/// Kept until the task is gone, then its address space is given back
+struct Process {
+    entry_point: VirtAddr,
+    stack_pointer: VirtAddr,
+    output: ProcessHandle,
+    address_space: ProcessAddressSpace,
+    pages: Vec<PageRangeInclusive>,
+}
+
+static PROCESSES: Spinlock<BTreeMap<TaskId, Process>> = Spinlock::new(BTreeMap::new());
+
+/// Load an ELF image into a new address space and start it.
+/// The frames of the address space are given back once the program is gone.
+pub fn spawn_elf(data: &[u8]) -> Result<ProcessHandle, &'static str> {
+    reap();
+
+    let program = {
+        let mut frame_allocator = FRAME_ALLOCATOR
+            .get()
+            .ok_or("Frame allocator not initialized")?
+            .lock();
+        load_elf(data, &mut frame_allocator)?
+    };
+
+    let output = ProcessHandle::default();
+
+    // The task may run on the next tick, its entry has to be there by then.
+    // Same priority as the desktop (the idle task) so neither starves the other.
+    without_interrupts(|| {
+        let tid = spawn_process(start_process, LOW_PRIORITY, program.address_space)
+            .map_err(|_| "Failed to create task")?;
+        PROCESSES.lock().insert(
+            tid,
+            Process {
+                entry_point: program.entry_point,
+                stack_pointer: program.stack_pointer,
+                output: output.clone(),
+                address_space: program.address_space,
+                pages: program.pages,
+            },
+        );
+        Ok(output)
+    })
+}
+
+extern "C" fn start_process() {
+    let start = without_interrupts(|| {
+        let tid = scheduler::get_current_taskid();
+        PROCESSES
+            .lock()
+            .get(&tid)
+            .map(|process| (process.entry_point, process.stack_pointer))
+    });
+
+    if let Some((entry, stack)) = start {
+        unsafe {
+            let func: extern "C" fn() = core::mem::transmute(entry.as_u64());
+            jump_to_user_land(func, stack);
+        }
+    }
+}
+
+/// Add `text` to the current task's output, fd 2 is stderr and anything
+/// else stdout. False when the task wasn't started by [`spawn_elf`].
+pub(crate) fn write_output(fd: usize, text: &str) -> bool {
+    without_interrupts(|| {
+        let tid = scheduler::get_current_taskid();
+        let processes = PROCESSES.lock();
+        let Some(process) = processes.get(&tid) else {
+            return false;
+        };
+
+        let mut output = process.output.lock();
+        match fd {
+            2 => output.stderr.push_str(text),
+            _ => output.stdout.push_str(text),
+        }
+        true
+    })
+}
+
+/// Whether [`kill`] was called for the current task's program
+pub(crate) fn killed() -> bool {
+    without_interrupts(|| {
+        let tid = scheduler::get_current_taskid();
+        PROCESSES
+            .lock()
+            .get(&tid)
+            .is_some_and(|process| process.output.lock().killed)
+    })
+}
+
+/// Record how the current task's program ended, before the task goes away
+pub(crate) fn exit(code: i32) {
+    without_interrupts(|| {
+        let tid = scheduler::get_current_taskid();
+        if let Some(process) = PROCESSES.lock().get(&tid) {
+            process.output.lock().exit_code = Some(code);
+        }
+    });
+}
+
+/// Stop the current task's program if [`kill`] was called for it. Runs from
+/// the timer interrupt, only when the program was interrupted in user code:
+/// inside a system call it may hold locks, and stops at its next write.
+pub(crate) fn stop_if_killed() {
+    let tid = scheduler::get_current_taskid();
+    let stopped = PROCESSES.lock().get(&tid).is_some_and(|process| {
+        let mut output = process.output.lock();
+        if output.killed && output.exit_code.is_none() {
+            output.exit_code = Some(KILLED_EXIT_CODE);
+            true
+        } else {
+            false
+        }
+    });
+
+    if stopped {
+        scheduler::kill_current();
+    }
+}
+
+/// Give back the address spaces of programs whose task is gone. The
+/// scheduler only drops a task after switching away from it, so nothing
+/// runs on them anymore.
+fn reap() {
+    let gone: Vec<Process> = without_interrupts(|| {
+        let mut processes = PROCESSES.lock();
+        let tids: Vec<TaskId> = processes
+            .keys()
+            .copied()
+            .filter(|&tid| !scheduler::task_exists(tid))
+            .collect();
+        tids.iter()
+            .filter_map(|tid| processes.remove(tid))
+            .collect()
+    });
+    if gone.is_empty() {
+        return;
+    }
+
+    let Some(frame_allocator) = FRAME_ALLOCATOR.get() else {
+        return;
+    };
+    let mut frame_allocator = frame_allocator.lock();
+    for mut process in gone {
+        unsafe {
+            process
+                .address_space
+                .release(&process.pages, &mut *frame_allocator);
+        }
+    }
+}
+
+/// Ask a program to stop. The scheduler stops it on the next timer tick
+/// that finds it in user code, or it ends at its next system call.
+pub fn kill(handle: &ProcessHandle) {
+    without_interrupts(|| handle.lock().killed = true);
+}
+
+/// Exit code of the program, None while it runs
+pub fn exit_code(handle: &ProcessHandle) -> Option<i32> {
+    reap();
+    without_interrupts(|| handle.lock().exit_code)
+}
+
+/// Hand over what the program wrote since the last call, as (stdout, stderr)
+pub fn take_output(handle: &ProcessHandle) -> (String, String) {
+    without_interrupts(|| handle.lock().take())
+}
+
+/// Wait up to `timeout_ms` for the program to exit, for callers that can't
+/// poll. Gives the CPU away meanwhile, the caller must not hold locks the
+/// program needs.
+pub fn wait(handle: &ProcessHandle, timeout_ms: i64) -> Option<i32> {
+    let deadline = get_ms_since_epoch() + timeout_ms;
+    loop {
+        if let Some(code) = exit_code(handle) {
+            return Some(code);
+        }
+        if get_ms_since_epoch() >= deadline {
+            return None;
+        }
+        hlt();
+    }
+}
@@ -11,9 +11,9 @@ use x86_64::registers::control::Cr3;
 use x86_64::structures::paging::{PhysFrame, Size4KiB};
 
 use crate::memory::ProcessAddressSpace;
-use crate::serial_println;
 use crate::tasks::switch::switch;
 use crate::tasks::task::{Task, TaskId, TaskStatus};
+use crate::{debug, info, serial_println};
 
 static TID_COUNTER: AtomicU32 = AtomicU32::new(0);
 
@@ -90,7 +90,7 @@ impl Scheduler {
         self.ready_queue.push(task.clone());
         self.tasks.insert(tid, task);
 
-        serial_println!("Creating task {}", tid);
+        debug!("Creating task {}", tid);
 
         Ok(tid)
     }
@@ -110,7 +110,7 @@ impl Scheduler {
 
     pub fn exit(&mut self) {
         if self.current_task.lock().status != TaskStatus::Idle {
-            serial_println!("finish task with id {}", self.current_task.lock().id);
+            debug!("finish task with id {}", self.current_task.lock().id);
             self.current_task.lock().status = TaskStatus::Finished;
         } else {
             panic!("unable to terminate idle task");
@@ -119,18 +119,27 @@ impl Scheduler {
 
     pub fn abort(&mut self) {
         if self.current_task.lock().status != TaskStatus::Idle {
-            serial_println!("abort task with id {}", self.current_task.lock().id);
+            debug!("abort task with id {}", self.current_task.lock().id);
             self.current_task.lock().status = TaskStatus::Finished;
         } else {
             panic!("unable to terminate idle task");
         }
     }
 
+    /// Like `abort`, but from the timer interrupt, where the log can't be used
+    pub fn kill_current(&mut self) {
+        let mut current = self.current_task.lock();
+        if current.status != TaskStatus::Idle {
+            serial_println!("kill task with id {}", current.id);
+            current.status = TaskStatus::Finished;
+        }
+    }
+
     #[allow(dead_code)]
     pub fn block_current_task(&mut self) -> Arc<Spinlock<Task>> {
         let closure = || {
             if self.current_task.lock().status == TaskStatus::Running {
-                serial_println!("block task {}", self.current_task.lock().id);
+                debug!("block task {}", self.current_task.lock().id);
 
                 self.current_task.lock().status = TaskStatus::Blocked;
                 self.current_task.clone()
@@ -146,6 +155,7 @@ impl Scheduler {
     pub fn wakeup_task(&mut self, task: Arc<Spinlock<Task>>) {
         let closure = || {
             if task.lock().status == TaskStatus::Blocked {
+                // Interrupt handlers wake tasks up, no logging here
                 serial_println!("wakeup task {}", task.lock().id);
 
                 task.lock().status = TaskStatus::Ready;
@@ -160,11 +170,19 @@ impl Scheduler {
         irqsave(|| self.current_task.lock().id)
     }
 
+    /// Whether the task wasn't dropped yet, finished ones are kept until
+    /// the scheduler switched away from them
+    pub fn task_exists(&self, id: TaskId) -> bool {
+        self.tasks.contains_key(&id)
+    }
+
     /// Determines the start address of the stack
     pub fn get_current_interrupt_stack(&self) -> VirtAddr {
         irqsave(|| (*self.current_task.lock().stack).interrupt_top())
     }
 
+    // Runs from the timer interrupt, where the log can't allocate, so the
+    // messages here go straight to the serial port
     pub fn schedule(&mut self) -> Option<(*mut usize, usize, PhysFrame<Size4KiB>)> {
         // do we have finished tasks? => drop tasks => deallocate implicitly the stack
         if let Some(id) = self.finished_tasks.pop_front() {
@@ -252,11 +270,11 @@ pub(crate) static SCHEDULER: Spinlock<Option<Scheduler>> = Spinlock::new(None);
 pub fn init() {
     *SCHEDULER.lock() = Some(Scheduler::new());
 
-    serial_println!("Scheduler initialized");
+    info!("Scheduler initialized");
 
     register_task();
 
-    serial_println!("Scheduler task registered");
+    info!("Scheduler task registered");
 }
 
 /// Create a new kernel task
@@ -327,6 +345,14 @@ pub fn abort() -> ! {
     loop {}
 }
 
+/// Terminate the current task from the timer interrupt, it goes away on
+/// the following [`schedule`]
+pub(crate) fn kill_current() {
+    if let Some(scheduler) = SCHEDULER.lock().as_mut() {
+        scheduler.kill_current();
+    }
+}
+
 pub(crate) fn get_current_interrupt_stack() -> VirtAddr {
     SCHEDULER
         .lock()
@@ -349,3 +375,8 @@ pub(crate) fn wakeup_task(task: Arc<Spinlock<Task>>) {
 pub fn get_current_taskid() -> TaskId {
     SCHEDULER.lock().as_ref().unwrap().get_current_taskid()
 }
+
+/// Whether the task `id` still exists
+pub fn task_exists(id: TaskId) -> bool {
+    irqsave(|| SCHEDULER.lock().as_ref().unwrap().task_exists(id))
+}
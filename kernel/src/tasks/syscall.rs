@@ -21,10 +21,11 @@ pub(crate) extern "C" fn syscall_handler() {
         "mov rcx, r10",
         "sti",
 
-        // Load address of SYSHANDLER_TABLE RIP-relatively, then fetch entry
-        "lea rdx, [rip + {sys_handler}]",
-        "mov rdx, [rdx + rax*8]",   // rdx = table.handle[rax]
-        "call rdx",
+        // Load address of SYSHANDLER_TABLE RIP-relatively, then fetch entry.
+        // r11 (saved above) is free, rdx still holds the 3rd argument.
+        "lea r11, [rip + {sys_handler}]",
+        "mov r11, [r11 + rax*8]",   // r11 = table.handle[rax]
+        "call r11",
 
         // restore context, see x86_64 ABI \n\t\
         "cli",
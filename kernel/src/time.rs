@@ -1,5 +1,10 @@
+use alloc::{format, string::String};
+use core::sync::atomic::{AtomicI32, Ordering};
 use x86_64::instructions::port::Port;
 
+/// Offset of local time from UTC in minutes, from the time zone setting
+static TIMEZONE_OFFSET: AtomicI32 = AtomicI32::new(0);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Date {
     pub day: u8,
@@ -56,6 +61,38 @@ impl DateTime {
         }
     }
 
+    /// Shift by a number of minutes, carrying over into the date
+    pub fn add_minutes(&mut self, minutes: i32) {
+        let total = self.hours as i32 * 60 + self.minutes as i32 + minutes;
+        let days = total.div_euclid(24 * 60);
+        let minute_of_day = total.rem_euclid(24 * 60);
+        self.hours = (minute_of_day / 60) as u8;
+        self.minutes = (minute_of_day % 60) as u8;
+
+        for _ in 0..days {
+            self.day += 1;
+            if self.day > days_in_month(self.year, self.month) {
+                self.day = 1;
+                self.month += 1;
+                if self.month > 12 {
+                    self.month = 1;
+                    self.year += 1;
+                }
+            }
+        }
+        for _ in days..0 {
+            self.day -= 1;
+            if self.day == 0 {
+                self.month -= 1;
+                if self.month == 0 {
+                    self.month = 12;
+                    self.year -= 1;
+                }
+                self.day = days_in_month(self.year, self.month);
+            }
+        }
+    }
+
     pub fn from_date_and_time(date: Date, time: Time) -> Self {
         DateTime {
             millis: time.millis,
@@ -133,54 +170,117 @@ fn bcd_to_binary(bcd_value: u8) -> u8 {
     (bcd_value & 0x0F) + ((bcd_value >> 4) * 10) // Magic :)
 }
 
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
 pub fn get_utc_time() -> DateTime {
     read_rtc()
 }
 
-pub fn get_ms_since_epoch() -> i64 {
-    let rtc_time = read_rtc();
-    let year = rtc_time.year as i64;
-    let month = rtc_time.month as i64;
-    let day = rtc_time.day as i64;
-    let hours = rtc_time.hours as i64;
-    let minutes = rtc_time.minutes as i64;
-    let seconds = rtc_time.seconds as i64;
-    let millis = rtc_time.millis as i64;
-
-    // Calculate the number of days since the epoch (1970-01-01)
-    fn is_leap_year(year: i64) -> bool {
-        (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0)
-    }
+/// Current time in the configured time zone
+pub fn get_local_time() -> DateTime {
+    let mut time = read_rtc();
+    time.add_minutes(get_timezone_offset());
+    time
+}
 
-    // Days in each month (non-leap year)
-    const DAYS_IN_MONTH: [i64; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+pub fn set_timezone_offset(minutes: i32) {
+    TIMEZONE_OFFSET.store(minutes, Ordering::Relaxed);
+}
 
-    // Calculate days since epoch
-    let mut days_since_epoch = 0;
+pub fn get_timezone_offset() -> i32 {
+    TIMEZONE_OFFSET.load(Ordering::Relaxed)
+}
 
-    // Add days for each year since 1970
-    for y in 1970..year {
-        days_since_epoch += if is_leap_year(y) { 366 } else { 365 };
+/// Parse a time zone written as `UTC`, `UTC+2` or `UTC-03:30` into minutes
+pub fn parse_timezone(name: &str) -> Option<i32> {
+    let offset = name.trim().strip_prefix("UTC")?;
+    if offset.is_empty() {
+        return Some(0);
     }
 
-    // Add days for each month in the current year
-    for m in 0..(month - 1) {
-        days_since_epoch += if m == 1 && is_leap_year(year) {
-            29
-        } else {
-            DAYS_IN_MONTH[m as usize]
-        };
+    let (sign, offset) = match offset.as_bytes()[0] {
+        b'+' => (1, &offset[1..]),
+        b'-' => (-1, &offset[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+    let (hours, minutes) = (hours.parse::<i32>().ok()?, minutes.parse::<i32>().ok()?);
+    if hours > 14 || minutes >= 60 {
+        return None;
     }
 
-    // Add days in the current month
-    days_since_epoch += day - 1;
+    Some(sign * (hours * 60 + minutes))
+}
 
-    // Calculate the number of milliseconds since the epoch
-    let ms_since_epoch = days_since_epoch * 24 * 60 * 60 * 1000
-        + hours * 60 * 60 * 1000
-        + minutes * 60 * 1000
-        + seconds * 1000
-        + millis;
+pub fn format_timezone(minutes: i32) -> String {
+    if minutes == 0 {
+        return String::from("UTC");
+    }
 
-    ms_since_epoch
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let minutes = minutes.abs();
+    format!("UTC{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+}
+
+pub fn get_ms_since_epoch() -> i64 {
+    read_rtc().ms_since_epoch()
+}
+
+impl DateTime {
+    /// Milliseconds since 1970-01-01, reading the date and time as UTC
+    pub fn ms_since_epoch(&self) -> i64 {
+        let year = self.year as i64;
+        let month = self.month as i64;
+        let day = self.day as i64;
+        let hours = self.hours as i64;
+        let minutes = self.minutes as i64;
+        let seconds = self.seconds as i64;
+        let millis = self.millis as i64;
+
+        // Calculate the number of days since the epoch (1970-01-01)
+        fn is_leap_year(year: i64) -> bool {
+            (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0)
+        }
+
+        // Days in each month (non-leap year)
+        const DAYS_IN_MONTH: [i64; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+        // Calculate days since epoch
+        let mut days_since_epoch = 0;
+
+        // Add days for each year since 1970
+        for y in 1970..year {
+            days_since_epoch += if is_leap_year(y) { 366 } else { 365 };
+        }
+
+        // Add days for each month in the current year
+        for m in 0..(month - 1) {
+            days_since_epoch += if m == 1 && is_leap_year(year) {
+                29
+            } else {
+                DAYS_IN_MONTH[m as usize]
+            };
+        }
+
+        // Add days in the current month
+        days_since_epoch += day - 1;
+
+        // Calculate the number of milliseconds since the epoch
+        let ms_since_epoch = days_since_epoch * 24 * 60 * 60 * 1000
+            + hours * 60 * 60 * 1000
+            + minutes * 60 * 1000
+            + seconds * 1000
+            + millis;
+
+        ms_since_epoch
+    }
 }
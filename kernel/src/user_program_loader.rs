@@ -1,14 +1,18 @@
 use crate::PHYSICAL_MEMORY_OFFSET;
 use crate::memory::{BootInfoFrameAllocator, ProcessAddressSpace};
+use alloc::vec::Vec;
 use goblin::elf::Elf;
 use goblin::elf::program_header::PT_LOAD;
 use x86_64::VirtAddr;
+use x86_64::structures::paging::page::PageRangeInclusive;
 use x86_64::structures::paging::{FrameAllocator, PageTableFlags, PhysFrame, Size4KiB};
 
 pub struct UserProgram {
     pub entry_point: VirtAddr,
     pub stack_pointer: VirtAddr,
     pub address_space: ProcessAddressSpace,
+    /// Everything mapped for the program, to unmap when it is gone
+    pub pages: Vec<PageRangeInclusive>,
 }
 
 pub fn load_elf(
@@ -23,6 +27,7 @@ pub fn load_elf(
 
     let mut address_space = ProcessAddressSpace::new(frame_allocator, *phys_mem_offset)
         .map_err(|_| "Failed to create process address space")?;
+    let mut pages = Vec::new();
 
     for ph in elf.program_headers {
         if ph.p_type == PT_LOAD {
@@ -40,6 +45,7 @@ pub fn load_elf(
 
             let page_range =
                 x86_64::structures::paging::Page::range_inclusive(start_page, end_page);
+            pages.push(page_range);
 
             for page in page_range {
                 let frame: PhysFrame<Size4KiB> = frame_allocator
@@ -106,6 +112,7 @@ pub fn load_elf(
         x86_64::structures::paging::Page::<Size4KiB>::containing_address(stack_start),
         x86_64::structures::paging::Page::<Size4KiB>::containing_address(stack_end - 1u64),
     );
+    pages.push(page_range);
 
     for page in page_range {
         let frame: PhysFrame<Size4KiB> = frame_allocator
@@ -122,11 +129,18 @@ pub fn load_elf(
                 frame_allocator,
             )
             .map_err(|_| "Failed to map stack")?;
+
+        // Frames are reused once a program is gone, don't hand its data over
+        let frame_virt = *phys_mem_offset + frame.start_address().as_u64();
+        unsafe {
+            core::ptr::write_bytes(frame_virt.as_mut_ptr::<u8>(), 0, 4096);
+        }
     }
 
     Ok(UserProgram {
         entry_point: VirtAddr::new(elf.entry),
         stack_pointer: stack_end,
         address_space,
+        pages,
     })
 }
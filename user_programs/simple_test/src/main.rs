@@ -7,6 +7,8 @@ use core::panic::PanicInfo;
 const SYSNO_EXIT: usize = 0;
 const SYSNO_WRITE: usize = 1;
 
+const STDOUT: usize = 1;
+
 #[panic_handler]
 fn panic(_info: &PanicInfo) -> ! {
     loop {}
@@ -18,28 +20,29 @@ pub extern "C" fn _start() -> ! {
 
     for _ in 0..20 {
         unsafe {
-            syscall2(SYSNO_WRITE, msg.as_ptr() as usize, msg.len());
+            syscall3(SYSNO_WRITE, STDOUT, msg.as_ptr() as usize, msg.len());
         }
     }
 
     unsafe {
-        syscall0(SYSNO_EXIT);
+        syscall1(SYSNO_EXIT, 0);
     }
 }
 
 #[inline(always)]
-unsafe fn syscall0(sysno: usize) -> ! {
+unsafe fn syscall1(sysno: usize, arg1: usize) -> ! {
     unsafe {
         asm!(
             "syscall",
             in("rax") sysno,
+            in("rdi") arg1,
             options(noreturn)
         )
     }
 }
 
 #[inline(always)]
-unsafe fn syscall2(sysno: usize, arg1: usize, arg2: usize) -> usize {
+unsafe fn syscall3(sysno: usize, arg1: usize, arg2: usize, arg3: usize) -> usize {
     let res;
     unsafe {
         asm!(
@@ -47,7 +50,10 @@ unsafe fn syscall2(sysno: usize, arg1: usize, arg2: usize) -> usize {
             in("rax") sysno,
             in("rdi") arg1,
             in("rsi") arg2,
+            in("rdx") arg3,
             lateout("rax") res,
+            lateout("rcx") _,
+            lateout("r11") _,
         );
     }
     res